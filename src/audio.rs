@@ -0,0 +1,261 @@
+// Audio de `sdl2.sdlmixer` (`Mix_OpenAudio`, `Mix_LoadWAV`, `Mix_PlayChannel`,
+// `Mix_PlayMusic`...) sobre `sdl2::mixer`. Los canales son números como en
+// PySDL2: `-1` es `Channel::all()` y cualquier otro valor `Channel(n)`.
+use std::collections::HashSet;
+
+use crate::types::{Expr, State, Stmt, StmtKind};
+use crate::writer::function_call;
+
+// Constantes de SDL_mixer y su nombre en `sdl2::mixer`
+const MIXER_CONSTANTS: [(&str, &str); 4] = [
+    ("MIX_DEFAULT_FORMAT", "DEFAULT_FORMAT"),
+    ("MIX_DEFAULT_FREQUENCY", "DEFAULT_FREQUENCY"),
+    ("MIX_DEFAULT_CHANNELS", "DEFAULT_CHANNELS"),
+    ("MIX_MAX_VOLUME", "MAX_VOLUME"),
+];
+
+// Formatos de audio, con el mismo nombre en `sdl2::mixer`
+const AUDIO_FORMATS: [&str; 18] = [
+    "AUDIO_U8", "AUDIO_S8", "AUDIO_U16LSB", "AUDIO_S16LSB", "AUDIO_U16MSB", "AUDIO_S16MSB",
+    "AUDIO_U16", "AUDIO_S16", "AUDIO_S32LSB", "AUDIO_S32MSB", "AUDIO_S32", "AUDIO_F32LSB",
+    "AUDIO_F32MSB", "AUDIO_F32", "AUDIO_U16SYS", "AUDIO_S16SYS", "AUDIO_S32SYS", "AUDIO_F32SYS",
+];
+
+// Formatos de `Mix_Init`
+const INIT_FLAGS: [&str; 6] = ["FLAC", "MOD", "MP3", "OGG", "MID", "OPUS"];
+
+// Funciones de canal sin resultado útil: `Mix_HaltChannel(-1)` -> `Channel::all().halt()`
+const CHANNEL_METHODS: [(&str, &str); 6] = [
+    ("Mix_HaltChannel", "halt"),
+    ("Mix_Pause", "pause"),
+    ("Mix_Resume", "resume"),
+    ("Mix_Volume", "set_volume"),
+    ("Mix_FadeOutChannel", "fade_out"),
+    ("Mix_ExpireChannel", "expire"),
+];
+
+// Funciones de la música en reproducción: `Mix_PauseMusic()` -> `Music::pause()`
+const MUSIC_FUNCTIONS: [(&str, &str); 5] = [
+    ("Mix_PauseMusic", "pause"),
+    ("Mix_ResumeMusic", "resume"),
+    ("Mix_HaltMusic", "halt"),
+    ("Mix_RewindMusic", "rewind"),
+    ("Mix_VolumeMusic", "set_volume"),
+];
+
+// Nombre en `sdl2::mixer` de una constante de SDL_mixer.
+pub fn rust_constant(name: &str) -> Option<String> {
+    if let Some((_, constant)) = MIXER_CONSTANTS.iter().find(|(c, _)| *c == name) {
+        return Some(constant.to_string());
+    }
+    if AUDIO_FORMATS.contains(&name) {
+        return Some(name.to_string());
+    }
+    let flag = name.strip_prefix("MIX_INIT_").filter(|flag| INIT_FLAGS.contains(flag))?;
+    Some(format!("InitFlag::{}", flag))
+}
+
+fn mixer_function(function: &str) -> Option<&str> {
+    function.rsplit('.').next().filter(|name| name.starts_with("Mix_"))
+}
+
+// Si el script usa SDL_mixer, lo que necesita la feature `mixer` de sdl2.
+pub fn uses_mixer(state: &State) -> bool {
+    !called_functions(state).is_empty()
+}
+
+// Funciones de SDL_mixer que llama el script, también dentro de condiciones.
+fn called_functions(state: &State) -> Vec<&str> {
+    state.called_functions().into_iter().filter_map(mixer_function).collect()
+}
+
+// Lo que hay que importar de `sdl2::mixer`, en el orden de rustfmt. `constants`
+// son los nombres de las constantes que usa el script.
+pub fn imports(state: &State, constants: &[String]) -> Vec<String> {
+    let functions = called_functions(state);
+    let calls = |names: &[&str]| functions.iter().any(|f| names.contains(f));
+    let mut imports = Vec::new();
+    let channel_calls = ["Mix_PlayChannel", "Mix_PlayChannelTimed", "Mix_FadeInChannel", "Mix_Playing", "Mix_Paused"];
+    if calls(&channel_calls) || functions.iter().any(|f| CHANNEL_METHODS.iter().any(|(m, _)| m == f)) {
+        imports.push("Channel".to_string());
+    }
+    if calls(&["Mix_LoadWAV"]) {
+        imports.push("Chunk".to_string());
+    }
+    if constants.iter().any(|c| c.starts_with("MIX_INIT_")) {
+        imports.push("InitFlag".to_string());
+    }
+    let music_calls = ["Mix_LoadMUS", "Mix_FadeOutMusic", "Mix_PlayingMusic", "Mix_PausedMusic"];
+    if calls(&music_calls) || functions.iter().any(|f| MUSIC_FUNCTIONS.iter().any(|(m, _)| m == f)) {
+        imports.push("Music".to_string());
+    }
+    for constant in constants {
+        if let Some(name) = rust_constant(constant).filter(|name| !name.contains("::")) {
+            if !imports.contains(&name) {
+                imports.push(name);
+            }
+        }
+    }
+    imports
+}
+
+// Ruta del archivo que carga `Mix_LoadWAV`/`Mix_LoadMUS`.
+pub fn loaded_file(value: &Expr) -> Option<&Expr> {
+    match value {
+        Expr::Call { function, args } if matches!(mixer_function(function), Some("Mix_LoadWAV" | "Mix_LoadMUS")) => args.first(),
+        _ => None,
+    }
+}
+
+// Variables con un sonido o una música cargados.
+pub fn sounds(state: &State) -> HashSet<String> {
+    state.statements().iter()
+        .filter_map(|stmt| match &stmt.kind {
+            StmtKind::Assign { target, value } if loaded_file(value).is_some() => Some(target.clone()),
+            _ => None,
+        })
+        .collect()
+}
+
+// Sonidos cuyo volumen se cambia con `Mix_VolumeChunk`: se declaran `let mut`.
+pub fn adjusted_chunks(state: &State) -> HashSet<String> {
+    state.statements().iter()
+        .filter_map(|stmt| match &stmt.kind {
+            StmtKind::Expr(Expr::Call { function, args }) if mixer_function(function) == Some("Mix_VolumeChunk") => {
+                match args.first() {
+                    Some(Expr::Name(name)) => Some(name.clone()),
+                    _ => None,
+                }
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+fn channel(arg: &Expr, expr: &dyn Fn(&Expr) -> String) -> String {
+    match arg {
+        Expr::Int(-1) => "Channel::all()".to_string(),
+        _ => format!("Channel({})", expr(arg)),
+    }
+}
+
+// Consultas que se usan en condiciones: `Mix_Playing(-1)`, `Mix_PlayingMusic()`.
+pub fn query_code(function: &str, args: &[Expr], expr: &dyn Fn(&Expr) -> String) -> Option<String> {
+    match (mixer_function(function)?, args) {
+        ("Mix_Playing", [arg]) => Some(format!("{}.is_playing()", channel(arg, expr))),
+        ("Mix_Paused", [arg]) => Some(format!("{}.is_paused()", channel(arg, expr))),
+        ("Mix_PlayingMusic", []) => Some("Music::is_playing()".to_string()),
+        ("Mix_PausedMusic", []) => Some("Music::is_paused()".to_string()),
+        _ => None,
+    }
+}
+
+// Código de una sentencia de SDL_mixer. `expr` traduce los argumentos y
+// `path` las rutas de los archivos de sonido.
+pub fn stmt_code(
+    stmt: &Stmt,
+    adjusted: &HashSet<String>,
+    declared: &HashSet<String>,
+    expr: &dyn Fn(&Expr) -> String,
+    path: &dyn Fn(&Expr) -> String,
+) -> Option<String> {
+    let (target, function, args) = match &stmt.kind {
+        StmtKind::Assign { target, value: Expr::Call { function, args } } => (Some(target), function, args),
+        StmtKind::Expr(Expr::Call { function, args }) => (None, function, args),
+        _ => return None,
+    };
+    let name = mixer_function(function)?;
+    let target_name = |target: &String| expr(&Expr::Name(target.clone()));
+    // Las variables declaradas al principio de `main` se reasignan
+    let binding = |target: &String| {
+        let keyword = if declared.contains(target) { "" } else { "let " };
+        format!("{}{}", keyword, target_name(target))
+    };
+    let args_code: Vec<String> = args.iter().map(expr).collect();
+    let code = match (name, args.as_slice()) {
+        // El contexto y el subsistema de audio tienen que vivir hasta el final de `main`
+        ("Mix_Init", [flags]) => return Some(format!("let _mixer_context = sdl2::mixer::init({})?;", expr(flags))),
+        ("Mix_OpenAudio", [_, _, _, _]) => {
+            let open = function_call("sdl2::mixer::open_audio", &args_code, "?;");
+            return Some(format!("let _audio_subsystem = sdl_context.audio()?;\n{}", open));
+        }
+        ("Mix_LoadWAV" | "Mix_LoadMUS", [file]) => {
+            let target = target?;
+            let mutable = if adjusted.contains(target) { "mut " } else { "" };
+            let kind = if name == "Mix_LoadWAV" { "Chunk" } else { "Music" };
+            return Some(format!("let {}{} = {}::from_file({})?;", mutable, target_name(target), kind, path(file)));
+        }
+        // `canal = Mix_PlayChannel(-1, sonido, 0)`: el número del canal, como en PySDL2
+        ("Mix_PlayChannel", [ch, chunk, loops]) => {
+            let play = format!("{}.play(&{}, {})?", channel(ch, expr), expr(chunk), expr(loops));
+            return Some(match target {
+                Some(target) => format!("{} = {}.0;", binding(target), play),
+                None => format!("{};", play),
+            });
+        }
+        ("Mix_PlayChannelTimed" | "Mix_FadeInChannel", [ch, chunk, loops, time]) => {
+            let method = if name == "Mix_FadeInChannel" { "fade_in" } else { "play_timed" };
+            let play = format!("{}.{}(&{}, {}, {})?", channel(ch, expr), method, expr(chunk), expr(loops), expr(time));
+            return Some(match target {
+                Some(target) => format!("{} = {}.0;", binding(target), play),
+                None => format!("{};", play),
+            });
+        }
+        ("Mix_AllocateChannels" | "Mix_ReserveChannels", [count]) => {
+            let function = if name == "Mix_AllocateChannels" { "allocate_channels" } else { "reserve_channels" };
+            format!("sdl2::mixer::{}({})", function, expr(count))
+        }
+        ("Mix_VolumeChunk", [chunk, volume]) => format!("{}.set_volume({})", expr(chunk), expr(volume)),
+        ("Mix_PlayMusic", [music, loops]) => format!("{}.play({})?", expr(music), expr(loops)),
+        ("Mix_FadeInMusic", [music, loops, ms]) => format!("{}.fade_in({}, {})?", expr(music), expr(loops), expr(ms)),
+        ("Mix_FadeOutMusic", [ms]) => format!("Music::fade_out({})?", expr(ms)),
+        ("Mix_FreeChunk" | "Mix_FreeMusic", [value]) => format!("drop({})", expr(value)),
+        ("Mix_CloseAudio", []) => "sdl2::mixer::close_audio()".to_string(),
+        (name, [ch, rest @ ..]) if CHANNEL_METHODS.iter().any(|(f, _)| *f == name) => {
+            let (_, method) = CHANNEL_METHODS.iter().find(|(f, _)| *f == name)?;
+            let rest: Vec<String> = rest.iter().map(expr).collect();
+            format!("{}.{}({})", channel(ch, expr), method, rest.join(", "))
+        }
+        (name, args) => {
+            let (_, method) = MUSIC_FUNCTIONS.iter().find(|(f, _)| *f == name)?;
+            let args: Vec<String> = args.iter().map(expr).collect();
+            format!("Music::{}({})", method, args.join(", "))
+        }
+    };
+    Some(match target {
+        Some(target) => format!("let {} = {};", target_name(target), code),
+        None => format!("{};", code),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::extract_state;
+    use crate::tokenizer::tokenize;
+
+    #[test]
+    fn test_mixer_statements() {
+        let code = "sdlmixer.Mix_OpenAudio(44100, sdlmixer.MIX_DEFAULT_FORMAT, 2, 1024)\nsalto = sdlmixer.Mix_LoadWAV(b\"salto.wav\")\nsdlmixer.Mix_VolumeChunk(salto, 64)\ncanal = sdlmixer.Mix_PlayChannel(-1, salto, 0)\nsdlmixer.Mix_HaltChannel(canal)\nsdlmixer.Mix_VolumeMusic(32)\n";
+        let state = extract_state(tokenize(code));
+        let adjusted = adjusted_chunks(&state);
+        let expr = |e: &Expr| match e {
+            Expr::Int(n) => n.to_string(),
+            Expr::Name(name) => name.clone(),
+            Expr::Attr(path) => rust_constant(path.rsplit('.').next().unwrap_or(path)).unwrap_or_default(),
+            _ => String::new(),
+        };
+        let path = |_: &Expr| "asset(\"salto.wav\")".to_string();
+        let code: Vec<String> = state.setup.iter().filter_map(|stmt| stmt_code(stmt, &adjusted, &HashSet::new(), &expr, &path)).collect();
+
+        assert!(uses_mixer(&state));
+        assert_eq!(code, vec![
+            "let _audio_subsystem = sdl_context.audio()?;\nsdl2::mixer::open_audio(44100, DEFAULT_FORMAT, 2, 1024)?;",
+            "let mut salto = Chunk::from_file(asset(\"salto.wav\"))?;",
+            "salto.set_volume(64);",
+            "let canal = Channel::all().play(&salto, 0)?.0;",
+            "Channel(canal).halt();",
+            "Music::set_volume(32);",
+        ]);
+    }
+}
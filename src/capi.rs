@@ -0,0 +1,196 @@
+// API C de PySDL2 (`sdl2.SDL_CreateWindow`, `sdl2.SDL_RenderFillRect`...): cada
+// llamada se reescribe como su equivalente de `sdl2.ext`, que es lo que el resto
+// del parser sabe traducir.
+use crate::mappings::ApiMapping;
+use crate::parser::{ASTNode, RENDERER_METHODS};
+
+// Funciones que actúan sobre la ventana o el renderer (primer argumento) y el
+// método de `sdl2.ext` que les corresponde.
+const OBJECT_METHODS: [(&str, &str); 16] = [
+    ("SDL_ShowWindow", "show"),
+    ("SDL_HideWindow", "hide"),
+    ("SDL_MaximizeWindow", "maximize"),
+    ("SDL_MinimizeWindow", "minimize"),
+    ("SDL_RestoreWindow", "restore"),
+    ("SDL_RenderClear", "clear"),
+    ("SDL_RenderPresent", "present"),
+    ("SDL_SetRenderDrawColor", "set_draw_color"),
+    ("SDL_RenderDrawPoint", "draw_point"),
+    ("SDL_RenderDrawLine", "draw_line"),
+    ("SDL_RenderDrawRect", "draw_rect"),
+    ("SDL_RenderFillRect", "fill"),
+    ("SDL_RenderCopy", "copy"),
+    ("SDL_RenderCopyEx", "copy"),
+    // El renderer ya se configura en sus atributos
+    ("SDL_RenderSetLogicalSize", "logical_size"),
+    ("SDL_SetRenderDrawBlendMode", "blendmode"),
+];
+
+// Funciones con parámetros de salida (`ctypes.byref(x)`) a partir de la
+// posición indicada; se traducen como si devolvieran esos valores.
+const OUT_PARAMS: [(&str, usize); 3] = [
+    ("SDL_GetMouseState", 0),
+    ("SDL_GetGlobalMouseState", 0),
+    ("SDL_GetRelativeMouseState", 0),
+];
+
+// Métodos de `sdl2.ext.Window` que se traducen sobre variables (`window.show()`)
+const WINDOW_METHODS: [&str; 5] = ["show", "hide", "maximize", "minimize", "restore"];
+
+// Nombres de `sdl2.ext` que trae `from sdl2.ext import *`
+const EXT_NAMES: [&str; 19] = [
+    "init", "quit", "Window", "Renderer", "get_events", "SpriteFactory", "TextureSprite", "Texture", "load_image",
+    "FontTTF", "Resources", "fill", "clipline", "cohensutherland", "liangbarsky", "Color", "TEXTURE", "SOFTWARE",
+    "SDLError",
+];
+
+// Prefijos de los nombres de cada módulo de PySDL2 para `from módulo import *`
+const MODULE_PREFIXES: [(&str, &[&str]); 4] = [
+    ("sdl2", &["SDL_", "SDLK_", "KMOD_"]),
+    ("sdl2.sdlttf", &["TTF_"]),
+    ("sdl2.sdlmixer", &["Mix_", "MIX_"]),
+    ("sdl2.sdlimage", &["IMG_"]),
+];
+
+// Submódulos que trae `from sdl2 import *`
+const SUBMODULES: [&str; 4] = ["ext", "sdlttf", "sdlmixer", "sdlimage"];
+
+// Posiciones de ventana que equivalen a no indicar ninguna
+const DEFAULT_POSITIONS: [&str; 2] = ["SDL_WINDOWPOS_CENTERED", "SDL_WINDOWPOS_UNDEFINED"];
+
+// PySDL2, siempre activo: las llamadas de `sdl2.ext` y los métodos de ventana y
+// renderer se quedan como están y las de la API C pasan a ser de `sdl2.ext`.
+pub(crate) struct CApi;
+
+impl ApiMapping for CApi {
+    fn module(&self) -> &str {
+        "sdl2"
+    }
+
+    fn lower_call(&self, function: &str, args: &[ASTNode], _kwargs: &[(String, ASTNode)], line: usize, column: usize) -> Option<ASTNode> {
+        lower(function, args, line, column).or_else(|| unwrap_reference(function, args))
+    }
+
+    fn lower_assignment(&mut self, target: Option<&str>, value: &ASTNode, _line: usize, _column: usize) -> Option<ASTNode> {
+        out_params(target, value)
+    }
+
+    fn exports(&self, module: &str, name: &str) -> bool {
+        match module {
+            "sdl2.ext" => EXT_NAMES.contains(&name),
+            "sdl2" if SUBMODULES.contains(&name) => true,
+            _ => MODULE_PREFIXES
+                .iter()
+                .any(|(m, prefixes)| *m == module && prefixes.iter().any(|prefix| name.starts_with(prefix))),
+        }
+    }
+
+    // Métodos de ventana y renderer sobre variables (`renderer.clear`) también cuentan
+    fn owns_call(&self, function: &str) -> bool {
+        match function.rsplit_once('.') {
+            _ if self.imported_by(function) => true,
+            Some((_, method)) => WINDOW_METHODS.contains(&method) || RENDERER_METHODS.contains(&method),
+            None => false,
+        }
+    }
+}
+
+// None si la función no es de la API C o no tiene equivalente.
+pub(crate) fn lower(function: &str, args: &[ASTNode], line: usize, column: usize) -> Option<ASTNode> {
+    let name = function.strip_prefix("sdl2.").filter(|n| n.starts_with("SDL_"))?;
+    let call = |function: &str, args: Vec<ASTNode>, kwargs: Vec<(String, ASTNode)>| {
+        Some(ASTNode::SDL2Call { function: function.to_string(), args, kwargs, line, column })
+    };
+
+    match name {
+        "SDL_Init" => call("sdl2.ext.init", Vec::new(), Vec::new()),
+        "SDL_Quit" => call("sdl2.ext.quit", Vec::new(), Vec::new()),
+        // `while SDL_PollEvent(ctypes.byref(event)):` es el bucle de eventos
+        "SDL_PollEvent" => call("sdl2.ext.get_events", args.to_vec(), Vec::new()),
+        // SDL_CreateWindow(title, x, y, w, h, flags)
+        "SDL_CreateWindow" if args.len() == 6 => {
+            let mut kwargs = vec![("size".to_string(), ASTNode::Tuple(args[3..5].to_vec()))];
+            let default_position = args[1..3].iter().all(|a| matches!(a, ASTNode::Attribute(path)
+                if DEFAULT_POSITIONS.iter().any(|p| path.ends_with(p))));
+            if !default_position {
+                kwargs.push(("position".to_string(), ASTNode::Tuple(args[1..3].to_vec())));
+            }
+            kwargs.push(("flags".to_string(), args[5].clone()));
+            call("sdl2.ext.Window", vec![args[0].clone()], kwargs)
+        }
+        // SDL_CreateRenderer(window, index, flags)
+        "SDL_CreateRenderer" if args.len() == 3 => {
+            call("sdl2.ext.Renderer", args[..2].to_vec(), vec![("flags".to_string(), args[2].clone())])
+        }
+        _ => {
+            let (_, method) = OBJECT_METHODS.iter().find(|(c, _)| *c == name)?;
+            let ASTNode::Identifier(object) = args.first()? else {
+                return None;
+            };
+            let rest = &args[1..];
+            match *method {
+                "logical_size" | "blendmode" => {
+                    let value = if rest.len() == 1 { rest[0].clone() } else { ASTNode::Tuple(rest.to_vec()) };
+                    Some(ASTNode::Assignment { name: format!("{}.{}", object, method), value: Box::new(value), line, column })
+                }
+                // Coordenadas sueltas: SDL_RenderDrawPoint(r, x, y), SDL_SetRenderDrawColor(r, r, g, b, a)...
+                "draw_point" | "draw_line" | "set_draw_color" => {
+                    call(&format!("{}.{}", object, method), vec![ASTNode::Tuple(rest.to_vec())], Vec::new())
+                }
+                _ => call(&format!("{}.{}", object, method), rest.to_vec(), Vec::new()),
+            }
+        }
+    }
+}
+
+// `ctypes.byref(rect)` y `ctypes.pointer(rect)` pasan el propio valor, y
+// `ctypes.c_int(0)` es su valor inicial.
+pub(crate) fn unwrap_reference(function: &str, args: &[ASTNode]) -> Option<ASTNode> {
+    let name = function.rsplit('.').next().unwrap_or(function);
+    match (name, args) {
+        ("byref" | "pointer", [value]) => Some(value.clone()),
+        ("c_int" | "c_uint" | "c_int32" | "c_uint32", [value]) => Some(value.clone()),
+        ("c_int" | "c_uint" | "c_int32" | "c_uint32", []) => Some(ASTNode::Number(0)),
+        _ => None,
+    }
+}
+
+// `[buttons =] SDL_GetMouseState(ctypes.byref(x), ctypes.byref(y))` ->
+// `buttons, x, y = SDL_GetMouseState()`; los `None` quedan como `_`.
+pub(crate) fn out_params(target: Option<&str>, value: &ASTNode) -> Option<ASTNode> {
+    let ASTNode::SDL2Call { function, args, kwargs, line, column } = value else {
+        return None;
+    };
+    let name = function.strip_prefix("sdl2.")?;
+    let (_, first) = OUT_PARAMS.iter().find(|(f, _)| *f == name)?;
+    let outputs = args.get(*first..).filter(|outputs| !outputs.is_empty())?;
+    let mut targets: Vec<String> = target.into_iter().map(String::from).collect();
+    for output in outputs {
+        match output {
+            ASTNode::Identifier(name) if name == "None" => targets.push("_".to_string()),
+            ASTNode::Identifier(name) => targets.push(name.clone()),
+            _ => return None,
+        }
+    }
+    let call = ASTNode::SDL2Call { function: function.clone(), args: args[..*first].to_vec(), kwargs: kwargs.clone(), line: *line, column: *column };
+    Some(ASTNode::Assignment { name: targets.join(", "), value: Box::new(call), line: *line, column: *column })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lower_renderer_calls() {
+        let renderer = ASTNode::Identifier("renderer".to_string());
+        let lowered = lower("sdl2.SDL_RenderDrawLine", &[renderer.clone(), ASTNode::Number(0), ASTNode::Number(1), ASTNode::Number(2), ASTNode::Number(3)], 4, 5);
+        assert_eq!(lowered, Some(ASTNode::SDL2Call {
+            function: "renderer.draw_line".to_string(),
+            args: vec![ASTNode::Tuple(vec![ASTNode::Number(0), ASTNode::Number(1), ASTNode::Number(2), ASTNode::Number(3)])],
+            kwargs: Vec::new(),
+            line: 4,
+            column: 5,
+        }));
+        assert_eq!(lower("sdl2.ext.Window", &[renderer], 1, 1), None);
+    }
+}
@@ -0,0 +1,1770 @@
+// Clases del script (`class Player:`) como un `struct` con su `impl`. Los
+// campos salen de las asignaciones `self.x = ...` de todos los métodos; sus
+// tipos, los de los parámetros y los que devuelven los métodos se deducen de
+// los valores, las anotaciones y las llamadas de todos los módulos. Al
+// enlazar, las llamadas se reescriben como en Rust: `Player(1, 2)` es
+// `Player::new(1, 2)`, `p.mover(1)` una llamada a un método y `p.x` un campo.
+//
+// Con herencia simple (`class Enemy(Sprite):`) la subclase guarda la parte
+// heredada en un campo (`self.sprite`), copia los métodos que no redefine y
+// los métodos de instancia de la raíz se despachan según la clase del objeto.
+// Una variable que guarda objetos de varias clases de la jerarquía es de
+// cualquiera de ellas: `Box<dyn SpriteTrait>` o `SpriteKind` en el generador.
+//
+// Las funciones del nivel superior se enlazan como los métodos estáticos de
+// una clase sin nombre, la última de su módulo: `doble(2)` o, desde otro
+// módulo, `nivel1.doble(2)`. Las llamadas del proyecto que no se pueden
+// traducir quedan como `Expr::Unlinked` y el generador omite su sentencia.
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+
+use crate::config;
+use crate::imports;
+use crate::parser::RENDERER_METHODS;
+use crate::timing;
+use crate::types::{ClassDef, Expr, Field, FunctionDef, Hierarchy, HierarchyMember, Method, MethodKind, Param, State, Stmt, StmtKind, Variable};
+
+// Tipos de Python con equivalente directo en Rust
+const VALUE_TYPES: [&str; 4] = ["int", "float", "bool", "str"];
+
+// Palabras reservadas de Rust que pueden ser nombres de métodos en Python
+const RUST_KEYWORDS: [&str; 24] = [
+    "box", "const", "do", "dyn", "enum", "extern", "final", "fn", "impl", "let", "loop", "macro",
+    "match", "mod", "move", "mut", "override", "priv", "pub", "ref", "static", "struct", "trait", "type",
+];
+
+// Vueltas de la deducción de tipos: cada una puede dar el tipo de lo que usa
+// lo deducido en la anterior
+const ROUNDS: usize = 6;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Ty {
+    Value(&'static str),
+    // Instancia de una clase: (módulo, clase)
+    Object(usize, usize),
+    // Instancia de cualquier clase de la jerarquía de una raíz: (módulo, raíz)
+    Dynamic(usize, usize),
+    // Lista de instancias de una clase o, con `true`, de cualquier clase de la
+    // jerarquía de la raíz
+    List(usize, usize, bool),
+    Unknown,
+}
+
+// Lo que nombra una ruta como `p`, `self.arma` o `Player`
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Receiver {
+    Instance(usize, usize),
+    Class(usize, usize),
+    // Objeto de cualquier clase de la jerarquía de la raíz
+    Dynamic(usize, usize),
+}
+
+// Cómo se llega a un campo desde su objeto
+enum Access {
+    // Partes heredadas que hay que atravesar: `e.sprite.x`
+    Chain(Vec<String>),
+    // Método de la jerarquía que da la parte de la raíz: `e.sprite().x`
+    Accessor(String),
+}
+
+// `objeto.nombre`: un campo con su tipo o un atributo de clase
+enum Member {
+    Field(Ty, Access),
+    Const((usize, usize), Ty),
+}
+
+// Tipo deducido para un parámetro: (módulo, clase, método, parámetro)
+type ParamUpdate = ((usize, usize, usize, usize), Ty);
+
+// Lo que deduce una vuelta a partir de las llamadas
+#[derive(Default)]
+struct Updates {
+    params: Vec<ParamUpdate>,
+    // Variables del nivel superior pasadas a un parámetro de cualquier clase
+    // de una jerarquía: (módulo, nombre, tipo)
+    globals: Vec<(usize, String, Ty)>,
+}
+
+// Dónde está una expresión: el módulo y, dentro de un método, su clase, el
+// nombre de `self` (o `cls`), las variables locales y parámetros con su tipo
+// y lo que devuelve el método
+struct Scope {
+    module: usize,
+    class: Option<usize>,
+    kind: MethodKind,
+    receiver: Option<String>,
+    locals: HashMap<String, Ty>,
+    returns: Ty,
+}
+
+struct Linker<'a> {
+    // Nombre de cada módulo; vacío en el script de entrada
+    names: Vec<&'a str>,
+    classes: Vec<Vec<ClassDef>>,
+    // Clase de la que hereda cada clase, si es del proyecto
+    parents: Vec<Vec<Option<(usize, usize)>>>,
+    // Tipo de las variables del nivel superior de cada módulo
+    globals: Vec<HashMap<String, Ty>>,
+    // Variables del nivel superior que guardan cualquier clase de una jerarquía
+    promoted: Vec<HashMap<String, Ty>>,
+    // Métodos de cada raíz que se despachan según la clase del objeto
+    dispatched: HashMap<(usize, usize), Vec<String>>,
+    // Raíces cuyos métodos despachados usa cada módulo
+    traits: RefCell<Vec<HashSet<(usize, usize)>>>,
+    // Variables de cada módulo que sus funciones no ven en Rust
+    hidden: Vec<HashSet<String>>,
+}
+
+// Si `tipo` es el de una instancia (o una lista de ellas) y no un valor de Python.
+pub(crate) fn is_class_type(tipo: &str) -> bool {
+    !VALUE_TYPES.contains(&tipo) && !["unknown", "tuple", "renderer"].contains(&tipo)
+}
+
+// Campo con la parte heredada de `class`: `sprite` para `Sprite`
+pub(crate) fn parent_field(class: &str) -> String {
+    config::to_snake_case(class)
+}
+
+// Un método se traduce si se conocen los tipos de sus parámetros y lo que
+// devuelve; uno que dibuja solo puede devolver el resultado del dibujo.
+pub(crate) fn translatable(method: &Method) -> bool {
+    receiver_params(method).iter().all(|param| param.tipo != "unknown")
+        && method.return_type.as_deref() != Some("unknown")
+        && !(draws(method) && method.return_type.is_some())
+}
+
+// Si recibe el renderer del script (`def draw(self, renderer)`): en Rust
+// recibe el canvas y devuelve `Result<(), String>`
+pub(crate) fn draws(method: &Method) -> bool {
+    receiver_params(method).iter().any(|param| param.tipo == "renderer")
+}
+
+// Si el cuerpo dibuja con `name` como renderer: `renderer.fill(...)`, `renderer.color = ...`
+fn draws_with(body: &[Stmt], name: &str) -> bool {
+    flatten(body).into_iter().any(|stmt| match &stmt.kind {
+        StmtKind::Expr(Expr::Call { function, .. }) => {
+            function.split_once('.').is_some_and(|(object, method)| object == name && RENDERER_METHODS.contains(&method))
+        }
+        StmtKind::Assign { target, .. } => *target == format!("{}.color", name),
+        _ => false,
+    })
+}
+
+// Parámetros sin `self` ni `cls`
+pub(crate) fn receiver_params(method: &Method) -> &[Param] {
+    let params = &method.function.params;
+    match method.kind {
+        MethodKind::Static => params,
+        _ => params.get(1..).unwrap_or_default(),
+    }
+}
+
+// Variable del nivel superior de un módulo que es una constante de Rust (`pub const`)
+pub(crate) fn is_constant(var: &Variable) -> bool {
+    match (var.tipo.as_str(), &var.value) {
+        ("int" | "float" | "bool", Some(_)) => true,
+        ("str", Some(Expr::Str(s))) => !s.starts_with(['f', 'F']),
+        _ => false,
+    }
+}
+
+// Variables del nivel superior que una función de Rust no ve: en el script de
+// entrada todas, que son locales de `main`, y en los demás módulos las que no
+// son constantes.
+pub(crate) fn hidden_globals(state: &State, entry: bool) -> HashSet<String> {
+    state.variables.iter().filter(|var| entry || !is_constant(var)).map(|var| var.name.clone()).collect()
+}
+
+// Primera variable de `hidden` que usa la función: no se traduce
+pub(crate) fn hidden_global(method: &Method, hidden: &HashSet<String>) -> Option<String> {
+    let mut found = Vec::new();
+    let mut locals: HashSet<&str> = method.function.params.iter().map(|param| param.name.as_str()).collect();
+    for stmt in flatten(&method.function.body) {
+        for expr in stmt_exprs(stmt) {
+            collect_paths(expr, &mut found);
+            collect_names(expr, &mut found);
+        }
+        match &stmt.kind {
+            StmtKind::Assign { target, .. } | StmtKind::For { target, .. } if !target.contains('.') => locals.extend(target.split(", ")),
+            StmtKind::Assign { target, .. } | StmtKind::AugAssign { target, .. } => found.push(target.clone()),
+            _ => {}
+        }
+    }
+    found.iter().map(|path| root(path)).find(|name| hidden.contains(*name) && !locals.contains(name)).map(String::from)
+}
+
+// Objeto de la lista `var` (`entidades = [jugador, enemigo]`) que el script
+// usa también fuera de ella: en Rust la lista se queda con sus elementos
+pub(crate) fn shared_item(state: &State, var: &Variable) -> Option<String> {
+    let Some(Expr::List(items)) = &var.value else { return None };
+    let mut found = Vec::new();
+    for stmt in state.statements().into_iter().filter(|stmt| stmt.line != var.line) {
+        match &stmt.kind {
+            StmtKind::Assign { target, .. } if target.contains('.') => found.push(target.clone()),
+            StmtKind::AugAssign { target, .. } => found.push(target.clone()),
+            _ => {}
+        }
+        for expr in stmt_exprs(stmt) {
+            collect_paths(expr, &mut found);
+            collect_names(expr, &mut found);
+        }
+    }
+    for arg in state.draw_calls.iter().flat_map(|call| call.args.iter().chain(call.kwargs.iter().map(|(_, value)| value))) {
+        collect_paths(arg, &mut found);
+        collect_names(arg, &mut found);
+    }
+    items.iter().find_map(|item| match item {
+        Expr::Name(name) if found.iter().any(|path| root(path) == name) => Some(name.clone()),
+        _ => None,
+    })
+}
+
+// Nombres sueltos de una expresión: `x` en `x + 1`
+fn collect_names(expr: &Expr, found: &mut Vec<String>) {
+    match expr {
+        Expr::Name(name) => found.push(name.clone()),
+        Expr::Call { args: items, .. } | Expr::Tuple(items) | Expr::List(items) => items.iter().for_each(|item| collect_names(item, found)),
+        Expr::Index { value, index } => {
+            collect_names(value, found);
+            collect_names(index, found);
+        }
+        Expr::MethodCall { receiver, args, .. } => {
+            collect_names(receiver, found);
+            args.iter().for_each(|arg| collect_names(arg, found));
+        }
+        Expr::BinOp { left, right, .. } => {
+            collect_names(left, found);
+            collect_names(right, found);
+        }
+        Expr::UnaryOp { operand, .. } | Expr::Cast { value: operand, .. } | Expr::Upcast { value: operand, .. } | Expr::Try(operand) => {
+            collect_names(operand, found)
+        }
+        _ => {}
+    }
+}
+
+// `move` es `r#move` en Rust.
+pub(crate) fn rust_ident(name: &str) -> String {
+    if RUST_KEYWORDS.contains(&name) { format!("r#{}", name) } else { name.to_string() }
+}
+
+// Rutas de atributos y llamadas de una expresión: `p.x`, `armas::Arma::new`
+fn collect_paths(expr: &Expr, found: &mut Vec<String>) {
+    match expr {
+        Expr::Attr(path) => found.push(path.clone()),
+        Expr::Call { function, args } => {
+            found.push(function.clone());
+            args.iter().for_each(|arg| collect_paths(arg, found));
+        }
+        Expr::Tuple(items) | Expr::List(items) => items.iter().for_each(|item| collect_paths(item, found)),
+        Expr::Index { value, index } => {
+            collect_paths(value, found);
+            collect_paths(index, found);
+        }
+        Expr::MethodCall { receiver, args, .. } => {
+            collect_paths(receiver, found);
+            args.iter().for_each(|arg| collect_paths(arg, found));
+        }
+        Expr::BinOp { left, right, .. } => {
+            collect_paths(left, found);
+            collect_paths(right, found);
+        }
+        Expr::UnaryOp { operand, .. } | Expr::Cast { value: operand, .. } | Expr::Try(operand) => collect_paths(operand, found),
+        Expr::Upcast { value, root, .. } => {
+            found.push(root.clone());
+            collect_paths(value, found);
+        }
+        _ => {}
+    }
+}
+
+// Rutas que usa la clase: tipos, llamadas y constantes (`player.Player`,
+// `armas::Arma::new`), para saber qué módulos necesita.
+pub(crate) fn paths(class: &ClassDef) -> Vec<String> {
+    let tipo = |tipo: &String| tipo.trim_start_matches("list ").trim_start_matches("dyn ").to_string();
+    let mut found: Vec<String> = class.fields.iter().map(|field| tipo(&field.tipo)).collect();
+    for method in &class.methods {
+        found.extend(method.function.params.iter().map(|param| tipo(&param.tipo)));
+        found.extend(method.return_type.as_ref().map(tipo));
+        for stmt in flatten(&method.function.body) {
+            stmt_exprs(stmt).into_iter().for_each(|expr| collect_paths(expr, &mut found));
+        }
+    }
+    class.attributes.iter().for_each(|attribute| collect_paths(&attribute.value, &mut found));
+    found
+}
+
+// Enlaza las clases de todos los módulos: `modules` son el nombre de cada
+// módulo (vacío el de entrada) y su estado.
+pub(crate) fn link(modules: &mut [(&str, &mut State)]) {
+    if modules.iter().all(|(_, state)| state.classes.is_empty() && state.functions.is_empty()) {
+        return;
+    }
+    let mut linker = Linker {
+        names: modules.iter().map(|(name, _)| *name).collect(),
+        classes: modules.iter().map(|(_, state)| {
+            let mut classes = state.classes.clone();
+            classes.push(functions_class(state));
+            classes
+        }).collect(),
+        parents: Vec::new(),
+        globals: vec![HashMap::new(); modules.len()],
+        promoted: vec![HashMap::new(); modules.len()],
+        dispatched: HashMap::new(),
+        traits: RefCell::new(vec![HashSet::new(); modules.len()]),
+        hidden: Vec::new(),
+    };
+    linker.find_parents();
+    linker.inherit();
+    for _ in 0..ROUNDS {
+        let states: Vec<&State> = modules.iter().map(|(_, state)| &**state).collect();
+        if !linker.infer(&states) {
+            break;
+        }
+    }
+    linker.mark_mutations();
+    linker.find_dispatched();
+
+    for (module, (_, state)) in modules.iter_mut().enumerate() {
+        for var in &mut state.variables {
+            if var.tipo == "unknown" {
+                let ty = linker.globals[module].get(&var.name).copied().unwrap_or(Ty::Unknown);
+                // Solo las instancias que se pueden construir en Rust
+                let constructible = match ty {
+                    Ty::Object(m, c) => linker.constructor_translatable(m, c),
+                    _ => true,
+                };
+                if ty != Ty::Unknown && ty != Ty::Value("renderer") && constructible {
+                    var.tipo = linker.ty_name(module, ty);
+                }
+            }
+        }
+    }
+    linker.hidden = modules.iter().enumerate().map(|(module, (_, state))| hidden_globals(state, module == 0)).collect();
+    for (module, (_, state)) in modules.iter_mut().enumerate() {
+        linker.rewrite_state(module, state);
+    }
+    for module in 0..linker.classes.len() {
+        for class in 0..linker.classes[module].len() {
+            linker.rewrite_class(module, class);
+        }
+    }
+    linker.describe_hierarchies();
+    let hierarchies: Vec<Vec<String>> = (0..modules.len()).map(|module| linker.hierarchies(module)).collect();
+    for (module, ((_, state), hierarchies)) in modules.iter_mut().zip(hierarchies).enumerate() {
+        state.hierarchies = hierarchies;
+        let mut classes = std::mem::take(&mut linker.classes[module]);
+        let linked = classes.pop().map(|functions| functions.methods).unwrap_or_default();
+        for function in &mut state.functions {
+            if let Some(linked) = linked.iter().find(|linked| linked.function.name == function.function.name) {
+                *function = linked.clone();
+            }
+        }
+        state.classes = classes;
+    }
+}
+
+// Clase sin nombre con las funciones del módulo; las que dibujan con el
+// renderer del script (`draw_circle(renderer, ...)`) las traduce el generador.
+fn functions_class(state: &State) -> ClassDef {
+    let methods = state.functions.iter()
+        .filter(|function| !state.draw_calls.iter().any(|call| call.method == function.function.name))
+        .cloned()
+        .collect();
+    ClassDef {
+        name: String::new(),
+        bases: Vec::new(),
+        docstring: None,
+        attributes: Vec::new(),
+        methods,
+        fields: Vec::new(),
+        metaclass: None,
+        hierarchy: None,
+        line: 0,
+        column: 0,
+    }
+}
+
+// Sentencias de los bloques de un `if` o un `for`, en orden
+pub(crate) fn flatten(stmts: &[Stmt]) -> Vec<&Stmt> {
+    let mut out = Vec::new();
+    for stmt in stmts {
+        out.push(stmt);
+        match &stmt.kind {
+            StmtKind::If { body, orelse, .. } => {
+                out.extend(flatten(body));
+                out.extend(flatten(orelse));
+            }
+            StmtKind::For { body, .. } => out.extend(flatten(body)),
+            _ => {}
+        }
+    }
+    out
+}
+
+// Expresiones de una sentencia, sin las de sus bloques
+pub(crate) fn stmt_exprs(stmt: &Stmt) -> Vec<&Expr> {
+    match &stmt.kind {
+        StmtKind::Assign { value, .. } | StmtKind::AugAssign { value, .. } | StmtKind::Expr(value) => vec![value],
+        StmtKind::If { condition, .. } | StmtKind::For { iter: condition, .. } | StmtKind::Return(Some(condition)) => vec![condition],
+        StmtKind::Break | StmtKind::Continue | StmtKind::Return(None) => Vec::new(),
+    }
+}
+
+// Llamadas dentro de una expresión, con sus argumentos
+fn calls<'e>(expr: &'e Expr, found: &mut Vec<(&'e str, &'e [Expr])>) {
+    match expr {
+        Expr::Call { function, args } => {
+            found.push((function, args));
+            args.iter().for_each(|arg| calls(arg, found));
+        }
+        Expr::Tuple(items) | Expr::List(items) => items.iter().for_each(|item| calls(item, found)),
+        Expr::Index { value, index } => {
+            calls(value, found);
+            calls(index, found);
+        }
+        Expr::MethodCall { receiver, args, .. } => {
+            calls(receiver, found);
+            args.iter().for_each(|arg| calls(arg, found));
+        }
+        Expr::BinOp { left, right, .. } => {
+            calls(left, found);
+            calls(right, found);
+        }
+        Expr::UnaryOp { operand, .. } | Expr::Cast { value: operand, .. } | Expr::Try(operand) => calls(operand, found),
+        _ => {}
+    }
+}
+
+fn root(path: &str) -> &str {
+    path.split('.').next().unwrap_or(path)
+}
+
+impl Scope {
+    fn module(module: usize) -> Scope {
+        Scope { module, class: None, kind: MethodKind::Static, receiver: None, locals: HashMap::new(), returns: Ty::Unknown }
+    }
+}
+
+impl Linker<'_> {
+    fn all_classes(&self) -> Vec<(usize, usize)> {
+        (0..self.classes.len()).flat_map(|m| (0..self.classes[m].len()).map(move |c| (m, c))).collect()
+    }
+
+    // Padre de cada clase con una sola base del proyecto; la herencia
+    // múltiple y las bases de fuera del proyecto las avisa el generador.
+    fn find_parents(&mut self) {
+        self.parents = self.classes.iter().enumerate()
+            .map(|(m, classes)| classes.iter().map(|class| match class.bases.as_slice() {
+                [base] => self.find_class(m, base),
+                _ => None,
+            }).collect())
+            .collect();
+        // Una herencia circular no tiene raíz
+        for (m, c) in self.all_classes() {
+            if self.ancestors((m, c)).last().and_then(|&last| self.parent(last)).is_some() {
+                self.parents[m][c] = None;
+            }
+        }
+    }
+
+    fn parent(&self, (m, c): (usize, usize)) -> Option<(usize, usize)> {
+        self.parents.get(m)?.get(c).copied().flatten()
+    }
+
+    // La clase y sus antecesoras hasta la raíz
+    fn ancestors(&self, class: (usize, usize)) -> Vec<(usize, usize)> {
+        let mut chain = vec![class];
+        while let Some(parent) = chain.last().and_then(|&last| self.parent(last)) {
+            if chain.contains(&parent) {
+                break;
+            }
+            chain.push(parent);
+        }
+        chain
+    }
+
+    fn root_of(&self, class: (usize, usize)) -> (usize, usize) {
+        self.ancestors(class).last().copied().unwrap_or(class)
+    }
+
+    // Si hereda de otra clase del proyecto o alguna hereda de ella
+    fn in_hierarchy(&self, class: (usize, usize)) -> bool {
+        self.parent(class).is_some() || self.parents.iter().flatten().any(|parent| *parent == Some(class))
+    }
+
+    fn parent_field(&self, (m, c): (usize, usize)) -> String {
+        parent_field(&self.classes[m][c].name)
+    }
+
+    // Las subclases copian los métodos del padre que no redefinen, sin cuerpo
+    // (el generador llama al del padre); sin `__init__` propio tienen uno que
+    // solo llama a `super().__init__` con los mismos parámetros.
+    fn inherit(&mut self) {
+        let mut order = self.all_classes();
+        order.sort_by_key(|&class| self.ancestors(class).len());
+        for (m, c) in order {
+            let Some((pm, pc)) = self.parent((m, c)) else { continue };
+            let parent = self.classes[pm][pc].clone();
+            let (line, column) = (self.classes[m][c].line, self.classes[m][c].column);
+            for method in &parent.methods {
+                if self.classes[m][c].methods.iter().any(|own| own.function.name == method.function.name) {
+                    continue;
+                }
+                let mut copy = Method {
+                    function: FunctionDef { docstring: None, body: Vec::new(), line, column, ..method.function.clone() },
+                    inherited: Some(method.inherited.clone().unwrap_or_else(|| parent.name.clone())),
+                    ..method.clone()
+                };
+                if method.function.name == "__init__" {
+                    let args = receiver_params(method).iter().map(|param| Expr::Name(param.name.clone())).collect();
+                    let call = Expr::Call { function: "super.__init__".to_string(), args };
+                    copy.function.body = vec![Stmt { kind: StmtKind::Expr(call), line, column }];
+                    copy.inherited = None;
+                }
+                self.classes[m][c].methods.push(copy);
+            }
+        }
+    }
+
+    // Método original de una copia heredada
+    fn defining(&self, (m, c): (usize, usize), method: usize) -> ((usize, usize), usize) {
+        let mut current = ((m, c), method);
+        for _ in 0..self.ancestors((m, c)).len() {
+            let ((m, c), method) = current;
+            let copy = &self.classes[m][c].methods[method];
+            let Some(parent) = copy.inherited.as_ref().and_then(|_| self.parent((m, c))) else { break };
+            let name = &copy.function.name;
+            match self.classes[parent.0][parent.1].methods.iter().position(|method| method.function.name == *name) {
+                Some(method) => current = (parent, method),
+                None => break,
+            }
+        }
+        current
+    }
+
+    // Tipo de `module` visto desde `to`: `Sprite` en `player` es `player.Sprite`
+    fn retype(&self, module: usize, to: usize, tipo: &str) -> String {
+        match self.parse_ty(module, tipo) {
+            Ty::Unknown => tipo.to_string(),
+            ty => self.ty_name(to, ty),
+        }
+    }
+
+    // Las copias heredadas tienen la firma del método original
+    fn sync_inherited(&mut self) {
+        for (m, c) in self.all_classes() {
+            for method in 0..self.classes[m][c].methods.len() {
+                if self.classes[m][c].methods[method].inherited.is_none() {
+                    continue;
+                }
+                let ((dm, dc), original) = self.defining((m, c), method);
+                let original = &self.classes[dm][dc].methods[original];
+                let params: Vec<(String, bool)> = original.function.params.iter()
+                    .map(|param| (self.retype(dm, m, &param.tipo), param.mutated))
+                    .collect();
+                let return_type = original.return_type.as_ref().map(|tipo| self.retype(dm, m, tipo));
+                let mutates = original.mutates;
+                let copy = &mut self.classes[m][c].methods[method];
+                for (param, (tipo, mutated)) in copy.function.params.iter_mut().zip(params) {
+                    param.tipo = tipo;
+                    param.mutated = mutated;
+                }
+                copy.return_type = return_type;
+                copy.mutates = mutates;
+            }
+        }
+    }
+
+    // Métodos de instancia de cada raíz junto con los que los redefinen con
+    // los mismos parámetros: comparten firma en el trait o en el enum.
+    fn overrides(&self) -> Vec<Vec<((usize, usize), usize)>> {
+        let mut groups = Vec::new();
+        for root in self.all_classes() {
+            if self.parent(root).is_some() || !self.in_hierarchy(root) {
+                continue;
+            }
+            let members: Vec<(usize, usize)> = self.all_classes().into_iter()
+                .filter(|&class| class != root && self.root_of(class) == root)
+                .collect();
+            for (index, method) in self.classes[root.0][root.1].methods.iter().enumerate() {
+                if method.kind != MethodKind::Instance || method.function.name == "__init__" {
+                    continue;
+                }
+                let mut group = vec![(root, index)];
+                for &(m, c) in &members {
+                    let own = self.classes[m][c].methods.iter()
+                        .position(|other| other.function.name == method.function.name && other.inherited.is_none());
+                    group.extend(own.map(|own| ((m, c), own)));
+                }
+                let same = group.iter().all(|&((m, c), other)| {
+                    let other = &self.classes[m][c].methods[other];
+                    other.kind == MethodKind::Instance && other.function.params.len() == method.function.params.len()
+                });
+                if same {
+                    groups.push(group);
+                }
+            }
+        }
+        groups
+    }
+
+    // Tipos comunes para los parámetros y lo que devuelven los métodos redefinidos
+    fn unify(&mut self) {
+        for group in self.overrides() {
+            let ((m, c), first) = group[0];
+            for index in 1..self.classes[m][c].methods[first].function.params.len() {
+                let ty = group.iter().fold(Ty::Unknown, |ty, &((m, c), method)| {
+                    self.merge(ty, self.parse_ty(m, &self.classes[m][c].methods[method].function.params[index].tipo))
+                });
+                if ty == Ty::Unknown {
+                    continue;
+                }
+                for &((m, c), method) in &group {
+                    self.classes[m][c].methods[method].function.params[index].tipo = self.ty_name(m, ty);
+                }
+            }
+            let returns: Vec<Ty> = group.iter()
+                .filter_map(|&((m, c), method)| self.classes[m][c].methods[method].return_type.as_ref().map(|tipo| self.parse_ty(m, tipo)))
+                .collect();
+            let ty = returns.iter().fold(Ty::Unknown, |ty, &other| self.merge(ty, other));
+            if returns.len() == group.len() && ty != Ty::Unknown {
+                for &((m, c), method) in &group {
+                    self.classes[m][c].methods[method].return_type = Some(self.ty_name(m, ty));
+                }
+            }
+        }
+    }
+
+    // Métodos de la raíz que se despachan: los que se traducen con la misma
+    // firma en toda la jerarquía
+    fn find_dispatched(&mut self) {
+        for group in self.overrides() {
+            let ((m, c), first) = group[0];
+            let signature = |((n, d), method): ((usize, usize), usize)| {
+                let method = &self.classes[n][d].methods[method];
+                let params: Vec<(String, bool)> = receiver_params(method).iter()
+                    .map(|param| (self.retype(n, m, &param.tipo), param.mutated))
+                    .collect();
+                (params, method.return_type.as_ref().map(|tipo| self.retype(n, m, tipo)), method.mutates)
+            };
+            let translated = group.iter().all(|&((n, d), method)| translatable(&self.classes[n][d].methods[method]));
+            if translated && group.iter().all(|&other| signature(other) == signature(group[0])) {
+                let name = self.classes[m][c].methods[first].function.name.clone();
+                self.dispatched.entry((m, c)).or_default().push(name);
+            }
+        }
+    }
+
+    fn is_dispatched(&self, root: (usize, usize), name: &str) -> bool {
+        self.dispatched.get(&root).is_some_and(|names| names.iter().any(|n| n == name))
+    }
+
+    // El módulo del ámbito usa los métodos de la jerarquía de `root`
+    fn use_trait(&self, scope: &Scope, root: (usize, usize)) {
+        if root.0 != scope.module {
+            self.traits.borrow_mut()[scope.module].insert(root);
+        }
+    }
+
+    fn merge(&self, a: Ty, b: Ty) -> Ty {
+        match (a, b) {
+            (Ty::Unknown, b) => b,
+            (Ty::Value("int"), Ty::Value("float")) => b,
+            // Dos clases de la misma jerarquía: cualquiera de ellas
+            (Ty::Object(m, c) | Ty::Dynamic(m, c), Ty::Object(n, d) | Ty::Dynamic(n, d)) if a != b => {
+                let root = self.root_of((m, c));
+                if root == self.root_of((n, d)) { Ty::Dynamic(root.0, root.1) } else { a }
+            }
+            (a, _) => a,
+        }
+    }
+
+    // Clase a la que se refiere `path` desde `module`: `Player` en su módulo o
+    // `player.Player` desde otro.
+    fn find_class(&self, module: usize, path: &str) -> Option<(usize, usize)> {
+        if !path.contains('.') {
+            let class = self.classes[module].iter().position(|class| class.name == path)?;
+            return Some((module, class));
+        }
+        self.names.iter().enumerate().filter(|(_, name)| !name.is_empty()).find_map(|(m, name)| {
+            let class = path.strip_prefix(*name)?.strip_prefix('.')?;
+            Some((m, self.classes[m].iter().position(|c| c.name == class)?))
+        })
+    }
+
+    // Clase sin nombre con las funciones de `module`
+    fn functions(&self, module: usize) -> usize {
+        self.classes[module].len() - 1
+    }
+
+    // Función del nivel superior a la que se refiere `path`: `doble` en su
+    // módulo o `nivel1.doble` desde otro.
+    fn function(&self, scope: &Scope, path: &str) -> Option<(Receiver, usize)> {
+        let (module, name) = match path.rsplit_once('.') {
+            Some((module, name)) => (self.names.iter().position(|name| !name.is_empty() && *name == module)?, name),
+            None => (scope.module, path),
+        };
+        let functions = self.functions(module);
+        let method = self.classes[module][functions].methods.iter().position(|method| method.function.name == name)?;
+        Some((Receiver::Class(module, functions), method))
+    }
+
+    // Si la función se puede llamar desde Rust: con tipos y sin usar variables
+    // del módulo que no son constantes
+    fn function_translatable(&self, module: usize, method: &Method) -> bool {
+        translatable(method) && self.hidden.get(module).is_none_or(|hidden| hidden_global(method, hidden).is_none())
+    }
+
+    // Constante de otro módulo: `nivel1.VELOCIDAD`
+    fn constant(&self, path: &str) -> Ty {
+        let constant = self.names.iter().enumerate().filter(|(_, name)| !name.is_empty()).find_map(|(m, name)| {
+            let constant = path.strip_prefix(*name)?.strip_prefix('.')?;
+            self.globals[m].get(constant).copied()
+        });
+        match constant {
+            Some(ty @ Ty::Value(_)) => ty,
+            _ => Ty::Unknown,
+        }
+    }
+
+    // Tipo guardado como texto en `module`
+    fn parse_ty(&self, module: usize, tipo: &str) -> Ty {
+        if let Some(item) = tipo.strip_prefix("list ") {
+            return match self.parse_ty(module, item) {
+                Ty::Object(m, c) => Ty::List(m, c, false),
+                Ty::Dynamic(m, c) => Ty::List(m, c, true),
+                _ => Ty::Unknown,
+            };
+        }
+        match tipo {
+            "int" => Ty::Value("int"),
+            "float" => Ty::Value("float"),
+            "bool" => Ty::Value("bool"),
+            "str" => Ty::Value("str"),
+            "renderer" => Ty::Value("renderer"),
+            _ => match (tipo.strip_prefix("dyn "), self.find_class(module, tipo.trim_start_matches("dyn "))) {
+                (Some(_), Some((m, c))) => Ty::Dynamic(m, c),
+                (None, Some((m, c))) => Ty::Object(m, c),
+                (_, None) => Ty::Unknown,
+            },
+        }
+    }
+
+    // Tipo como texto visto desde `module`: `Player` o `player.Player`
+    fn ty_name(&self, module: usize, ty: Ty) -> String {
+        match ty {
+            Ty::Value(name) => name.to_string(),
+            Ty::Object(m, c) if m == module => self.classes[m][c].name.clone(),
+            Ty::Object(m, c) => imports::member(self.names[m], &self.classes[m][c].name),
+            Ty::Dynamic(m, c) => format!("dyn {}", self.ty_name(module, Ty::Object(m, c))),
+            Ty::List(m, c, false) => format!("list {}", self.ty_name(module, Ty::Object(m, c))),
+            Ty::List(m, c, true) => format!("list {}", self.ty_name(module, Ty::Dynamic(m, c))),
+            Ty::Unknown => "unknown".to_string(),
+        }
+    }
+
+    // Ruta Rust de la clase desde el ámbito: `Self` dentro de sus métodos
+    fn rust_path(&self, scope: &Scope, (m, c): (usize, usize)) -> String {
+        if scope.module == m && scope.class == Some(c) {
+            return "Self".to_string();
+        }
+        self.ty_name(scope.module, Ty::Object(m, c)).replace('.', "::")
+    }
+
+    fn constructor_translatable(&self, module: usize, class: usize) -> bool {
+        self.classes[module][class].methods.iter()
+            .find(|method| method.function.name == "__init__")
+            .is_none_or(translatable)
+    }
+
+    fn name_ty(&self, scope: &Scope, name: &str) -> Ty {
+        if let Some(ty) = scope.locals.get(name) {
+            return *ty;
+        }
+        if let (Some(receiver), Some(class), MethodKind::Instance) = (&scope.receiver, scope.class, scope.kind) {
+            if receiver == name {
+                return Ty::Object(scope.module, class);
+            }
+        }
+        self.globals[scope.module].get(name).copied().unwrap_or(Ty::Unknown)
+    }
+
+    fn receiver(&self, scope: &Scope, path: &str) -> Option<Receiver> {
+        if let (Some(receiver), Some(class), MethodKind::Class) = (&scope.receiver, scope.class, scope.kind) {
+            if receiver == path {
+                return Some(Receiver::Class(scope.module, class));
+            }
+        }
+        // `super()` en un método de instancia es la parte heredada
+        if path == "super" && scope.kind == MethodKind::Instance {
+            let (m, c) = self.parent((scope.module, scope.class?))?;
+            return Some(Receiver::Instance(m, c));
+        }
+        if let Some((m, c)) = self.find_class(scope.module, path) {
+            return Some(Receiver::Class(m, c));
+        }
+        let ty = match path.rsplit_once('.') {
+            Some(_) => match self.member(scope, path)? {
+                Member::Field(ty, _) => ty,
+                Member::Const(..) => return None,
+            },
+            None => self.name_ty(scope, path),
+        };
+        match ty {
+            Ty::Object(m, c) => Some(Receiver::Instance(m, c)),
+            Ty::Dynamic(m, c) => Some(Receiver::Dynamic(m, c)),
+            _ => None,
+        }
+    }
+
+    // Los campos y atributos de clase heredados se buscan en las antecesoras;
+    // en un objeto de cualquier clase de la jerarquía, solo los de la raíz.
+    fn member(&self, scope: &Scope, path: &str) -> Option<Member> {
+        let (object, name) = path.rsplit_once('.')?;
+        let (class, instance, dynamic) = match self.receiver(scope, object)? {
+            Receiver::Instance(m, c) => ((m, c), true, false),
+            Receiver::Class(m, c) => ((m, c), false, false),
+            Receiver::Dynamic(m, c) => ((m, c), true, true),
+        };
+        let ancestors = self.ancestors(class);
+        let mut chain = Vec::new();
+        for &(m, c) in ancestors.iter().filter(|_| instance) {
+            if let Some(field) = self.classes[m][c].fields.iter().find(|field| field.name == name) {
+                let access = if dynamic { Access::Accessor(self.parent_field((m, c))) } else { Access::Chain(chain) };
+                return Some(Member::Field(self.parse_ty(m, &field.tipo), access));
+            }
+            chain.extend(self.parent((m, c)).map(|parent| self.parent_field(parent)));
+        }
+        ancestors.iter().find_map(|&(m, c)| {
+            let attribute = self.classes[m][c].attributes.iter().find(|attribute| attribute.name == name)?;
+            Some(Member::Const((m, c), self.parse_ty(m, &attribute.tipo)))
+        })
+    }
+
+    // Método que llama `function` (`p.mover`, `Player.crear`, `self.arma.disparar`);
+    // sobre cualquier clase de una jerarquía, un método de instancia de la raíz
+    fn method(&self, scope: &Scope, function: &str) -> Option<(Receiver, usize)> {
+        let Some((receiver, name)) = function.rsplit_once('.').and_then(|(object, name)| Some((self.receiver(scope, object)?, name))) else {
+            return self.function(scope, function);
+        };
+        let (Receiver::Instance(m, c) | Receiver::Class(m, c) | Receiver::Dynamic(m, c)) = receiver;
+        let method = self.classes[m][c].methods.iter().position(|method| method.function.name == name)?;
+        let dispatch = self.classes[m][c].methods[method].kind == MethodKind::Instance && name != "__init__";
+        if matches!(receiver, Receiver::Dynamic(..)) && !dispatch {
+            return None;
+        }
+        Some((receiver, method))
+    }
+
+    fn expr_ty(&self, scope: &Scope, expr: &Expr) -> Ty {
+        match expr {
+            Expr::Int(_) => Ty::Value("int"),
+            Expr::Float(_) => Ty::Value("float"),
+            Expr::Bool(_) => Ty::Value("bool"),
+            Expr::Str(_) => Ty::Value("str"),
+            Expr::Name(name) => self.name_ty(scope, name),
+            Expr::Attr(path) => match self.member(scope, path) {
+                Some(Member::Field(ty, _) | Member::Const(_, ty)) => ty,
+                None => self.constant(path),
+            },
+            Expr::Call { function, args } => match function.as_str() {
+                "int" | "len" => Ty::Value("int"),
+                "float" => Ty::Value("float"),
+                "str" => Ty::Value("str"),
+                "bool" => Ty::Value("bool"),
+                "abs" | "min" | "max" => args.first().map_or(Ty::Unknown, |arg| self.expr_ty(scope, arg)),
+                _ if timing::is_float(expr) => Ty::Value("float"),
+                _ => {
+                    if let Some(Receiver::Class(m, c)) = self.receiver(scope, function) {
+                        return Ty::Object(m, c);
+                    }
+                    match self.method(scope, function) {
+                        Some((Receiver::Instance(m, c) | Receiver::Class(m, c) | Receiver::Dynamic(m, c), method)) => {
+                            match &self.classes[m][c].methods[method].return_type {
+                                Some(tipo) => self.parse_ty(m, tipo),
+                                None => Ty::Unknown,
+                            }
+                        }
+                        None => Ty::Unknown,
+                    }
+                }
+            },
+            Expr::BinOp { op, left, right } => match op.as_str() {
+                "==" | "!=" | "<" | "<=" | ">" | ">=" | "and" | "or" => Ty::Value("bool"),
+                "/" => Ty::Value("float"),
+                _ => match (self.expr_ty(scope, left), self.expr_ty(scope, right)) {
+                    (Ty::Value("float"), Ty::Value("int" | "float")) | (Ty::Value("int"), Ty::Value("float")) => Ty::Value("float"),
+                    (Ty::Value("int"), Ty::Value("int")) => Ty::Value("int"),
+                    (Ty::Value("str"), Ty::Value("str")) if op == "+" => Ty::Value("str"),
+                    _ => Ty::Unknown,
+                },
+            },
+            Expr::UnaryOp { op, .. } if op == "not" => Ty::Value("bool"),
+            Expr::UnaryOp { operand, .. } => self.expr_ty(scope, operand),
+            // `enemigos[0]`: un elemento de una lista de objetos
+            Expr::Index { value, .. } => match self.expr_ty(scope, value) {
+                Ty::List(m, c, false) => Ty::Object(m, c),
+                Ty::List(m, c, true) => Ty::Dynamic(m, c),
+                _ => Ty::Unknown,
+            },
+            // `[jugador, enemigo]`: objetos de una clase o de una jerarquía
+            Expr::List(items) => {
+                let tys: Vec<Ty> = items.iter().map(|item| self.expr_ty(scope, item)).collect();
+                let in_root = |ty: Ty, root: (usize, usize)| matches!(ty, Ty::Object(m, c) | Ty::Dynamic(m, c) if self.root_of((m, c)) == root);
+                match tys.iter().fold(Ty::Unknown, |ty, &other| self.merge(ty, other)) {
+                    ty @ Ty::Object(m, c) if tys.iter().all(|&other| other == ty) => Ty::List(m, c, false),
+                    Ty::Dynamic(m, c) if tys.iter().all(|&other| in_root(other, (m, c))) => Ty::List(m, c, true),
+                    _ => Ty::Unknown,
+                }
+            }
+            _ => Ty::Unknown,
+        }
+    }
+
+    // Tipo de la variable de un `for`: un entero en `range(...)` y cada
+    // objeto en una lista de ellos
+    fn loop_ty(&self, scope: &Scope, iter: &Expr) -> Ty {
+        match iter {
+            Expr::Call { function, args } if function == "range" && (1..=2).contains(&args.len()) => Ty::Value("int"),
+            iter => match self.expr_ty(scope, iter) {
+                Ty::List(m, c, false) => Ty::Object(m, c),
+                Ty::List(m, c, true) => Ty::Dynamic(m, c),
+                _ => Ty::Unknown,
+            },
+        }
+    }
+
+    // El ámbito con las variables de los `for` de `stmts`
+    fn loop_scope<'s>(&self, mut scope: Scope, stmts: impl IntoIterator<Item = &'s Stmt>) -> Scope {
+        for stmt in stmts {
+            if let StmtKind::For { target, iter, .. } = &stmt.kind {
+                let ty = self.loop_ty(&scope, iter);
+                scope.locals.insert(target.clone(), ty);
+            }
+        }
+        scope
+    }
+
+    // Ámbito de un método, con sus parámetros
+    fn method_scope(&self, module: usize, class: usize, method: usize) -> Scope {
+        let method = &self.classes[module][class].methods[method];
+        let mut scope = Scope {
+            module,
+            class: Some(class),
+            kind: method.kind,
+            receiver: None,
+            locals: HashMap::new(),
+            returns: method.return_type.as_ref().map_or(Ty::Unknown, |tipo| self.parse_ty(module, tipo)),
+        };
+        if method.kind != MethodKind::Static {
+            scope.receiver = method.function.params.first().map(|param| param.name.clone());
+        }
+        for param in receiver_params(method) {
+            scope.locals.insert(param.name.clone(), self.parse_ty(module, &param.tipo));
+        }
+        scope
+    }
+
+    // Una vuelta de la deducción; true si ha cambiado algún tipo.
+    fn infer(&mut self, states: &[&State]) -> bool {
+        let before = (self.classes.clone(), self.globals.clone());
+
+        for (module, state) in states.iter().enumerate() {
+            let scope = Scope::module(module);
+            let mut globals: HashMap<String, Ty> = self.promoted[module].clone();
+            for var in &state.variables {
+                let ty = match (self.parse_ty(module, &var.tipo), &var.value) {
+                    // El renderer del script se pasa a los métodos que dibujan
+                    _ if state.renderer.as_ref().is_some_and(|renderer| renderer.variable == var.name) => Ty::Value("renderer"),
+                    // Una lista de objetos que también se usan fuera de ella no se traduce
+                    (Ty::Unknown, Some(value)) => match self.expr_ty(&scope, value) {
+                        Ty::List(..) if shared_item(state, var).is_some() => Ty::Unknown,
+                        ty => ty,
+                    },
+                    (ty, _) => ty,
+                };
+                match globals.get(&var.name).copied() {
+                    None => {
+                        globals.insert(var.name.clone(), ty);
+                    }
+                    // `e = Enemy()` y después `e = Boss()`: cualquier clase de la jerarquía
+                    Some(current @ (Ty::Object(..) | Ty::Dynamic(..))) => {
+                        globals.insert(var.name.clone(), self.merge(current, ty));
+                    }
+                    Some(_) => {}
+                }
+            }
+            self.globals[module] = globals;
+        }
+
+        // Atributos de clase y parámetros anotados o con valor por defecto
+        let mut updates = Updates::default();
+        for module in 0..self.classes.len() {
+            let scope = Scope::module(module);
+            for class in 0..self.classes[module].len() {
+                for attribute in 0..self.classes[module][class].attributes.len() {
+                    let ty = self.expr_ty(&scope, &self.classes[module][class].attributes[attribute].value);
+                    if matches!(ty, Ty::Value(_)) {
+                        self.classes[module][class].attributes[attribute].tipo = self.ty_name(module, ty);
+                    }
+                }
+                for method in 0..self.classes[module][class].methods.len() {
+                    let function = &self.classes[module][class].methods[method].function;
+                    for (index, param) in function.params.iter().enumerate() {
+                        let ty = match (&param.annotation, &param.default) {
+                            (Some(annotation), _) => self.parse_ty(module, annotation),
+                            (None, Some(default)) => self.expr_ty(&scope, default),
+                            // Aunque nadie lo llame, `def draw(self, renderer)` dibuja
+                            (None, None) if draws_with(&function.body, &param.name) => Ty::Value("renderer"),
+                            (None, None) => continue,
+                        };
+                        updates.params.push(((module, class, method, index), ty));
+                    }
+                }
+            }
+        }
+
+        // Llamadas: los argumentos dan el tipo de los parámetros
+        for (module, state) in states.iter().enumerate() {
+            let scope = self.loop_scope(Scope::module(module), state.statements());
+            let mut exprs: Vec<&Expr> = state.variables.iter().filter_map(|var| var.value.as_ref()).collect();
+            for stmt in state.statements() {
+                exprs.extend(stmt_exprs(stmt));
+            }
+            for call in state.draw_calls.iter() {
+                exprs.extend(call.args.iter().chain(call.kwargs.iter().map(|(_, value)| value)));
+            }
+            for expr in exprs {
+                self.call_site_types(&scope, expr, &mut updates);
+            }
+        }
+        for module in 0..self.classes.len() {
+            for class in 0..self.classes[module].len() {
+                self.infer_class(module, class, &mut updates);
+            }
+        }
+        for ((module, class, method, index), ty) in updates.params {
+            let param = &self.classes[module][class].methods[method].function.params[index];
+            let current = self.parse_ty(module, &param.tipo);
+            if param.annotation.is_none() || current == Ty::Unknown {
+                let tipo = self.ty_name(module, self.merge(current, ty));
+                self.classes[module][class].methods[method].function.params[index].tipo = tipo;
+            }
+        }
+        let mut promoted = false;
+        for (module, name, ty) in updates.globals {
+            if let Ty::Dynamic(m, c) = ty {
+                if self.root_api_only(states[module], &name, (m, c)) {
+                    promoted |= self.promoted[module].insert(name, ty) != Some(ty);
+                }
+            }
+        }
+        self.unify();
+        self.sync_inherited();
+
+        promoted || (self.classes.clone(), self.globals.clone()) != before
+    }
+
+    // Si una variable del nivel superior solo se usa con los campos y métodos
+    // de la raíz `root`: entonces puede guardar cualquier clase de la jerarquía
+    fn root_api_only(&self, state: &State, name: &str, (m, c): (usize, usize)) -> bool {
+        let root = &self.classes[m][c];
+        let mut found = Vec::new();
+        for stmt in state.statements() {
+            if let StmtKind::Assign { target, .. } | StmtKind::AugAssign { target, .. } = &stmt.kind {
+                found.push(target.clone());
+            }
+            stmt_exprs(stmt).into_iter().for_each(|expr| collect_paths(expr, &mut found));
+        }
+        state.variables.iter().filter_map(|var| var.value.as_ref()).for_each(|value| collect_paths(value, &mut found));
+        found.iter()
+            .filter_map(|path| path.strip_prefix(name)?.strip_prefix('.'))
+            .map(|rest| rest.split('.').next().unwrap_or(rest))
+            .all(|member| {
+                root.fields.iter().any(|field| field.name == member)
+                    || root.methods.iter().any(|method| method.function.name == member && method.kind == MethodKind::Instance)
+            })
+    }
+
+    fn call_site_types(&self, scope: &Scope, expr: &Expr, updates: &mut Updates) {
+        let mut found = Vec::new();
+        calls(expr, &mut found);
+        for (function, args) in found {
+            let (m, c, method, skip) = if let Some(Receiver::Class(m, c)) = self.receiver(scope, function) {
+                match self.classes[m][c].methods.iter().position(|method| method.function.name == "__init__") {
+                    Some(method) => (m, c, method, 1),
+                    None => continue,
+                }
+            } else if let Some((receiver, method)) = self.method(scope, function) {
+                let (Receiver::Instance(m, c) | Receiver::Class(m, c) | Receiver::Dynamic(m, c)) = receiver;
+                let skip = match (self.classes[m][c].methods[method].kind, receiver) {
+                    (MethodKind::Static, _) | (MethodKind::Instance, Receiver::Class(..)) => 0,
+                    _ => 1,
+                };
+                // Los tipos de una copia heredada son los del método original
+                let ((m, c), method) = self.defining((m, c), method);
+                (m, c, method, skip)
+            } else {
+                continue;
+            };
+            let params = &self.classes[m][c].methods[method].function.params;
+            for (index, arg) in args.iter().enumerate() {
+                let Some(param) = params.get(index + skip) else { break };
+                let ty = self.expr_ty(scope, arg);
+                if ty != Ty::Unknown {
+                    updates.params.push(((m, c, method, index + skip), ty));
+                }
+                // Una variable pasada a un parámetro de cualquier clase de la
+                // jerarquía pasa a guardar cualquiera de ellas
+                let param_ty = self.parse_ty(m, &param.tipo);
+                if let (Ty::Dynamic(..), Ty::Object(..), Expr::Name(name), None) = (param_ty, ty, arg, scope.class) {
+                    if self.merge(param_ty, ty) == param_ty && !scope.locals.contains_key(name) {
+                        updates.globals.push((scope.module, name.clone(), param_ty));
+                    }
+                }
+            }
+        }
+    }
+
+    // Campos, tipos de retorno y llamadas dentro de los métodos de una clase
+    fn infer_class(&mut self, module: usize, class: usize, updates: &mut Updates) {
+        // `__init__` primero: sus campos van delante. Las copias heredadas no
+        // tienen cuerpo.
+        let mut order: Vec<usize> = (0..self.classes[module][class].methods.len())
+            .filter(|&method| self.classes[module][class].methods[method].inherited.is_none())
+            .collect();
+        order.sort_by_key(|&method| self.classes[module][class].methods[method].function.name != "__init__");
+
+        // La parte heredada es el primer campo; los campos de las antecesoras
+        // se quedan en ellas
+        let mut fields: Vec<(String, Ty, usize, usize)> = Vec::new();
+        let ancestors = self.ancestors((module, class));
+        if let Some(parent) = self.parent((module, class)) {
+            let (line, column) = (self.classes[module][class].line, self.classes[module][class].column);
+            fields.push((self.parent_field(parent), Ty::Object(parent.0, parent.1), line, column));
+        }
+        let inherited = |name: &str| ancestors[1..].iter().any(|&(m, c)| self.classes[m][c].fields.iter().any(|field| field.name == name));
+        let inherited: HashSet<String> = self.classes[module][class].methods.iter()
+            .flat_map(|method| flatten(&method.function.body))
+            .filter_map(|stmt| match &stmt.kind {
+                StmtKind::Assign { target, .. } => target.split_once('.').map(|(_, field)| field.to_string()),
+                _ => None,
+            })
+            .filter(|field| inherited(field))
+            .collect();
+        let mut returns = Vec::new();
+        for method in order {
+            let mut scope = self.method_scope(module, class, method);
+            let function = self.classes[module][class].methods[method].function.clone();
+            let mut return_ty = None;
+            for stmt in flatten(&function.body) {
+                for expr in stmt_exprs(stmt) {
+                    self.call_site_types(&scope, expr, updates);
+                }
+                match &stmt.kind {
+                    StmtKind::Assign { target, value } => {
+                        let ty = self.expr_ty(&scope, value);
+                        match target.split_once('.') {
+                            Some((object, field)) if Some(object) == scope.receiver.as_deref()
+                                && scope.kind == MethodKind::Instance && !field.contains('.') && !inherited.contains(field) => {
+                                match fields.iter_mut().find(|(name, ..)| name == field) {
+                                    Some(existing) => existing.1 = self.merge(existing.1, ty),
+                                    None => fields.push((field.to_string(), ty, stmt.line, stmt.column)),
+                                }
+                            }
+                            None if !target.contains(',') => {
+                                let merged = self.merge(scope.locals.get(target).copied().unwrap_or(Ty::Unknown), ty);
+                                scope.locals.insert(target.clone(), merged);
+                            }
+                            _ => {}
+                        }
+                    }
+                    // `self.x += self.vx` con `vx` float hace float a `x`
+                    StmtKind::AugAssign { target, op, value } => {
+                        let ty = if op == "/" { Ty::Value("float") } else { self.expr_ty(&scope, value) };
+                        if !matches!(ty, Ty::Value("float")) {
+                            continue;
+                        }
+                        match target.split_once('.') {
+                            Some((object, field)) if Some(object) == scope.receiver.as_deref() => {
+                                if let Some(existing) = fields.iter_mut().find(|(name, ..)| name == field) {
+                                    existing.1 = self.merge(existing.1, ty);
+                                }
+                            }
+                            None => {
+                                if let Some(local) = scope.locals.get(target).copied() {
+                                    scope.locals.insert(target.clone(), self.merge(local, ty));
+                                }
+                            }
+                            _ => {}
+                        }
+                    }
+                    StmtKind::For { target, iter, .. } => {
+                        let ty = self.loop_ty(&scope, iter);
+                        scope.locals.insert(target.clone(), ty);
+                    }
+                    StmtKind::Return(value) => {
+                        let ty = value.as_ref().map_or(Ty::Unknown, |value| self.expr_ty(&scope, value));
+                        return_ty = Some(self.merge(return_ty.unwrap_or(Ty::Unknown), ty));
+                    }
+                    _ => {}
+                }
+            }
+            // `-> int` tiene prioridad; sin `return valor` no devuelve nada
+            let returns_value = flatten(&function.body).iter().any(|stmt| matches!(stmt.kind, StmtKind::Return(Some(_))));
+            let return_type = match (&function.returns, returns_value) {
+                (Some(annotation), _) if annotation != "None" => Some(self.ty_name(module, self.parse_ty(module, annotation))),
+                (_, true) => Some(self.ty_name(module, return_ty.unwrap_or(Ty::Unknown))),
+                _ => None,
+            };
+            // `__init__` construye la instancia y no devuelve nada
+            let return_type = return_type.filter(|_| function.name != "__init__");
+            returns.push((method, return_type));
+        }
+
+        for (method, return_type) in returns {
+            self.classes[module][class].methods[method].return_type = return_type;
+        }
+        let fields = fields.into_iter()
+            .map(|(name, ty, line, column)| Field { name, tipo: self.ty_name(module, ty), line, column })
+            .collect();
+        self.classes[module][class].fields = fields;
+    }
+
+    // Rutas que modifica un bloque: `self.x = ...`, `p.mover()` si `mover`
+    // modifica su objeto... La primera parte es la variable modificada.
+    fn mutated_roots(&self, scope: &Scope, stmts: &[Stmt]) -> HashSet<String> {
+        let mut roots = HashSet::new();
+        for stmt in flatten(stmts) {
+            if let StmtKind::Assign { target, .. } | StmtKind::AugAssign { target, .. } = &stmt.kind {
+                if target.contains('.') {
+                    roots.insert(root(target).to_string());
+                }
+            }
+            for expr in stmt_exprs(stmt) {
+                let mut found = Vec::new();
+                calls(expr, &mut found);
+                for (function, _) in found {
+                    if let Some((Receiver::Instance(m, c) | Receiver::Dynamic(m, c), method)) = self.method(scope, function) {
+                        let method = &self.classes[m][c].methods[method];
+                        if method.kind == MethodKind::Instance && method.mutates {
+                            // `super().mover()` modifica `self`
+                            match (root(function), &scope.receiver) {
+                                ("super", Some(receiver)) => roots.insert(receiver.clone()),
+                                (root, _) => roots.insert(root.to_string()),
+                            };
+                        }
+                    }
+                }
+            }
+        }
+        // `for e in self.entidades: e.update()` modifica la lista
+        for stmt in flatten(stmts).into_iter().rev() {
+            if let StmtKind::For { target, iter: Expr::Name(path) | Expr::Attr(path), .. } = &stmt.kind {
+                if roots.contains(target) {
+                    roots.insert(root(path).to_string());
+                }
+            }
+        }
+        roots
+    }
+
+    // `&mut self` en los métodos que modifican la instancia y `&mut` en los
+    // parámetros objeto que se modifican, hasta que no cambia nada. Los
+    // métodos redefinidos comparten la firma.
+    fn mark_mutations(&mut self) {
+        let groups = self.overrides();
+        loop {
+            let before = self.classes.clone();
+            for (module, class) in self.all_classes() {
+                for method in 0..self.classes[module][class].methods.len() {
+                    if self.classes[module][class].methods[method].inherited.is_some() {
+                        continue;
+                    }
+                    let body = &self.classes[module][class].methods[method].function.body;
+                    let scope = self.loop_scope(self.method_scope(module, class, method), flatten(body));
+                    let roots = self.mutated_roots(&scope, body);
+                    let method = &mut self.classes[module][class].methods[method];
+                    method.mutates = method.kind == MethodKind::Instance
+                        && scope.receiver.as_ref().is_some_and(|receiver| roots.contains(receiver));
+                    for param in &mut method.function.params {
+                        param.mutated = is_class_type(&param.tipo) && roots.contains(&param.name);
+                    }
+                }
+            }
+            for group in &groups {
+                let methods: Vec<&Method> = group.iter().map(|&((m, c), method)| &self.classes[m][c].methods[method]).collect();
+                let mutates = methods.iter().any(|method| method.mutates);
+                let mutated: Vec<bool> = (0..methods[0].function.params.len())
+                    .map(|index| methods.iter().any(|method| method.function.params[index].mutated))
+                    .collect();
+                for &((m, c), method) in group {
+                    let method = &mut self.classes[m][c].methods[method];
+                    method.mutates = mutates;
+                    for (param, mutated) in method.function.params.iter_mut().zip(&mutated) {
+                        param.mutated = *mutated;
+                    }
+                }
+            }
+            self.sync_inherited();
+            if self.classes == before {
+                break;
+            }
+        }
+    }
+
+    // `self.x` con el nombre `self` aunque el script use otro; `super()` es
+    // la parte heredada de `self`
+    fn rooted(&self, scope: &Scope, path: &str) -> String {
+        if let (Some(parent), MethodKind::Instance) = (scope.class.and_then(|class| self.parent((scope.module, class))), scope.kind) {
+            if root(path) == "super" {
+                return path.replacen("super", &format!("self.{}", self.parent_field(parent)), 1);
+            }
+        }
+        match (&scope.receiver, path.split_once('.')) {
+            (Some(receiver), Some((first, rest))) if first == receiver && scope.kind == MethodKind::Instance => format!("self.{}", rest),
+            (Some(receiver), None) if path == receiver && scope.kind == MethodKind::Instance => "self".to_string(),
+            _ => path.to_string(),
+        }
+    }
+
+    // Ruta Rust de un campo: los heredados pasan por la parte del padre
+    // (`self.sprite.x`) y los de un objeto de cualquier clase de la jerarquía
+    // por su raíz (`e.sprite().x`, o `e.sprite_mut().x` si se modifica).
+    fn field_path(&self, scope: &Scope, path: &str, target: bool) -> Option<String> {
+        let (object, name) = path.rsplit_once('.')?;
+        let Member::Field(_, access) = self.member(scope, path)? else { return None };
+        let object = self.field_path(scope, object, target).unwrap_or_else(|| self.rooted(scope, object));
+        Some(match access {
+            Access::Chain(chain) => format!("{}{}.{}", object, chain.iter().map(|field| format!(".{}", field)).collect::<String>(), name),
+            Access::Accessor(accessor) => {
+                if let Some(Receiver::Dynamic(m, c)) = self.receiver(scope, path.rsplit_once('.').map_or(path, |(object, _)| object)) {
+                    self.use_trait(scope, (m, c));
+                }
+                format!("{}.{}{}().{}", object, accessor, if target { "_mut" } else { "" }, name)
+            }
+        })
+    }
+
+    // Un objeto guardado donde va cualquier clase de su jerarquía
+    fn upcast(&self, scope: &Scope, value: Expr, from: Ty, to: Ty) -> Expr {
+        match (from, to) {
+            (Ty::Object(m, c), Ty::Dynamic(rm, rc)) if self.root_of((m, c)) == (rm, rc) => Expr::Upcast {
+                value: Box::new(value),
+                root: self.ty_name(scope.module, Ty::Object(rm, rc)),
+                class: Some(self.classes[m][c].name.clone()),
+            },
+            _ => value,
+        }
+    }
+
+    // `value` reescrito para guardarlo donde va `to`; en una lista de
+    // cualquier clase de la jerarquía, cada objeto
+    fn convert(&self, scope: &Scope, value: &Expr, to: Ty) -> Expr {
+        match (value, to) {
+            (Expr::List(items), Ty::List(m, c, true)) => Expr::List(items.iter().map(|item| self.convert(scope, item, Ty::Dynamic(m, c))).collect()),
+            _ => self.upcast(scope, self.rewrite(scope, value), self.expr_ty(scope, value), to),
+        }
+    }
+
+    fn rewrite(&self, scope: &Scope, expr: &Expr) -> Expr {
+        match expr {
+            Expr::Attr(path) => match self.member(scope, path) {
+                Some(Member::Field(..)) => Expr::Name(self.field_path(scope, path, false).unwrap_or_else(|| self.rooted(scope, path))),
+                Some(Member::Const(class, _)) => {
+                    let name = path.rsplit('.').next().unwrap_or(path);
+                    Expr::Attr(format!("{}::{}", self.rust_path(scope, class), name))
+                }
+                None => expr.clone(),
+            },
+            Expr::Call { function, args } => {
+                if let Some(check) = self.rewrite_isinstance(scope, function, args) {
+                    return check;
+                }
+                let types: Vec<Ty> = args.iter().map(|arg| self.expr_ty(scope, arg)).collect();
+                let args: Vec<Expr> = args.iter().map(|arg| self.rewrite(scope, arg)).collect();
+                // Una clase, método o función del proyecto que no se traduce
+                let linked = matches!(self.receiver(scope, function), Some(Receiver::Class(..))) || self.method(scope, function).is_some();
+                match self.rewrite_call(scope, function, &args, &types) {
+                    Some(call) => call,
+                    None if linked => Expr::Unlinked(function.clone()),
+                    None => Expr::Call { function: function.clone(), args },
+                }
+            }
+            Expr::Tuple(items) => Expr::Tuple(items.iter().map(|item| self.rewrite(scope, item)).collect()),
+            Expr::List(items) => Expr::List(items.iter().map(|item| self.rewrite(scope, item)).collect()),
+            Expr::Index { value, index } => Expr::Index {
+                value: Box::new(self.rewrite(scope, value)),
+                index: Box::new(self.rewrite(scope, index)),
+            },
+            Expr::MethodCall { receiver, method, args } => Expr::MethodCall {
+                receiver: Box::new(self.rewrite(scope, receiver)),
+                method: method.clone(),
+                args: args.iter().map(|arg| self.rewrite(scope, arg)).collect(),
+            },
+            Expr::UnaryOp { op, operand } => Expr::UnaryOp { op: op.clone(), operand: Box::new(self.rewrite(scope, operand)) },
+            Expr::Cast { value, ty } => Expr::Cast { value: Box::new(self.rewrite(scope, value)), ty: ty.clone() },
+            Expr::BinOp { op, left, right } => Expr::BinOp {
+                op: op.clone(),
+                left: Box::new(self.rewrite(scope, left)),
+                right: Box::new(self.rewrite(scope, right)),
+            },
+            _ => expr.clone(),
+        }
+    }
+
+    // `isinstance(e, Enemy)` se resuelve al traducir si se conoce la clase de
+    // `e`; si puede ser cualquiera de una jerarquía se pregunta con
+    // `e.is_instance_of("Enemy")`. Si no se sabe qué es `e`, la llamada no se
+    // traduce y el generador avisa.
+    fn rewrite_isinstance(&self, scope: &Scope, function: &str, args: &[Expr]) -> Option<Expr> {
+        let [object, classes] = args else { return None };
+        if function != "isinstance" {
+            return None;
+        }
+        let classes: Vec<&Expr> = match classes {
+            Expr::Tuple(items) => items.iter().collect(),
+            class => vec![class],
+        };
+        let classes: Vec<(usize, usize)> = classes.iter()
+            .map(|class| match class {
+                Expr::Name(path) | Expr::Attr(path) => self.find_class(scope.module, path),
+                _ => None,
+            })
+            .collect::<Option<_>>()?;
+        match self.expr_ty(scope, object) {
+            Ty::Object(m, c) => {
+                let ancestors = self.ancestors((m, c));
+                Some(Expr::Bool(classes.iter().any(|class| ancestors.contains(class))))
+            }
+            Ty::Dynamic(m, c) => {
+                self.use_trait(scope, (m, c));
+                let object = self.rewrite(scope, object);
+                classes.iter()
+                    .map(|&(n, d)| Expr::MethodCall {
+                        receiver: Box::new(object.clone()),
+                        method: "is_instance_of".to_string(),
+                        args: vec![Expr::Str(format!("\"{}\"", self.classes[n][d].name))],
+                    })
+                    .reduce(|left, right| Expr::BinOp { op: "or".to_string(), left: Box::new(left), right: Box::new(right) })
+            }
+            _ => Some(Expr::Unlinked(function.to_string())),
+        }
+    }
+
+    // `Player(1)` -> `Player::new(1)`, `p.mover(1)` -> método, `Player.crear()`
+    // -> `Player::crear()`; None si no es una llamada a una clase traducida.
+    // `types` son los tipos de los argumentos antes de reescribirlos.
+    fn rewrite_call(&self, scope: &Scope, function: &str, args: &[Expr], types: &[Ty]) -> Option<Expr> {
+        if let Some(Receiver::Class(m, c)) = self.receiver(scope, function) {
+            let class = &self.classes[m][c];
+            let args = match class.methods.iter().find(|method| method.function.name == "__init__") {
+                Some(init) if translatable(init) => self.fill_args(scope, m, receiver_params(init), args, types)?,
+                Some(_) => return None,
+                None if args.is_empty() => Vec::new(),
+                None => return None,
+            };
+            return Some(Expr::Call { function: format!("{}::new", self.rust_path(scope, (m, c))), args });
+        }
+        let (receiver, method) = self.method(scope, function)?;
+        let (Receiver::Instance(m, c) | Receiver::Class(m, c) | Receiver::Dynamic(m, c)) = receiver;
+        let method = &self.classes[m][c].methods[method];
+        if !translatable(method) {
+            return None;
+        }
+        // Lo que dibuja puede fallar: `e.draw(&mut canvas)?`
+        let call = self.rewrite_method_call(scope, function, receiver, method, args, types)?;
+        Some(if draws(method) { Expr::Try(Box::new(call)) } else { call })
+    }
+
+    // Llamada al método `method` de `receiver`, ya traducible
+    fn rewrite_method_call(&self, scope: &Scope, function: &str, receiver: Receiver, method: &Method, args: &[Expr], types: &[Ty]) -> Option<Expr> {
+        let (Receiver::Instance(m, c) | Receiver::Class(m, c) | Receiver::Dynamic(m, c)) = receiver;
+        // `doble(x)` en su módulo, `niveles::nivel1::doble(x)` desde otro
+        if c == self.functions(m) {
+            if !self.function_translatable(m, method) {
+                return None;
+            }
+            let args = self.fill_args(scope, m, receiver_params(method), args, types)?;
+            let name = rust_ident(&method.function.name);
+            let path = if m == scope.module { name } else { imports::member(self.names[m], &name).replace('.', "::") };
+            return Some(Expr::Call { function: path, args });
+        }
+        let (object, name) = function.rsplit_once('.')?;
+        // Los métodos despachados de una subclase están en el trait de la raíz
+        let root = self.root_of((m, c));
+        match receiver {
+            Receiver::Dynamic(..) if !self.is_dispatched(root, name) => return None,
+            Receiver::Dynamic(..) => self.use_trait(scope, root),
+            Receiver::Instance(..) if root != (m, c) && self.is_dispatched(root, name) => self.use_trait(scope, root),
+            _ => {}
+        }
+        let args = self.fill_args(scope, m, receiver_params(method), args, types)?;
+        match (method.kind, receiver) {
+            (MethodKind::Instance, Receiver::Instance(..) | Receiver::Dynamic(..)) => Some(Expr::MethodCall {
+                receiver: Box::new(Expr::Name(self.field_path(scope, object, method.mutates).unwrap_or_else(|| self.rooted(scope, object)))),
+                method: rust_ident(name),
+                args,
+            }),
+            (MethodKind::Instance, _) | (_, Receiver::Dynamic(..)) => None,
+            _ => Some(Expr::Call { function: format!("{}::{}", self.rust_path(scope, (m, c)), rust_ident(name)), args }),
+        }
+    }
+
+    // Argumentos completados con los valores por defecto; los objetos se
+    // pasan por referencia. `module` es el de la clase del método.
+    fn fill_args(&self, scope: &Scope, module: usize, params: &[Param], args: &[Expr], types: &[Ty]) -> Option<Vec<Expr>> {
+        if args.len() > params.len() {
+            return None;
+        }
+        params.iter().enumerate().map(|(index, param)| {
+            let arg = match args.get(index) {
+                Some(arg) => arg.clone(),
+                None => param.default.clone()?,
+            };
+            // El renderer del script es el canvas; el de un método, su parámetro
+            if let (Expr::Name(name), "renderer") = (&arg, param.tipo.as_str()) {
+                if !scope.locals.contains_key(name) {
+                    return Some(Expr::UnaryOp { op: "&mut ".to_string(), operand: Box::new(Expr::Name("canvas".to_string())) });
+                }
+            }
+            if !is_class_type(&param.tipo) || arg == Expr::Name("self".to_string()) {
+                return Some(arg);
+            }
+            // Un objeto para un parámetro de cualquier clase de su jerarquía;
+            // las variables se prestan tal cual y las que ya guardan cualquiera
+            // de ellas se prestan sin su `Box`
+            let ty = types.get(index).copied().unwrap_or(Ty::Unknown);
+            let arg = match (arg, ty) {
+                (arg @ Expr::Name(_), Ty::Dynamic(m, c)) => Expr::Upcast {
+                    value: Box::new(arg),
+                    root: self.ty_name(scope.module, Ty::Object(m, c)),
+                    class: None,
+                },
+                (arg @ Expr::Name(_), _) => arg,
+                (arg, ty) => self.upcast(scope, arg, ty, self.parse_ty(module, &param.tipo)),
+            };
+            let op = if param.mutated { "&mut " } else { "&" };
+            Some(Expr::UnaryOp { op: op.to_string(), operand: Box::new(arg) })
+        }).collect()
+    }
+
+    // `super().__init__(x)` construye la parte heredada: `self.sprite = Sprite::new(x)`
+    fn rewrite_super_init(&self, scope: &Scope, args: &[Expr]) -> Option<StmtKind> {
+        let parent = self.parent((scope.module, scope.class?))?;
+        let types: Vec<Ty> = args.iter().map(|arg| self.expr_ty(scope, arg)).collect();
+        let args: Vec<Expr> = args.iter().map(|arg| self.rewrite(scope, arg)).collect();
+        let value = self.rewrite_call(scope, &self.ty_name(scope.module, Ty::Object(parent.0, parent.1)), &args, &types)?;
+        Some(StmtKind::Assign { target: format!("self.{}", self.parent_field(parent)), value })
+    }
+
+    // Tipo deducido de una variable local en el ámbito
+    fn assign_local(&self, scope: &mut Scope, target: &str, value: &Expr) {
+        let ty = self.merge(scope.locals.get(target).copied().unwrap_or(Ty::Unknown), self.expr_ty(scope, value));
+        scope.locals.insert(target.to_string(), ty);
+    }
+
+    fn rewrite_stmts(&self, scope: &mut Scope, stmts: &[Stmt]) -> Vec<Stmt> {
+        stmts.iter().map(|stmt| {
+            let kind = match &stmt.kind {
+                StmtKind::Assign { target, value } => {
+                    if !target.contains(['.', ',']) && scope.class.is_some() {
+                        self.assign_local(scope, target, value);
+                    }
+                    let to = match self.member(scope, target) {
+                        Some(Member::Field(ty, _)) => ty,
+                        _ if target.contains('.') => Ty::Unknown,
+                        _ => self.name_ty(scope, target),
+                    };
+                    let rewritten = match (to, self.convert(scope, value, to)) {
+                        // `self.x = 0` en un campo heredado `f64`
+                        (Ty::Value("float"), Expr::Int(n)) if target.contains('.') => Expr::Float(n as f64),
+                        (_, rewritten) => rewritten,
+                    };
+                    let target = self.field_path(scope, target, true).unwrap_or_else(|| self.rooted(scope, target));
+                    StmtKind::Assign { target, value: rewritten }
+                }
+                StmtKind::AugAssign { target, op, value } => StmtKind::AugAssign {
+                    target: self.field_path(scope, target, true).unwrap_or_else(|| self.rooted(scope, target)),
+                    op: op.clone(),
+                    value: self.rewrite(scope, value),
+                },
+                StmtKind::If { condition, body, orelse } => StmtKind::If {
+                    condition: self.rewrite(scope, condition),
+                    body: self.rewrite_stmts(scope, body),
+                    orelse: self.rewrite_stmts(scope, orelse),
+                },
+                StmtKind::Expr(expr @ Expr::Call { function, args }) if function == "super.__init__" => {
+                    self.rewrite_super_init(scope, args).unwrap_or_else(|| StmtKind::Expr(self.rewrite(scope, expr)))
+                }
+                StmtKind::Expr(expr) => StmtKind::Expr(self.rewrite(scope, expr)),
+                StmtKind::Return(value) => StmtKind::Return(value.as_ref().map(|value| self.convert(scope, value, scope.returns))),
+                // Una lista de objetos se recorre por referencia, con `iter_mut()`
+                // si el cuerpo los modifica
+                StmtKind::For { target, iter, body } => {
+                    let ty = self.loop_ty(scope, iter);
+                    let shadowed = scope.locals.insert(target.clone(), ty);
+                    let iter = match ty {
+                        Ty::Object(..) | Ty::Dynamic(..) => Expr::MethodCall {
+                            receiver: Box::new(self.rewrite(scope, iter)),
+                            method: if self.mutated_roots(scope, body).contains(target) { "iter_mut" } else { "iter" }.to_string(),
+                            args: Vec::new(),
+                        },
+                        _ => self.rewrite(scope, iter),
+                    };
+                    let body = self.rewrite_stmts(scope, body);
+                    match shadowed {
+                        Some(ty) => scope.locals.insert(target.clone(), ty),
+                        None => scope.locals.remove(target),
+                    };
+                    StmtKind::For { target: target.clone(), iter, body }
+                }
+                kind => kind.clone(),
+            };
+            Stmt { kind, line: stmt.line, column: stmt.column }
+        }).collect()
+    }
+
+    // Sentencias, variables y llamadas de dibujo del nivel superior
+    fn rewrite_state(&self, module: usize, state: &mut State) {
+        let mut scope = Scope::module(module);
+        for var in &mut state.variables {
+            var.value = var.value.as_ref().map(|value| self.convert(&scope, value, self.parse_ty(module, &var.tipo)));
+        }
+        // Las llamadas de dibujo de un `for` usan su variable
+        let loops = self.loop_scope(Scope::module(module), state.statements());
+        for call in &mut state.draw_calls {
+            call.args = call.args.iter().map(|arg| self.rewrite(&loops, arg)).collect();
+            call.kwargs = call.kwargs.iter().map(|(name, value)| (name.clone(), self.rewrite(&loops, value))).collect();
+        }
+        state.setup = self.rewrite_stmts(&mut scope, &state.setup);
+        state.loop_body = self.rewrite_stmts(&mut scope, &state.loop_body);
+        if let Some(events) = state.events.as_mut() {
+            for handler in &mut events.handlers {
+                handler.guard = handler.guard.as_ref().map(|guard| self.rewrite(&scope, guard));
+                handler.body = self.rewrite_stmts(&mut scope, &handler.body);
+            }
+        }
+    }
+
+    fn rewrite_class(&mut self, module: usize, class: usize) {
+        for method in 0..self.classes[module][class].methods.len() {
+            let mut scope = self.method_scope(module, class, method);
+            let body = &self.classes[module][class].methods[method].function.body;
+            // Las variables locales con el tipo de todas sus asignaciones:
+            // `e = Enemy()` y después `e = Boss()` guarda cualquiera de las dos
+            for stmt in flatten(body) {
+                if let StmtKind::Assign { target, value } = &stmt.kind {
+                    if !target.contains(['.', ',']) {
+                        self.assign_local(&mut scope, target, value);
+                    }
+                }
+            }
+            let body = self.rewrite_stmts(&mut scope, body);
+            self.classes[module][class].methods[method].function.body = body;
+        }
+        let scope = Scope::module(module);
+        for attribute in 0..self.classes[module][class].attributes.len() {
+            let value = self.rewrite(&scope, &self.classes[module][class].attributes[attribute].value);
+            self.classes[module][class].attributes[attribute].value = value;
+        }
+    }
+
+    // Jerarquía de cada clase que hereda o de la que heredan, para el generador
+    fn describe_hierarchies(&mut self) {
+        for (m, c) in self.all_classes() {
+            if !self.in_hierarchy((m, c)) {
+                continue;
+            }
+            let root = self.root_of((m, c));
+            let ancestors = |class| -> Vec<String> {
+                self.ancestors(class).iter().map(|&(n, d)| self.classes[n][d].name.clone()).collect()
+            };
+            let members = if root == (m, c) {
+                self.all_classes().into_iter()
+                    .filter(|&class| self.root_of(class) == root)
+                    .map(|(n, d)| {
+                        let name = &self.classes[n][d].name;
+                        let path = if n == m { name.clone() } else { format!("crate::{}", imports::member(self.names[n], name).replace('.', "::")) };
+                        HierarchyMember { path, ancestors: ancestors((n, d)) }
+                    })
+                    .collect()
+            } else {
+                Vec::new()
+            };
+            let hierarchy = Hierarchy {
+                root: self.ty_name(m, Ty::Object(root.0, root.1)),
+                parent: self.parent((m, c)).map(|(n, d)| self.ty_name(m, Ty::Object(n, d))),
+                ancestors: ancestors((m, c)),
+                methods: self.dispatched.get(&root).cloned().unwrap_or_default(),
+                members,
+            };
+            self.classes[m][c].hierarchy = Some(hierarchy);
+        }
+    }
+
+    // Raíces de otros módulos cuyo trait necesita `module`: las de sus
+    // subclases y las de los métodos despachados que llama
+    fn hierarchies(&self, module: usize) -> Vec<String> {
+        let mut roots: Vec<(usize, usize)> = self.traits.borrow()[module].iter().copied().collect();
+        roots.extend((0..self.classes[module].len()).map(|c| self.root_of((module, c))).filter(|root| root.0 != module));
+        roots.sort();
+        roots.dedup();
+        roots.iter().map(|&(m, c)| imports::member(self.names[m], &self.classes[m][c].name)).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::extract_state;
+    use crate::tokenizer::tokenize;
+    use crate::types::StmtKind;
+
+    #[test]
+    fn test_fields_and_receivers() {
+        let code = "class Pelota:\n    RADIO = 10\n    def __init__(self, x, vx=1.5):\n        self.x = x\n        self.vx = vx\n    def mover(self):\n        self.x += self.vx\n    def radio(self):\n        return Pelota.RADIO\np = Pelota(3)\nwhile running:\n    p.mover()\n";
+        let state = extract_state(tokenize(code));
+
+        let class = &state.classes[0];
+        let fields: Vec<(&str, &str)> = class.fields.iter().map(|f| (f.name.as_str(), f.tipo.as_str())).collect();
+        assert_eq!(fields, vec![("x", "float"), ("vx", "float")]);
+        assert_eq!(class.attributes[0].tipo, "int");
+        assert!(class.methods[1].mutates);
+        assert!(!class.methods[2].mutates);
+        assert_eq!(class.methods[2].return_type.as_deref(), Some("int"));
+        assert!(matches!(&class.methods[2].function.body[0].kind, StmtKind::Return(Some(Expr::Attr(path))) if path == "Self::RADIO"));
+
+        assert_eq!(state.variables[0].tipo, "Pelota");
+        assert_eq!(state.variables[0].value, Some(Expr::Call {
+            function: "Pelota::new".to_string(),
+            args: vec![Expr::Int(3), Expr::Float(1.5)],
+        }));
+        assert!(matches!(&state.loop_body[0].kind, StmtKind::Expr(Expr::MethodCall { method, .. }) if method == "mover"));
+        assert_eq!(rust_ident("move"), "r#move");
+    }
+
+    #[test]
+    fn test_single_inheritance() {
+        let code = "class Sprite:\n    def __init__(self, x):\n        self.x = x\n    def update(self):\n        self.x += 1\nclass Enemy(Sprite):\n    def __init__(self, x, vida):\n        super().__init__(x)\n        self.vida = vida\n    def update(self):\n        self.x -= self.vida\nclass Boss(Enemy):\n    pass\ne = Enemy(1, 3)\nb = Boss(2, 5)\nif e.vida > 0:\n    e = Sprite(0)\nwhile running:\n    e.update()\n    if isinstance(e, Enemy):\n        b.x = 0\n";
+        let state = extract_state(tokenize(code));
+
+        let enemy = &state.classes[1];
+        let fields: Vec<(&str, &str)> = enemy.fields.iter().map(|f| (f.name.as_str(), f.tipo.as_str())).collect();
+        assert_eq!(fields, vec![("sprite", "Sprite"), ("vida", "int")]);
+        assert!(matches!(&enemy.methods[0].function.body[0].kind, StmtKind::Assign { target, .. } if target == "self.sprite"));
+        assert!(matches!(&enemy.methods[1].function.body[0].kind, StmtKind::AugAssign { target, .. } if target == "self.sprite.x"));
+        let boss = &state.classes[2];
+        assert_eq!(boss.methods[1].inherited.as_deref(), Some("Enemy"));
+        let hierarchy = boss.hierarchy.as_ref().unwrap();
+        assert_eq!(hierarchy.ancestors, vec!["Boss", "Enemy", "Sprite"]);
+        assert_eq!(hierarchy.methods, vec!["update"]);
+
+        assert_eq!(state.variables[0].tipo, "dyn Sprite");
+        assert_eq!(state.variables[1].tipo, "Boss");
+        assert!(matches!(&state.loop_body[1].kind, StmtKind::If { condition: Expr::MethodCall { method, .. }, .. } if method == "is_instance_of"));
+        assert!(matches!(&state.loop_body[1].kind, StmtKind::If { body, .. }
+            if matches!(&body[0].kind, StmtKind::Assign { target, .. } if target == "b.enemy.sprite.x")));
+    }
+}
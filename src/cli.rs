@@ -0,0 +1,163 @@
+#[derive(Debug, PartialEq)]
+pub enum Command {
+    Compile(String),
+    ShowConfig(Option<String>),
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Cli {
+    pub command: Command,
+    pub config_path: Option<String>,
+    pub overrides: Vec<(String, String)>,
+}
+
+pub const USAGE: &str = "Uso: py2rust <archivo.py|directorio> [opciones]
+       py2rust config --show [archivo.py] [opciones]
+
+Opciones:
+  --config <ruta>          Usa este py2rust.toml en lugar de buscarlo
+  --backend <nombre>       target.backend
+  --sdl2-version <v>       dependencies.sdl2_version
+  --sdl2-features <a,b>    dependencies.sdl2_features
+  --sdl2-link <modo>       dependencies.sdl2_link (bundled, pkg-config, system)
+  --offline                build.offline: cargo sin acceso a la red
+  --vendor-dir <dir>       build.vendor_dir: directorio de `cargo vendor`
+  --registry-mirror <url>  build.registry_mirror: espejo del registro
+  --int-type <i32|i64>     integers.type
+  --overflow <modo>        integers.overflow (wrapping, checked)
+  --fps <n>                frame.fps (0 = sin límite)
+  --frame-limiter <modo>   frame.limiter si el script no limita los fotogramas (fixed, vsync, none)
+  --naming <estilo>        naming.variables (preserve, snake_case)
+  --inheritance <modo>     classes.inheritance: herencia como trait o enum (trait, enum)
+  --allow/--warn/--deny <lints>
+  --no-escape-quit         events.escape_quits = false
+  --audio-driver <nombre>  audio.driver: SDL_AUDIODRIVER por defecto (p. ej. dummy)
+  --line-comments          output.line_comments: `// py: archivo:línea` en el código
+  --no-source-map          output.source_map = false
+  --rustfmt                output.rustfmt: pasa el resultado por rustfmt si está instalado
+  --instrument             instrument.enabled: medición por frame (feature `instrument`)
+  --lang <es|en>           messages.language
+  --set <clave>=<valor>    Cualquier opción de py2rust.toml";
+
+pub fn parse_args(args: &[String]) -> Result<Cli, String> {
+    let mut positional = Vec::new();
+    let mut config_path = None;
+    let mut overrides = Vec::new();
+    let mut show = false;
+
+    let mut iter = args.iter().skip(1);
+    while let Some(arg) = iter.next() {
+        let key = match arg.as_str() {
+            "--config" => {
+                config_path = Some(flag_value(arg, iter.next())?);
+                continue;
+            }
+            "--show" => {
+                show = true;
+                continue;
+            }
+            "--offline" => {
+                overrides.push(("build.offline".to_string(), "true".to_string()));
+                continue;
+            }
+            "--line-comments" => {
+                overrides.push(("output.line_comments".to_string(), "true".to_string()));
+                continue;
+            }
+            "--instrument" => {
+                overrides.push(("instrument.enabled".to_string(), "true".to_string()));
+                continue;
+            }
+            "--rustfmt" => {
+                overrides.push(("output.rustfmt".to_string(), "true".to_string()));
+                continue;
+            }
+            "--no-source-map" => {
+                overrides.push(("output.source_map".to_string(), "false".to_string()));
+                continue;
+            }
+            "--no-escape-quit" => {
+                overrides.push(("events.escape_quits".to_string(), "false".to_string()));
+                continue;
+            }
+            "--set" => {
+                let pair = flag_value(arg, iter.next())?;
+                let (key, value) = pair
+                    .split_once('=')
+                    .ok_or_else(|| format!("Se esperaba <clave>=<valor> en --set: {}", pair))?;
+                overrides.push((key.trim().to_string(), value.trim().to_string()));
+                continue;
+            }
+            "--backend" => "target.backend",
+            "--sdl2-version" => "dependencies.sdl2_version",
+            "--sdl2-features" => "dependencies.sdl2_features",
+            "--sdl2-link" => "dependencies.sdl2_link",
+            "--vendor-dir" => "build.vendor_dir",
+            "--registry-mirror" => "build.registry_mirror",
+            "--int-type" => "integers.type",
+            "--overflow" => "integers.overflow",
+            "--fps" => "frame.fps",
+            "--frame-limiter" => "frame.limiter",
+            "--inheritance" => "classes.inheritance",
+            "--naming" => "naming.variables",
+            "--allow" => "lints.allow",
+            "--warn" => "lints.warn",
+            "--deny" => "lints.deny",
+            "--audio-driver" => "audio.driver",
+            "--lang" => "messages.language",
+            other if other.starts_with("--") => {
+                return Err(format!("Opción desconocida: {}", other));
+            }
+            _ => {
+                positional.push(arg.clone());
+                continue;
+            }
+        };
+        overrides.push((key.to_string(), flag_value(arg, iter.next())?));
+    }
+
+    let command = match positional.first().map(|s| s.as_str()) {
+        Some("config") => {
+            if !show {
+                return Err("Uso: py2rust config --show [archivo.py]".to_string());
+            }
+            Command::ShowConfig(positional.get(1).cloned())
+        }
+        Some(input) if positional.len() == 1 => Command::Compile(input.to_string()),
+        _ => return Err(USAGE.to_string()),
+    };
+
+    Ok(Cli { command, config_path, overrides })
+}
+
+fn flag_value(flag: &str, value: Option<&String>) -> Result<String, String> {
+    value
+        .cloned()
+        .ok_or_else(|| format!("Falta el valor de {}", flag))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(list: &[&str]) -> Vec<String> {
+        std::iter::once("py2rust").chain(list.iter().copied()).map(String::from).collect()
+    }
+
+    #[test]
+    fn test_flags_become_overrides() {
+        let cli = parse_args(&args(&["juego.py", "--fps", "30", "--set", "naming.variables=snake_case"])).unwrap();
+        assert_eq!(cli.command, Command::Compile("juego.py".to_string()));
+        assert_eq!(cli.overrides, vec![
+            ("frame.fps".to_string(), "30".to_string()),
+            ("naming.variables".to_string(), "snake_case".to_string()),
+        ]);
+    }
+
+    #[test]
+    fn test_config_show() {
+        let cli = parse_args(&args(&["config", "--show"])).unwrap();
+        assert_eq!(cli.command, Command::ShowConfig(None));
+        assert!(parse_args(&args(&["config"])).is_err());
+    }
+}
@@ -0,0 +1,465 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+pub const CONFIG_FILE_NAME: &str = "py2rust.toml";
+
+// Mapeo de un módulo Python (`[modules.random]`): el crate que lo sustituye y
+// reglas declarativas para sus llamadas y constantes, ver `mappings.rs`.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ModuleMapping {
+    // None si basta con std (o con el crate del mapeo incorporado)
+    pub crate_name: Option<String>,
+    pub version: Option<String>,
+    pub features: Vec<String>,
+    // `use` que necesitan las traducciones, como `rand::Rng`
+    pub uses: Vec<String>,
+    // Función del módulo y plantilla Rust con `{0}`, `{1}`... (`{args}` son todos)
+    pub calls: BTreeMap<String, String>,
+    pub constants: BTreeMap<String, String>,
+    // Funciones que devuelven un float, para los literales que las acompañan
+    pub floats: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Config {
+    pub backend: String,
+    pub sdl2_version: String,
+    pub sdl2_features: Vec<String>,
+    pub sdl2_link: String,
+    pub offline: bool,
+    pub vendor_dir: Option<PathBuf>,
+    pub registry_mirror: Option<String>,
+    pub int_type: String,
+    pub overflow: String,
+    pub fps: u32,
+    pub frame_limiter: String,
+    pub naming_variables: String,
+    pub lints_allow: Vec<String>,
+    pub lints_warn: Vec<String>,
+    pub lints_deny: Vec<String>,
+    pub escape_quits: bool,
+    pub audio_driver: Option<String>,
+    pub source_map: bool,
+    pub line_comments: bool,
+    pub rustfmt: bool,
+    pub instrument: bool,
+    pub instrument_log: bool,
+    // Herencia como trait con métodos por defecto ("trait") o enum cerrado ("enum")
+    pub inheritance: String,
+    pub language: String,
+    pub modules: BTreeMap<String, ModuleMapping>,
+    pub source: Option<PathBuf>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            backend: "sdl2".to_string(),
+            sdl2_version: "0.35".to_string(),
+            sdl2_features: Vec::new(),
+            sdl2_link: "bundled".to_string(),
+            offline: false,
+            vendor_dir: None,
+            registry_mirror: None,
+            int_type: "i32".to_string(),
+            overflow: "wrapping".to_string(),
+            fps: 60,
+            frame_limiter: "fixed".to_string(),
+            naming_variables: "preserve".to_string(),
+            lints_allow: Vec::new(),
+            lints_warn: Vec::new(),
+            lints_deny: Vec::new(),
+            escape_quits: true,
+            audio_driver: None,
+            source_map: true,
+            line_comments: false,
+            rustfmt: false,
+            instrument: false,
+            instrument_log: true,
+            inheritance: "trait".to_string(),
+            language: "es".to_string(),
+            modules: BTreeMap::new(),
+            source: None,
+        }
+    }
+}
+
+impl Config {
+    // Busca `py2rust.toml` junto al archivo de entrada o en sus directorios padre.
+    pub fn discover(input_path: &Path) -> Option<PathBuf> {
+        let start = input_path
+            .canonicalize()
+            .unwrap_or_else(|_| input_path.to_path_buf());
+        let mut dir = if start.is_dir() { Some(start.as_path()) } else { start.parent() };
+        while let Some(current) = dir {
+            let candidate = current.join(CONFIG_FILE_NAME);
+            if candidate.is_file() {
+                return Some(candidate);
+            }
+            dir = current.parent();
+        }
+        None
+    }
+
+    pub fn load(path: &Path) -> Result<Config, String> {
+        let text = fs::read_to_string(path)
+            .map_err(|e| format!("Error al leer {}: {}", path.display(), e))?;
+        let mut config = Config::from_toml(&text)
+            .map_err(|e| format!("{}: {}", path.display(), e))?;
+        // Las rutas relativas del archivo se interpretan desde su directorio
+        if let (Some(vendor), Some(dir)) = (&config.vendor_dir, path.parent()) {
+            if vendor.is_relative() {
+                config.vendor_dir = Some(dir.join(vendor));
+            }
+        }
+        config.source = Some(path.to_path_buf());
+        Ok(config)
+    }
+
+    pub fn from_toml(text: &str) -> Result<Config, String> {
+        let table: toml::Table = text
+            .parse()
+            .map_err(|e| format!("TOML inválido: {}", e))?;
+        let mut config = Config::default();
+
+        for (section, value) in &table {
+            let entries = value
+                .as_table()
+                .ok_or_else(|| format!("Se esperaba una sección [{}]", section))?;
+
+            if section == "modules" {
+                for (module, mapping) in entries {
+                    config.modules.insert(module.clone(), parse_module_mapping(module, mapping)?);
+                }
+                continue;
+            }
+
+            for (key, value) in entries {
+                config.set(&format!("{}.{}", section, key), &value_to_string(value)?)?;
+            }
+        }
+
+        Ok(config)
+    }
+
+    // Asigna una opción usando su clave con puntos (`frame.fps`), tal como
+    // aparece en el archivo o en `--set`.
+    pub fn set(&mut self, key: &str, value: &str) -> Result<(), String> {
+        match key {
+            "target.backend" => {
+                if value != "sdl2" {
+                    return Err(format!("Backend no soportado: {}", value));
+                }
+                self.backend = value.to_string();
+            }
+            "dependencies.sdl2_version" => self.sdl2_version = value.to_string(),
+            "dependencies.sdl2_features" => self.sdl2_features = split_list(value),
+            "dependencies.sdl2_link" => {
+                if !["bundled", "pkg-config", "system"].contains(&value) {
+                    return Err(format!("Modo de enlace de SDL2 no soportado: {}", value));
+                }
+                self.sdl2_link = value.to_string();
+            }
+            "build.offline" => self.offline = parse_bool(key, value)?,
+            "build.vendor_dir" => self.vendor_dir = non_empty(value).map(PathBuf::from),
+            "build.registry_mirror" => self.registry_mirror = non_empty(value).map(String::from),
+            "integers.type" => {
+                if !["i32", "i64"].contains(&value) {
+                    return Err(format!("Tipo entero no soportado: {}", value));
+                }
+                self.int_type = value.to_string();
+            }
+            "integers.overflow" => {
+                if !["wrapping", "checked"].contains(&value) {
+                    return Err(format!("Semántica de desbordamiento no soportada: {}", value));
+                }
+                self.overflow = value.to_string();
+            }
+            "frame.fps" => {
+                self.fps = value
+                    .parse()
+                    .map_err(|_| format!("Valor de FPS inválido: {}", value))?;
+            }
+            "frame.limiter" => {
+                if !["fixed", "vsync", "none"].contains(&value) {
+                    return Err(format!("Limitador de fotogramas no soportado: {}", value));
+                }
+                self.frame_limiter = value.to_string();
+            }
+            "naming.variables" => {
+                if !["preserve", "snake_case"].contains(&value) {
+                    return Err(format!("Convención de nombres no soportada: {}", value));
+                }
+                self.naming_variables = value.to_string();
+            }
+            "lints.allow" => self.lints_allow = split_list(value),
+            "lints.warn" => self.lints_warn = split_list(value),
+            "lints.deny" => self.lints_deny = split_list(value),
+            "events.escape_quits" => self.escape_quits = parse_bool(key, value)?,
+            "audio.driver" => self.audio_driver = non_empty(value).map(String::from),
+            "output.source_map" => self.source_map = parse_bool(key, value)?,
+            "output.line_comments" => self.line_comments = parse_bool(key, value)?,
+            "output.rustfmt" => self.rustfmt = parse_bool(key, value)?,
+            "instrument.enabled" => self.instrument = parse_bool(key, value)?,
+            "instrument.log" => self.instrument_log = parse_bool(key, value)?,
+            "classes.inheritance" => {
+                if !["trait", "enum"].contains(&value) {
+                    return Err(format!("Modo de herencia no soportado: {}", value));
+                }
+                self.inheritance = value.to_string();
+            }
+            "messages.language" => {
+                if !["es", "en"].contains(&value) {
+                    return Err(format!("Idioma no soportado: {}", value));
+                }
+                self.language = value.to_string();
+            }
+            _ => return Err(format!("Opción de configuración desconocida: {}", key)),
+        }
+        Ok(())
+    }
+
+    // Features efectivas del crate sdl2: las configuradas más la que exige el modo de enlace.
+    pub fn sdl2_crate_features(&self) -> Vec<String> {
+        let mut features: Vec<String> = self
+            .sdl2_features
+            .iter()
+            .filter(|f| !["bundled", "use-pkgconfig"].contains(&f.as_str()))
+            .cloned()
+            .collect();
+        match self.sdl2_link.as_str() {
+            "bundled" => features.insert(0, "bundled".to_string()),
+            "pkg-config" => features.insert(0, "use-pkgconfig".to_string()),
+            _ => {}
+        }
+        features
+    }
+
+    // Elige el mensaje según `messages.language`.
+    pub fn msg<'a>(&self, es: &'a str, en: &'a str) -> &'a str {
+        if self.language == "en" { en } else { es }
+    }
+
+    // Nombre Rust para una variable de usuario según `naming.variables`.
+    pub fn variable_name(&self, name: &str) -> String {
+        if self.naming_variables == "snake_case" {
+            to_snake_case(name)
+        } else {
+            name.to_string()
+        }
+    }
+
+    pub fn to_toml(&self) -> String {
+        let mut out = String::new();
+        if let Some(source) = &self.source {
+            out.push_str(&format!("# Cargado desde {}\n\n", source.display()));
+        }
+        out.push_str("[target]\n");
+        out.push_str(&format!("backend = {}\n\n", quote(&self.backend)));
+        out.push_str("[dependencies]\n");
+        out.push_str(&format!("sdl2_version = {}\n", quote(&self.sdl2_version)));
+        out.push_str(&format!("sdl2_features = {}\n", quote_list(&self.sdl2_features)));
+        out.push_str(&format!("sdl2_link = {}\n\n", quote(&self.sdl2_link)));
+        out.push_str("[build]\n");
+        out.push_str(&format!("offline = {}\n", self.offline));
+        let vendor_dir = self.vendor_dir.as_ref().map(|p| p.display().to_string()).unwrap_or_default();
+        out.push_str(&format!("vendor_dir = {}\n", quote(&vendor_dir)));
+        out.push_str(&format!("registry_mirror = {}\n\n", quote(self.registry_mirror.as_deref().unwrap_or(""))));
+        out.push_str("[integers]\n");
+        out.push_str(&format!("type = {}\n", quote(&self.int_type)));
+        out.push_str(&format!("overflow = {}\n\n", quote(&self.overflow)));
+        out.push_str("[frame]\n");
+        out.push_str(&format!("fps = {}\n", self.fps));
+        out.push_str(&format!("limiter = {}\n\n", quote(&self.frame_limiter)));
+        out.push_str("[naming]\n");
+        out.push_str(&format!("variables = {}\n\n", quote(&self.naming_variables)));
+        out.push_str("[lints]\n");
+        out.push_str(&format!("allow = {}\n", quote_list(&self.lints_allow)));
+        out.push_str(&format!("warn = {}\n", quote_list(&self.lints_warn)));
+        out.push_str(&format!("deny = {}\n\n", quote_list(&self.lints_deny)));
+        out.push_str("[events]\n");
+        out.push_str(&format!("escape_quits = {}\n\n", self.escape_quits));
+        out.push_str("[audio]\n");
+        out.push_str(&format!("driver = {}\n\n", quote(self.audio_driver.as_deref().unwrap_or(""))));
+        out.push_str("[output]\n");
+        out.push_str(&format!("source_map = {}\n", self.source_map));
+        out.push_str(&format!("line_comments = {}\n", self.line_comments));
+        out.push_str(&format!("rustfmt = {}\n\n", self.rustfmt));
+        out.push_str("[instrument]\n");
+        out.push_str(&format!("enabled = {}\n", self.instrument));
+        out.push_str(&format!("log = {}\n\n", self.instrument_log));
+        out.push_str("[classes]\n");
+        out.push_str(&format!("inheritance = {}\n\n", quote(&self.inheritance)));
+        out.push_str("[messages]\n");
+        out.push_str(&format!("language = {}\n", quote(&self.language)));
+        for (module, mapping) in &self.modules {
+            out.push_str(&format!("\n[modules.{}]\n", module));
+            if let Some(crate_name) = &mapping.crate_name {
+                out.push_str(&format!("crate = {}\n", quote(crate_name)));
+            }
+            if let Some(version) = &mapping.version {
+                out.push_str(&format!("version = {}\n", quote(version)));
+            }
+            out.push_str(&format!("features = {}\n", quote_list(&mapping.features)));
+            out.push_str(&format!("uses = {}\n", quote_list(&mapping.uses)));
+            out.push_str(&format!("floats = {}\n", quote_list(&mapping.floats)));
+            for (table, rules) in [("calls", &mapping.calls), ("constants", &mapping.constants)] {
+                if !rules.is_empty() {
+                    out.push_str(&format!("\n[modules.{}.{}]\n", module, table));
+                    for (name, template) in rules {
+                        out.push_str(&format!("{} = {}\n", name, quote(template)));
+                    }
+                }
+            }
+        }
+        out
+    }
+}
+
+fn parse_module_mapping(module: &str, value: &toml::Value) -> Result<ModuleMapping, String> {
+    let table = value
+        .as_table()
+        .ok_or_else(|| format!("Se esperaba una tabla en [modules.{}]", module))?;
+    let mut mapping = ModuleMapping::default();
+    for (key, value) in table {
+        match key.as_str() {
+            "crate" => mapping.crate_name = Some(value_to_string(value)?),
+            "version" => mapping.version = Some(value_to_string(value)?),
+            "features" => mapping.features = split_list(&value_to_string(value)?),
+            "uses" => mapping.uses = split_list(&value_to_string(value)?),
+            "floats" => mapping.floats = split_list(&value_to_string(value)?),
+            "calls" | "constants" => {
+                let rules = value
+                    .as_table()
+                    .ok_or_else(|| format!("Se esperaba una tabla en [modules.{}.{}]", module, key))?;
+                for (name, template) in rules {
+                    let template = template
+                        .as_str()
+                        .ok_or_else(|| format!("Se esperaba una plantilla de texto en modules.{}.{}.{}", module, key, name))?;
+                    let target = if key == "calls" { &mut mapping.calls } else { &mut mapping.constants };
+                    target.insert(name.clone(), template.to_string());
+                }
+            }
+            _ => return Err(format!("Opción de configuración desconocida: modules.{}.{}", module, key)),
+        }
+    }
+    Ok(mapping)
+}
+
+fn value_to_string(value: &toml::Value) -> Result<String, String> {
+    match value {
+        toml::Value::String(s) => Ok(s.clone()),
+        toml::Value::Integer(i) => Ok(i.to_string()),
+        toml::Value::Boolean(b) => Ok(b.to_string()),
+        toml::Value::Array(items) => {
+            let parts: Result<Vec<String>, String> = items.iter().map(value_to_string).collect();
+            Ok(parts?.join(","))
+        }
+        other => Err(format!("Valor no soportado: {}", other)),
+    }
+}
+
+fn split_list(value: &str) -> Vec<String> {
+    value
+        .split(',')
+        .map(|s| s.trim())
+        .filter(|s| !s.is_empty())
+        .map(|s| s.to_string())
+        .collect()
+}
+
+fn parse_bool(key: &str, value: &str) -> Result<bool, String> {
+    match value {
+        "true" => Ok(true),
+        "false" => Ok(false),
+        _ => Err(format!("Se esperaba true o false en {}: {}", key, value)),
+    }
+}
+
+fn non_empty(value: &str) -> Option<&str> {
+    if value.is_empty() { None } else { Some(value) }
+}
+
+fn quote(value: &str) -> String {
+    format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+fn quote_list(values: &[String]) -> String {
+    let items: Vec<String> = values.iter().map(|v| quote(v)).collect();
+    format!("[{}]", items.join(", "))
+}
+
+pub(crate) fn to_snake_case(name: &str) -> String {
+    let mut out = String::new();
+    let chars: Vec<char> = name.chars().collect();
+    for (i, c) in chars.iter().enumerate() {
+        if c.is_uppercase() {
+            let prev_lower = i > 0 && (chars[i - 1].is_lowercase() || chars[i - 1].is_ascii_digit());
+            if prev_lower {
+                out.push('_');
+            }
+            out.extend(c.to_lowercase());
+        } else {
+            out.push(*c);
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_file_values_override_defaults() {
+        let config = Config::from_toml(
+            "[frame]\nfps = 30\n\n[dependencies]\nsdl2_features = [\"gfx\"]\n\n[modules.random]\ncrate = \"rand\"\nversion = \"0.8\"\n",
+        )
+        .unwrap();
+        assert_eq!(config.fps, 30);
+        assert_eq!(config.sdl2_features, vec!["gfx".to_string()]);
+        assert_eq!(config.sdl2_version, "0.35");
+        assert_eq!(config.modules["random"].crate_name.as_deref(), Some("rand"));
+    }
+
+    #[test]
+    fn test_module_rules() {
+        let config = Config::from_toml(
+            "[modules.vector]\nuses = [\"glam::Vec2\"]\n\n[modules.vector.calls]\nlength = \"Vec2::new({0}, {1}).length()\"\n",
+        )
+        .unwrap();
+        let mapping = &config.modules["vector"];
+        assert_eq!(mapping.crate_name, None);
+        assert_eq!(mapping.uses, vec!["glam::Vec2".to_string()]);
+        assert_eq!(mapping.calls["length"], "Vec2::new({0}, {1}).length()");
+        assert_eq!(Config::from_toml(&config.to_toml()).unwrap().modules, config.modules);
+        assert!(Config::from_toml("[modules.vector]\nlenght = \"x\"\n").is_err());
+    }
+
+    #[test]
+    fn test_unknown_key_is_rejected() {
+        assert!(Config::from_toml("[frame]\nspeed = 2\n").is_err());
+        assert!(Config::default().set("integers.type", "u8").is_err());
+        assert!(Config::default().set("classes.inheritance", "mixin").is_err());
+    }
+
+    #[test]
+    fn test_sdl2_link_selects_feature() {
+        let mut config = Config::default();
+        assert_eq!(config.sdl2_crate_features(), vec!["bundled".to_string()]);
+        config.set("dependencies.sdl2_features", "bundled,gfx").unwrap();
+        config.set("dependencies.sdl2_link", "pkg-config").unwrap();
+        assert_eq!(config.sdl2_crate_features(), vec!["use-pkgconfig".to_string(), "gfx".to_string()]);
+        config.set("dependencies.sdl2_link", "system").unwrap();
+        assert_eq!(config.sdl2_crate_features(), vec!["gfx".to_string()]);
+    }
+
+    #[test]
+    fn test_snake_case_naming() {
+        let mut config = Config::default();
+        config.set("naming.variables", "snake_case").unwrap();
+        assert_eq!(config.variable_name("NEGRO"), "negro");
+        assert_eq!(config.variable_name("centroX"), "centro_x");
+    }
+}
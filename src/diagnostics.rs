@@ -0,0 +1,152 @@
+// Traduce los diagnósticos de rustc (cargo --message-format=json) a líneas del script Python.
+use serde_json::Value;
+
+use crate::config::Config;
+use crate::sourcemap::SourceMap;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Diagnostic {
+    pub level: String,
+    pub message: String,
+    pub rust_file: String,
+    pub rust_line: usize,
+    pub rust_column: usize,
+    pub python_line: Option<usize>,
+    // Texto completo de rustc; es lo único que explica un error sin posición
+    pub rendered: Option<String>,
+}
+
+pub fn parse_cargo_messages(output: &str, source_map: &SourceMap) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+
+    for line in output.lines() {
+        let Ok(message) = serde_json::from_str::<Value>(line) else {
+            continue;
+        };
+        if message["reason"] != "compiler-message" {
+            continue;
+        }
+        let compiler = &message["message"];
+        let level = compiler["level"].as_str().unwrap_or("");
+        if level != "error" {
+            continue;
+        }
+
+        // Los errores del enlazador o de un build script no tienen posición
+        let spans = compiler["spans"].as_array().cloned().unwrap_or_default();
+        let span = spans.iter().find(|s| s["is_primary"] == true).or(spans.first()).unwrap_or(&Value::Null);
+        let rust_file = span["file_name"].as_str().unwrap_or("").to_string();
+        let rust_line = span["line_start"].as_u64().unwrap_or(0) as usize;
+        let python_line = if rust_file.ends_with("main.rs") {
+            source_map.python_line(rust_line)
+        } else {
+            None
+        };
+
+        diagnostics.push(Diagnostic {
+            level: level.to_string(),
+            message: compiler["message"].as_str().unwrap_or("").to_string(),
+            rust_file,
+            rust_line,
+            rust_column: span["column_start"].as_u64().unwrap_or(0) as usize,
+            python_line,
+            rendered: compiler["rendered"].as_str().map(str::to_string),
+        });
+    }
+
+    diagnostics
+}
+
+impl Diagnostic {
+    // Aviso del propio traductor, sin código Rust asociado
+    pub fn warning(message: String, python_line: usize) -> Diagnostic {
+        Diagnostic {
+            level: "warning".to_string(),
+            message,
+            rust_file: String::new(),
+            rust_line: 0,
+            rust_column: 0,
+            python_line: Some(python_line),
+            rendered: None,
+        }
+    }
+}
+
+pub fn render(diagnostic: &Diagnostic, python_file: &str, python_code: &str, config: &Config) -> String {
+    let mut out = format!("{}: {}\n", diagnostic.level, diagnostic.message);
+    let generated = format!("{}:{}:{}", diagnostic.rust_file, diagnostic.rust_line, diagnostic.rust_column);
+
+    match diagnostic.python_line {
+        Some(line) => {
+            let snippet = python_code.lines().nth(line - 1).unwrap_or("");
+            let gutter = " ".repeat(line.to_string().len());
+            out.push_str(&format!("{}--> {}:{}\n", gutter, python_file, line));
+            out.push_str(&format!("{} |\n", gutter));
+            out.push_str(&format!("{} | {}\n", line, snippet));
+            out.push_str(&format!("{} |\n", gutter));
+            if !diagnostic.rust_file.is_empty() {
+                let label = config.msg("código generado", "generated code");
+                out.push_str(&format!("{} = {}: {}\n", gutter, label, generated));
+            }
+        }
+        None if diagnostic.rendered.is_some() => {
+            // Sin línea en Python se muestra el error tal como lo da rustc
+            out = format!("{}\n", diagnostic.rendered.as_deref().unwrap_or("").trim_end());
+        }
+        None => {
+            let label = config.msg("sin correspondencia en", "no match in");
+            out.push_str(&format!("  --> {} ({} {})\n", generated, label, python_file));
+        }
+    }
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_error_is_mapped_to_python_line() {
+        let mut map = SourceMap::new("juego.py");
+        map.add(30, 34, 47, 5);
+        let output = concat!(
+            r#"{"reason":"compiler-artifact","target":{}}"#, "\n",
+            r#"{"reason":"compiler-message","message":{"level":"error","message":"cannot find value `rect_x`","spans":[{"file_name":"src/main.rs","line_start":32,"column_start":20,"is_primary":true}]}}"#, "\n",
+            r#"{"reason":"compiler-message","message":{"level":"warning","message":"unused","spans":[]}}"#, "\n",
+        );
+
+        let diagnostics = parse_cargo_messages(output, &map);
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].python_line, Some(47));
+
+        let rendered = render(&diagnostics[0], "juego.py", &"x = 1\n".repeat(50), &Config::default());
+        assert!(rendered.contains("--> juego.py:47"));
+        assert!(rendered.contains("47 | x = 1"));
+        assert!(rendered.contains("= código generado: src/main.rs:32:20"));
+
+        let mut config = Config::default();
+        config.set("messages.language", "en").unwrap();
+        assert!(render(&diagnostics[0], "juego.py", &"x = 1\n".repeat(50), &config).contains("= generated code: src/main.rs:32:20"));
+        let mut unmapped = diagnostics[0].clone();
+        unmapped.python_line = None;
+        assert!(render(&unmapped, "juego.py", "", &config).contains("(no match in juego.py)"));
+    }
+
+    #[test]
+    fn test_error_without_spans_is_kept() {
+        let map = SourceMap::new("juego.py");
+        let output = concat!(
+            r#"{"reason":"compiler-message","message":{"level":"error","message":"linking with `cc` failed: exit status: 1","spans":[],"rendered":"error: linking with `cc` failed: exit status: 1\n  = note: /usr/bin/ld: cannot find -lSDL2\n"}}"#, "\n",
+        );
+
+        let diagnostics = parse_cargo_messages(output, &map);
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].python_line, None);
+        assert!(diagnostics[0].rust_file.is_empty());
+
+        let rendered = render(&diagnostics[0], "juego.py", "", &Config::default());
+        assert!(rendered.contains("cannot find -lSDL2"));
+        assert!(!rendered.contains("sin correspondencia"));
+    }
+}
@@ -1,23 +1,2634 @@
-use crate::types::DrawType;
-use crate::types::State;
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
 
-pub fn generate_rust(state: &State) -> String {
-    let mut rust_code = String::new();
+use crate::audio;
+use crate::classes;
+use crate::config::Config;
+use crate::geometry::{self, Shape};
+use crate::diagnostics::Diagnostic;
+use crate::keys;
+use crate::mappings;
+use crate::pygame;
+use crate::sourcemap::SourceMap;
+use crate::textures::{self, ImageValue};
+use crate::timing;
+use crate::types::{
+    ClassDef, DrawCall, EventLoop, Expr, Field, Hierarchy, HierarchyMember, Method, MethodKind, Param, RendererSetting, RendererSpec, State, Stmt, StmtKind,
+    Variable, WindowSpec,
+};
+use crate::writer::{array, fn_signature, function_call, method_chain, vec_declaration, CodeWriter};
 
-    // Importaciones
-    rust_code.push_str("use sdl2::pixels::Color;\n");
-    rust_code.push_str("use sdl2::event::Event;\n");
-    rust_code.push_str("use sdl2::keyboard::Keycode;\n");
-    rust_code.push_str("use sdl2::rect::Rect;\n");
-    rust_code.push_str("use std::time::Duration;\n\n");
+pub struct GeneratedCode {
+    pub code: String,
+    pub source_map: SourceMap,
+    // Avisos sobre lo que no se pudo traducir, con su línea Python
+    pub warnings: Vec<Diagnostic>,
+}
+
+pub fn generate_cargo_toml(package_name: &str, state: &State, config: &Config) -> String {
+    let mut cargo_toml = format!(r#"[package]
+name = "{}"
+version = "0.1.0"
+edition = "2021"
+
+[dependencies]
+"#, package_name);
+
+    // Features que necesita el script aunque no estén en la configuración
+    let mut sdl2_features = config.sdl2_crate_features();
+    let used_features = [
+        ("image", textures::uses_image(state)),
+        ("ttf", textures::uses_fonts(state)),
+        ("mixer", audio::uses_mixer(state)),
+    ];
+    for (feature, used) in used_features {
+        if used && !sdl2_features.iter().any(|f| f == feature) {
+            sdl2_features.push(feature.to_string());
+        }
+    }
+    let features: Vec<String> = sdl2_features.iter().map(|f| format!("\"{}\"", f)).collect();
+    if features.is_empty() {
+        cargo_toml.push_str(&format!("sdl2 = \"{}\"\n", config.sdl2_version));
+    } else {
+        cargo_toml.push_str(&format!("sdl2 = {{ version = \"{}\", features = [{}] }}\n",
+            config.sdl2_version, features.join(", ")));
+    }
+
+    // Dependencias de los módulos Python importados: `random` y los de [modules]
+    for dependency in mappings::dependencies(state, config) {
+        cargo_toml.push_str(&format!("{}\n", dependency));
+    }
+
+    // Instrumentación opcional: solo se compila con `--features instrument`
+    if config.instrument {
+        if config.instrument_log {
+            cargo_toml.push_str("log = { version = \"0.4\", optional = true }\n");
+            cargo_toml.push_str("env_logger = { version = \"0.11\", optional = true }\n");
+            cargo_toml.push_str("\n[features]\ninstrument = [\"dep:log\", \"dep:env_logger\"]\n");
+        } else {
+            cargo_toml.push_str("\n[features]\ninstrument = []\n");
+        }
+    }
+
+    let overflow_checks = config.overflow == "checked";
+    cargo_toml.push_str(&format!("\n[profile.dev]\noverflow-checks = {}\n", overflow_checks));
+    cargo_toml.push_str(&format!("\n[profile.release]\noverflow-checks = {}\n", overflow_checks));
+
+    cargo_toml
+}
+
+// `.cargo/config.toml` del proyecto generado, solo si hace falta para compilar sin red.
+pub fn generate_cargo_config(config: &Config) -> Option<String> {
+    if config.vendor_dir.is_none() && config.registry_mirror.is_none() && !config.offline {
+        return None;
+    }
+
+    let mut cargo_config = String::new();
+    if let Some(vendor_dir) = &config.vendor_dir {
+        cargo_config.push_str("[source.crates-io]\nreplace-with = \"vendored-sources\"\n\n");
+        cargo_config.push_str(&format!("[source.vendored-sources]\ndirectory = {:?}\n\n", vendor_dir.display().to_string()));
+    } else if let Some(mirror) = &config.registry_mirror {
+        cargo_config.push_str("[source.crates-io]\nreplace-with = \"mirror\"\n\n");
+        cargo_config.push_str(&format!("[source.mirror]\nregistry = {:?}\n\n", mirror));
+    }
+    if config.offline {
+        cargo_config.push_str("[net]\noffline = true\n");
+    }
+    Some(cargo_config)
+}
+
+fn variable_position(state: &State, name: &str) -> (usize, usize) {
+    state.variables.iter()
+        .find(|v| v.name == name)
+        .map(|v| (v.line, v.column))
+        .unwrap_or((0, 0))
+}
+
+fn sdl_call_position(state: &State, function: &str) -> (usize, usize) {
+    state.sdl_calls.iter()
+        .find(|c| c.function.ends_with(function))
+        .map(|c| (c.line, c.column))
+        .unwrap_or((0, 0))
+}
+
+// Constantes de dimensiones, que se generan como `u32`
+const SIZE_CONSTANTS: [(&str, u32); 2] = [("ANCHO", 800), ("ALTO", 600)];
+
+// Flags de `sdl2.ext.Window` y su método en `WindowBuilder`
+const WINDOW_FLAGS: [(&str, Option<&str>); 12] = [
+    ("SDL_WINDOW_FULLSCREEN", Some("fullscreen")),
+    ("SDL_WINDOW_FULLSCREEN_DESKTOP", Some("fullscreen_desktop")),
+    ("SDL_WINDOW_RESIZABLE", Some("resizable")),
+    ("SDL_WINDOW_BORDERLESS", Some("borderless")),
+    ("SDL_WINDOW_HIDDEN", Some("hidden")),
+    ("SDL_WINDOW_SHOWN", None),
+    ("SDL_WINDOW_MAXIMIZED", Some("maximized")),
+    ("SDL_WINDOW_MINIMIZED", Some("minimized")),
+    ("SDL_WINDOW_OPENGL", Some("opengl")),
+    ("SDL_WINDOW_VULKAN", Some("vulkan")),
+    ("SDL_WINDOW_ALLOW_HIGHDPI", Some("allow_highdpi")),
+    ("SDL_WINDOW_INPUT_GRABBED", Some("input_grabbed")),
+];
+
+// Flags de `sdl2.ext.Renderer` y su método en `CanvasBuilder`
+const RENDERER_FLAGS: [(&str, &str); 4] = [
+    ("SDL_RENDERER_SOFTWARE", "software"),
+    ("SDL_RENDERER_ACCELERATED", "accelerated"),
+    ("SDL_RENDERER_PRESENTVSYNC", "present_vsync"),
+    ("SDL_RENDERER_TARGETTEXTURE", "target_texture"),
+];
+
+const BLEND_MODES: [(&str, &str); 5] = [
+    ("SDL_BLENDMODE_NONE", "None"),
+    ("SDL_BLENDMODE_BLEND", "Blend"),
+    ("SDL_BLENDMODE_ADD", "Add"),
+    ("SDL_BLENDMODE_MOD", "Mod"),
+    ("SDL_BLENDMODE_MUL", "Mul"),
+];
+
+// Métodos de la ventana Python con equivalente en `sdl2::video::Window`
+const WINDOW_METHODS: [&str; 5] = ["show", "hide", "maximize", "minimize", "restore"];
+
+// Campo de un evento de PySDL2 (tras `event.`), campo de la variante de `Event`,
+// nombre con el que se enlaza y tipo entero Rust ("" si no es un entero).
+type EventField = (&'static str, &'static str, &'static str, &'static str);
+
+const KEY_FIELDS: &[EventField] = &[
+    ("key.keysym.sym", "keycode", "keycode", ""),
+    ("key.keysym.scancode", "scancode", "scancode", ""),
+    ("key.keysym.mod", "keymod", "keymod", ""),
+    ("key.repeat", "repeat", "repeat", ""),
+];
+
+const BUTTON_FIELDS: &[EventField] = &[
+    ("button.button", "mouse_btn", "mouse_btn", ""),
+    ("button.x", "x", "mouse_x", "i32"),
+    ("button.y", "y", "mouse_y", "i32"),
+    ("button.clicks", "clicks", "clicks", "u8"),
+];
+
+// Constante de `event.type` y la variante de `Event` que le corresponde
+const EVENT_PATTERNS: [(&str, &str, &[EventField]); 10] = [
+    ("SDL_QUIT", "Quit", &[]),
+    ("SDL_KEYDOWN", "KeyDown", KEY_FIELDS),
+    ("SDL_KEYUP", "KeyUp", KEY_FIELDS),
+    ("SDL_MOUSEMOTION", "MouseMotion", &[
+        ("motion.x", "x", "mouse_x", "i32"),
+        ("motion.y", "y", "mouse_y", "i32"),
+        ("motion.xrel", "xrel", "xrel", "i32"),
+        ("motion.yrel", "yrel", "yrel", "i32"),
+        ("motion.state", "mousestate", "mousestate", ""),
+    ]),
+    ("SDL_MOUSEBUTTONDOWN", "MouseButtonDown", BUTTON_FIELDS),
+    ("SDL_MOUSEBUTTONUP", "MouseButtonUp", BUTTON_FIELDS),
+    ("SDL_MOUSEWHEEL", "MouseWheel", &[
+        ("wheel.x", "x", "wheel_x", "i32"),
+        ("wheel.y", "y", "wheel_y", "i32"),
+        ("wheel.direction", "direction", "direction", ""),
+    ]),
+    // `data1`/`data2` salen de los datos de `WindowEvent::Resized(w, h)`...
+    ("SDL_WINDOWEVENT", "Window", &[
+        ("window.event", "win_event", "win_event", ""),
+        ("window.data1", "win_event", "data1", "i32"),
+        ("window.data2", "win_event", "data2", "i32"),
+    ]),
+    ("SDL_TEXTINPUT", "TextInput", &[("text.text", "text", "text", "")]),
+    ("SDL_TEXTEDITING", "TextEditing", &[("edit.text", "text", "text", "")]),
+];
+
+// `SDLK_LEFT` -> `Left`, `SDL_WINDOWEVENT_FOCUS_GAINED` -> `FocusGained`
+fn camel_case(name: &str) -> String {
+    name.split('_')
+        .map(|part| {
+            let mut chars = part.chars();
+            match chars.next() {
+                Some(first) => first.to_ascii_uppercase().to_string() + &chars.as_str().to_ascii_lowercase(),
+                None => String::new(),
+            }
+        })
+        .collect()
+}
+
+// Constantes de teclado y de botones del ratón de PySDL2 en sdl2. Las teclas se
+// comparan con el `Option` de los eventos.
+fn sdl_constant(name: &str) -> Option<String> {
+    if keys::is_keyboard_constant(name) {
+        let constant = keys::rust_constant(name)?;
+        return Some(match name {
+            _ if name.starts_with("KMOD_") && constant.contains('|') => format!("({})", constant),
+            _ if name.starts_with("KMOD_") => constant,
+            _ => format!("Some({})", constant),
+        });
+    }
+    if let Some(button) = name.strip_prefix("SDL_BUTTON_") {
+        return Some(format!("MouseButton::{}", camel_case(button)));
+    }
+    audio::rust_constant(name)
+}
+
+// Botones del estado del ratón (`MouseState`) y su método
+const BUTTON_MASKS: [(&str, &str); 5] = [
+    ("SDL_BUTTON_LMASK", "left()"),
+    ("SDL_BUTTON_MMASK", "middle()"),
+    ("SDL_BUTTON_RMASK", "right()"),
+    ("SDL_BUTTON_X1MASK", "x1()"),
+    ("SDL_BUTTON_X2MASK", "x2()"),
+];
+
+fn button_mask(expr: &Expr) -> Option<String> {
+    match expr {
+        Expr::Attr(path) => {
+            let name = path.rsplit('.').next().unwrap_or(path);
+            BUTTON_MASKS.iter().find(|(mask, _)| *mask == name).map(|(_, method)| method.to_string())
+        }
+        Expr::Call { function, args } if function.ends_with("SDL_BUTTON") => match args.as_slice() {
+            [Expr::Attr(path)] => {
+                let button = sdl_constant(path.rsplit('.').next().unwrap_or(path))?;
+                Some(format!("is_mouse_button_pressed({})", button))
+            }
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+fn is_modifier(expr: &Expr) -> bool {
+    matches!(expr, Expr::Attr(path) if path.rsplit('.').next().is_some_and(|name| name.starts_with("KMOD_")))
+}
+
+// Precedencia de un operador binario de Python, de menor a mayor
+fn precedence(op: &str) -> usize {
+    match op {
+        "or" => 0,
+        "and" => 1,
+        "==" | "!=" | "<" | "<=" | ">" | ">=" => 2,
+        "|" => 3,
+        "&" => 4,
+        "+" | "-" => 5,
+        _ => 6,
+    }
+}
+
+// Literal de string Python (comillas simples, dobles o triples) como literal Rust.
+// `format!` de un f-string (`f"Puntos: {puntos:05d}"`) o de una concatenación
+// de textos con valores (`"Vidas: " + str(vidas)`).
+fn format_text(expr: &Expr, config: &Config) -> Option<String> {
+    let mut template = String::new();
+    let mut args = Vec::new();
+    match expr {
+        Expr::Str(raw) if raw.starts_with(['f', 'F']) => {
+            let raw = &raw[1..];
+            let quote = raw.get(..1)?;
+            let mut chars = raw[1..raw.len().saturating_sub(1)].chars().peekable();
+            while let Some(c) = chars.next() {
+                match c {
+                    '{' if chars.peek() == Some(&'{') => {
+                        chars.next();
+                        template.push_str("{{");
+                    }
+                    '}' if chars.peek() == Some(&'}') => {
+                        chars.next();
+                        template.push_str("}}");
+                    }
+                    '{' => {
+                        let field: String = chars.by_ref().take_while(|c| *c != '}').collect();
+                        let (value, spec) = field.split_once(':').unwrap_or((&field, ""));
+                        // Rust no lleva el tipo de la conversión: `05d` -> `05`, `.2f` -> `.2`
+                        let spec = spec.trim_end_matches(['d', 'f', 's', 'g']);
+                        template.push_str(&if spec.is_empty() { "{}".to_string() } else { format!("{{:{}}}", spec) });
+                        let value = value.trim();
+                        let is_name = value.chars().all(|c| c.is_alphanumeric() || c == '_');
+                        args.push(if is_name { rust_expr(&Expr::Name(value.to_string()), config) } else { value.to_string() });
+                    }
+                    _ => template.push(c),
+                }
+            }
+            template = rust_string(&format!("{}{}{}", quote, template, quote));
+        }
+        Expr::BinOp { op, .. } if op == "+" => {
+            let mut parts = Vec::new();
+            concatenation(expr, &mut parts);
+            if !parts.iter().any(|p| matches!(p, Expr::Str(_))) || parts.iter().all(|p| matches!(p, Expr::Str(_))) {
+                return None;
+            }
+            for part in parts {
+                match part {
+                    Expr::Str(raw) if !raw.starts_with(['f', 'F']) => {
+                        let text = rust_string(raw);
+                        template.push_str(&text[1..text.len() - 1].replace('{', "{{").replace('}', "}}"));
+                    }
+                    Expr::Call { function, args: inner } if function == "str" && inner.len() == 1 => {
+                        template.push_str("{}");
+                        args.push(rust_expr(&inner[0], config));
+                    }
+                    other => {
+                        template.push_str("{}");
+                        args.push(rust_expr(other, config));
+                    }
+                }
+            }
+            template = format!("\"{}\"", template);
+        }
+        _ => return None,
+    }
+    if args.is_empty() {
+        return Some(format!("{}.to_string()", template));
+    }
+    Some(format!("format!({}, {})", template, args.join(", ")))
+}
+
+// Operandos de una cadena de `+`.
+fn concatenation<'a>(expr: &'a Expr, parts: &mut Vec<&'a Expr>) {
+    match expr {
+        Expr::BinOp { op, left, right } if op == "+" => {
+            concatenation(left, parts);
+            concatenation(right, parts);
+        }
+        _ => parts.push(expr),
+    }
+}
+
+fn rust_string(raw: &str) -> String {
+    if raw.starts_with('"') && !raw.starts_with("\"\"\"") {
+        return raw.to_string();
+    }
+    let quote_len = if raw.starts_with("\"\"\"") || raw.starts_with("'''") { 3 } else { 1 };
+    let inner = raw.get(quote_len..raw.len().saturating_sub(quote_len)).unwrap_or("");
+    format!("\"{}\"", inner.replace("\\'", "'").replace('"', "\\\""))
+}
+
+// Expresión Rust de tipo `ty` (para argumentos de sdl2 que no son `integers.type`).
+fn rust_expr_as(expr: &Expr, ty: &str, config: &Config) -> String {
+    match expr {
+        Expr::Int(n) => n.to_string(),
+        Expr::Name(name) if ty == "u32" && SIZE_CONSTANTS.iter().any(|(c, _)| c == name) => name.clone(),
+        _ if ty == config.int_type => rust_expr(expr, config),
+        Expr::Name(name) => format!("{} as {}", config.variable_name(name), ty),
+        // `ajustes::TAMANO as u32`
+        Expr::Attr(_) => format!("{} as {}", rust_expr(expr, config), ty),
+        _ => format!("({}) as {}", rust_expr(expr, config), ty),
+    }
+}
+
+// Expresión Rust equivalente, con los enteros en `integers.type`.
+fn rust_expr(expr: &Expr, config: &Config) -> String {
+    match expr {
+        Expr::Int(n) => n.to_string(),
+        Expr::Float(x) => format!("{:?}", x),
+        Expr::Bool(b) => b.to_string(),
+        Expr::Str(s) if s.starts_with(['f', 'F']) => format_text(expr, config).unwrap_or_else(|| rust_string(&s[1..])),
+        Expr::Str(s) => rust_string(s),
+        Expr::Name(name) if SIZE_CONSTANTS.iter().any(|(c, _)| c == name) => format!("{} as {}", name, config.int_type),
+        Expr::Name(name) => config.variable_name(name),
+        Expr::Attr(path) => match sdl_constant(path.rsplit('.').next().unwrap_or(path)) {
+            Some(constant) => constant,
+            // `math.pi` y las constantes de [modules]
+            None => mappings::constant_code(path, config).unwrap_or_else(|| path.replace('.', "::")),
+        },
+        Expr::Tuple(items) => {
+            let items: Vec<String> = items.iter().map(|e| rust_expr(e, config)).collect();
+            format!("({})", items.join(", "))
+        }
+        Expr::List(items) => {
+            let items: Vec<String> = items.iter().map(|e| rust_expr(e, config)).collect();
+            format!("[{}]", items.join(", "))
+        }
+        // `sdlmixer.Mix_PlayingMusic()` en una condición
+        Expr::Call { function, args } if audio::query_code(function, args, &|e| rust_expr(e, config)).is_some() => {
+            audio::query_code(function, args, &|e| rust_expr(e, config)).unwrap_or_default()
+        }
+        // `sdl2.SDL_Rect(...)`, `sdl2.SDL_HasIntersection(a, b)`...
+        Expr::Call { function, args } if geometry::call_code(function, args, &|e| rust_expr(e, config), &|e, ty| rust_expr_as(e, ty, config)).is_some() => {
+            geometry::call_code(function, args, &|e| rust_expr(e, config), &|e, ty| rust_expr_as(e, ty, config)).unwrap_or_default()
+        }
+        // `sdl2.SDL_GetTicks()`, `time.time()`
+        Expr::Call { function, args } if timing::value_code(function, args, &config.int_type).is_some() => {
+            timing::value_code(function, args, &config.int_type).unwrap_or_default()
+        }
+        // `int(y)`, `abs(dx)`, `max(a, b)`
+        Expr::Call { function, args } if builtin_code(function, args, config).is_some() => {
+            builtin_code(function, args, config).unwrap_or_default()
+        }
+        // `math.sqrt(x)`, `random.randint(a, b)` y las llamadas de [modules]
+        Expr::Call { function, args } => {
+            let args: Vec<String> = args.iter().map(|e| rust_expr(e, config)).collect();
+            mappings::call_code(function, &args, config)
+                .unwrap_or_else(|| format!("{}({})", function.replace('.', "::"), args.join(", ")))
+        }
+        // `keys[sdl2.SDL_SCANCODE_LEFT]` sobre `event_pump.keyboard_state()`
+        Expr::Index { value, index } => match index.as_ref() {
+            Expr::Attr(path) if path.contains("SDL_SCANCODE_") => {
+                let scancode = keys::rust_constant(path.rsplit('.').next().unwrap_or(path))
+                    .unwrap_or_else(|| path.replace('.', "::"));
+                format!("{}.is_scancode_pressed({})", rust_expr(value, config), scancode)
+            }
+            _ => format!("{}[{} as usize]", rust_expr(value, config), rust_expr(index, config)),
+        },
+        Expr::MethodCall { receiver, method, args } => {
+            let args: Vec<String> = args.iter().map(|e| rust_expr(e, config)).collect();
+            format!("{}.{}({})", rust_expr(receiver, config), method, args.join(", "))
+        }
+        Expr::Cast { value, ty } => format!("{} as {}", rust_expr(value, config), ty),
+        Expr::Try(value) => format!("{}?", rust_expr(value, config)),
+        // `Box::new(Enemy::new())` o `SpriteKind::Enemy(Enemy::new())`
+        Expr::Upcast { value, root, class } => match (config.inheritance.as_str(), class) {
+            ("enum", Some(class)) => format!("{}Kind::{}({})", root.replace('.', "::"), class, rust_expr(value, config)),
+            (_, Some(_)) => format!("Box::new({})", rust_expr(value, config)),
+            ("enum", None) => rust_expr(value, config),
+            (_, None) => format!("*{}", rust_expr(value, config)),
+        },
+        // Su sentencia se omite; aquí solo queda si no hay otra forma de escribirla
+        Expr::Unlinked(function) => format!("{}()", function.replace('.', "::")),
+        Expr::UnaryOp { op, operand } => {
+            let op = if op == "not" { "!" } else { op.as_str() };
+            match operand.as_ref() {
+                Expr::BinOp { .. } => format!("{}({})", op, rust_expr(operand, config)),
+                // `&Enemy::new()` ya es un `&dyn SpriteTrait`
+                Expr::Upcast { value, class: Some(_), .. } if config.inheritance != "enum" => format!("{}{}", op, rust_expr(value, config)),
+                _ => format!("{}{}", op, rust_expr(operand, config)),
+            }
+        }
+        // `win_event == sdl2.SDL_WINDOWEVENT_RESIZED`: las variantes con datos no se comparan con `==`
+        Expr::BinOp { op, left, right } if op == "==" && matches!(right.as_ref(),
+            Expr::Attr(path) if path.contains("SDL_WINDOWEVENT_")) => {
+            let Expr::Attr(path) = right.as_ref() else { unreachable!() };
+            let variant = camel_case(path.rsplit("SDL_WINDOWEVENT_").next().unwrap_or(path));
+            let data = if ["Moved", "Resized", "SizeChanged"].contains(&variant.as_str()) { "(..)" } else { "" };
+            format!("matches!({}, WindowEvent::{}{})", rust_expr(left, config), variant, data)
+        }
+        // `keymod & sdl2.KMOD_SHIFT`, solo o comparado con 0, es `keymod.intersects(..)`
+        // `buttons & sdl2.SDL_BUTTON_LMASK`, `buttons & sdl2.SDL_BUTTON(sdl2.SDL_BUTTON_LEFT)`
+        Expr::BinOp { op, left, right } if op == "&" && button_mask(right).is_some() => {
+            format!("{}.{}", rust_expr(left, config), button_mask(right).unwrap_or_default())
+        }
+        Expr::BinOp { op, left, right } if op == "&" && is_modifier(right) => {
+            format!("{}.intersects({})", rust_expr(left, config), rust_expr(right, config).trim_start_matches('(').trim_end_matches(')'))
+        }
+        Expr::BinOp { op, left, right } if (op == "==" || op == "!=") && *right.as_ref() == Expr::Int(0)
+            && matches!(left.as_ref(), Expr::BinOp { op, right, .. } if op == "&" && is_modifier(right)) => {
+            format!("{}{}", if op == "==" { "!" } else { "" }, rust_expr(left, config))
+        }
+        // `"Vidas: " + str(vidas)`
+        Expr::BinOp { op, .. } if op == "+" && format_text(expr, config).is_some() => {
+            format_text(expr, config).unwrap_or_default()
+        }
+        Expr::BinOp { op, left, right } => {
+            let level = precedence(op);
+            // Junto a un `float`, los enteros literales llevan decimales: `time.time() - inicio > 10`
+            let floats = is_float(left, config) || is_float(right, config);
+            let operand = |e: &Expr, right_side: bool| match e {
+                Expr::BinOp { op: inner, .. } if precedence(inner) < level
+                    || (precedence(inner) == level && (right_side || level == 2)) => format!("({})", rust_expr(e, config)),
+                Expr::Int(n) if floats => format!("{}.0", n),
+                // `(rect.width() as i32) < x`: sin paréntesis `<` abriría genéricos
+                Expr::Cast { .. } => format!("({})", rust_expr(e, config)),
+                Expr::Call { function, .. } if CASTS.contains(&function.as_str()) => format!("({})", rust_expr(e, config)),
+                _ => rust_expr(e, config),
+            };
+            let op = match op.as_str() {
+                "//" => "/",
+                "and" => "&&",
+                "or" => "||",
+                op => op,
+            };
+            format!("{} {} {}", operand(left, false), op, operand(right, true))
+        }
+    }
+}
+
+// Funciones de Python que no son de ninguna biblioteca
+const BUILTINS: [&str; 6] = ["int", "float", "abs", "round", "min", "max"];
+
+// Las que se traducen con `as`
+const CASTS: [&str; 3] = ["int", "float", "round"];
+
+// `int(y)` es `y as i32`, `abs(dx)` es `dx.abs()` y `max(a, b)` es `a.max(b)`.
+fn builtin_code(function: &str, args: &[Expr], config: &Config) -> Option<String> {
+    let receiver = |e: &Expr| match e {
+        Expr::BinOp { .. } | Expr::UnaryOp { .. } | Expr::Cast { .. } => format!("({})", rust_expr(e, config)),
+        Expr::Call { function, .. } if CASTS.contains(&function.as_str()) => format!("({})", rust_expr(e, config)),
+        // `abs(-5)`: un literal solo no tiene tipo para llamar a sus métodos
+        Expr::Int(n) => format!("{}{}", n, config.int_type),
+        Expr::Float(x) => format!("{:?}f64", x),
+        _ => rust_expr(e, config),
+    };
+    match (function, args) {
+        ("int", [value]) => Some(format!("{} as {}", receiver(value), config.int_type)),
+        ("float", [value]) => Some(format!("{} as f64", receiver(value))),
+        ("round", [value]) => Some(format!("{}.round() as {}", receiver(value), config.int_type)),
+        ("abs", [value]) => Some(format!("{}.abs()", receiver(value))),
+        ("min" | "max", [first, second]) => Some(format!("{}.{}({})", receiver(first), function, rust_expr(second, config))),
+        _ => None,
+    }
+}
+
+// `timing::is_float` y además las llamadas de bibliotecas que devuelven un
+// float, como `math.sin(angulo) * 100`.
+fn is_float(expr: &Expr, config: &Config) -> bool {
+    match expr {
+        Expr::Call { function, .. } if mappings::returns_float(function, config) || function == "float" => true,
+        Expr::BinOp { op, left, right } if ["+", "-", "*", "/"].contains(&op.as_str()) => {
+            is_float(left, config) || is_float(right, config)
+        }
+        Expr::UnaryOp { operand, .. } => is_float(operand, config),
+        _ => timing::is_float(expr),
+    }
+}
+
+// Declaración Rust de una variable de usuario, si se puede traducir; `indent`
+// es la sangría a la que se escribe.
+fn variable_declaration(var: &Variable, indent: usize, config: &Config) -> Option<String> {
+    let value_expr = var.value.as_ref().filter(|value| unlinked(value).is_empty())?;
+    let value = rust_expr(value_expr, config);
+    let name = config.variable_name(&var.name);
+    match var.tipo.as_str() {
+        // `200 + math.sin(angulo) * 100`: el parser no sabe qué devuelve `math.sin`
+        "int" if !is_float(value_expr, config) => Some(format!("let mut {}: {} = {};", name, config.int_type, value)),
+        "float" => Some(format!("let mut {}: f64 = {};", name, value)),
+        "bool" | "str" => Some(format!("let mut {} = {};", name, value)),
+        // `e = Enemy()` y después `e = Boss()`: cualquier clase de la jerarquía
+        tipo if tipo.starts_with("dyn ") => Some(format!("let mut {}: {} = {};", name, rust_type(tipo, config), value)),
+        // `entidades = [jugador, enemigo]`
+        tipo if tipo.starts_with("list ") => match value_expr {
+            Expr::List(items) => {
+                let items: Vec<String> = items.iter().map(|item| rust_expr(item, config)).collect();
+                Some(vec_declaration(&format!("let mut {}: {}", name, rust_type(tipo, config)), &items, indent))
+            }
+            _ => Some(format!("let mut {}: {} = {};", name, rust_type(tipo, config), typed_expr(value_expr, tipo, config))),
+        },
+        // `p = Player(1, 2)`: `Player::new(1, 2)`
+        tipo if classes::is_class_type(tipo) => Some(format!("let mut {} = {};", name, value)),
+        _ if is_float(value_expr, config) => Some(format!("let mut {}: f64 = {};", name, value)),
+        // `x = random.randint(0, ANCHO)`, `x = abs(dx)`: el tipo lo deduce rustc
+        _ if matches!(value_expr, Expr::Call { function, .. }
+            if mappings::translates(function, config) || BUILTINS.contains(&function.as_str())) => {
+            Some(format!("let mut {} = {};", name, value))
+        }
+        _ => None,
+    }
+}
+
+// `WindowBuilder` con el título, tamaño, posición y flags de `sdl2.ext.Window`, y
+// los métodos de la ventana llamados antes de crear el renderer.
+fn write_window(w: &mut CodeWriter, window: Option<&WindowSpec>, renderer_line: usize, config: &Config) {
+    let title = match window.and_then(|win| win.title.as_ref()) {
+        Some(Expr::Str(raw)) => rust_string(raw),
+        Some(Expr::Name(name)) => config.variable_name(name),
+        _ => "\"SDL Window\"".to_string(),
+    };
+    let (width, height) = match window.and_then(|win| win.size.as_ref()) {
+        Some((width, height)) => (rust_expr_as(width, "u32", config), rust_expr_as(height, "u32", config)),
+        None => ("ANCHO".to_string(), "ALTO".to_string()),
+    };
+    let early_ops: Vec<_> = window
+        .map(|win| win.operations.iter()
+            .filter(|op| op.line < renderer_line && WINDOW_METHODS.contains(&op.method.as_str()))
+            .collect())
+        .unwrap_or_default();
+
+    let mut builder = vec![format!(".window({}, {}, {})", title, width, height)];
+    match window.and_then(|win| win.position.as_ref()) {
+        Some((x, y)) => builder.push(format!(".position({}, {})", rust_expr_as(x, "i32", config), rust_expr_as(y, "i32", config))),
+        None => builder.push(".position_centered()".to_string()),
+    }
+    // Sin `flags`, PySDL2 crea la ventana oculta hasta `window.show()`
+    let default_flags = vec!["SDL_WINDOW_HIDDEN".to_string()];
+    let flags = match window {
+        Some(win) => win.flags.as_ref().unwrap_or(&default_flags).clone(),
+        None => Vec::new(),
+    };
+    let mut unsupported = Vec::new();
+    for flag in &flags {
+        match WINDOW_FLAGS.iter().find(|(name, _)| name == flag) {
+            Some((_, Some(method))) => builder.push(format!(".{}()", method)),
+            Some((_, None)) => {}
+            None => unsupported.push(flag.clone()),
+        }
+    }
+    builder.push(".build()".to_string());
+    builder.push(".map_err(|e| e.to_string())?;".to_string());
+
+    let (line, column) = window.map(|win| (win.line, win.column)).unwrap_or((0, 0));
+    let region = w.begin(line, column);
+    for flag in &unsupported {
+        w.line(&format!("// {}: sin equivalente en WindowBuilder", flag));
+    }
+    let binding = if early_ops.is_empty() { "let window" } else { "let mut window" };
+    w.line(&format!("{} = video_subsystem", binding));
+    for call in &builder {
+        w.line(&format!("    {}", call));
+    }
+    w.end(region);
+    for op in early_ops {
+        let region = w.begin(op.line, op.column);
+        w.line(&format!("window.{}();", op.method));
+        w.end(region);
+    }
+}
+
+// `CanvasBuilder` con el índice de driver y los flags de `sdl2.ext.Renderer`.
+fn write_canvas_builder(w: &mut CodeWriter, renderer: Option<&RendererSpec>, vsync: bool, config: &Config) {
+    let mut chain = vec![".into_canvas()".to_string()];
+    let mut unsupported = Vec::new();
+    match renderer.and_then(|r| r.index.as_ref()) {
+        Some(Expr::Int(index)) if *index < 0 => {}
+        Some(index @ (Expr::Int(_) | Expr::Name(_) | Expr::BinOp { .. })) => {
+            chain.push(format!(".index({})", rust_expr_as(index, "u32", config)));
+        }
+        Some(_) => unsupported.push("backend".to_string()),
+        None => {}
+    }
+    // Sin `flags`, PySDL2 pide un renderer acelerado
+    let default_flags = vec!["SDL_RENDERER_ACCELERATED".to_string()];
+    let flags = match renderer {
+        Some(r) => r.flags.as_ref().unwrap_or(&default_flags).clone(),
+        None => Vec::new(),
+    };
+    for flag in &flags {
+        match RENDERER_FLAGS.iter().find(|(name, _)| name == flag) {
+            Some((_, method)) => chain.push(format!(".{}()", method)),
+            None => unsupported.push(flag.clone()),
+        }
+    }
+    // `frame.limiter = "vsync"` cuando el script no limita los fotogramas
+    if vsync && !chain.iter().any(|method| method == ".present_vsync()") {
+        chain.push(".present_vsync()".to_string());
+    }
+    chain.push(".build()".to_string());
+    chain.push(".map_err(|e| e.to_string())?".to_string());
+
+    for name in &unsupported {
+        w.line(&format!("// {}: sin equivalente en CanvasBuilder", name));
+    }
+    w.line(&format!("let mut canvas = {}", method_chain("window", &chain, ";")));
+}
+
+// `canvas.set_logical_size(...)` o `canvas.set_blend_mode(...)` para un ajuste del renderer.
+fn renderer_setting(setting: &RendererSetting, config: &Config) -> Option<String> {
+    match (setting.attribute.as_str(), &setting.value) {
+        ("logical_size", Expr::Tuple(size)) if size.len() == 2 => {
+            let call = format!(
+                ".set_logical_size({}, {})",
+                rust_expr_as(&size[0], "u32", config),
+                rust_expr_as(&size[1], "u32", config)
+            );
+            Some(method_chain("canvas", &[call, ".map_err(|e| e.to_string())?".to_string()], ";"))
+        }
+        ("blendmode", Expr::Attr(path)) => {
+            let name = path.rsplit('.').next().unwrap_or(path);
+            BLEND_MODES.iter()
+                .find(|(constant, _)| *constant == name)
+                .map(|(_, mode)| format!("canvas.set_blend_mode(sdl2::render::BlendMode::{});", mode))
+        }
+        _ => None,
+    }
+}
+
+// Argumento `index` o `name=` de una llamada; `None` de Python cuenta como ausente.
+fn call_arg<'a>(call: &'a DrawCall, index: usize, name: &str) -> Option<&'a Expr> {
+    call.kwargs.iter()
+        .find(|(k, _)| k == name)
+        .map(|(_, v)| v)
+        .or(call.args.get(index))
+        .filter(|e| !matches!(e, Expr::Name(n) if n == "None"))
+}
+
+// Tupla RGB(A) de enteros o `sdl2.ext.Color(...)`.
+fn is_color(expr: &Expr) -> bool {
+    match expr {
+        Expr::Tuple(items) => {
+            (3..=4).contains(&items.len()) && items.iter().all(|e| matches!(e, Expr::Int(n) if (0..=255).contains(n)))
+        }
+        Expr::Call { function, .. } => function.ends_with(".Color"),
+        _ => false,
+    }
+}
+
+fn color_expr(expr: &Expr, config: &Config) -> String {
+    let components = match expr {
+        Expr::Tuple(items) | Expr::Call { args: items, .. } if (3..=4).contains(&items.len()) => items,
+        _ => return rust_expr(expr, config),
+    };
+    let values: Vec<String> = components.iter().map(|e| rust_expr_as(e, "u8", config)).collect();
+    let constructor = if values.len() == 4 { "RGBA" } else { "RGB" };
+    format!("Color::{}({})", constructor, values.join(", "))
+}
+
+// `name` aparece en `code` como identificador completo.
+fn mentions(code: &str, name: &str) -> bool {
+    identifier_count(code, name) > 0
+}
+
+// Veces que `name` aparece en `code` como identificador completo.
+fn identifier_count(code: &str, name: &str) -> usize {
+    let is_ident = |c: char| c.is_alphanumeric() || c == '_';
+    code.match_indices(name).filter(|(start, _)| {
+        let before = code[..*start].chars().next_back();
+        let after = code[start + name.len()..].chars().next();
+        !before.is_some_and(is_ident) && !after.is_some_and(is_ident)
+    }).count()
+}
+
+// Componentes `(x, y, w, h)` de un rectángulo: tupla, `sdl2.SDL_Rect(...)` o una
+// variable asignada con uno de ellos antes de `line`.
+fn rect_components<'a>(expr: &'a Expr, state: &'a State, line: usize) -> Option<&'a [Expr]> {
+    match expr {
+        Expr::Tuple(items) | Expr::List(items) if items.len() == 4 && !items.iter().any(is_collection) => Some(items),
+        Expr::Call { function, args } if function.ends_with("Rect") && args.len() == 4 => Some(args),
+        Expr::Name(name) => {
+            let mut definitions = state.variables.iter().filter(|v| &v.name == name);
+            let var = definitions.clone().rfind(|v| v.line <= line).or(definitions.next())?;
+            // Un `sdl2.SDL_Rect(...)` asignado es un `Rect` que se usa por su nombre
+            match var.value.as_ref()? {
+                Expr::Name(_) | Expr::Call { .. } => None,
+                value => rect_components(value, state, line),
+            }
+        }
+        _ => None,
+    }
+}
+
+// Tupla, lista o rectángulo construido (`sdl2.SDL_Rect(...)`); `int(y)` o
+// `abs(y)` son números
+fn is_collection(expr: &Expr) -> bool {
+    match expr {
+        Expr::Tuple(_) | Expr::List(_) => true,
+        Expr::Call { function, .. } => function.ends_with("Rect"),
+        _ => false,
+    }
+}
+
+fn rect_expr(expr: &Expr, state: &State, line: usize, config: &Config) -> String {
+    match rect_components(expr, state, line) {
+        Some([x, y, w, h]) => format!(
+            "Rect::new({}, {}, {}, {})",
+            rust_expr_as(x, "i32", config),
+            rust_expr_as(y, "i32", config),
+            rust_expr_as(w, "u32", config),
+            rust_expr_as(h, "u32", config)
+        ),
+        // Variable que ya contiene un `Rect`
+        _ => rust_expr(expr, config),
+    }
+}
+
+// Uno o varios rectángulos: `fill(rect)` o `fill([r1, r2])`.
+fn rects(expr: &Expr, state: &State, line: usize, config: &Config) -> Vec<String> {
+    match expr {
+        Expr::List(items) | Expr::Tuple(items) if rect_components(expr, state, line).is_none()
+            && items.iter().all(|item| is_collection(item) || matches!(item, Expr::Name(_))) => {
+            items.iter().map(|item| rect_expr(item, state, line, config)).collect()
+        }
+        _ => vec![rect_expr(expr, state, line, config)],
+    }
+}
+
+// Puntos de `draw_point`/`draw_line`: `(x, y)`, `[(x1, y1), (x2, y2)]` o la
+// lista plana `[x1, y1, x2, y2]`.
+fn points(expr: &Expr) -> Option<Vec<(&Expr, &Expr)>> {
+    let items = match expr {
+        Expr::Tuple(items) | Expr::List(items) => items,
+        _ => return None,
+    };
+    if items.iter().all(is_collection) {
+        items.iter()
+            .map(|item| match item {
+                Expr::Tuple(pair) | Expr::List(pair) if pair.len() == 2 => Some((&pair[0], &pair[1])),
+                _ => None,
+            })
+            .collect()
+    } else if items.len() % 2 == 0 && !items.iter().any(is_collection) {
+        Some(items.chunks(2).map(|pair| (&pair[0], &pair[1])).collect())
+    } else {
+        None
+    }
+}
+
+fn point_expr(point: (&Expr, &Expr), config: &Config) -> String {
+    format!("({}, {})", rust_expr_as(point.0, "i32", config), rust_expr_as(point.1, "i32", config))
+}
+
+fn point_slice(points: &[(&Expr, &Expr)], config: &Config) -> String {
+    let items: Vec<String> = points.iter().map(|p| format!("Point::new{}", point_expr(*p, config))).collect();
+    format!("&{}[..]", array(&items))
+}
+
+// rustfmt deja en la línea de la llamada una lista que cabe en una sola línea.
+fn points_call(method: &str, points: &[(&Expr, &Expr)], config: &Config) -> String {
+    let slice = point_slice(points, config);
+    if slice.contains('\n') {
+        function_call(method, &[slice], "?;")
+    } else {
+        format!("{}({})?;", method, slice)
+    }
+}
+
+// Textura de `copy`/`rcopy`/`blit`: `&sprite`.
+fn texture_ref(expr: &Expr, config: &Config) -> String {
+    match expr {
+        Expr::Name(name) => format!("&{}", config.variable_name(name)),
+        _ => format!("&{}", rust_expr(expr, config)),
+    }
+}
+
+// `(flip_horizontal, flip_vertical)` a partir de `sdl2.SDL_FLIP_*`.
+fn flip_flags(expr: Option<&Expr>) -> (bool, bool) {
+    let mut names = Vec::new();
+    if let Some(expr) = expr {
+        collect_attr_names(expr, &mut names);
+    }
+    (
+        names.iter().any(|n| n == "SDL_FLIP_HORIZONTAL"),
+        names.iter().any(|n| n == "SDL_FLIP_VERTICAL"),
+    )
+}
+
+fn collect_attr_names(expr: &Expr, names: &mut Vec<String>) {
+    match expr {
+        Expr::BinOp { op, left, right } if op == "|" => {
+            collect_attr_names(left, names);
+            collect_attr_names(right, names);
+        }
+        Expr::Attr(path) => names.push(path.rsplit('.').next().unwrap_or(path).to_string()),
+        _ => {}
+    }
+}
+
+// Copia con el tamaño de la textura, que se consulta antes.
+fn query_block(texture: &str, copy: &str) -> String {
+    format!("{{\n    let query = ({}).query();\n    {}\n}}", texture, copy.replace('\n', "\n    "))
+}
+
+// `canvas.copy(...)` o, con ángulo, centro o volteo, `canvas.copy_ex(...)`.
+// `dst` es el rectángulo ya traducido o None para toda la ventana.
+fn copy_code(call: &DrawCall, texture: &str, src: &str, dst: &str, first_extra: usize, config: &Config) -> String {
+    let angle = call_arg(call, first_extra, "angle");
+    let center = call_arg(call, first_extra + 1, "center");
+    let flip = call_arg(call, first_extra + 2, "flip");
+    if angle.is_none() && center.is_none() && flip.is_none() {
+        return function_call("canvas.copy", &[texture.to_string(), src.to_string(), dst.to_string()], "?;");
+    }
+    let angle = match angle {
+        Some(Expr::Int(n)) => format!("{}.0", n),
+        Some(expr) => rust_expr_as(expr, "f64", config),
+        None => "0.0".to_string(),
+    };
+    let center = match center.and_then(points).as_deref() {
+        Some([point]) => format!("Some(Point::new{})", point_expr(*point, config)),
+        _ => "None".to_string(),
+    };
+    let (horizontal, vertical) = flip_flags(flip);
+    let args = [texture.to_string(), src.to_string(), dst.to_string(), angle, center, horizontal.to_string(), vertical.to_string()];
+    function_call("canvas.copy_ex", &args, "?;")
+}
+
+// Código Rust de una llamada de dibujo del renderer sobre `canvas`.
+fn draw_call_code(call: &DrawCall, state: &State, config: &Config) -> String {
+    // Color opcional: el de PySDL2 se fija antes de dibujar
+    let color_index = match call.method.as_str() {
+        "clear" => Some(0),
+        "draw_point" | "draw_line" | "draw_rect" | "fill" => Some(1),
+        _ => None,
+    };
+    let mut code = String::new();
+    if let Some(color) = color_index.and_then(|index| call_arg(call, index, "color")) {
+        code.push_str(&format!("canvas.set_draw_color({});\n", color_expr(color, config)));
+    }
+
+    let first = call.args.first();
+    let line = match call.method.as_str() {
+        "set_draw_color" => format!("canvas.set_draw_color({});", first.map(|e| color_expr(e, config)).unwrap_or_default()),
+        "clear" => "canvas.clear();".to_string(),
+        "present" => "canvas.present();".to_string(),
+        "draw_point" => match first.and_then(points).as_deref() {
+            Some([point]) => format!("canvas.draw_point({})?;", point_expr(*point, config)),
+            Some(many) => points_call("canvas.draw_points", many, config),
+            None => format!("canvas.draw_point({})?;", first.map(|e| rust_expr(e, config)).unwrap_or_default()),
+        },
+        "draw_line" => match first.and_then(points).as_deref() {
+            Some([start, end]) => format!("canvas.draw_line({}, {})?;", point_expr(*start, config), point_expr(*end, config)),
+            Some(many) => points_call("canvas.draw_lines", many, config),
+            None => format!("canvas.draw_lines({})?;", first.map(|e| rust_expr(e, config)).unwrap_or_default()),
+        },
+        "draw_rect" | "fill" => {
+            let rects = first.map(|e| rects(e, state, call.line, config)).unwrap_or_default();
+            let single = if call.method == "fill" { "fill_rect" } else { "draw_rect" };
+            match rects.as_slice() {
+                [_] => function_call(&format!("canvas.{}", single), &rects, "?;"),
+                many => format!("canvas.{}s(&[{}])?;", single, many.join(", ")),
+            }
+        }
+        "copy" | "blit" => {
+            let texture = first.map(|e| texture_ref(e, config)).unwrap_or_default();
+            let src = call_arg(call, 1, "srcrect").map(|e| rect_expr(e, state, call.line, config)).unwrap_or("None".to_string());
+            match call_arg(call, 2, "dstrect") {
+                // Solo posición: el tamaño es el de la textura
+                Some(Expr::Tuple(position)) if position.len() == 2 => {
+                    let dst = format!(
+                        "Rect::new({}, {}, query.width, query.height)",
+                        rust_expr_as(&position[0], "i32", config),
+                        rust_expr_as(&position[1], "i32", config)
+                    );
+                    query_block(&texture, &copy_code(call, &texture, &src, &dst, 3, config))
+                }
+                Some(dst) => copy_code(call, &texture, &src, &rect_expr(dst, state, call.line, config), 3, config),
+                None => copy_code(call, &texture, &src, "None", 3, config),
+            }
+        }
+        // rcopy(src, loc, size=None, align=(0.0, 0.0), angle=0, center=None, flip=0)
+        "rcopy" => {
+            let texture = first.map(|e| texture_ref(e, config)).unwrap_or_default();
+            let location = call_arg(call, 1, "loc").and_then(points);
+            let size = call_arg(call, 2, "size").and_then(points);
+            match (location.as_deref(), size.as_deref()) {
+                (Some([(x, y)]), Some([(width, height)])) => {
+                    let dst = format!(
+                        "Rect::new({}, {}, {}, {})",
+                        rust_expr_as(x, "i32", config),
+                        rust_expr_as(y, "i32", config),
+                        rust_expr_as(width, "u32", config),
+                        rust_expr_as(height, "u32", config)
+                    );
+                    copy_code(call, &texture, "None", &dst, 4, config)
+                }
+                (Some([(x, y)]), _) => {
+                    let dst = format!(
+                        "Rect::new({}, {}, query.width, query.height)",
+                        rust_expr_as(x, "i32", config),
+                        rust_expr_as(y, "i32", config)
+                    );
+                    query_block(&texture, &copy_code(call, &texture, "None", &dst, 4, config))
+                }
+                _ => copy_code(call, &texture, "None", "None", 4, config),
+            }
+        }
+        // Función del script que dibuja con el renderer: draw_circle(renderer, color, center, radius)
+        "draw_circle" => {
+            let color = call_arg(call, 0, "color").map(|e| color_expr(e, config)).unwrap_or_default();
+            let center = match call_arg(call, 1, "center").and_then(points).as_deref() {
+                Some([point]) => point_expr(*point, config),
+                _ => call_arg(call, 1, "center").map(|e| rust_expr(e, config)).unwrap_or_default(),
+            };
+            let radius = call_arg(call, 2, "radius").map(|e| rust_expr_as(e, "i32", config)).unwrap_or_default();
+            format!("draw_circle(&mut canvas, {}, {}, {});", color, center, radius)
+        }
+        other => format!("// {}: sin traducción", other),
+    };
+    code.push_str(&line);
+    code
+}
+
+// Copia de `expr` con las rutas `Attr` que `bind` sustituye.
+fn replace_attrs(expr: &Expr, bind: &mut dyn FnMut(&str) -> Option<Expr>) -> Expr {
+    match expr {
+        Expr::Attr(path) => bind(path).unwrap_or_else(|| expr.clone()),
+        Expr::Tuple(items) => Expr::Tuple(items.iter().map(|e| replace_attrs(e, bind)).collect()),
+        Expr::List(items) => Expr::List(items.iter().map(|e| replace_attrs(e, bind)).collect()),
+        Expr::Call { function, args } => Expr::Call {
+            function: function.clone(),
+            args: args.iter().map(|e| replace_attrs(e, bind)).collect(),
+        },
+        Expr::Index { value, index } => Expr::Index {
+            value: Box::new(replace_attrs(value, bind)),
+            index: Box::new(replace_attrs(index, bind)),
+        },
+        Expr::MethodCall { receiver, method, args } => Expr::MethodCall {
+            receiver: Box::new(replace_attrs(receiver, bind)),
+            method: method.clone(),
+            args: args.iter().map(|e| replace_attrs(e, bind)).collect(),
+        },
+        Expr::Try(value) => Expr::Try(Box::new(replace_attrs(value, bind))),
+        Expr::UnaryOp { op, operand } => Expr::UnaryOp { op: op.clone(), operand: Box::new(replace_attrs(operand, bind)) },
+        Expr::BinOp { op, left, right } => Expr::BinOp {
+            op: op.clone(),
+            left: Box::new(replace_attrs(left, bind)),
+            right: Box::new(replace_attrs(right, bind)),
+        },
+        _ => expr.clone(),
+    }
+}
+
+fn replace_attrs_in(stmts: &[Stmt], bind: &mut dyn FnMut(&str) -> Option<Expr>) -> Vec<Stmt> {
+    stmts.iter().map(|stmt| {
+        let kind = match &stmt.kind {
+            StmtKind::Assign { target, value } => StmtKind::Assign { target: target.clone(), value: replace_attrs(value, bind) },
+            StmtKind::AugAssign { target, op, value } => {
+                StmtKind::AugAssign { target: target.clone(), op: op.clone(), value: replace_attrs(value, bind) }
+            }
+            StmtKind::If { condition, body, orelse } => StmtKind::If {
+                condition: replace_attrs(condition, bind),
+                body: replace_attrs_in(body, bind),
+                orelse: replace_attrs_in(orelse, bind),
+            },
+            StmtKind::For { target, iter, body } => StmtKind::For {
+                target: target.clone(),
+                iter: replace_attrs(iter, bind),
+                body: replace_attrs_in(body, bind),
+            },
+            StmtKind::Expr(expr) => StmtKind::Expr(replace_attrs(expr, bind)),
+            kind => kind.clone(),
+        };
+        Stmt { kind, line: stmt.line, column: stmt.column }
+    }).collect()
+}
+
+// Constantes (último segmento de las rutas) de las sentencias, con su línea Python
+fn collect_constants(stmts: &[Stmt], found: &mut Vec<(usize, String)>) {
+    for stmt in stmts {
+        let mut names = Vec::new();
+        let mut collect = |path: &str| {
+            names.push(path.rsplit('.').next().unwrap_or(path).to_string());
+            None
+        };
+        match &stmt.kind {
+            StmtKind::Assign { value, .. } | StmtKind::AugAssign { value, .. } | StmtKind::Expr(value) | StmtKind::Return(Some(value)) => {
+                replace_attrs(value, &mut collect);
+            }
+            StmtKind::If { condition, body, orelse } => {
+                replace_attrs(condition, &mut collect);
+                collect_constants(body, found);
+                collect_constants(orelse, found);
+            }
+            StmtKind::For { iter, body, .. } => {
+                replace_attrs(iter, &mut collect);
+                collect_constants(body, found);
+            }
+            StmtKind::Break | StmtKind::Continue | StmtKind::Return(None) => {}
+        }
+        found.extend(names.into_iter().map(|name| (stmt.line, name)));
+    }
+}
+
+// Constantes que usan los manejadores de eventos y el bucle principal
+fn used_constants(state: &State) -> Vec<(usize, String)> {
+    let mut found = Vec::new();
+    if let Some(events) = &state.events {
+        for handler in &events.handlers {
+            if let Some(guard) = &handler.guard {
+                replace_attrs(guard, &mut |path| {
+                    found.push((handler.line, path.rsplit('.').next().unwrap_or(path).to_string()));
+                    None
+                });
+            }
+            collect_constants(&handler.body, &mut found);
+        }
+    }
+    collect_constants(&state.loop_body, &mut found);
+    found
+}
+
+// Líneas de todas las sentencias, incluidas las de sus bloques
+fn stmt_lines(stmts: &[Stmt], lines: &mut HashSet<usize>) {
+    for stmt in stmts {
+        lines.insert(stmt.line);
+        match &stmt.kind {
+            StmtKind::If { body, orelse, .. } => {
+                stmt_lines(body, lines);
+                stmt_lines(orelse, lines);
+            }
+            StmtKind::For { body, .. } => stmt_lines(body, lines),
+            _ => {}
+        }
+    }
+}
+
+// Lo que necesitan las sentencias traducidas: las llamadas de dibujo ya
+// generadas y qué variables se declararon o se sustituyen por su valor.
+struct StmtContext<'a> {
+    state: &'a State,
+    config: &'a Config,
+    draw_code: &'a [String],
+    declared: HashSet<String>,
+    inlined: HashSet<String>,
+    // Variables con un `MouseState` (`buttons = sdl2.ext.mouse_button_state()`)
+    mouse_states: HashSet<String>,
+    // Sonidos y músicas cargados, y los que cambian de volumen
+    sounds: HashSet<String>,
+    adjusted_sounds: HashSet<String>,
+    // Rectángulos y puntos, y los que cambian después de crearse
+    shapes: HashMap<String, Shape>,
+    mutated_shapes: HashSet<String>,
+    // Instancias de clases del script, cuyos campos se asignan (`p.x = 0`)
+    objects: HashSet<String>,
+    // Funciones traducidas del script
+    functions: HashSet<String>,
+    // Líneas de las sentencias que no se traducen
+    dropped: RefCell<Vec<usize>>,
+}
+
+impl StmtContext<'_> {
+    // `target` es una variable declarada o un campo de una instancia
+    fn assignable(&self, target: &str) -> bool {
+        match target.split_once('.') {
+            Some((object, _)) => self.objects.contains(object),
+            None => self.declared.contains(target),
+        }
+    }
+
+    // `buttons.left` como `buttons.left()` sobre un `MouseState` y `rect.w` como
+    // `rect.width()` sobre un `Rect`
+    fn bind_attrs(&self, expr: &Expr) -> Expr {
+        replace_attrs(expr, &mut |path| {
+            let (object, attribute) = path.split_once('.')?;
+            let button = ["left", "middle", "right", "x1", "x2"].contains(&attribute);
+            if button && self.mouse_states.contains(object) {
+                return Some(Expr::MethodCall {
+                    receiver: Box::new(Expr::Name(object.to_string())),
+                    method: attribute.to_string(),
+                    args: Vec::new(),
+                });
+            }
+            geometry::field_read(path, &self.shapes, &self.config.int_type)
+        })
+    }
+
+    fn expr(&self, expr: &Expr) -> String {
+        rust_expr(&self.bind_attrs(expr), self.config)
+    }
+
+    // Lo que recorre un `for`: una lista declarada o un `range`
+    fn loop_code(&self, iter: &Expr) -> Option<String> {
+        match iter {
+            Expr::MethodCall { receiver, .. } if list_iter(iter) => match receiver.as_ref() {
+                Expr::Name(name) if self.declared.contains(name) => Some(self.expr(iter)),
+                _ => None,
+            },
+            _ => range_code(iter, &|expr| self.expr(expr)),
+        }
+    }
+
+    fn drop_stmt(&self, w: &mut CodeWriter, stmt: &Stmt) {
+        self.dropped.borrow_mut().push(stmt.line);
+        w.line(&format!("// sin traducción (línea {})", stmt.line));
+    }
+
+    fn expr_as(&self, expr: &Expr, ty: &str) -> String {
+        rust_expr_as(&self.bind_attrs(expr), ty, self.config)
+    }
+
+    // Argumento de SDL_mixer: los números son `i32` y los sonidos se pasan tal cual
+    fn mixer_arg(&self, expr: &Expr) -> String {
+        match expr {
+            Expr::Name(name) if self.sounds.contains(name) => self.config.variable_name(name),
+            Expr::Attr(_) | Expr::BinOp { .. } => rust_expr(expr, self.config),
+            _ => rust_expr_as(expr, "i32", self.config),
+        }
+    }
+}
+
+fn geometry_stmt_code(stmt: &Stmt, ctx: &StmtContext) -> Option<String> {
+    let expr = |expr: &Expr| ctx.expr(expr);
+    let expr_as = |expr: &Expr, ty: &str| ctx.expr_as(expr, ty);
+    geometry::stmt_code(stmt, &ctx.shapes, &ctx.declared, &ctx.mutated_shapes, &expr, &expr_as)
+}
+
+fn timing_stmt_code(stmt: &Stmt, ctx: &StmtContext) -> Option<String> {
+    timing::stmt_code(stmt, &ctx.declared, &HashSet::new(), &|expr| ctx.expr(expr), &ctx.config.int_type)
+}
+
+fn mixer_stmt_code(stmt: &Stmt, ctx: &StmtContext) -> Option<String> {
+    let path = |expr: &Expr| image_path(expr, ctx.state, ctx.config);
+    audio::stmt_code(stmt, &ctx.adjusted_sounds, &ctx.declared, &|expr| ctx.mixer_arg(expr), &path)
+}
+
+// Funciones de PySDL2 que leen el estado del ratón y el método de `EventPump`
+const MOUSE_STATE_FUNCTIONS: [(&str, &str); 6] = [
+    ("SDL_GetMouseState", "mouse_state"),
+    ("SDL_GetGlobalMouseState", "mouse_state"),
+    ("SDL_GetRelativeMouseState", "relative_mouse_state"),
+    ("mouse_coords", "mouse_state"),
+    ("mouse_button_state", "mouse_state"),
+    ("mouse_delta", "relative_mouse_state"),
+];
+
+// `SDL_SYSTEM_CURSOR_*` -> `SystemCursor`
+const SYSTEM_CURSORS: [(&str, &str); 12] = [
+    ("SDL_SYSTEM_CURSOR_ARROW", "Arrow"),
+    ("SDL_SYSTEM_CURSOR_IBEAM", "IBeam"),
+    ("SDL_SYSTEM_CURSOR_WAIT", "Wait"),
+    ("SDL_SYSTEM_CURSOR_CROSSHAIR", "Crosshair"),
+    ("SDL_SYSTEM_CURSOR_WAITARROW", "WaitArrow"),
+    ("SDL_SYSTEM_CURSOR_SIZENWSE", "SizeNWSE"),
+    ("SDL_SYSTEM_CURSOR_SIZENESW", "SizeNESW"),
+    ("SDL_SYSTEM_CURSOR_SIZEWE", "SizeWE"),
+    ("SDL_SYSTEM_CURSOR_SIZENS", "SizeNS"),
+    ("SDL_SYSTEM_CURSOR_SIZEALL", "SizeAll"),
+    ("SDL_SYSTEM_CURSOR_NO", "No"),
+    ("SDL_SYSTEM_CURSOR_HAND", "Hand"),
+];
+
+// Función de una llamada sin el módulo: `sdl2.ext.mouse.hide_cursor` -> `hide_cursor`
+fn function_name(function: &str) -> &str {
+    function.rsplit('.').next().unwrap_or(function)
+}
+
+// Si el argumento activa la opción: `SDL_ENABLE`, `SDL_TRUE`, `True` o 1
+fn flag_value(expr: Option<&Expr>) -> Option<bool> {
+    match expr? {
+        Expr::Int(n) => Some(*n != 0),
+        Expr::Bool(b) => Some(*b),
+        Expr::Attr(path) => match function_name(path) {
+            "SDL_ENABLE" | "SDL_TRUE" => Some(true),
+            "SDL_DISABLE" | "SDL_FALSE" => Some(false),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+// `x, y = SDL_GetMouseState()` y similares: el estado se lee de `event_pump` y
+// cada coordenada se asigna (o se declara si el script no la declaró)
+fn mouse_state_code(targets: &[&str], function: &str, ctx: &StmtContext) -> Option<String> {
+    let name = function_name(function);
+    let (_, getter) = MOUSE_STATE_FUNCTIONS.iter().find(|(f, _)| *f == name)?;
+    let default_var = if *getter == "mouse_state" { "mouse_state" } else { "relative_state" };
+    let (state_var, coords) = match targets {
+        [buttons] if name == "mouse_button_state" => (*buttons, &[][..]),
+        [buttons, _, _] if name.starts_with("SDL_") && *buttons != "_" => (*buttons, &targets[1..]),
+        [_, _, _] if name.starts_with("SDL_") => (default_var, &targets[1..]),
+        [_, _] => (default_var, targets),
+        _ => return None,
+    };
+    let config = ctx.config;
+    let state_var = config.variable_name(state_var);
+    let mut lines = vec![format!("let {} = event_pump.{}();", state_var, getter)];
+    for (target, method) in coords.iter().zip(["x", "y"]) {
+        if *target == "_" {
+            continue;
+        }
+        let value = if config.int_type == "i32" {
+            format!("{}.{}()", state_var, method)
+        } else {
+            format!("{}::from({}.{}())", config.int_type, state_var, method)
+        };
+        let keyword = if ctx.declared.contains(*target) { "" } else { "let " };
+        lines.push(format!("{}{} = {};", keyword, config.variable_name(target), value));
+    }
+    Some(lines.join("\n"))
+}
+
+// Cursor y modos del ratón: `SDL_ShowCursor`, `sdl2.ext.mouse.hide_cursor()`,
+// `SDL_SetRelativeMouseMode`, `SDL_WarpMouseInWindow`, `SDL_SetCursor`...
+fn mouse_call_code(function: &str, args: &[Expr], ctx: &StmtContext) -> Option<String> {
+    let method = match (function_name(function), args) {
+        ("SDL_ShowCursor", [arg]) => format!(".show_cursor({})", flag_value(Some(arg))?),
+        ("show_cursor", []) => ".show_cursor(true)".to_string(),
+        ("hide_cursor", []) => ".show_cursor(false)".to_string(),
+        ("SDL_SetRelativeMouseMode", [arg]) => format!(".set_relative_mouse_mode({})", flag_value(Some(arg))?),
+        ("SDL_CaptureMouse", [arg]) => format!(".capture({})", flag_value(Some(arg))?),
+        ("SDL_WarpMouseInWindow", [_, x, y]) | ("warp_mouse", [x, y]) => format!(
+            ".warp_mouse_in_window(canvas.window(), {}, {})",
+            rust_expr_as(x, "i32", ctx.config),
+            rust_expr_as(y, "i32", ctx.config)
+        ),
+        (name, args) => return cursor_call_code(name, args, ctx),
+    };
+    Some(method_chain("sdl_context", &[".mouse()".to_string(), method], ";"))
+}
+
+fn cursor_call_code(name: &str, args: &[Expr], ctx: &StmtContext) -> Option<String> {
+    match (name, args) {
+        ("SDL_SetCursor", [cursor]) => Some(format!("{}.set();", ctx.expr(cursor))),
+        ("SDL_FreeCursor", [cursor]) => Some(format!("drop({});", ctx.expr(cursor))),
+        _ => None,
+    }
+}
+
+// `cursor = sdl2.SDL_CreateSystemCursor(sdl2.SDL_SYSTEM_CURSOR_HAND)`
+fn cursor_code(target: &str, value: &Expr, config: &Config) -> Option<String> {
+    let Expr::Call { function, args } = value else {
+        return None;
+    };
+    let [Expr::Attr(path)] = args.as_slice() else {
+        return None;
+    };
+    if function_name(function) != "SDL_CreateSystemCursor" {
+        return None;
+    }
+    let (_, cursor) = SYSTEM_CURSORS.iter().find(|(c, _)| *c == function_name(path))?;
+    Some(format!("let {} = Cursor::from_system(SystemCursor::{})?;", config.variable_name(target), cursor))
+}
+
+// Variables que guardan el `MouseState` de un `SDL_GetMouseState` o
+// `mouse_button_state()`
+fn mouse_state_vars(stmts: &[Stmt], vars: &mut HashSet<String>) {
+    for stmt in stmts {
+        match &stmt.kind {
+            StmtKind::Assign { target, value: Expr::Call { function, .. } } => {
+                let name = function_name(function);
+                let targets: Vec<&str> = target.split(", ").collect();
+                let buttons = match targets.as_slice() {
+                    [buttons] if name == "mouse_button_state" => Some(*buttons),
+                    [buttons, _, _] if name.starts_with("SDL_Get") && name.ends_with("MouseState") => Some(*buttons),
+                    _ => None,
+                };
+                vars.extend(buttons.filter(|b| *b != "_").map(String::from));
+            }
+            StmtKind::If { body, orelse, .. } => {
+                mouse_state_vars(body, vars);
+                mouse_state_vars(orelse, vars);
+            }
+            StmtKind::For { body, .. } => mouse_state_vars(body, vars),
+            _ => {}
+        }
+    }
+}
+
+// Código de las sentencias del ratón, tanto en el bucle como antes de él
+fn mouse_stmt_code(stmt: &Stmt, ctx: &StmtContext) -> Option<String> {
+    match &stmt.kind {
+        StmtKind::Assign { target, value: Expr::Call { function, .. } } if target.contains(", ")
+            || MOUSE_STATE_FUNCTIONS.iter().any(|(f, _)| *f == function_name(function)) => {
+            let targets: Vec<&str> = target.split(", ").collect();
+            mouse_state_code(&targets, function, ctx)
+        }
+        StmtKind::Assign { target, value } => cursor_code(target, value, ctx.config),
+        StmtKind::Expr(Expr::Call { function, args }) => mouse_call_code(function, args, ctx),
+        _ => None,
+    }
+}
+
+// Ruta de un archivo de imagen: las que se copian al proyecto van con `asset(..)`.
+fn image_path(expr: &Expr, state: &State, config: &Config) -> String {
+    match textures::asset_path(expr, state) {
+        Some(path) => format!("asset({:?})", path),
+        None => rust_expr(expr, config),
+    }
+}
+
+// `let` de una textura o superficie; las texturas son de `texture_creator`.
+fn image_code(target: &str, image: &ImageValue, state: &State, config: &Config) -> String {
+    let value = match image {
+        ImageValue::Texture(path) => {
+            let load = format!(".load_texture({})", image_path(path, state, config));
+            method_chain("texture_creator", &[load], "?;")
+        }
+        ImageValue::Surface(path) => format!("Surface::from_file({})?;", image_path(path, state, config)),
+        ImageValue::Blank { width, height } => {
+            let args = [rust_expr_as(width, "u32", config), rust_expr_as(height, "u32", config), "PixelFormatEnum::RGBA32".to_string()];
+            let new = function_call("Surface::new", &args, "?;");
+            // `sdl2.ext.fill` la modifica
+            let mutable = if textures::filled_surfaces(state).contains(target) { "mut " } else { "" };
+            return format!("let {}{} = {}", mutable, config.variable_name(target), new);
+        }
+        ImageValue::FromSurface(surface) => {
+            let create = format!(".create_texture_from_surface(&{})", rust_expr(surface, config));
+            method_chain("texture_creator", &[create, ".map_err(|e| e.to_string())".to_string()], "?;")
+        }
+        ImageValue::Sprite(texture) => format!("{};", rust_expr(texture, config)),
+        ImageValue::Font { path, size } => {
+            let size = match size {
+                Some(Expr::Int(n)) => n.to_string(),
+                // `"24px"`, `"24pt"`
+                Some(Expr::Str(raw)) => raw.chars().filter(|c| c.is_ascii_digit()).collect(),
+                Some(expr) => rust_expr_as(expr, "u16", config),
+                None => "16".to_string(),
+            };
+            let load = format!(".load_font({}, {})", image_path(path, state, config), size);
+            method_chain("ttf_context", &[load], "?;")
+        }
+        ImageValue::Text { font, text, style, colors } => {
+            let mut colors: Vec<String> = colors.iter().map(|c| color_expr(c, config)).collect();
+            if colors.is_empty() {
+                colors.push(font_color(font, state, config));
+            }
+            let calls = [
+                format!(".render({})", text_arg(text, config)),
+                format!(".{}({})", style, colors.join(", ")),
+                ".map_err(|e| e.to_string())".to_string(),
+            ];
+            method_chain(&config.variable_name(font), &calls, "?;")
+        }
+    };
+    format!("let {} = {}", config.variable_name(target), value)
+}
+
+// Color de `sdl2.ext.FontTTF(ruta, tamaño, color)`; blanco si no lo indica.
+fn font_color(font: &str, state: &State, config: &Config) -> String {
+    state.variables.iter()
+        .find(|v| v.name == font)
+        .and_then(|v| match &v.value {
+            Some(Expr::Call { function, args }) if function.ends_with("FontTTF") => args.get(2),
+            _ => None,
+        })
+        .map(|color| color_expr(color, config))
+        .unwrap_or("Color::RGB(255, 255, 255)".to_string())
+}
+
+// Texto a dibujar como `&str`.
+fn text_arg(text: &Expr, config: &Config) -> String {
+    match text {
+        Expr::Str(raw) if !raw.starts_with(['f', 'F']) => rust_string(raw),
+        _ => format!("&{}", rust_expr(text, config)),
+    }
+}
+
+// `sdl2.ext.fill(superficie, color, area)` sobre una superficie propia.
+fn fill_stmt_code(stmt: &Stmt, ctx: &StmtContext) -> Option<String> {
+    let StmtKind::Expr(Expr::Call { function, args }) = &stmt.kind else {
+        return None;
+    };
+    let (surface, color, area) = textures::fill_args(function, args)?;
+    let area = area.map(|area| rect_expr(area, ctx.state, stmt.line, ctx.config)).unwrap_or("None".to_string());
+    let fill = format!(".fill_rect({}, {})", area, color_expr(color, ctx.config));
+    Some(method_chain(&ctx.config.variable_name(surface), &[fill], "?;"))
+}
+
+// Texturas, superficies y fuentes creadas dentro del bucle, y su liberación.
+fn image_stmt_code(stmt: &Stmt, ctx: &StmtContext) -> Option<String> {
+    match &stmt.kind {
+        StmtKind::Assign { target, value } => {
+            textures::image_value(value).map(|image| image_code(target, &image, ctx.state, ctx.config))
+        }
+        StmtKind::Expr(Expr::Call { function, args }) if textures::is_release(function) => match args.as_slice() {
+            [Expr::Name(name)] => Some(format!("drop({});", ctx.config.variable_name(name))),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+// Última línea Python de una sentencia, contando sus bloques
+fn last_line(stmt: &Stmt) -> usize {
+    match &stmt.kind {
+        StmtKind::If { body, orelse, .. } => body.iter().chain(orelse).map(last_line).max().unwrap_or(stmt.line),
+        StmtKind::For { body, .. } => body.iter().map(last_line).max().unwrap_or(stmt.line),
+        _ => stmt.line,
+    }
+}
+
+// Código de la llamada de dibujo, con el contador de `--instrument`
+fn write_draw_call(w: &mut CodeWriter, call: &DrawCall, code: &str, config: &Config) {
+    w.line(code);
+    if config.instrument && !code.starts_with("//") && !["set_draw_color", "clear", "present"].contains(&call.method.as_str()) {
+        w.line("#[cfg(feature = \"instrument\")]");
+        w.line("{");
+        w.line("    frame_stats.draw_calls += 1;");
+        w.line("}");
+    }
+}
+
+// Sentencias del bucle principal o de un manejador de eventos; las líneas en
+// blanco separan lo que en Python no estaba en líneas seguidas
+fn write_stmts(w: &mut CodeWriter, stmts: &[Stmt], ctx: &StmtContext) {
+    let config = ctx.config;
+    for (index, stmt) in stmts.iter().enumerate() {
+        let region = w.begin(stmt.line, stmt.column);
+        let draw = ctx.state.draw_calls.iter().position(|call| call.line == stmt.line);
+        let code = mouse_stmt_code(stmt, ctx)
+            .or_else(|| image_stmt_code(stmt, ctx))
+            .or_else(|| fill_stmt_code(stmt, ctx))
+            .or_else(|| mixer_stmt_code(stmt, ctx))
+            .or_else(|| timing_stmt_code(stmt, ctx))
+            .or_else(|| geometry_stmt_code(stmt, ctx));
+        match &stmt.kind {
+            // Llamadas del proyecto sin traducir: se avisa al generar
+            _ if classes::stmt_exprs(stmt).into_iter().any(|expr| !unlinked(expr).is_empty()) => {
+                w.line(&format!("// sin traducción (línea {})", stmt.line));
+            }
+            _ if code.is_some() => w.line(&code.unwrap_or_default()),
+            // `renderer.clear(...)`, `renderer.color = ...`, `draw_circle(renderer, ...)`
+            StmtKind::Expr(_) | StmtKind::Assign { .. } if draw.is_some() => {
+                let index = draw.unwrap_or_default();
+                write_draw_call(w, &ctx.state.draw_calls[index], &ctx.draw_code[index], config);
+            }
+            StmtKind::Assign { target, value: Expr::Call { function, .. } } if function.ends_with("SDL_GetKeyboardState") => {
+                w.line(&format!("let {} = event_pump.keyboard_state();", config.variable_name(target)));
+            }
+            // Colores, rectángulos... se usan ya sustituidos por su valor
+            StmtKind::Assign { target, .. } | StmtKind::AugAssign { target, .. } if ctx.inlined.contains(target) => {}
+            StmtKind::Assign { target, value } if ctx.assignable(target) => {
+                w.line(&format!("{} = {};", config.variable_name(target), ctx.expr(value)));
+            }
+            StmtKind::AugAssign { target, op, value } if ctx.assignable(target) => {
+                let op = if op == "//" { "/" } else { op.as_str() };
+                w.line(&format!("{} {}= {};", config.variable_name(target), op, ctx.expr(value)));
+            }
+            StmtKind::If { condition, body, orelse } => {
+                w.open(&format!("if {} {{", ctx.expr(condition)));
+                write_stmts(w, body, ctx);
+                let mut orelse = orelse.as_slice();
+                loop {
+                    match orelse {
+                        [] => break,
+                        [Stmt { kind: StmtKind::If { condition, body, orelse: next }, .. }] => {
+                            w.reopen(&format!("}} else if {} {{", ctx.expr(condition)));
+                            write_stmts(w, body, ctx);
+                            orelse = next;
+                        }
+                        stmts => {
+                            w.reopen("} else {");
+                            write_stmts(w, stmts, ctx);
+                            break;
+                        }
+                    }
+                }
+                w.close("}");
+            }
+            // `for e in entidades:` o `for i in range(3):`
+            StmtKind::For { target, iter, body } if ctx.loop_code(iter).is_some() => {
+                w.open(&format!("for {} in {} {{", config.variable_name(target), ctx.loop_code(iter).unwrap_or_default()));
+                write_stmts(w, body, ctx);
+                w.close("}");
+            }
+            // Sin el bucle, sus llamadas de dibujo se dibujan una vez en su lugar
+            StmtKind::For { body, .. } => {
+                ctx.drop_stmt(w, stmt);
+                for inner in classes::flatten(body) {
+                    if let Some(index) = ctx.state.draw_calls.iter().position(|call| call.line == inner.line) {
+                        write_draw_call(w, &ctx.state.draw_calls[index], &ctx.draw_code[index], config);
+                    }
+                }
+            }
+            StmtKind::Break => w.line("break;"),
+            StmtKind::Continue => w.line("continue;"),
+            // Métodos y funciones del script: `p.mover(1)`, `Player::reiniciar()`, `empujar(&mut p)`,
+            // `e.draw(&mut canvas)?`
+            StmtKind::Expr(expr @ (Expr::MethodCall { .. } | Expr::Try(_))) => w.line(&format!("{};", ctx.expr(expr))),
+            StmtKind::Expr(expr @ Expr::Call { function, .. }) if function.contains("::") || ctx.functions.contains(function) => {
+                w.line(&format!("{};", ctx.expr(expr)));
+            }
+            StmtKind::Expr(Expr::Call { function, args }) if args.is_empty() && ctx.state.window.as_ref().is_some_and(|window| {
+                function.strip_prefix(&format!("{}.", window.variable)).is_some_and(|m| WINDOW_METHODS.contains(&m))
+            }) => {
+                let method = function.rsplit('.').next().unwrap_or(function);
+                w.line(&format!("canvas.window_mut().{}();", method));
+            }
+            _ => ctx.drop_stmt(w, stmt),
+        }
+        w.end(region);
+        if stmts.get(index + 1).is_some_and(|next| next.line > last_line(stmt) + 1) {
+            w.blank();
+        }
+    }
+}
+
+// Un brazo del `match event` por cada tipo de evento que comprueba el script,
+// con los campos usados enlazados en el patrón y las condiciones restantes como `if`.
+fn write_event_arms(w: &mut CodeWriter, events: &EventLoop, ctx: &StmtContext) {
+    let config = ctx.config;
+    let mut types: Vec<&str> = Vec::new();
+    for handler in &events.handlers {
+        if !types.contains(&handler.event_type.as_str()) {
+            types.push(&handler.event_type);
+        }
+    }
+
+    let prefix = format!("{}.", events.variable);
+    for event_type in types {
+        let Some((_, variant, fields)) = EVENT_PATTERNS.iter().find(|(c, _, _)| *c == event_type) else {
+            w.line(&format!("// sin traducción: {}", event_type));
+            continue;
+        };
+
+        let mut used: Vec<EventField> = Vec::new();
+        let mut bind = |path: &str| {
+            let field = fields.iter().find(|(python, ..)| path.strip_prefix(&prefix) == Some(*python))?;
+            if !used.contains(field) {
+                used.push(*field);
+            }
+            let (_, _, binding, ty) = *field;
+            let name = Expr::Name(binding.to_string());
+            if ty == "u8" || (ty == "i32" && config.int_type != "i32") {
+                return Some(Expr::Call { function: format!("{}.from", config.int_type), args: vec![name] });
+            }
+            Some(name)
+        };
+        let handlers: Vec<(usize, Option<Expr>, Vec<Stmt>)> = events.handlers.iter().enumerate()
+            .filter(|(_, handler)| handler.event_type == event_type)
+            .map(|(index, handler)| (index, handler.guard.as_ref().map(|g| replace_attrs(g, &mut bind)), replace_attrs_in(&handler.body, &mut bind)))
+            .collect();
+
+        // `data1`/`data2` se enlazan a través de `win_event`
+        let uses_data = used.iter().any(|(_, _, binding, _)| binding.starts_with("data"));
+        let mut bindings: Vec<String> = Vec::new();
+        for (_, field, binding, _) in &used {
+            let binding = if field == binding || binding.starts_with("data") { field.to_string() } else { format!("{}: {}", field, binding) };
+            if !bindings.contains(&binding) {
+                bindings.push(binding);
+            }
+        }
+        // Como rustfmt: los campos van en una línea si caben en `struct_lit_width`
+        // (18), y junto a las llaves si también cabe `..`
+        let horizontal = bindings.join(", ").len() <= 18;
+        bindings.push("..".to_string());
+        let pattern = if horizontal && bindings.join(", ").len() <= 18 {
+            format!("Event::{} {{ {} }}", variant, bindings.join(", "))
+        } else if horizontal {
+            format!("Event::{} {{\n    {}\n}}", variant, bindings.join(", "))
+        } else {
+            format!("Event::{} {{\n    {}\n}}", variant, bindings.join(",\n    "))
+        };
+        w.open(&format!("{} => {{", pattern));
+        if uses_data {
+            let data = |name: &'static str| if used.iter().any(|(_, _, binding, _)| *binding == name) { name } else { "_" };
+            w.line(&format!("let ({}, {}) = match win_event {{", data("data1"), data("data2")));
+            w.line("    WindowEvent::Moved(x, y) => (x, y),
+    WindowEvent::Resized(w, h) | WindowEvent::SizeChanged(w, h) => (w, h),
+    _ => (0, 0),
+};");
+        }
+
+        // Las ramas `elif` del mismo tipo siguen la cadena `if`
+        let mut open_if = false;
+        for (index, guard, body) in &handlers {
+            let handler = &events.handlers[*index];
+            let else_branch = open_if && handler.chained && events.handlers[index - 1].event_type == event_type;
+            let region = w.begin(handler.line, handler.column);
+            match (guard, else_branch) {
+                (Some(guard), true) => w.reopen(&format!("}} else if {} {{", ctx.expr(guard))),
+                (None, true) => w.reopen("} else {"),
+                (guard, false) => {
+                    if open_if {
+                        w.close("}");
+                    }
+                    if let Some(guard) = guard {
+                        w.open(&format!("if {} {{", ctx.expr(guard)));
+                    }
+                }
+            }
+            open_if = guard.is_some() || else_branch;
+            write_stmts(w, body, ctx);
+            w.end(region);
+        }
+        if open_if {
+            w.close("}");
+        }
+        w.close("}");
+    }
+}
+
+// Tipo Rust de un tipo deducido; los objetos por su ruta (`player::Player`)
+fn rust_type(tipo: &str, config: &Config) -> String {
+    match tipo {
+        "int" => config.int_type.clone(),
+        "float" => "f64".to_string(),
+        "bool" => "bool".to_string(),
+        "str" => "String".to_string(),
+        // `[jugador, enemigo]`: `Vec<Box<dyn SpriteTrait>>` o `Vec<SpriteKind>`
+        path if path.starts_with("list ") => format!("Vec<{}>", rust_type(&path[5..], config)),
+        // Cualquier clase de la jerarquía de la raíz, según `classes.inheritance`
+        path => match path.strip_prefix("dyn ") {
+            Some(root) if config.inheritance == "enum" => format!("{}Kind", root.replace('.', "::")),
+            Some(root) => format!("Box<dyn {}Trait>", root.replace('.', "::")),
+            None => path.replace('.', "::"),
+        },
+    }
+}
+
+// Tipo de un parámetro: los textos se prestan y los objetos se pasan por referencia
+fn param_type(param: &Param, config: &Config) -> String {
+    match param.tipo.as_str() {
+        "str" => "&str".to_string(),
+        // `def draw(self, renderer)` dibuja en el canvas
+        "renderer" => "&mut Canvas<Window>".to_string(),
+        tipo if classes::is_class_type(tipo) => {
+            let ty = match tipo.strip_prefix("dyn ") {
+                Some(root) if config.inheritance != "enum" => format!("dyn {}Trait", root.replace('.', "::")),
+                _ => rust_type(tipo, config),
+            };
+            format!("&{}{}", if param.mutated { "mut " } else { "" }, ty)
+        }
+        tipo => rust_type(tipo, config),
+    }
+}
+
+// Valor para un destino de tipo `tipo`: `0` en un `f64` es `0.0` y los textos se copian
+fn typed_expr(expr: &Expr, tipo: &str, config: &Config) -> String {
+    match (tipo, expr) {
+        ("float", Expr::Int(n)) => format!("{}.0", n),
+        (tipo, Expr::List(_)) if tipo.starts_with("list ") => format!("vec!{}", rust_expr(expr, config)),
+        ("str", _) => {
+            let code = rust_expr(expr, config);
+            if code.starts_with("format!(") { code } else { format!("{}.to_string()", code) }
+        }
+        _ => rust_expr(expr, config),
+    }
+}
+
+// Valor inicial de un campo que `__init__` no asigna
+fn default_value(tipo: &str, config: &Config) -> String {
+    match tipo {
+        "int" => "0".to_string(),
+        "float" => "0.0".to_string(),
+        "bool" => "false".to_string(),
+        "str" => "String::new()".to_string(),
+        tipo if tipo.starts_with("list ") => "Vec::new()".to_string(),
+        path => format!("{}::new()", rust_type(path, config)),
+    }
+}
+
+// `self.x` como `this.x` dentro de `new`, donde la instancia aún se está creando
+fn rename_self(expr: &Expr, to: &str) -> Expr {
+    let rename = |e: &Expr| rename_self(e, to);
+    match expr {
+        Expr::Name(name) if name == "self" => Expr::Name(to.to_string()),
+        Expr::Name(name) if name.starts_with("self.") => Expr::Name(format!("{}{}", to, &name[4..])),
+        Expr::Tuple(items) => Expr::Tuple(items.iter().map(rename).collect()),
+        Expr::List(items) => Expr::List(items.iter().map(rename).collect()),
+        Expr::Call { function, args } => Expr::Call { function: function.clone(), args: args.iter().map(rename).collect() },
+        Expr::Index { value, index } => Expr::Index { value: Box::new(rename(value)), index: Box::new(rename(index)) },
+        Expr::MethodCall { receiver, method, args } => Expr::MethodCall {
+            receiver: Box::new(rename(receiver)),
+            method: method.clone(),
+            args: args.iter().map(rename).collect(),
+        },
+        Expr::UnaryOp { op, operand } => Expr::UnaryOp { op: op.clone(), operand: Box::new(rename(operand)) },
+        Expr::Cast { value, ty } => Expr::Cast { value: Box::new(rename(value)), ty: ty.clone() },
+        Expr::Try(value) => Expr::Try(Box::new(rename(value))),
+        Expr::BinOp { op, left, right } => Expr::BinOp { op: op.clone(), left: Box::new(rename(left)), right: Box::new(rename(right)) },
+        _ => expr.clone(),
+    }
+}
+
+// Si la expresión usa algo de `names` que no se pudo enlazar: `self.rect`
+// sin tipo, `renderer.fill(...)` sobre un parámetro...
+fn unresolved(expr: &Expr, names: &HashSet<String>) -> bool {
+    let rooted = |path: &str| path.contains('.') && !path.contains("::") && names.contains(path.split('.').next().unwrap_or(path));
+    match expr {
+        Expr::Attr(path) => rooted(path),
+        Expr::Call { function, args } => rooted(function) || args.iter().any(|arg| unresolved(arg, names)),
+        Expr::Tuple(items) | Expr::List(items) => items.iter().any(|item| unresolved(item, names)),
+        Expr::Index { value, index } => unresolved(value, names) || unresolved(index, names),
+        Expr::MethodCall { receiver, args, .. } => unresolved(receiver, names) || args.iter().any(|arg| unresolved(arg, names)),
+        Expr::UnaryOp { operand, .. } | Expr::Cast { value: operand, .. } | Expr::Try(operand) => unresolved(operand, names),
+        Expr::BinOp { left, right, .. } => unresolved(left, names) || unresolved(right, names),
+        Expr::Unlinked(_) => true,
+        _ => false,
+    }
+}
+
+// Llamadas del proyecto que el enlazador no pudo traducir: `nivel1.doble`
+fn unlinked(expr: &Expr) -> Vec<&str> {
+    match expr {
+        Expr::Unlinked(function) => vec![function],
+        Expr::Call { args: items, .. } | Expr::Tuple(items) | Expr::List(items) => items.iter().flat_map(unlinked).collect(),
+        Expr::Index { value, index } => unlinked(value).into_iter().chain(unlinked(index)).collect(),
+        Expr::MethodCall { receiver, args, .. } => unlinked(receiver).into_iter().chain(args.iter().flat_map(unlinked)).collect(),
+        Expr::UnaryOp { operand, .. } | Expr::Cast { value: operand, .. } | Expr::Upcast { value: operand, .. } | Expr::Try(operand) => {
+            unlinked(operand)
+        }
+        Expr::BinOp { left, right, .. } => unlinked(left).into_iter().chain(unlinked(right)).collect(),
+        _ => Vec::new(),
+    }
+}
+
+// Lo que los métodos ven de su módulo: las funciones traducidas, que se pueden
+// llamar como sentencia, y las variables, para dibujar con sus colores
+struct ModuleContext<'a> {
+    state: &'a State,
+    functions: &'a HashSet<String>,
+}
+
+// Lo que necesita el cuerpo de un método: la clase, cómo se llama la
+// instancia (`self`, o `this` en `new`), las variables ya declaradas y lo que
+// devuelve el método.
+struct MethodContext<'a> {
+    config: &'a Config,
+    // None en una función del nivel superior
+    class: Option<&'a ClassDef>,
+    module: &'a ModuleContext<'a>,
+    receiver: &'a str,
+    // Nombres locales que no deben quedar como rutas sin enlazar
+    names: HashSet<String>,
+    declared: HashSet<String>,
+    params: &'a [Param],
+    return_type: Option<&'a str>,
+    // Si dibuja: devuelve `Result<(), String>`
+    draws: bool,
+    // Líneas de las sentencias que no se traducen
+    dropped: Vec<usize>,
+}
+
+impl MethodContext<'_> {
+    fn expr(&self, expr: &Expr) -> String {
+        rust_expr(&rename_self(expr, self.receiver), self.config)
+    }
+
+    // Valor para un destino de tipo `tipo`; un parámetro o campo entero en un
+    // `f64` se convierte: `self.puntos += n` es `self.puntos += n as f64`.
+    fn value(&self, expr: &Expr, tipo: &str) -> String {
+        let int_name = match expr {
+            Expr::Name(name) => self.field_type(name).or_else(|| {
+                self.params.iter().find(|param| param.name == *name).map(|param| param.tipo.as_str())
+            }) == Some("int"),
+            _ => false,
+        };
+        if tipo == "float" && int_name {
+            return format!("{} as f64", self.expr(expr));
+        }
+        typed_expr(&rename_self(expr, self.receiver), tipo, self.config)
+    }
+
+    fn target(&self, target: &str) -> String {
+        let target = match target.strip_prefix("self") {
+            Some(rest) if rest.is_empty() || rest.starts_with('.') => format!("{}{}", self.receiver, rest),
+            _ => target.to_string(),
+        };
+        self.config.variable_name(&target)
+    }
+
+    // Tipo del campo `self.x`
+    fn field_type(&self, target: &str) -> Option<&str> {
+        let field = target.strip_prefix("self.")?;
+        self.class?.fields.iter().find(|f| f.name == field).map(|f| f.tipo.as_str())
+    }
+
+    fn translated(&self, stmt: &Stmt) -> bool {
+        let names = &self.names;
+        match &stmt.kind {
+            StmtKind::Assign { target, value } | StmtKind::AugAssign { target, value, .. } => {
+                !unresolved(value, names) && !target.contains(',')
+                    && (!target.starts_with("self.") || self.field_type(target).is_some_and(|tipo| tipo != "unknown")
+                        || target.matches('.').count() > 1)
+            }
+            StmtKind::If { condition, .. } | StmtKind::Return(Some(condition)) => !unresolved(condition, names),
+            StmtKind::Expr(expr) => !unresolved(expr, names),
+            StmtKind::For { iter, .. } => self.loop_code(iter).is_some(),
+            StmtKind::Break | StmtKind::Continue | StmtKind::Return(None) => true,
+        }
+    }
+
+    // Lo que recorre un `for`: una lista de la instancia, de un parámetro o
+    // local, o un `range`
+    fn loop_code(&self, iter: &Expr) -> Option<String> {
+        match iter {
+            Expr::MethodCall { receiver, .. } if list_iter(iter) && !unresolved(receiver, &self.names) => Some(self.expr(iter)),
+            _ => range_code(iter, &|expr| self.expr(expr)),
+        }
+    }
+
+    // `renderer.fill(...)` o `renderer.color = ...` sobre el renderer que recibe;
+    // los colores del módulo van con su valor, porque son locales de `main`
+    fn draw_code(&self, stmt: &Stmt) -> Option<String> {
+        let (object, method, args) = match &stmt.kind {
+            StmtKind::Expr(Expr::Call { function, args }) => {
+                let (object, method) = function.split_once('.')?;
+                (object, method, args.clone())
+            }
+            StmtKind::Assign { target, value } => {
+                let (object, "color") = target.split_once('.')? else { return None };
+                (object, "set_draw_color", vec![value.clone()])
+            }
+            _ => return None,
+        };
+        let renderer = self.params.iter().find(|param| param.name == object && param.tipo == "renderer")?;
+        if !METHOD_DRAWING.contains(&method) {
+            return None;
+        }
+        let state = self.module.state;
+        let args = args.iter().map(|arg| {
+            let color = match arg {
+                Expr::Name(name) => state.variables.iter().find(|var| var.name == *name).and_then(|var| var.value.as_ref()).filter(|value| is_color(value)),
+                _ => None,
+            };
+            rename_self(color.unwrap_or(arg), self.receiver)
+        }).collect();
+        let call = DrawCall { method: method.to_string(), args, kwargs: Vec::new(), line: stmt.line, column: stmt.column };
+        let code = draw_call_code(&call, state, self.config);
+        Some(code.replace("canvas.", &format!("{}.", self.config.variable_name(&renderer.name))))
+    }
+}
+
+// Si algún método o función que se traduce recibe el renderer
+fn draws_in_methods(state: &State) -> bool {
+    state.classes.iter().flat_map(|class| &class.methods).chain(&state.functions)
+        .any(|method| classes::translatable(method) && classes::draws(method))
+}
+
+// Código de dibujo de los métodos que reciben el renderer, para saber qué tipos importar
+fn method_draw_code(state: &State, config: &Config) -> Vec<String> {
+    let functions = HashSet::new();
+    let module = ModuleContext { state, functions: &functions };
+    state.classes.iter().flat_map(|class| &class.methods).chain(&state.functions)
+        .filter(|method| classes::translatable(method) && classes::draws(method))
+        .flat_map(|method| {
+            let ctx = method_context(method, None, &module, config, "self");
+            classes::flatten(&method.function.body).into_iter().filter_map(|stmt| ctx.draw_code(stmt)).collect::<Vec<_>>()
+        })
+        .collect()
+}
+
+// Llamadas del renderer que se traducen dentro de un método que lo recibe
+const METHOD_DRAWING: [&str; 7] = ["clear", "present", "set_draw_color", "draw_point", "draw_line", "draw_rect", "fill"];
+
+// `entidades.iter_mut()`: el recorrido de una lista que el enlazador ya tradujo
+fn list_iter(iter: &Expr) -> bool {
+    matches!(iter, Expr::MethodCall { method, args, .. } if args.is_empty() && (method == "iter" || method == "iter_mut"))
+}
+
+// `range(n)` y `range(a, b)` como rangos de Rust: `0..n`, `a..b`
+fn range_code(iter: &Expr, expr: &dyn Fn(&Expr) -> String) -> Option<String> {
+    let Expr::Call { function, args } = iter else { return None };
+    match args.as_slice() {
+        [end] if function == "range" => Some(format!("0..{}", expr(end))),
+        [start, end] if function == "range" => Some(format!("{}..{}", expr(start), expr(end))),
+        _ => None,
+    }
+}
+
+// Sentencias de un método; con `tail`, un `return` final es la expresión del bloque.
+fn write_method_stmts(w: &mut CodeWriter, stmts: &[Stmt], ctx: &mut MethodContext, tail: bool) {
+    for (index, stmt) in stmts.iter().enumerate() {
+        let last = tail && index + 1 == stmts.len();
+        let region = w.begin(stmt.line, stmt.column);
+        let draw = ctx.draw_code(stmt);
+        match &stmt.kind {
+            _ if draw.is_some() => w.line(&draw.unwrap_or_default()),
+            _ if !ctx.translated(stmt) => {
+                ctx.dropped.push(stmt.line);
+                w.line(&format!("// sin traducción (línea {})", stmt.line));
+            }
+            StmtKind::Assign { target, value } => {
+                let code = match ctx.field_type(target) {
+                    Some(tipo) => ctx.value(value, tipo),
+                    None => ctx.expr(value),
+                };
+                if target.contains('.') || !ctx.declared.insert(target.clone()) {
+                    w.line(&format!("{} = {};", ctx.target(target), code));
+                } else if let Expr::Upcast { root, .. } = value {
+                    let ty = rust_type(&format!("dyn {}", root), ctx.config);
+                    w.line(&format!("let mut {}: {} = {};", ctx.target(target), ty, code));
+                } else {
+                    w.line(&format!("let mut {} = {};", ctx.target(target), code));
+                }
+            }
+            StmtKind::AugAssign { target, op, value } => {
+                let op = if op == "//" { "/" } else { op.as_str() };
+                let code = match ctx.field_type(target) {
+                    Some("float") => ctx.value(value, "float"),
+                    _ => ctx.expr(value),
+                };
+                w.line(&format!("{} {}= {};", ctx.target(target), op, code));
+            }
+            StmtKind::If { condition, body, orelse } => {
+                w.open(&format!("if {} {{", ctx.expr(condition)));
+                write_method_stmts(w, body, ctx, false);
+                let mut orelse = orelse.as_slice();
+                loop {
+                    match orelse {
+                        [] => break,
+                        [Stmt { kind: StmtKind::If { condition, body, orelse: next }, .. }] => {
+                            w.reopen(&format!("}} else if {} {{", ctx.expr(condition)));
+                            write_method_stmts(w, body, ctx, false);
+                            orelse = next;
+                        }
+                        stmts => {
+                            w.reopen("} else {");
+                            write_method_stmts(w, stmts, ctx, false);
+                            break;
+                        }
+                    }
+                }
+                w.close("}");
+            }
+            StmtKind::For { target, iter, body } => {
+                w.open(&format!("for {} in {} {{", ctx.config.variable_name(target), ctx.loop_code(iter).unwrap_or_default()));
+                ctx.declared.insert(target.clone());
+                write_method_stmts(w, body, ctx, false);
+                w.close("}");
+            }
+            // Métodos, funciones asociadas y funciones ya enlazados: `self.mover(1)`, `Player::crear()`,
+            // `empujar(self)`, `e.draw(renderer)?`
+            StmtKind::Expr(expr @ (Expr::MethodCall { .. } | Expr::Try(_))) => w.line(&format!("{};", ctx.expr(expr))),
+            StmtKind::Expr(expr @ Expr::Call { function, .. }) if function.contains("::") || ctx.module.functions.contains(function) => {
+                w.line(&format!("{};", ctx.expr(expr)));
+            }
+            StmtKind::Return(Some(value)) => {
+                let code = match ctx.return_type {
+                    Some(tipo) => ctx.value(value, tipo),
+                    None => ctx.expr(value),
+                };
+                if last { w.line(&code) } else { w.line(&format!("return {};", code)) }
+            }
+            StmtKind::Return(None) if last => {}
+            StmtKind::Return(None) => w.line(if ctx.draws { "return Ok(());" } else { "return;" }),
+            StmtKind::Break => w.line("break;"),
+            StmtKind::Continue => w.line("continue;"),
+            StmtKind::Expr(_) => {
+                ctx.dropped.push(stmt.line);
+                w.line(&format!("// sin traducción (línea {})", stmt.line));
+            }
+        }
+        w.end(region);
+        if stmts.get(index + 1).is_some_and(|next| next.line > last_line(stmt) + 1) {
+            w.blank();
+        }
+    }
+}
+
+// Parámetros de la firma de un método, con `&self`/`&mut self` delante
+fn method_params(method: &Method, config: &Config) -> Vec<String> {
+    let mut params = Vec::new();
+    if method.kind == MethodKind::Instance && method.function.name != "__init__" {
+        params.push(if method.mutates { "&mut self" } else { "&self" }.to_string());
+    }
+    let assigned: HashSet<&str> = classes::flatten(&method.function.body)
+        .into_iter()
+        .filter_map(|stmt| match &stmt.kind {
+            StmtKind::Assign { target, .. } | StmtKind::AugAssign { target, .. } => Some(target.as_str()),
+            _ => None,
+        })
+        .collect();
+    for param in classes::receiver_params(method) {
+        let mutable = if assigned.contains(param.name.as_str()) { "mut " } else { "" };
+        params.push(format!("{}{}: {}", mutable, config.variable_name(&param.name), param_type(param, config)));
+    }
+    params
+}
+
+// `pub fn nombre(params) -> tipo {`, con un parámetro por línea si no cabe;
+// `head` es `pub fn nombre` o, en un trait, `fn nombre`
+fn write_signature(w: &mut CodeWriter, head: &str, params: &[String], returns: Option<String>) {
+    let returns = returns.map(|ty| format!(" -> {}", ty)).unwrap_or_default();
+    w.open(&fn_signature(head, params, &format!("{} {{", returns), w.indent_width()));
+}
+
+fn method_context<'a>(
+    method: &'a Method,
+    class: Option<&'a ClassDef>,
+    module: &'a ModuleContext<'a>,
+    config: &'a Config,
+    receiver: &'a str,
+) -> MethodContext<'a> {
+    let mut names: HashSet<String> = method.function.params.iter().map(|param| param.name.clone()).collect();
+    names.extend(["self".to_string(), "super".to_string()]);
+    let declared = classes::receiver_params(method).iter().map(|param| param.name.clone()).collect();
+    for stmt in classes::flatten(&method.function.body) {
+        match &stmt.kind {
+            StmtKind::Assign { target, .. } if !target.contains(['.', ',']) => {
+                names.insert(target.clone());
+            }
+            StmtKind::For { target, .. } => {
+                names.insert(target.clone());
+            }
+            _ => {}
+        }
+    }
+    MethodContext {
+        config,
+        class,
+        module,
+        receiver,
+        names,
+        declared,
+        params: &method.function.params,
+        return_type: method.return_type.as_deref(),
+        draws: classes::draws(method),
+        dropped: Vec::new(),
+    }
+}
+
+// Tipo que devuelve un método; los que dibujan devuelven el de las llamadas de dibujo
+fn return_type(method: &Method, config: &Config) -> Option<String> {
+    match classes::draws(method) {
+        true => Some("Result<(), String>".to_string()),
+        false => method.return_type.as_ref().map(|tipo| rust_type(tipo, config)),
+    }
+}
+
+// Un aviso por cada sentencia de un método que no se traduce
+fn dropped_warnings(ctx: MethodContext, warnings: &mut Vec<Diagnostic>) {
+    let message = ctx.config.msg("sentencia sin traducir: se omite", "statement not translated: skipped");
+    for line in ctx.dropped {
+        warnings.push(Diagnostic::warning(message.to_string(), line));
+    }
+}
+
+// `new` a partir de `__init__`: las primeras asignaciones de campos forman el
+// literal `Self { .. }` y el resto del cuerpo trabaja sobre la instancia creada.
+fn write_constructor(w: &mut CodeWriter, class: &ClassDef, module: &ModuleContext, config: &Config, warnings: &mut Vec<Diagnostic>) {
+    let init = class.methods.iter().find(|method| method.function.name == "__init__");
+    let (line, column) = init.map_or((class.line, class.column), |init| (init.function.line, init.function.column));
+    let region = w.begin(line, column);
+    if let Some(docstring) = init.and_then(|init| init.function.docstring.as_ref()) {
+        w.doc_comment("///", docstring);
+    }
+    let params = init.map(|init| method_params(init, config)).unwrap_or_default();
+    write_signature(w, "pub fn new", &params, Some("Self".to_string()));
+
+    let fields: Vec<&Field> = class.fields.iter().filter(|field| field.tipo != "unknown").collect();
+    let body: &[Stmt] = init.map_or(&[], |init| &init.function.body);
+    let mut values: Vec<(&str, String)> = Vec::new();
+    let mut rest = body;
+    while let Some((stmt, tail)) = rest.split_first() {
+        let StmtKind::Assign { target, value } = &stmt.kind else { break };
+        let Some(field) = fields.iter().find(|field| target.strip_prefix("self.") == Some(field.name.as_str())) else { break };
+        if rename_self(value, "this") != *value || !unlinked(value).is_empty() || values.iter().any(|(name, _)| *name == field.name) {
+            break;
+        }
+        values.push((&field.name, typed_expr(value, &field.tipo, config)));
+        rest = tail;
+    }
+
+    let entries: Vec<String> = fields.iter().map(|field| {
+        let name = config.variable_name(&field.name);
+        match values.iter().find(|(n, _)| *n == field.name) {
+            Some((_, value)) if *value == name => name,
+            Some((_, value)) => format!("{}: {}", name, value),
+            None => format!("{}: {}", name, default_value(&field.tipo, config)),
+        }
+    }).collect();
+    // Como rustfmt: en una línea si el contenido no pasa de `struct_lit_width` (18)
+    let literal = if entries.is_empty() {
+        "Self".to_string()
+    } else if entries.join(", ").len() <= 18 {
+        format!("Self {{ {} }}", entries.join(", "))
+    } else {
+        format!("Self {{\n{}}}", entries.iter().map(|entry| format!("    {},\n", entry)).collect::<String>())
+    };
+
+    if rest.is_empty() {
+        w.line(&literal);
+    } else if let Some(init) = init {
+        w.line(&format!("let mut this = {};", literal));
+        let mut ctx = method_context(init, Some(class), module, config, "this");
+        write_method_stmts(w, rest, &mut ctx, false);
+        w.line("this");
+        dropped_warnings(ctx, warnings);
+    }
+    w.close("}");
+    w.end(region);
+}
+
+// Parámetros de un método que solo llama a otro: sin `mut`
+fn forwarded_params(method: &Method, config: &Config) -> Vec<String> {
+    method_params(method, config).into_iter().map(|param| param.trim_start_matches("mut ").to_string()).collect()
+}
+
+// `self.sprite.mover(dx)`: el mismo método sobre otro objeto, con los mismos argumentos
+fn write_forward(w: &mut CodeWriter, head: &str, method: &Method, object: &str, config: &Config) {
+    let returns = return_type(method, config);
+    let args: Vec<String> = classes::receiver_params(method).iter().map(|param| config.variable_name(&param.name)).collect();
+    let call = format!("{}.{}({})", object, classes::rust_ident(&method.function.name), args.join(", "));
+    let tail = if returns.is_some() { "" } else { ";" };
+    if let Some(docstring) = &method.function.docstring {
+        w.doc_comment("///", docstring);
+    }
+    write_signature(w, head, &forwarded_params(method, config), returns);
+    w.line(&format!("{}{}", call, tail));
+    w.close("}");
+}
+
+// Método copiado del padre: llama al del padre
+fn write_inherited(w: &mut CodeWriter, head: &str, method: &Method, hierarchy: &Hierarchy, config: &Config) {
+    let region = w.begin(method.function.line, method.function.column);
+    let parent = hierarchy.parent.as_deref().unwrap_or_default();
+    match method.kind {
+        MethodKind::Instance => {
+            let object = format!("self.{}", classes::parent_field(parent.rsplit('.').next().unwrap_or(parent)));
+            write_forward(w, head, method, &object, config);
+        }
+        _ => {
+            let returns = return_type(method, config);
+            let args: Vec<String> = classes::receiver_params(method).iter().map(|param| config.variable_name(&param.name)).collect();
+            let call = format!("{}::{}({})", parent.replace('.', "::"), classes::rust_ident(&method.function.name), args.join(", "));
+            let tail = if returns.is_some() { "" } else { ";" };
+            if let Some(docstring) = &method.function.docstring {
+                w.doc_comment("///", docstring);
+            }
+            write_signature(w, head, &forwarded_params(method, config), returns);
+            w.line(&format!("{}{}", call, tail));
+            w.close("}");
+        }
+    }
+    w.end(region);
+}
+
+// Un método de la clase, o una función sin ella, con su docstring y su cuerpo
+fn write_method(
+    w: &mut CodeWriter,
+    head: &str,
+    method: &Method,
+    class: Option<&ClassDef>,
+    module: &ModuleContext,
+    config: &Config,
+    warnings: &mut Vec<Diagnostic>,
+) {
+    let function = &method.function;
+    let region = w.begin(function.line, function.column);
+    if let Some(docstring) = &function.docstring {
+        w.doc_comment("///", docstring);
+    }
+    write_signature(w, head, &method_params(method, config), return_type(method, config));
+    let mut ctx = method_context(method, class, module, config, "self");
+    write_method_stmts(w, &function.body, &mut ctx, true);
+    if ctx.draws {
+        w.line("Ok(())");
+    }
+    w.close("}");
+    w.end(region);
+    dropped_warnings(ctx, warnings);
+}
+
+// `class == "Sprite"` o `["Enemy", "Sprite"].contains(&class)`
+fn instance_check(ancestors: &[String]) -> String {
+    match ancestors {
+        [single] => format!("class == \"{}\"", single),
+        names => {
+            let names: Vec<String> = names.iter().map(|name| format!("\"{}\"", name)).collect();
+            format!("[{}].contains(&class)", names.join(", "))
+        }
+    }
+}
+
+// Parte de la raíz dentro de un objeto: `self`, `self.sprite`, `self.enemy.sprite`
+fn root_part(object: &str, ancestors: &[String]) -> String {
+    ancestors.iter().skip(1).fold(object.to_string(), |path, name| format!("{}.{}", path, classes::parent_field(name)))
+}
+
+// Con `classes.inheritance = "trait"`, la raíz declara `SpriteTrait`: el acceso
+// a su parte, `is_instance_of` y sus métodos despachados, que por defecto
+// llaman a los de la raíz.
+fn write_hierarchy_trait(w: &mut CodeWriter, class: &ClassDef, hierarchy: &Hierarchy, config: &Config) {
+    let accessor = classes::parent_field(&class.name);
+    w.open(&format!("pub trait {}Trait {{", class.name));
+    w.line(&format!("fn {}(&self) -> &{};", accessor, class.name));
+    w.line(&format!("fn {}_mut(&mut self) -> &mut {};", accessor, class.name));
+    w.line("fn is_instance_of(&self, class: &str) -> bool;");
+    for method in class.methods.iter().filter(|method| hierarchy.methods.contains(&method.function.name)) {
+        w.blank();
+        let object = format!("self.{}{}()", accessor, if method.mutates { "_mut" } else { "" });
+        let head = format!("fn {}", classes::rust_ident(&method.function.name));
+        write_forward(w, &head, method, &object, config);
+    }
+    w.close("}");
+}
+
+// `impl SpriteTrait for Enemy`: el acceso a la parte de la raíz y los métodos
+// despachados que la clase redefine o hereda de una clase intermedia
+fn write_trait_impl(
+    w: &mut CodeWriter,
+    class: &ClassDef,
+    hierarchy: &Hierarchy,
+    module: &ModuleContext,
+    config: &Config,
+    warnings: &mut Vec<Diagnostic>,
+) {
+    let root_name = hierarchy.ancestors.last().cloned().unwrap_or_default();
+    let root_type = hierarchy.root.replace('.', "::");
+    let accessor = classes::parent_field(&root_name);
+    let part = root_part("self", &hierarchy.ancestors);
+    w.open(&format!("impl {}Trait for {} {{", root_name, class.name));
+    w.open(&format!("fn {}(&self) -> &{} {{", accessor, root_type));
+    w.line(if part == "self" { "self".to_string() } else { format!("&{}", part) }.as_str());
+    w.close("}");
+    w.blank();
+    w.open(&format!("fn {}_mut(&mut self) -> &mut {} {{", accessor, root_type));
+    w.line(if part == "self" { "self".to_string() } else { format!("&mut {}", part) }.as_str());
+    w.close("}");
+    w.blank();
+    w.open("fn is_instance_of(&self, class: &str) -> bool {");
+    w.line(&instance_check(&hierarchy.ancestors));
+    w.close("}");
+    for method in class.methods.iter().filter(|method| hierarchy.methods.contains(&method.function.name)) {
+        let head = format!("fn {}", classes::rust_ident(&method.function.name));
+        match &method.inherited {
+            // Los de la raíz ya los da el trait
+            Some(origin) if *origin == root_name => {}
+            Some(_) => {
+                w.blank();
+                write_inherited(w, &head, method, hierarchy, config);
+            }
+            None if hierarchy.parent.is_some() => {
+                w.blank();
+                write_method(w, &head, method, Some(class), module, config, warnings);
+            }
+            None => {}
+        }
+    }
+    w.close("}");
+}
+
+// Con `classes.inheritance = "enum"`, la raíz declara `SpriteKind` con una
+// variante por clase de la jerarquía y sus métodos despachados con `match`.
+fn write_hierarchy_enum(w: &mut CodeWriter, class: &ClassDef, hierarchy: &Hierarchy, config: &Config) {
+    let variant = |member: &HierarchyMember| member.path.rsplit("::").next().unwrap_or(&member.path).to_string();
+    w.open(&format!("pub enum {}Kind {{", class.name));
+    for member in &hierarchy.members {
+        w.line(&format!("{}({}),", variant(member), member.path));
+    }
+    w.close("}");
+    w.blank();
+
+    let accessor = classes::parent_field(&class.name);
+    w.open(&format!("impl {}Kind {{", class.name));
+    for (suffix, reference) in [("", "&"), ("_mut", "&mut ")] {
+        w.open(&format!("pub fn {}{}({}self) -> {}{} {{", accessor, suffix, reference, reference, class.name));
+        w.open("match self {");
+        for member in &hierarchy.members {
+            let part = root_part("object", &member.ancestors);
+            let part = if part == "object" { part } else { format!("{}{}", reference, part) };
+            w.line(&format!("Self::{}(object) => {},", variant(member), part));
+        }
+        w.close("}");
+        w.close("}");
+        w.blank();
+    }
+    w.open("pub fn is_instance_of(&self, class: &str) -> bool {");
+    w.open("match self {");
+    for member in &hierarchy.members {
+        w.line(&format!("Self::{}(_) => {},", variant(member), instance_check(&member.ancestors)));
+    }
+    w.close("}");
+    w.close("}");
+    for method in class.methods.iter().filter(|method| hierarchy.methods.contains(&method.function.name)) {
+        w.blank();
+        let returns = return_type(method, config);
+        let args: Vec<String> = classes::receiver_params(method).iter().map(|param| config.variable_name(&param.name)).collect();
+        let name = classes::rust_ident(&method.function.name);
+        if let Some(docstring) = &method.function.docstring {
+            w.doc_comment("///", docstring);
+        }
+        write_signature(w, &format!("pub fn {}", name), &forwarded_params(method, config), returns);
+        w.open("match self {");
+        for member in &hierarchy.members {
+            w.line(&format!("Self::{}(object) => object.{}({}),", variant(member), name, args.join(", ")));
+        }
+        w.close("}");
+        w.close("}");
+    }
+    w.close("}");
+}
 
-    // Constantes
-    rust_code.push_str("const ANCHO: u32 = 800;\n");
-    rust_code.push_str("const ALTO: u32 = 600;\n\n");
+// Avisos de herencia que no se traduce: varias bases, metaclases y bases de
+// fuera del proyecto (salvo `abc.ABC`, que no aporta nada)
+fn inheritance_warnings(class: &ClassDef, config: &Config, warnings: &mut Vec<Diagnostic>) {
+    if class.bases.len() > 1 {
+        let message = config.msg("herencia múltiple no soportada en ", "multiple inheritance is not supported in ");
+        warnings.push(Diagnostic::warning(format!("{}{} ({})", message, class.name, class.bases.join(", ")), class.line));
+    }
+    if let Some(metaclass) = &class.metaclass {
+        let message = config.msg("metaclase no soportada en ", "metaclass is not supported in ");
+        warnings.push(Diagnostic::warning(format!("{}{} ({})", message, class.name, metaclass), class.line));
+    }
+    let parent = class.hierarchy.as_ref().and_then(|hierarchy| hierarchy.parent.as_ref());
+    if let ([base], None) = (class.bases.as_slice(), parent) {
+        if !["abc.ABC", "ABC"].contains(&base.as_str()) {
+            let message = config.msg(" no es una clase del proyecto: se ignora como base de ", " is not a project class: ignored as base of ");
+            warnings.push(Diagnostic::warning(format!("{}{}{}", base, message, class.name), class.line));
+        }
+    }
+}
+
+// `// sin traducción: def mover(dx)` y el aviso con lo que falta: el tipo de un
+// parámetro, el que devuelve o, en una función, la variable del módulo que usa
+fn write_untranslated(w: &mut CodeWriter, method: &Method, name: &str, hidden: Option<String>, config: &Config, warnings: &mut Vec<Diagnostic>) {
+    let function = &method.function;
+    let region = w.begin(function.line, function.column);
+    let params: Vec<&str> = function.params.iter().map(|param| param.name.as_str()).collect();
+    w.line(&format!("// sin traducción: def {}({})", function.name, params.join(", ")));
+    w.end(region);
+    let message = match (classes::receiver_params(method).iter().find(|param| param.tipo == "unknown"), hidden) {
+        (Some(param), _) => format!("{}`{}`{}{}", config.msg("no se pudo deducir el tipo de ", "could not infer the type of "),
+            param.name, config.msg(" en ", " in "), name),
+        (None, Some(variable)) => format!("{}{}`{}`{}", name, config.msg(" no se traduce: usa ", " is not translated: it uses "),
+            variable, config.msg(", una variable del módulo que no es constante", ", a module variable that is not a constant")),
+        (None, None) => format!("{}{}", config.msg("no se pudo deducir el tipo que devuelve ", "could not infer the return type of "), name),
+    };
+    warnings.push(Diagnostic::warning(message, function.line));
+}
+
+// `impl` con las constantes, `new` y los métodos propios de la clase
+fn write_impl(
+    w: &mut CodeWriter,
+    class: &ClassDef,
+    constructor: bool,
+    skipped: impl Fn(&Method) -> bool,
+    module: &ModuleContext,
+    config: &Config,
+    warnings: &mut Vec<Diagnostic>,
+) {
+    w.open(&format!("impl {} {{", class.name));
+    for attribute in class.attributes.iter().filter(|attribute| unlinked(&attribute.value).is_empty()) {
+        let ty = match attribute.tipo.as_str() {
+            "str" => "&str".to_string(),
+            "unknown" => continue,
+            tipo => rust_type(tipo, config),
+        };
+        let value = match attribute.tipo.as_str() {
+            "str" => rust_expr(&attribute.value, config),
+            tipo => typed_expr(&attribute.value, tipo, config),
+        };
+        let region = w.begin(attribute.line, attribute.column);
+        w.line(&format!("pub const {}: {} = {};", attribute.name, ty, value));
+        w.end(region);
+    }
+    w.blank();
+
+    if constructor {
+        write_constructor(w, class, module, config, warnings);
+    }
+    for method in &class.methods {
+        let function = &method.function;
+        if skipped(method) {
+            continue;
+        }
+        w.blank();
+        if let (Some(hierarchy), Some(_)) = (&class.hierarchy, &method.inherited) {
+            write_inherited(w, &format!("pub fn {}", classes::rust_ident(&function.name)), method, hierarchy, config);
+            continue;
+        }
+        if !classes::translatable(method) {
+            write_untranslated(w, method, &format!("{}.{}", class.name, function.name), None, config, warnings);
+            continue;
+        }
+        write_method(w, &format!("pub fn {}", classes::rust_ident(&function.name)), method, Some(class), module, config, warnings);
+    }
+    w.close("}");
+}
+
+// `struct` con los campos de la clase e `impl` con sus constantes, `new` y los
+// métodos. En una jerarquía, según `classes.inheritance`, los métodos que se
+// despachan van en el trait de la raíz o en su enum.
+fn write_class(w: &mut CodeWriter, class: &ClassDef, module: &ModuleContext, config: &Config, warnings: &mut Vec<Diagnostic>) {
+    inheritance_warnings(class, config, warnings);
+    let hierarchy = class.hierarchy.as_ref();
+    let traits = config.inheritance != "enum";
+    let region = w.begin(class.line, class.column);
+    if let Some(docstring) = &class.docstring {
+        w.doc_comment("///", docstring);
+    }
+    let fields: Vec<&Field> = class.fields.iter().filter(|field| field.tipo != "unknown").collect();
+    if fields.is_empty() {
+        w.line(&format!("pub struct {};", class.name));
+    } else {
+        w.open(&format!("pub struct {} {{", class.name));
+        for field in &fields {
+            w.line(&format!("pub {}: {},", config.variable_name(&field.name), rust_type(&field.tipo, config)));
+        }
+        w.close("}");
+    }
+    w.end(region);
+    for field in class.fields.iter().filter(|field| field.tipo == "unknown") {
+        let message = config.msg("no se pudo deducir el tipo del campo ", "could not infer the type of field ");
+        warnings.push(Diagnostic::warning(format!("{}{}.{}", message, class.name, field.name), field.line));
+    }
+    w.blank();
+
+    // Los despachados de las subclases van en `impl SpriteTrait for ..`
+    let skipped = |method: &Method| {
+        let dispatched = hierarchy.is_some_and(|hierarchy| hierarchy.methods.contains(&method.function.name));
+        let in_trait = traits && dispatched && hierarchy.is_some_and(|hierarchy| hierarchy.parent.is_some());
+        method.function.name == "__init__" || in_trait || (method.inherited.is_some() && !classes::translatable(method))
+    };
+    let constructed = class.methods.iter().find(|method| method.function.name == "__init__");
+    let constructor = constructed.is_none_or(classes::translatable);
+    let empty = !constructor
+        && class.attributes.iter().all(|attribute| attribute.tipo == "unknown")
+        && class.methods.iter().all(&skipped);
+    if !empty {
+        write_impl(w, class, constructor, skipped, module, config, warnings);
+    }
+
+    let Some(hierarchy) = hierarchy else { return };
+    let root = hierarchy.parent.is_none();
+    if traits && root {
+        w.blank();
+        write_hierarchy_trait(w, class, hierarchy, config);
+    }
+    if traits {
+        w.blank();
+        write_trait_impl(w, class, hierarchy, module, config, warnings);
+    } else if root {
+        w.blank();
+        write_hierarchy_enum(w, class, hierarchy, config);
+    }
+}
+
+// Funciones del nivel superior que se traducen, por su nombre en Rust: con
+// tipos y sin variables del módulo que no ven
+fn translated_functions(state: &State, entry: bool) -> HashSet<String> {
+    let hidden = classes::hidden_globals(state, entry);
+    state.functions.iter()
+        .filter(|method| classes::translatable(method) && classes::hidden_global(method, &hidden).is_none())
+        .map(|method| classes::rust_ident(&method.function.name))
+        .collect()
+}
+
+// Funciones del nivel superior: `fn` en el script de entrada y `pub fn` en los
+// demás módulos. Las que dibujan con el renderer van con las llamadas de dibujo.
+fn write_functions(w: &mut CodeWriter, module: &ModuleContext, entry: bool, config: &Config, warnings: &mut Vec<Diagnostic>) {
+    let (state, functions) = (module.state, module.functions);
+    let hidden = classes::hidden_globals(state, entry);
+    for method in state.functions.iter().filter(|method| !state.draw_calls.iter().any(|call| call.method == method.function.name)) {
+        let name = &method.function.name;
+        if functions.contains(&classes::rust_ident(name)) {
+            let head = format!("{}fn {}", if entry { "" } else { "pub " }, classes::rust_ident(name));
+            write_method(w, &head, method, None, module, config, warnings);
+        } else {
+            write_untranslated(w, method, name, classes::hidden_global(method, &hidden), config, warnings);
+        }
+        w.blank();
+    }
+}
+
+// Un aviso por cada llamada del proyecto que no se pudo traducir, en la línea
+// de la sentencia que se omite
+fn unlinked_warnings(state: &State, functions: &HashSet<String>, config: &Config) -> Vec<Diagnostic> {
+    let mut stmts = state.statements();
+    for class in &state.classes {
+        for method in class.methods.iter().filter(|method| classes::translatable(method)) {
+            stmts.extend(classes::flatten(&method.function.body));
+        }
+    }
+    for method in state.functions.iter().filter(|method| functions.contains(&classes::rust_ident(&method.function.name))) {
+        stmts.extend(classes::flatten(&method.function.body));
+    }
+    let mut found: Vec<(usize, &str)> = Vec::new();
+    for stmt in stmts {
+        for expr in classes::stmt_exprs(stmt) {
+            found.extend(unlinked(expr).into_iter().map(|function| (stmt.line, function)));
+        }
+    }
+    for var in &state.variables {
+        found.extend(var.value.iter().flat_map(unlinked).map(|function| (var.line, function)));
+    }
+    for call in &state.draw_calls {
+        let args = call.args.iter().chain(call.kwargs.iter().map(|(_, value)| value));
+        found.extend(args.flat_map(unlinked).map(|function| (call.line, function)));
+    }
+    for attribute in state.classes.iter().flat_map(|class| &class.attributes) {
+        found.extend(unlinked(&attribute.value).into_iter().map(|function| (attribute.line, function)));
+    }
+    found.sort();
+    found.dedup();
+    found.into_iter()
+        .map(|(line, function)| {
+            let message = format!("{}`{}`{}", config.msg("no se pudo traducir la llamada a ", "could not translate the call to "),
+                function, config.msg(": se omite su sentencia", ": its statement is skipped"));
+            Diagnostic::warning(message, line)
+        })
+        .collect()
+}
+
+// Módulo de medición por frame que se añade con `--instrument`.
+fn write_instrument_module(w: &mut CodeWriter, config: &Config) {
+    let (report_frame, report_summary) = if config.instrument_log {
+        (
+            "log::debug!(\n    \"frame {}: {:?}, {} draw calls\",\n    self.frames,\n    elapsed,\n    self.draw_calls\n);",
+            "log::info!(\n    \"media de {} frames: {:?}\",\n    self.frames,\n    self.total / self.frames as u32\n);",
+        )
+    } else {
+        (
+            "",
+            "eprintln!(\n    \"media de {} frames: {:?}, {} draw calls\",\n    self.frames,\n    self.total / self.frames as u32,\n    self.draw_calls\n);",
+        )
+    };
 
-    // Función draw_circle corregida
-    rust_code.push_str(r#"
-fn draw_circle(canvas: &mut sdl2::render::Canvas<sdl2::video::Window>, color: Color, center: (i32, i32), radius: i32) {
+    w.line("#[cfg(feature = \"instrument\")]");
+    w.open("mod instrument {");
+    w.line("use std::time::{Duration, Instant};");
+    w.blank();
+    w.open("pub struct FrameStats {");
+    w.line("frame_start: Instant,");
+    w.line("frames: u64,");
+    w.line("total: Duration,");
+    w.line("pub draw_calls: u32,");
+    w.close("}");
+    w.blank();
+    w.open("impl FrameStats {");
+    w.open("pub fn new() -> Self {");
+    w.open("FrameStats {");
+    w.line("frame_start: Instant::now(),");
+    w.line("frames: 0,");
+    w.line("total: Duration::ZERO,");
+    w.line("draw_calls: 0,");
+    w.close("}");
+    w.close("}");
+    w.blank();
+    w.open("pub fn begin_frame(&mut self) {");
+    w.line("self.frame_start = Instant::now();");
+    w.line("self.draw_calls = 0;");
+    w.close("}");
+    w.blank();
+    w.open("pub fn end_frame(&mut self) {");
+    w.line("let elapsed = self.frame_start.elapsed();");
+    w.line("self.frames += 1;");
+    w.line("self.total += elapsed;");
+    w.line(report_frame);
+    w.open("if self.frames % 60 == 0 {");
+    w.line(report_summary);
+    w.close("}");
+    w.close("}");
+    w.close("}");
+    w.close("}");
+}
+
+// Versión corregida de `draw_circle`, que PySDL2 no trae: un punto por píxel del círculo.
+fn write_draw_circle(w: &mut CodeWriter, state: &State) {
+    let circle_def = state.functions.iter().map(|f| &f.function).find(|f| f.name == "draw_circle");
+    let (line, column) = circle_def.map(|f| (f.line, f.column)).unwrap_or((0, 0));
+    let region = w.begin(line, column);
+    if let Some(docstring) = circle_def.and_then(|f| f.docstring.as_ref()) {
+        w.doc_comment("///", docstring);
+    }
+    w.line(r#"fn draw_circle(
+    canvas: &mut sdl2::render::Canvas<sdl2::video::Window>,
+    color: Color,
+    center: (i32, i32),
+    radius: i32,
+) {
     let (cx, cy) = center;
     canvas.set_draw_color(color);
     for dx in -radius..=radius {
@@ -27,148 +2638,1087 @@ fn draw_circle(canvas: &mut sdl2::render::Canvas<sdl2::video::Window>, color: Co
             }
         }
     }
+}"#);
+    w.end(region);
+}
+
+pub fn generate_rust(state: &State, config: &Config, python_file: &str) -> GeneratedCode {
+    // `ANCHO` y `ALTO` solo se declaran si el resto del código los usa: se
+    // genera con las dos y, si alguna sobra, otra vez sin ella
+    let generated = write_main_file(state, config, python_file, &SIZE_CONSTANTS);
+    let used: Vec<(&str, u32)> = SIZE_CONSTANTS.into_iter()
+        .filter(|(name, _)| identifier_count(&generated.code, name) > 1)
+        .collect();
+    if used.len() == SIZE_CONSTANTS.len() {
+        return generated;
+    }
+    write_main_file(state, config, python_file, &used)
 }
-"#);
+
+fn write_main_file(state: &State, config: &Config, python_file: &str, size_constants: &[(&str, u32)]) -> GeneratedCode {
+    let mut w = CodeWriter::new(python_file, &state.comments, config.line_comments);
+
+    // Docstring del módulo
+    if let Some(docstring) = &state.module_docstring {
+        w.doc_comment("//!", docstring);
+        w.blank();
+    }
+
+    // Niveles de lint configurados
+    for (level, lints) in [("allow", &config.lints_allow), ("warn", &config.lints_warn), ("deny", &config.lints_deny)] {
+        if !lints.is_empty() {
+            w.line(&format!("#![{}({})]", level, lints.join(", ")));
+        }
+    }
+    w.blank();
+
+    // Módulos del proyecto que importa el script
+    write_modules(&mut w, &state.modules, "mod");
+
+    // El código de dibujo se genera antes para saber qué tipos importar. Lo
+    // que dibuja con la fuente por defecto de pygame o con llamadas del
+    // proyecto sin traducir se queda sin traducir.
+    let default_fonts = textures::default_font_bindings(state);
+    let default_font_draw = |call: &DrawCall| {
+        call.args.iter().chain(call.kwargs.iter().map(|(_, value)| value)).any(|arg| textures::uses_binding(arg, &default_fonts))
+    };
+    let draw_code: Vec<String> = state.draw_calls.iter()
+        .map(|call| match default_font_draw(call) || call.args.iter().chain(call.kwargs.iter().map(|(_, value)| value)).any(|arg| !unlinked(arg).is_empty()) {
+            true => format!("// sin traducción (línea {})", call.line),
+            false => draw_call_code(call, state, config),
+        })
+        .collect();
+    let method_code = method_draw_code(state, config);
+    let uses_type = |name: &str| draw_code.iter().chain(&method_code).any(|code| code.contains(&format!("{}::new", name)));
+    let geometry_types = geometry::types_used(state);
+    let rect_types: Vec<&str> = ["Point", "Rect"].into_iter().filter(|t| uses_type(t) || geometry_types.contains(t)).collect();
+
+    // Limitador de fotogramas por defecto, solo si el script no tiene uno propio
+    let limiter = match config.frame_limiter.as_str() {
+        _ if timing::has_limiter(state) => "none",
+        "fixed" if config.fps == 0 => "none",
+        limiter => limiter,
+    };
+
+    // Importaciones
+    let (import_line, import_column) = state.imports.first().map(|i| (i.line, 1)).unwrap_or((0, 0));
+    let region = w.begin(import_line, import_column);
+    let constants = used_constants(state);
+    let uses_constant = |prefix: &str| constants.iter().any(|(_, c)| c.starts_with(prefix) && keys::rust_constant(c).is_some());
+    let window_events = constants.iter().any(|(_, c)| c.starts_with("SDL_WINDOWEVENT_") || c.starts_with("data"));
+    // `rand::Rng` y los `use` de [modules] van antes que los de sdl2, como los ordena rustfmt
+    let (mut uses_before, uses_after): (Vec<String>, Vec<String>) = mappings::uses(state, config)
+        .into_iter()
+        .chain(hierarchy_traits(state, config).into_iter().map(|path| format!("crate::{}", path)))
+        .partition(|path| path.as_str() < "sdl2");
+    uses_before.sort();
+    for path in &uses_before {
+        w.line(&format!("use {};", path));
+    }
+    if window_events {
+        w.line("use sdl2::event::{Event, WindowEvent};");
+    } else {
+        w.line("use sdl2::event::Event;");
+    }
+    let images: Vec<(&Stmt, &str, ImageValue)> = textures::images(state).collect();
+    let loads_textures = images.iter().any(|(_, _, image)| matches!(image, ImageValue::Texture(_)));
+    let loads_surfaces = images.iter().any(|(_, _, image)| matches!(image, ImageValue::Surface(_)));
+    match (loads_surfaces, loads_textures) {
+        (true, true) => w.line("use sdl2::image::{LoadSurface, LoadTexture};"),
+        (true, false) => w.line("use sdl2::image::LoadSurface;"),
+        (false, true) => w.line("use sdl2::image::LoadTexture;"),
+        (false, false) => {}
+    }
+    let mut keyboard_types = Vec::new();
+    if config.escape_quits || uses_constant("SDLK_") {
+        keyboard_types.push("Keycode");
+    }
+    if uses_constant("KMOD_") {
+        keyboard_types.push("Mod");
+    }
+    if uses_constant("SDL_SCANCODE_") {
+        keyboard_types.push("Scancode");
+    }
+    match keyboard_types.as_slice() {
+        [] => {}
+        [single] => w.line(&format!("use sdl2::keyboard::{};", single)),
+        types => w.line(&format!("use sdl2::keyboard::{{{}}};", types.join(", "))),
+    }
+    let mut mixer_constants = Vec::new();
+    collect_constants(&state.setup, &mut mixer_constants);
+    mixer_constants.extend(constants.iter().cloned());
+    let mixer_constants: Vec<String> = mixer_constants.into_iter().map(|(_, name)| name).collect();
+    match audio::imports(state, &mixer_constants).as_slice() {
+        [] => {}
+        [single] => w.line(&format!("use sdl2::mixer::{};", single)),
+        types => w.line(&format!("use sdl2::mixer::{{{}}};", types.join(", "))),
+    }
+    let mut mouse_types = Vec::new();
+    if state.sdl_calls.iter().any(|call| call.function.ends_with("SDL_CreateSystemCursor")) {
+        mouse_types.push("Cursor");
+    }
+    if constants.iter().any(|(_, c)| c.starts_with("SDL_BUTTON_") && !c.ends_with("MASK")) {
+        mouse_types.push("MouseButton");
+    }
+    if mouse_types.contains(&"Cursor") {
+        mouse_types.push("SystemCursor");
+    }
+    match mouse_types.as_slice() {
+        [] => {}
+        [single] => w.line(&format!("use sdl2::mouse::{};", single)),
+        types => w.line(&format!("use sdl2::mouse::{{{}}};", types.join(", "))),
+    }
+    let blank_surfaces = images.iter().any(|(_, _, image)| matches!(image, ImageValue::Blank { .. }));
+    if blank_surfaces {
+        w.line("use sdl2::pixels::{Color, PixelFormatEnum};");
+    } else {
+        w.line("use sdl2::pixels::Color;");
+    }
+    match rect_types.as_slice() {
+        [] => {}
+        [single] => w.line(&format!("use sdl2::rect::{};", single)),
+        types => w.line(&format!("use sdl2::rect::{{{}}};", types.join(", "))),
+    }
+    let draws_in_methods = draws_in_methods(state);
+    if draws_in_methods {
+        w.line("use sdl2::render::Canvas;");
+    }
+    if loads_surfaces || blank_surfaces {
+        w.line("use sdl2::surface::Surface;");
+    }
+    if draws_in_methods {
+        w.line("use sdl2::video::Window;");
+    }
+    let assets = textures::assets(state);
+    if !assets.is_empty() {
+        w.line("use std::path::PathBuf;");
+    }
+    let mut time_types = Vec::new();
+    if limiter == "fixed" || timing::uses_sleep(state) {
+        time_types.push("Duration");
+    }
+    if timing::uses_clock(state) {
+        time_types.push("Instant");
+    }
+    match time_types.as_slice() {
+        [] => {}
+        [single] => w.line(&format!("use std::time::{};", single)),
+        types => w.line(&format!("use std::time::{{{}}};", types.join(", "))),
+    }
+    for path in &uses_after {
+        w.line(&format!("use {};", path));
+    }
+    w.end(region);
+    w.blank();
+
+    // Constantes
+    for &(name, default) in size_constants {
+        let (line, column) = variable_position(state, name);
+        let value = state.variables.iter()
+            .find(|v| v.name == name)
+            .and_then(|v| match v.value {
+                Some(Expr::Int(n)) if n >= 0 => Some(n.to_string()),
+                _ => None,
+            })
+            .unwrap_or(default.to_string());
+        let region = w.begin(line, column);
+        w.line(&format!("const {}: u32 = {};", name, value));
+        w.end(region);
+    }
+    w.blank();
+
+    // Función draw_circle corregida, si se dibuja con ella
+    if state.draw_calls.iter().any(|call| call.method == "draw_circle") {
+        write_draw_circle(&mut w, state);
+        w.blank();
+    }
+
+    // Las imágenes se copian al proyecto generado con la ruta que tenían junto al script
+    if !assets.is_empty() {
+        w.line("/// Ruta de un archivo copiado desde el directorio del script: junto al");
+        w.line("/// ejecutable o, con `cargo run`, en el directorio del proyecto.");
+        w.open("fn asset(path: &str) -> PathBuf {");
+        w.line("let exe = std::env::current_exe().unwrap_or_default();");
+        w.line("let beside_exe = exe.with_file_name(path);");
+        w.open("if beside_exe.exists() {");
+        w.line("return beside_exe;");
+        w.close("}");
+        w.line("PathBuf::from(std::env::var_os(\"CARGO_MANIFEST_DIR\").unwrap_or_default()).join(path)");
+        w.close("}");
+        w.blank();
+    }
+
+    if config.instrument {
+        write_instrument_module(&mut w, config);
+        w.blank();
+    }
+
+    // Clases del script: un `struct` con su `impl`
+    let mut warnings: Vec<Diagnostic> = Vec::new();
+    let functions = translated_functions(state, true);
+    let module = ModuleContext { state, functions: &functions };
+    for class in &state.classes {
+        write_class(&mut w, class, &module, config, &mut warnings);
+        w.blank();
+    }
+    write_functions(&mut w, &module, true, config, &mut warnings);
+    warnings.extend(unlinked_warnings(state, &functions, config));
 
     // Función main
-    rust_code.push_str("\nfn main() -> Result<(), String> {\n");
-    
-    // Colores
-    rust_code.push_str("    // Definición de colores\n");
-    rust_code.push_str("    let _BLANCO: Color = Color::RGB(255, 255, 255);\n");
-    rust_code.push_str("    let NEGRO: Color = Color::RGB(0, 0, 0);\n");
-    rust_code.push_str("    let _ROJO: Color = Color::RGB(255, 0, 0);\n\n");
-    
-
-
-// Variables de usuario (evitando duplicados y variables sin inicializar)
-rust_code.push_str("    // Variables de usuario\n");
-let mut used_vars = std::collections::HashSet::new();
-let skip_vars = vec!["window", "size", "renderer", "cy", "type", "rect", "center"];
-
-for var in &state.variables {
-    if !used_vars.contains(&var.name) && 
-       !["ANCHO", "ALTO", "_BLANCO", "NEGRO", "_ROJO"].contains(&var.name.as_str()) &&
-       !skip_vars.contains(&var.name.as_str()) {
-        match var.tipo.as_str() {
-            "bool" => {
-                let bool_value = if var.value.contains("true") { "true" } else { "false" };
-                rust_code.push_str(&format!("    let mut {} = {};\n", var.name, bool_value));
+    w.open("fn main() -> Result<(), String> {");
+    if config.instrument {
+        if config.instrument_log {
+            w.line("#[cfg(feature = \"instrument\")]");
+            w.line("env_logger::init();");
+        }
+        w.line("#[cfg(feature = \"instrument\")]");
+        w.line("let mut frame_stats = instrument::FrameStats::new();");
+        w.blank();
+    }
+
+    // `time.time()` cuenta los segundos desde aquí
+    if timing::uses_clock(state) {
+        w.line("let start_time = Instant::now();");
+        w.blank();
+    }
+
+    let images_code: Vec<String> = images.iter().map(|(_, target, image)| image_code(target, image, state, config)).collect();
+    let (main_loop_line, main_loop_column) = state.control_flow.iter()
+        .find(|c| c.control_type == "while")
+        .map(|c| (c.line, c.column))
+        .unwrap_or((0, 0));
+    // Las variables que se asignan por primera vez en el bucle se declaran
+    // antes; sus comentarios van con la asignación
+    let begin_declaration = |w: &mut CodeWriter, var: &Variable| {
+        if main_loop_line > 0 && var.line > main_loop_line {
+            w.begin_hoisted(var.line, var.column)
+        } else {
+            w.begin(var.line, var.column)
+        }
+    };
+
+    // Colores: tuplas RGB(A) del script; con `_` delante si no se dibuja con ellos
+    let mut colors = HashSet::new();
+    for var in &state.variables {
+        let Some(value) = var.value.as_ref().filter(|v| is_color(v)) else {
+            continue;
+        };
+        if !colors.insert(var.name.clone()) {
+            continue;
+        }
+        if colors.len() == 1 {
+            w.line("// Definición de colores");
+        }
+        let name = config.variable_name(&var.name);
+        let used = draw_code.iter().chain(&images_code).any(|code| mentions(code, &name));
+        let region = begin_declaration(&mut w, var);
+        w.line(&format!("let {}{}: Color = {};", if used { "" } else { "_" }, name, color_expr(value, config)));
+        w.end(region);
+    }
+    w.blank();
+
+    // Variables de usuario (evitando duplicados y variables sin inicializar)
+    w.line("// Variables de usuario");
+    let mut used_vars = HashSet::new();
+    let mut declared = HashSet::new();
+    let skip_vars = ["window", "size", "renderer", "cy", "type", "center"];
+    // Los objetos de `entidades = [jugador, enemigo]` solo se mueven a la lista
+    let list_items: HashSet<&str> = state.variables.iter()
+        .filter(|var| var.tipo.starts_with("list "))
+        .filter_map(|var| match &var.value {
+            Some(Expr::List(items)) => Some(items),
+            _ => None,
+        })
+        .flatten()
+        .filter_map(|item| match item {
+            Expr::Name(name) => Some(name.as_str()),
+            Expr::Upcast { value, .. } => match value.as_ref() {
+                Expr::Name(name) => Some(name.as_str()),
+                _ => None,
             },
-            "tuple" => {
-                if !var.value.contains("undefined") && !var.value.is_empty() {
-                    // Extraer los valores numéricos de la tupla
-                    let values: String = var.value
-                        .chars()
-                        .filter(|c| c.is_numeric() || *c == ',' || *c == ' ')
-                        .collect();
-                    rust_code.push_str(&format!("    let mut {} = {};\n", var.name, values));
+            _ => None,
+        })
+        .collect();
+
+    for var in &state.variables {
+        // `running` se declara junto al bucle principal, que la usa siempre
+        if var.name == "running" {
+            declared.insert(var.name.clone());
+            continue;
+        }
+        if !used_vars.contains(&var.name) &&
+           !SIZE_CONSTANTS.iter().any(|(c, _)| c == &var.name) && !colors.contains(&var.name) &&
+           !skip_vars.contains(&var.name.as_str()) {
+            if let Some(mut declaration) = variable_declaration(var, w.indent_width(), config) {
+                if list_items.contains(var.name.as_str()) {
+                    declaration = declaration.replacen("let mut ", "let ", 1);
                 }
-            },
-            _ => {
-                // Para valores numéricos y otros tipos
-                if var.value.contains("Number(") {
-                    let num = var.value
-                        .replace("Number(", "")
-                        .replace(")", "")
-                        .trim()
-                        .to_string();
-                    rust_code.push_str(&format!("    let mut {} = {};\n", var.name, num));
-                } else if var.value.contains("BinaryOp") {
-                    // Manejar operaciones binarias
-                    if var.name == "centro_x" {
-                        rust_code.push_str(&format!("    let mut {} = (ANCHO / 2) as i32;\n", var.name));
-                    } else if var.name == "centro_y" {
-                        rust_code.push_str(&format!("    let mut {} = (ALTO / 2) as i32;\n", var.name));
-                    }
-                } else if !var.value.contains("undefined") && !var.value.contains("Ignore") {
-                    let value = var.value.trim_matches('"');
-                    rust_code.push_str(&format!("    let mut {} = {};\n", var.name, value));
+                let region = begin_declaration(&mut w, var);
+                w.line(&declaration);
+                w.end(region);
+                declared.insert(var.name.clone());
+            }
+            used_vars.insert(var.name.clone());
+        }
+    }
+    w.blank();
+    let mut inlined: HashSet<String> = colors.iter().cloned().collect();
+    inlined.extend(SIZE_CONSTANTS.iter().map(|(c, _)| c.to_string()));
+    inlined.extend(skip_vars.iter().map(|v| v.to_string()));
+    inlined.extend(images.iter().map(|(_, target, _)| target.to_string()));
+    let mut mouse_states = HashSet::new();
+    mouse_state_vars(&state.setup, &mut mouse_states);
+    mouse_state_vars(&state.loop_body, &mut mouse_states);
+    for handler in state.events.iter().flat_map(|events| &events.handlers) {
+        mouse_state_vars(&handler.body, &mut mouse_states);
+    }
+    let sounds = audio::sounds(state);
+    inlined.extend(sounds.iter().cloned());
+    let adjusted_sounds = audio::adjusted_chunks(state);
+    // Las medidas de tiempo del nivel superior se declaran antes del bucle
+    let user_declared = declared.clone();
+    declared.extend(timing::setup_targets(state));
+    declared.extend(geometry::setup_targets(state));
+    let shapes = geometry::shapes(state);
+    let mutated_shapes = geometry::mutated(state);
+    let mut objects: HashSet<String> = state.variables.iter()
+        .filter(|var| classes::is_class_type(&var.tipo) && user_declared.contains(&var.name))
+        .map(|var| var.name.clone())
+        .collect();
+    // `for e in entidades:` recorre objetos
+    for stmt in state.loop_statements() {
+        if let StmtKind::For { target, iter, .. } = &stmt.kind {
+            if list_iter(iter) {
+                objects.insert(target.clone());
+            }
+        }
+    }
+    let ctx = StmtContext {
+        state,
+        config,
+        draw_code: &draw_code,
+        declared,
+        inlined,
+        mouse_states,
+        sounds,
+        adjusted_sounds,
+        shapes,
+        mutated_shapes,
+        objects,
+        functions,
+        dropped: RefCell::new(Vec::new()),
+    };
+
+    // Driver de audio por defecto (`dummy` en máquinas sin tarjeta de sonido);
+    // una variable SDL_AUDIODRIVER ya definida tiene prioridad
+    if let Some(driver) = config.audio_driver.as_ref().filter(|_| audio::uses_mixer(state)) {
+        w.open("if std::env::var_os(\"SDL_AUDIODRIVER\").is_none() {");
+        w.line(&format!("std::env::set_var(\"SDL_AUDIODRIVER\", {:?});", driver));
+        w.close("}");
+        w.blank();
+    }
+
+    // Inicialización de SDL2
+    let (line, column) = sdl_call_position(state, ".init");
+    let region = w.begin(line, column);
+    w.line("let sdl_context = sdl2::init()?;");
+    w.line("let video_subsystem = sdl_context.video()?;");
+    if timing::uses_timer(state) {
+        w.line("let timer = sdl_context.timer()?;");
+    }
+    w.end(region);
+    // Las fuentes toman prestado `ttf_context`, que dura todo `main`
+    if textures::uses_fonts(state) {
+        let (line, column) = state.setup.iter()
+            .find(|stmt| matches!(&stmt.kind, StmtKind::Expr(Expr::Call { function, .. }) if function.ends_with("TTF_Init")))
+            .map(|stmt| (stmt.line, stmt.column))
+            .unwrap_or((0, 0));
+        let region = w.begin(line, column);
+        w.line("let ttf_context = sdl2::ttf::init().map_err(|e| e.to_string())?;");
+        w.end(region);
+    }
+    w.blank();
+
+    let (renderer_line, renderer_column) = sdl_call_position(state, ".Renderer");
+    write_window(&mut w, state.window.as_ref(), renderer_line, config);
+    w.blank();
+
+    let region = w.begin(renderer_line, renderer_column);
+    write_canvas_builder(&mut w, state.renderer.as_ref(), limiter == "vsync", config);
+    w.end(region);
+
+    // Ajustes del renderer y métodos de la ventana anteriores al bucle principal,
+    // en el orden del script
+    let before_loop = |line: usize| line > 0 && (main_loop_line == 0 || line < main_loop_line);
+    let mut setup: Vec<(usize, usize, String)> = Vec::new();
+    if let Some(window) = &state.window {
+        for op in &window.operations {
+            if op.line > renderer_line && before_loop(op.line) && WINDOW_METHODS.contains(&op.method.as_str()) {
+                setup.push((op.line, op.column, format!("canvas.window_mut().{}();", op.method)));
+            }
+        }
+    }
+    if let Some(renderer) = &state.renderer {
+        for setting in &renderer.settings {
+            if before_loop(setting.line) {
+                if let Some(code) = renderer_setting(setting, config) {
+                    setup.push((setting.line, setting.column, code));
+                }
+            }
+        }
+    }
+    // Cursores y modos del ratón (el estado del ratón necesita `event_pump`), audio
+    // y medidas de tiempo
+    let mut loop_mutable = state.loop_targets();
+    loop_mutable.extend(ctx.mutated_shapes.iter().cloned());
+    for stmt in state.setup.iter().filter(|stmt| before_loop(stmt.line)) {
+        let code = mouse_stmt_code(stmt, &ctx)
+            .filter(|code| !code.contains("event_pump"))
+            .or_else(|| fill_stmt_code(stmt, &ctx))
+            .or_else(|| mixer_stmt_code(stmt, &ctx))
+            .or_else(|| match &stmt.kind {
+                // Ya declaradas con su valor entre las variables de usuario
+                StmtKind::Assign { target, .. } if user_declared.contains(target) => None,
+                _ => {
+                    let expr = |expr: &Expr| ctx.expr(expr);
+                    let expr_as = |expr: &Expr, ty: &str| ctx.expr_as(expr, ty);
+                    timing::stmt_code(stmt, &HashSet::new(), &loop_mutable, &expr, &config.int_type)
+                        .or_else(|| geometry::stmt_code(stmt, &ctx.shapes, &HashSet::new(), &loop_mutable, &expr, &expr_as))
                 }
+            });
+        if let Some(code) = code {
+            setup.push((stmt.line, stmt.column, code));
+        }
+    }
+    // Texturas del script; `texture_creator` vive en `main` mientras se usan
+    // Si solo se crean en el bucle, `texture_creator` va junto al canvas
+    let creates_texture = |image: &ImageValue| matches!(image, ImageValue::Texture(_) | ImageValue::FromSurface(_));
+    if images.iter().any(|(_, _, image)| creates_texture(image)) {
+        let (line, column) = images.iter()
+            .find(|(stmt, _, image)| before_loop(stmt.line) && creates_texture(image))
+            .map(|(stmt, _, _)| (stmt.line, stmt.column))
+            .unwrap_or((renderer_line, renderer_column));
+        setup.push((line, column, "let texture_creator = canvas.texture_creator();".to_string()));
+    }
+    for ((stmt, _, _), code) in images.iter().zip(&images_code).filter(|((stmt, _, _), _)| before_loop(stmt.line)) {
+        setup.push((stmt.line, stmt.column, code.clone()));
+    }
+    setup.sort_by_key(|(line, _, _)| *line);
+    for (line, column, code) in setup {
+        let region = w.begin(line, column);
+        w.line(&code);
+        w.end(region);
+    }
+    w.blank();
+
+    w.line("let mut event_pump = sdl_context.event_pump()?;");
+    let (line, column) = variable_position(state, "running");
+    let region = w.begin(line, column);
+    w.line("let mut running = true;");
+    w.end(region);
+    w.blank();
+
+    let main_loop = w.begin(main_loop_line, main_loop_column);
+    w.open("while running {");
+    if config.instrument {
+        w.line("#[cfg(feature = \"instrument\")]");
+        w.line("frame_stats.begin_frame();");
+        w.blank();
+    }
+
+    let (line, column) = sdl_call_position(state, "get_events");
+    let region = w.begin(line, column);
+    w.open("for event in event_pump.poll_iter() {");
+    w.open("match event {");
+    if let Some(events) = &state.events {
+        if config.escape_quits {
+            w.line("Event::KeyDown {
+    keycode: Some(Keycode::Escape),
+    ..
+} => {
+    running = false;
+}");
+        }
+        write_event_arms(&mut w, events, &ctx);
+    } else if config.escape_quits {
+        w.line("Event::Quit { .. }
+| Event::KeyDown {
+    keycode: Some(Keycode::Escape),
+    ..
+} => {
+    running = false;
+}");
+    } else {
+        w.line("Event::Quit { .. } => {\n    running = false;\n}");
+    }
+    w.line("_ => {}");
+    w.close("}");
+    w.close("}");
+    w.end(region);
+    w.blank();
+
+    // Sentencias del bucle principal, en el orden del script. Las llamadas de
+    // dibujo que no están en ninguna sentencia traducida (fuera del bucle o
+    // dentro de un `for`) se dibujan en cada fotograma en su lugar.
+    let mut covered = HashSet::new();
+    stmt_lines(&state.loop_body, &mut covered);
+    for handler in state.events.iter().flat_map(|events| &events.handlers) {
+        stmt_lines(&handler.body, &mut covered);
+    }
+    let mut body = state.loop_body.clone();
+    for call in state.draw_calls.iter().filter(|call| !covered.contains(&call.line)) {
+        body.push(Stmt { kind: StmtKind::Expr(Expr::Name(call.method.clone())), line: call.line, column: call.column });
+    }
+    body.sort_by_key(|stmt| stmt.line);
+    write_stmts(&mut w, &body, &ctx);
+    w.blank();
+
+    if config.instrument {
+        w.line("#[cfg(feature = \"instrument\")]");
+        w.line("frame_stats.end_frame();");
+    }
+    if limiter == "fixed" {
+        w.line(&format!("::std::thread::sleep(Duration::new(0, 1_000_000_000u32 / {}));", config.fps));
+    }
+    w.close("}");
+    w.end(main_loop);
+    w.blank();
+
+    let (line, column) = sdl_call_position(state, ".quit");
+    let region = w.begin(line, column);
+    w.line("Ok(())");
+    w.end(region);
+    w.close("}");
+
+    // Sentencias omitidas que no tienen ya su aviso
+    for line in ctx.dropped.take() {
+        if !warnings.iter().any(|d| d.python_line == Some(line)) {
+            warnings.push(Diagnostic::warning(config.msg("sentencia sin traducir: se omite", "statement not translated: skipped").to_string(), line));
+        }
+    }
+    // Listas con un objeto que también se usa fuera de ellas
+    for var in &state.variables {
+        if let Some(item) = classes::shared_item(state, var).filter(|_| var.tipo == "unknown") {
+            let message = format!("{}`{}`{}`{}`{}", config.msg("la lista ", "list "), var.name,
+                config.msg(" no se traduce: ", " is not translated: "), item, config.msg(" se usa también fuera de ella", " is also used outside it"));
+            warnings.push(Diagnostic::warning(message, var.line));
+        }
+    }
+
+    // Constantes de teclado sin equivalente en sdl2
+    for (line, name) in constants {
+        if keys::is_keyboard_constant(&name) && keys::rust_constant(&name).is_none()
+            && !warnings.iter().any(|d| d.python_line == Some(line) && d.message.ends_with(&name)) {
+            let message = config.msg("constante de teclado sin equivalente en sdl2: ", "keyboard constant without an sdl2 equivalent: ");
+            warnings.push(Diagnostic::warning(format!("{}{}", message, name), line));
+        }
+    }
+
+    // La fuente por defecto de pygame y lo que se hace con ella
+    for (line, name) in &default_fonts {
+        let opens_font = state.statements().iter()
+            .any(|stmt| stmt.line == *line && matches!(&stmt.kind, StmtKind::Assign { value, .. } if textures::is_default_font(value)));
+        let message = match opens_font {
+            true => config.msg(
+                "la fuente por defecto de pygame (`Font(None, …)`) no existe en sdl2: usa la ruta de un archivo .ttf",
+                "pygame's default font (`Font(None, …)`) does not exist in sdl2: use the path of a .ttf file",
+            ).to_string(),
+            false => format!("`{}`{}", name, config.msg(" usa la fuente por defecto de pygame: no se traduce", " uses pygame's default font: not translated")),
+        };
+        warnings.push(Diagnostic::warning(message, *line));
+    }
+    for call in state.draw_calls.iter().filter(|call| default_font_draw(call)) {
+        let message = config.msg("sin traducción: dibuja con la fuente por defecto de pygame", "not translated: draws with pygame's default font");
+        warnings.push(Diagnostic::warning(message.to_string(), call.line));
+    }
+    // Dibujos de pygame que sdl2 no hace igual
+    for call in &state.draw_calls {
+        let Some((_, Expr::Str(what))) = call.kwargs.iter().find(|(name, _)| name == pygame::APPROXIMATION) else {
+            continue;
+        };
+        let message = match what.as_str() {
+            "polygon" => config.msg(
+                "`pygame.draw.polygon` se dibuja solo con su contorno: sdl2 no rellena polígonos",
+                "`pygame.draw.polygon` is drawn as an outline only: sdl2 cannot fill polygons",
+            ),
+            "circle" => config.msg(
+                "`pygame.draw.circle` no respeta `width`: el círculo se dibuja relleno",
+                "`pygame.draw.circle` ignores `width`: the circle is drawn filled",
+            ),
+            _ => config.msg(
+                "`pygame.draw.line` no respeta `width`: la línea se dibuja de un píxel",
+                "`pygame.draw.line` ignores `width`: the line is drawn one pixel wide",
+            ),
+        };
+        warnings.push(Diagnostic::warning(message.to_string(), call.line));
+    }
+
+    // sdl2 0.35 no tiene rectángulos de `float`
+    for (line, name) in geometry::float_shapes(state) {
+        let message = config.msg(" sin equivalente en sdl2 0.35: se usa con coordenadas enteras", " has no sdl2 0.35 equivalent: using integer coordinates");
+        warnings.push(Diagnostic::warning(format!("{}{}", name, message), line));
+    }
+
+    let (code, source_map) = w.finish();
+    GeneratedCode { code, source_map, warnings }
+}
+
+// Traits de jerarquías de otros módulos cuyos métodos se usan: `player::SpriteTrait`
+fn hierarchy_traits(state: &State, config: &Config) -> Vec<String> {
+    if config.inheritance == "enum" {
+        return Vec::new();
+    }
+    state.hierarchies.iter().map(|root| format!("{}Trait", root.replace('.', "::"))).collect()
+}
+
+fn write_modules(w: &mut CodeWriter, modules: &[String], keyword: &str) {
+    for module in modules {
+        w.line(&format!("{} {};", keyword, module));
+    }
+    w.blank();
+}
+
+// Módulo del proyecto que no es el script de entrada: sus submódulos, las
+// constantes del nivel superior y, sin traducir, sus funciones. `crate_uses`
+// son los módulos de la raíz que importa (`use crate::player;`).
+pub fn generate_module(state: &State, config: &Config, python_file: &str, crate_uses: &[String]) -> GeneratedCode {
+    let mut w = CodeWriter::new(python_file, &state.comments, config.line_comments);
+
+    if let Some(docstring) = &state.module_docstring {
+        w.doc_comment("//!", docstring);
+        w.blank();
+    }
+    write_modules(&mut w, &state.modules, "pub mod");
+
+    // Constantes con su tipo y su valor; una constante solo se declara una vez
+    let mut declared = HashSet::new();
+    let mut constants = Vec::new();
+    for var in &state.variables {
+        let ty = match (var.tipo.as_str(), &var.value) {
+            ("int", Some(value)) if is_float(value, config) => "f64",
+            ("int", _) => config.int_type.as_str(),
+            ("float", _) => "f64",
+            ("bool", _) => "bool",
+            ("str", Some(Expr::Str(s))) if !s.starts_with(['f', 'F']) => "&str",
+            _ => continue,
+        };
+        if let Some(value) = var.value.as_ref() {
+            if declared.insert(var.name.clone()) {
+                constants.push((var, ty, rust_expr(value, config)));
             }
         }
-        used_vars.insert(var.name.clone());
     }
+
+    // Solo los `use` de los módulos que aparecen en el código
+    let class_paths: Vec<String> = state.classes.iter().flat_map(classes::paths).collect();
+    let mut used: Vec<String> = crate_uses
+        .iter()
+        .filter(|module| {
+            constants.iter().any(|(_, _, value)| value.contains(&format!("{}::", module)))
+                || class_paths.iter().any(|path| path.starts_with(&format!("{}::", module)) || path.starts_with(&format!("{}.", module)))
+        })
+        .cloned()
+        .chain(hierarchy_traits(state, config))
+        .collect();
+    used.sort();
+    match used.as_slice() {
+        [] => {}
+        [single] => w.line(&format!("use crate::{};", single)),
+        modules => w.line(&format!("use crate::{{{}}};", modules.join(", "))),
+    }
+    // Lo que usan los métodos que dibujan
+    if draws_in_methods(state) {
+        let method_code = method_draw_code(state, config);
+        if method_code.iter().any(|code| code.contains("Color::")) {
+            w.line("use sdl2::pixels::Color;");
+        }
+        if method_code.iter().any(|code| code.contains("Rect::new")) {
+            w.line("use sdl2::rect::Rect;");
+        }
+        w.line("use sdl2::render::Canvas;");
+        w.line("use sdl2::video::Window;");
+    }
+    w.blank();
+
+    for (var, ty, value) in &constants {
+        let region = w.begin(var.line, var.column);
+        if var.name.chars().any(|c| c.is_lowercase()) {
+            w.line("#[allow(non_upper_case_globals)]");
+        }
+        w.line(&format!("pub const {}: {} = {};", var.name, ty, value));
+        w.end(region);
+    }
+    w.blank();
+
+    let mut warnings = Vec::new();
+    let functions = translated_functions(state, false);
+    let module = ModuleContext { state, functions: &functions };
+    for class in &state.classes {
+        write_class(&mut w, class, &module, config, &mut warnings);
+        w.blank();
+    }
+    write_functions(&mut w, &module, false, config, &mut warnings);
+    warnings.extend(unlinked_warnings(state, &functions, config));
+
+    let (code, source_map) = w.finish();
+    GeneratedCode { code, source_map, warnings }
 }
 
-    rust_code.push_str("\n");
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::extract_state;
+    use crate::tokenizer::tokenize;
 
-    // Inicialización de SDL2
-    rust_code.push_str(r#"    let sdl_context = sdl2::init()?;
-    let video_subsystem = sdl_context.video()?;
-
-    let window = video_subsystem
-        .window("SDL Window", ANCHO, ALTO)
-        .position_centered()
-        .build()
-        .map_err(|e| e.to_string())?;
-
-    let mut canvas = window
-        .into_canvas()
-        .build()
-        .map_err(|e| e.to_string())?;
-    
-    let mut event_pump = sdl_context.event_pump()?;
-    let mut running = true;
-
-    while running {
-        for event in event_pump.poll_iter() {
-            match event {
-                Event::Quit { .. } |
-                Event::KeyDown { keycode: Some(Keycode::Escape), .. } => {
-                    running = false;
-                },
-                _ => {}
-            }
-        }
-
-        canvas.set_draw_color(NEGRO);
-        canvas.clear();
-"#);
-
-    // Dibujar elementos
-    for draw_call in &state.draw_calls {
-        rust_code.push_str(&format!("        println!(\"Debug: Processing draw call: {{:?}}\", {:?});\n", draw_call));
-        match draw_call.draw_type {
-            DrawType::Rect => {
-               rust_code.push_str(&format!(r#"
-                {{
-                   let rect_x = {};
-                   let rect_y = {};
-                   let rect_w = {};
-                   let rect_h = {};
-                   let rect = Rect::new(rect_x, rect_y, rect_w as u32, rect_h as u32);
-                   let color = {};
-                   canvas.set_draw_color(color);
-                   canvas.fill_rect(rect)?;
-                 }}
-               "#,
-                    draw_call.x, draw_call.y, 
-                    draw_call.w.as_ref().unwrap_or(&"50".to_string()), 
-                    draw_call.h.as_ref().unwrap_or(&"50".to_string()),
-                    draw_call.color
-                ));
-            }
-            DrawType::Circle => {
-                rust_code.push_str(&format!(r#"
-        draw_circle(&mut canvas, {}, ({}, {}), {});
-"#,
-                    draw_call.color, draw_call.x, draw_call.y, 
-                    draw_call.radius.as_ref().unwrap_or(&"50".to_string())
-                ));
-            }
-        }
-    }
-    
-    // Final del bucle principal y función main
-    rust_code.push_str(r#"
-        canvas.present();
-        ::std::thread::sleep(Duration::new(0, 1_000_000_000u32 / 60));
-    }
-
-    Ok(())
-}
-"#);
-    
-    rust_code
-}
\ No newline at end of file
+    #[test]
+    fn test_output_layout() {
+        let state = extract_state(tokenize(include_str!("../juego.py")));
+        let code = generate_rust(&state, &Config::default(), "juego.py").code;
+
+        assert!(code.contains("    let mut centro_x: i32 = ANCHO as i32 / 2;\n"));
+        assert!(code.contains("        draw_circle(&mut canvas, BLANCO, (centro_x, centro_y), radio);\n"));
+        assert!(code.contains("        let rect = Rect::new(x, y, 50, 50);\n"));
+        assert!(code.contains("        canvas.fill_rect(rect)?;\n"));
+        assert_eq!(code.matches("let mut running = true;").count(), 1);
+        // Los comentarios van con su sentencia, no con la declaración adelantada
+        assert!(code.contains("    // Bucle principal\n    let mut running = true;\n"));
+        assert!(code.contains("        // Dibujar círculo en el centro de la pantalla\n        centro_x = ANCHO as i32 / 2;\n"));
+        assert_eq!(code.matches("// Dibujar círculo").count(), 1);
+        assert!(!code.contains("\n\n\n"));
+        assert!(code.lines().all(|l| l == l.trim_end()));
+        assert!(code.lines().all(|l| (l.len() - l.trim_start().len()) % 4 == 0));
+    }
+
+    #[test]
+    fn test_window_builder() {
+        let code = "import sdl2.ext\nwindow = sdl2.ext.Window('Mi juego', (640, 480), position=(10, 20),\n    flags=sdl2.SDL_WINDOW_RESIZABLE | sdl2.SDL_WINDOW_BORDERLESS)\nrenderer = sdl2.ext.Renderer(window)\nwindow.maximize()\n";
+        let state = extract_state(tokenize(code));
+        let code = generate_rust(&state, &Config::default(), "juego.py").code;
+
+        assert!(code.contains("    let window = video_subsystem\n        .window(\"Mi juego\", 640, 480)\n        .position(10, 20)\n        .resizable()\n        .borderless()\n"));
+        assert!(!code.contains(".hidden()"));
+        assert!(code.contains("    canvas.window_mut().maximize();\n"));
+        // Sin `ANCHO`/`ALTO` ni llamadas a `draw_circle` no se declaran
+        assert!(!code.contains("const ANCHO"));
+        assert!(!code.contains("const ALTO"));
+        assert!(!code.contains("fn draw_circle"));
+    }
+
+    #[test]
+    fn test_renderer_options() {
+        let code = "window = sdl2.ext.Window('R', (320, 240))\nrenderer = sdl2.ext.Renderer(window, logical_size=(160, 120),\n    flags=sdl2.SDL_RENDERER_SOFTWARE | sdl2.SDL_RENDERER_PRESENTVSYNC)\nrenderer.blendmode = sdl2.SDL_BLENDMODE_ADD\n";
+        let state = extract_state(tokenize(code));
+        let code = generate_rust(&state, &Config::default(), "juego.py").code;
+
+        assert!(code.contains("        .into_canvas()\n        .software()\n        .present_vsync()\n        .build()\n"));
+        assert!(code.contains("        .set_logical_size(160, 120)\n"));
+        assert!(code.contains("    canvas.set_blend_mode(sdl2::render::BlendMode::Add);\n"));
+    }
+
+    #[test]
+    fn test_renderer_primitives() {
+        let code = "renderer = sdl2.ext.Renderer(window)\nrenderer.draw_point((5, 5), (255, 0, 0))\nrenderer.draw_line([0, 0, 10, 10, 20, 0])\nrenderer.fill([(1, 2, 3, 4), (5, 6, 7, 8)])\nrenderer.copy(sprite, dstrect=(10, 20))\nrenderer.rcopy(sprite, (1, 2), size=(3, 4), flip=sdl2.SDL_FLIP_VERTICAL)\nrenderer.fill((x, abs(y), 10, 10))\n";
+        let state = extract_state(tokenize(code));
+        let code = generate_rust(&state, &Config::default(), "juego.py").code;
+
+        assert!(code.contains("canvas.set_draw_color(Color::RGB(255, 0, 0));\n        canvas.draw_point((5, 5))?;\n"));
+        assert!(code.contains("canvas.draw_lines(&[Point::new(0, 0), Point::new(10, 10), Point::new(20, 0)][..])?;"));
+        assert!(code.contains("canvas.fill_rects(&[Rect::new(1, 2, 3, 4), Rect::new(5, 6, 7, 8)])?;"));
+        // `abs(y)` es un número, no un rectángulo
+        assert!(code.contains("canvas.fill_rect(Rect::new(x, y.abs(), 10, 10))?;"));
+        assert!(!code.contains("fill_rects(&[x"));
+        assert!(code.contains("let query = (&sprite).query();\n            canvas.copy(&sprite, None, Rect::new(10, 20, query.width, query.height))?;"));
+        assert!(code.contains("canvas.copy_ex(&sprite, None, Rect::new(1, 2, 3, 4), 0.0, None, false, true)?;"));
+        assert!(code.contains("use sdl2::rect::{Point, Rect};"));
+    }
+
+    #[test]
+    fn test_event_match() {
+        let code = "window = sdl2.ext.Window('E', (320, 240))\nx = 0\nrunning = True\nfor event in sdl2.ext.get_events():\n    if event.type == sdl2.SDL_MOUSEBUTTONDOWN and event.button.button == sdl2.SDL_BUTTON_LEFT:\n        x = event.button.x\n    elif event.type == sdl2.SDL_WINDOWEVENT:\n        if event.window.event == sdl2.SDL_WINDOWEVENT_CLOSE:\n            running = False\n";
+        let state = extract_state(tokenize(code));
+        let code = generate_rust(&state, &Config::default(), "juego.py").code;
+
+        assert!(code.contains("use sdl2::event::{Event, WindowEvent};\nuse sdl2::keyboard::Keycode;\nuse sdl2::mouse::MouseButton;\n"));
+        assert!(code.contains("                Event::MouseButtonDown {\n                    mouse_btn,\n                    x: mouse_x,\n                    ..\n                } => {\n                    if mouse_btn == MouseButton::Left {\n                        x = mouse_x;\n"));
+        assert!(code.contains("                Event::Window { win_event, .. } => {\n                    if matches!(win_event, WindowEvent::Close) {\n"));
+    }
+    #[test]
+    fn test_keyboard_state() {
+        let code = "x = 0\nwhile running:\n    keys = sdl2.SDL_GetKeyboardState(None)\n    if keys[sdl2.SDL_SCANCODE_A] or keys[sdl2.SDL_SCANCODE_NOEXISTE]:\n        x -= 1\n";
+        let state = extract_state(tokenize(code));
+        let generated = generate_rust(&state, &Config::default(), "juego.py");
+
+        assert!(generated.code.contains("use sdl2::keyboard::{Keycode, Scancode};"));
+        assert!(generated.code.contains("        let keys = event_pump.keyboard_state();\n        if keys.is_scancode_pressed(Scancode::A) || "));
+        assert!(generated.code.contains("            x -= 1;\n"));
+        assert_eq!(generated.warnings.len(), 1);
+        assert_eq!(generated.warnings[0].python_line, Some(4));
+        assert!(generated.warnings[0].message.ends_with("SDL_SCANCODE_NOEXISTE"));
+    }
+
+    #[test]
+    fn test_mouse_state() {
+        let code = "mx, my = ctypes.c_int(0), ctypes.c_int(0)\nsdl2.SDL_ShowCursor(sdl2.SDL_DISABLE)\nwhile running:\n    buttons = sdl2.SDL_GetMouseState(ctypes.byref(mx), ctypes.byref(my))\n    if buttons & sdl2.SDL_BUTTON_LMASK:\n        mx += 1\n";
+        let state = extract_state(tokenize(code));
+        let generated = generate_rust(&state, &Config::default(), "juego.py");
+
+        assert!(generated.code.contains("    let mut mx: i32 = 0;\n"));
+        assert!(generated.code.contains("    sdl_context.mouse().show_cursor(false);\n"));
+        assert!(generated.code.contains("        let buttons = event_pump.mouse_state();\n        mx = buttons.x();\n        my = buttons.y();\n"));
+        assert!(generated.code.contains("        if buttons.left() {\n"));
+    }
+
+    #[test]
+    fn test_textures() {
+        let code = "renderer = sdl2.ext.Renderer(window)\nfactory = sdl2.ext.SpriteFactory(sdl2.ext.TEXTURE, renderer=renderer)\njugador = factory.from_image(\"img/jugador.png\")\nsprite = sdl2.ext.TextureSprite(jugador)\nwhile running:\n    renderer.copy(sprite, dstrect=(0, 0, 32, 32))\n";
+        let state = extract_state(tokenize(code));
+        let generated = generate_rust(&state, &Config::default(), "juego.py");
+
+        assert!(generated.code.contains("use sdl2::image::LoadTexture;\n"));
+        assert!(generated.code.contains("    let beside_exe = exe.with_file_name(path);\n"));
+        assert!(generated.code.contains("    PathBuf::from(std::env::var_os(\"CARGO_MANIFEST_DIR\").unwrap_or_default()).join(path)\n"));
+        assert!(!generated.code.contains("env!("));
+        assert!(generated.code.contains("    let texture_creator = canvas.texture_creator();\n    let jugador = texture_creator.load_texture(asset(\"img/jugador.png\"))?;\n    let sprite = jugador;\n"));
+        assert!(generated.code.contains("        canvas.copy(&sprite, None, Rect::new(0, 0, 32, 32))?;\n"));
+        assert!(generate_cargo_toml("juego", &state, &Config::default()).contains("\"image\"] }"));
+    }
+
+    #[test]
+    fn test_text_rendering() {
+        let code = "BLANCO = (255, 255, 255)\nfuente = sdl2.ext.FontTTF(\"DejaVuSans.ttf\", \"24px\", BLANCO)\npequena = sdlttf.TTF_OpenFont(b\"DejaVuSans.ttf\", 12)\nwhile running:\n    texto = fuente.render_text(f\"Puntos: {puntos:05d}\")\n    info = sdlttf.TTF_RenderUTF8_Solid(pequena, b\"Vidas: \" + str(vidas).encode(), sdl2.SDL_Color(255, 255, 0))\n    sdl2.SDL_FreeSurface(info)\n";
+        let state = extract_state(tokenize(code));
+        let generated = generate_rust(&state, &Config::default(), "juego.py");
+
+        assert!(generated.code.contains("    let ttf_context = sdl2::ttf::init().map_err(|e| e.to_string())?;\n"));
+        assert!(generated.code.contains("    let BLANCO: Color = Color::RGB(255, 255, 255);\n"));
+        assert!(generated.code.contains("    let fuente = ttf_context.load_font(asset(\"DejaVuSans.ttf\"), 24)?;\n"));
+        assert!(generated.code.contains("        let texto = fuente\n            .render(&format!(\"Puntos: {:05}\", puntos))\n            .blended(BLANCO)\n"));
+        assert!(generated.code.contains("            .render(&format!(\"Vidas: {}\", vidas))\n            .solid(Color::RGB(255, 255, 0))\n"));
+        assert!(generated.code.contains("        drop(info);\n"));
+        assert!(generate_cargo_toml("juego", &state, &Config::default()).contains("\"ttf\"] }"));
+    }
+
+    #[test]
+    fn test_pygame_default_font() {
+        let code = "import pygame\npygame.init()\nscreen = pygame.display.set_mode((640, 480))\nfont = pygame.font.Font(None, 36)\ntexto = font.render(\"Hola\", True, (255, 255, 255))\nrunning = True\nwhile running:\n    for event in pygame.event.get():\n        if event.type == pygame.QUIT:\n            running = False\n    screen.fill((0, 0, 0))\n    screen.blit(texto, (10, 10))\n    pygame.display.flip()\n";
+        let state = extract_state(tokenize(code));
+        let generated = generate_rust(&state, &Config::default(), "fuente.py");
+
+        assert!(generated.code.contains("        // sin traducción (línea 12)\n"));
+        assert!(!mentions(&generated.code, "texto"));
+        let lines: Vec<Option<usize>> = generated.warnings.iter().map(|warning| warning.python_line).collect();
+        assert_eq!(lines, [Some(4), Some(5), Some(12)]);
+        assert_eq!(generated.warnings[1].message, "`texto` usa la fuente por defecto de pygame: no se traduce");
+
+        // Ni la fuente ni la textura se declaran, así que nada las usa
+        assert!(!mentions(&generated.code, "font"));
+        assert!(generated.code.contains("        // sin traducción (línea 12)\n        canvas.present();\n"));
+    }
+
+    #[test]
+    fn test_pygame_draw_approximations() {
+        let code = "import pygame\nscreen = pygame.display.set_mode((640, 480))\nwhile running:\n    pygame.draw.polygon(screen, (255, 0, 0), [(0, 0), (10, 0), (5, 8)])\n    pygame.draw.circle(screen, (0, 255, 0), (50, 50), 20, 2)\n    pygame.draw.circle(screen, (0, 255, 0), (90, 50), 20)\n    pygame.draw.line(screen, (0, 0, 255), (0, 0), (100, 100), width=3)\n    pygame.draw.line(screen, (0, 0, 255), (0, 0), (100, 100))\n";
+        let state = extract_state(tokenize(code));
+        let generated = generate_rust(&state, &Config::default(), "formas.py");
+
+        assert!(generated.code.contains("        draw_circle(&mut canvas, Color::RGB(0, 255, 0), (50, 50), 20);\n"));
+        let warnings: Vec<(Option<usize>, &str)> = generated.warnings.iter().map(|warning| (warning.python_line, warning.message.as_str())).collect();
+        assert_eq!(warnings, [
+            (Some(4), "`pygame.draw.polygon` se dibuja solo con su contorno: sdl2 no rellena polígonos"),
+            (Some(5), "`pygame.draw.circle` no respeta `width`: el círculo se dibuja relleno"),
+            (Some(7), "`pygame.draw.line` no respeta `width`: la línea se dibuja de un píxel"),
+        ]);
+    }
+
+    #[test]
+    fn test_mixer_audio() {
+        let code = "sdlmixer.Mix_OpenAudio(44100, sdlmixer.MIX_DEFAULT_FORMAT, 2, 1024)\nsalto = sdlmixer.Mix_LoadWAV(b\"salto.wav\")\nmusica = sdlmixer.Mix_LoadMUS(b\"musica.ogg\")\nsdlmixer.Mix_PlayMusic(musica, -1)\ncanal = 0\nwhile running:\n    canal = sdlmixer.Mix_PlayChannel(-1, salto, 0)\n    if not sdlmixer.Mix_PlayingMusic():\n        sdlmixer.Mix_HaltChannel(canal)\n";
+        let state = extract_state(tokenize(code));
+        let config = Config { audio_driver: Some("dummy".to_string()), ..Config::default() };
+        let generated = generate_rust(&state, &config, "juego.py");
+
+        assert!(generated.code.contains("use sdl2::mixer::{Channel, Chunk, Music, DEFAULT_FORMAT};\n"));
+        assert!(generated.code.contains("        std::env::set_var(\"SDL_AUDIODRIVER\", \"dummy\");\n"));
+        assert!(generated.code.contains("    let salto = Chunk::from_file(asset(\"salto.wav\"))?;\n"));
+        assert!(generated.code.contains("    musica.play(-1)?;\n"));
+        assert!(generated.code.contains("        canal = Channel::all().play(&salto, 0)?.0;\n"));
+        assert!(generated.code.contains("        if !Music::is_playing() {\n            Channel(canal).halt();\n"));
+        assert!(generate_cargo_toml("juego", &state, &config).contains("\"mixer\"] }"));
+        assert!(!generate_rust(&state, &Config::default(), "juego.py").code.contains("SDL_AUDIODRIVER"));
+    }
+
+    #[test]
+    fn test_frame_limiter() {
+        let code = "antes = sdl2.SDL_GetTicks()\nwhile running:\n    if time.time() > 2:\n        antes = sdl2.SDL_GetTicks()\n    sdl2.SDL_Delay(16)\n";
+        let state = extract_state(tokenize(code));
+        let generated = generate_rust(&state, &Config::default(), "juego.py");
+
+        assert!(generated.code.contains("use std::time::{Duration, Instant};\n"));
+        assert!(generated.code.contains("    let timer = sdl_context.timer()?;\n"));
+        assert!(generated.code.contains("    let mut antes = timer.ticks() as i32;\n"));
+        assert!(generated.code.contains("        if start_time.elapsed().as_secs_f64() > 2.0 {\n            antes = timer.ticks() as i32;\n"));
+        assert!(generated.code.contains("        ::std::thread::sleep(Duration::from_millis(16));\n"));
+        assert!(!generated.code.contains("1_000_000_000u32"));
+
+        // Sin limitador propio se usa `frame.limiter`
+        let state = extract_state(tokenize("while running:\n    x = 1\n"));
+        let vsync = Config { frame_limiter: "vsync".to_string(), ..Config::default() };
+        let generated = generate_rust(&state, &vsync, "juego.py");
+        assert!(generated.code.contains("        .present_vsync()\n"));
+        assert!(!generated.code.contains("use std::time"));
+        let fixed = generate_rust(&state, &Config::default(), "juego.py");
+        assert!(fixed.code.contains("::std::thread::sleep(Duration::new(0, 1_000_000_000u32 / 60));"));
+    }
+
+    #[test]
+    fn test_math_and_builtins() {
+        let code = "import math\nangulo = 0.5\nx = 3\ny = 200 + math.sin(angulo) * 100\ndx = abs(x - 7)\nwhile running:\n    x = int(y) + max(x, 4)\n    if round(y) < x:\n        x = -x\n";
+        let state = extract_state(tokenize(code));
+        let generated = generate_rust(&state, &Config::default(), "juego.py");
+
+        // `math.sin` devuelve un float aunque el parser no lo sepa
+        assert!(generated.code.contains("    let mut y: f64 = 200.0 + (angulo as f64).sin() * 100.0;\n"));
+        assert!(generated.code.contains("    let mut dx = (x - 7).abs();\n"));
+        assert!(generated.code.contains("        x = (y as i32) + x.max(4);\n"));
+        assert!(generated.code.contains("        if (y.round() as i32) < x {\n"));
+        assert!(generated.warnings.is_empty());
+    }
+
+    #[test]
+    fn test_instrument() {
+        let code = "renderer = sdl2.ext.Renderer(window)\nwhile running:\n    renderer.clear(sdl2.ext.Color(0, 0, 0))\n    renderer.fill((10, 10, 20, 20), sdl2.ext.Color(255, 0, 0))\n    renderer.draw_line((0, 0, 5, 5), sdl2.ext.Color(0, 255, 0))\n    renderer.present()\n";
+        let state = extract_state(tokenize(code));
+        let mut config = Config::default();
+        config.set("instrument.enabled", "true").unwrap();
+        let generated = generate_rust(&state, &config, "juego.py").code;
+
+        assert!(generate_cargo_toml("juego", &state, &config).contains("log = { version = \"0.4\", optional = true }\nenv_logger = { version = \"0.11\", optional = true }\n\n[features]\ninstrument = [\"dep:log\", \"dep:env_logger\"]\n"));
+        assert!(generated.contains("#[cfg(feature = \"instrument\")]\nmod instrument {\n    use std::time::{Duration, Instant};\n"));
+        assert!(generated.contains("    pub struct FrameStats {\n"));
+        assert!(generated.contains("            log::debug!(\n"));
+        assert!(generated.contains("    #[cfg(feature = \"instrument\")]\n    env_logger::init();\n    #[cfg(feature = \"instrument\")]\n    let mut frame_stats = instrument::FrameStats::new();\n"));
+        assert!(generated.contains("        #[cfg(feature = \"instrument\")]\n        frame_stats.begin_frame();\n"));
+        assert!(generated.contains("        #[cfg(feature = \"instrument\")]\n        frame_stats.end_frame();\n"));
+        // Una vez por cada dibujo: ni `clear` ni `present` cuentan
+        assert_eq!(generated.matches("frame_stats.draw_calls += 1;").count(), 2);
+        assert!(generated.contains("        #[cfg(feature = \"instrument\")]\n        {\n            frame_stats.draw_calls += 1;\n        }\n"));
+
+        config.set("instrument.log", "false").unwrap();
+        assert!(generate_cargo_toml("juego", &state, &config).contains("\n[features]\ninstrument = []\n"));
+        let generated = generate_rust(&state, &config, "juego.py").code;
+        assert!(!generated.contains("log::") && !generated.contains("env_logger"));
+        assert!(generated.contains("                eprintln!(\n"));
+
+        let plain = generate_rust(&state, &Config::default(), "juego.py").code;
+        assert!(!plain.contains("instrument"));
+        assert!(!generate_cargo_toml("juego", &state, &Config::default()).contains("[features]"));
+    }
+
+    #[test]
+    fn test_project_module() {
+        let code = "\"\"\"Jugador.\"\"\"\nimport levels, ajustes\nVELOCIDAD = levels.PASO * 2\nnombre = \"Ana\"\nVELOCIDAD = 6\ndef mover(dx):\n    pass\n";
+        let mut state = extract_state(tokenize(code));
+        state.modules = vec!["armas".to_string()];
+        let code = generate_module(&state, &Config::default(), "player.py", &["ajustes".to_string(), "levels".to_string()]).code;
+
+        assert!(code.starts_with("//! Jugador.\n\npub mod armas;\n\nuse crate::levels;\n\npub const VELOCIDAD: i32 = levels::PASO * 2;\n"));
+        assert!(code.contains("#[allow(non_upper_case_globals)]\npub const nombre: &str = \"Ana\";\n"));
+        assert!(!code.contains("= 6;"));
+        assert!(code.contains("// sin traducción: def mover(dx)"));
+
+        let mut state = extract_state(tokenize("import player\nx = player.VELOCIDAD\n"));
+        state.modules = vec!["player".to_string()];
+        let code = generate_rust(&state, &Config::default(), "main.py").code;
+        assert!(code.starts_with("mod player;\n\nuse sdl2::event::Event;\n"));
+    }
+
+    #[test]
+    fn test_classes() {
+        let code = "class Marcador:\n    \"\"\"Puntos.\"\"\"\n    MAXIMO = 10\n    def __init__(self, inicial=0.0):\n        self.puntos = inicial\n        self.nombre = \"ana\"\n    def sumar(self, n):\n        self.puntos += n\n    def lleno(self):\n        return self.puntos >= Marcador.MAXIMO\n    def datos(self):\n        return (self.puntos, 1)\nm = Marcador()\nwhile running:\n    m.sumar(1)\n";
+        let state = extract_state(tokenize(code));
+        let generated = generate_rust(&state, &Config::default(), "juego.py");
+        let code = generated.code;
+
+        assert!(code.contains("/// Puntos.\npub struct Marcador {\n    pub puntos: f64,\n    pub nombre: String,\n}\n"));
+        assert!(code.contains("    pub const MAXIMO: i32 = 10;\n"));
+        assert!(code.contains("    pub fn new(inicial: f64) -> Self {\n        Self {\n            puntos: inicial,\n            nombre: \"ana\".to_string(),\n        }\n    }\n"));
+        assert!(code.contains("    pub fn sumar(&mut self, n: i32) {\n        self.puntos += n as f64;\n    }\n"));
+        assert!(code.contains("    pub fn lleno(&self) -> bool {\n        self.puntos >= Self::MAXIMO\n    }\n"));
+        assert!(code.contains("    // sin traducción: def datos(self)\n"));
+        assert!(code.contains("let mut m = Marcador::new(0.0);"));
+        assert!(code.contains("m.sumar(1);"));
+        assert_eq!(generated.warnings.len(), 1);
+        assert_eq!(generated.warnings[0].python_line, Some(11));
+    }
+
+    #[test]
+    fn test_inheritance() {
+        let code = "class Sprite:\n    def __init__(self, x):\n        self.x = x\n    def update(self):\n        \"\"\"Avanza un paso.\"\"\"\n        self.x += 1\nclass Enemy(Sprite):\n    def __init__(self, x, vida):\n        super().__init__(x)\n        self.vida = vida\n    def update(self):\n        super().update()\n        self.vida -= 1\nclass Raro(Sprite, metaclass=Meta):\n    pass\nclass Doble(Sprite, Enemy):\n    pass\ne = Enemy(1, 3)\nwhile running:\n    e.update()\n    if e.x > 9:\n        e = Sprite(0)\n";
+        let state = extract_state(tokenize(code));
+        let generated = generate_rust(&state, &Config::default(), "juego.py");
+        let code = generated.code;
+
+        assert!(code.contains("pub trait SpriteTrait {\n    fn sprite(&self) -> &Sprite;\n    fn sprite_mut(&mut self) -> &mut Sprite;\n"));
+        assert!(code.contains("    /// Avanza un paso.\n    fn update(&mut self) {\n        self.sprite_mut().update();\n    }\n"));
+        assert!(code.contains("impl SpriteTrait for Enemy {\n"));
+        assert!(code.contains("    fn update(&mut self) {\n        self.sprite.update();\n        self.vida -= 1;\n    }\n"));
+        assert!(code.contains("            sprite: Sprite::new(x),\n"));
+        assert!(code.contains("        [\"Enemy\", \"Sprite\"].contains(&class)\n"));
+        assert!(code.contains("let mut e: Box<dyn SpriteTrait> = Box::new(Enemy::new(1, 3));"));
+        assert!(code.contains("e = Box::new(Sprite::new(0));"));
+        let messages: Vec<&str> = generated.warnings.iter().map(|warning| warning.message.as_str()).collect();
+        assert!(messages.contains(&"metaclase no soportada en Raro (Meta)"));
+        assert!(messages.contains(&"herencia múltiple no soportada en Doble (Sprite, Enemy)"));
+
+        let mut config = Config::default();
+        config.set("classes.inheritance", "enum").unwrap();
+        let code = generate_rust(&state, &config, "juego.py").code;
+        assert!(code.contains("pub enum SpriteKind {\n    Sprite(Sprite),\n    Enemy(Enemy),\n"));
+        assert!(code.contains("            Self::Enemy(object) => &mut object.sprite,\n"));
+        assert!(code.contains("            Self::Enemy(object) => object.update(),\n"));
+        assert!(code.contains("    /// Avanza un paso.\n    pub fn update(&mut self) {\n        match self {\n"));
+        assert!(!code.contains("trait"));
+        assert!(code.contains("let mut e: SpriteKind = SpriteKind::Enemy(Enemy::new(1, 3));"));
+        assert!(code.contains("e = SpriteKind::Sprite(Sprite::new(0));"));
+    }
+
+    #[test]
+    fn test_list_of_hierarchy() {
+        let script = "import sdl2.ext\nROJO = (255, 0, 0)\nclass Sprite:\n    def __init__(self, x):\n        self.x = x\n    def update(self):\n        self.x += 1\n    def draw(self, renderer):\n        renderer.fill((self.x, 0, 10, 10), ROJO)\nclass Enemy(Sprite):\n    def __init__(self, x):\n        super().__init__(x)\n    def draw(self, renderer):\n        super().draw(renderer)\n        renderer.draw_rect((self.x, 0, 12, 12))\nwindow = sdl2.ext.Window(\"J\", size=(640, 480))\nrenderer = sdl2.ext.Renderer(window)\njugador = Sprite(1)\nenemigo = Enemy(2)\nentidades = [jugador, enemigo]\nwhile running:\n    for e in entidades:\n        e.update()\n        e.draw(renderer)\n";
+        let state = extract_state(tokenize(script));
+        let generated = generate_rust(&state, &Config::default(), "juego.py");
+        let code = generated.code;
+        assert!(code.contains("use sdl2::render::Canvas;\nuse sdl2::video::Window;\n"));
+        assert!(code.contains("    pub fn draw(&self, renderer: &mut Canvas<Window>) -> Result<(), String> {\n        renderer.set_draw_color(Color::RGB(255, 0, 0));\n        renderer.fill_rect(Rect::new(self.x, 0, 10, 10))?;\n        Ok(())\n    }\n"));
+        assert!(code.contains("    fn draw(&self, renderer: &mut Canvas<Window>) -> Result<(), String> {\n        self.sprite.draw(renderer)?;\n        renderer.draw_rect(Rect::new(self.sprite.x, 0, 12, 12))?;\n        Ok(())\n    }\n"));
+        assert!(code.contains("let jugador = Sprite::new(1);"));
+        assert!(code.contains("let mut entidades: Vec<Box<dyn SpriteTrait>> = vec![Box::new(jugador), Box::new(enemigo)];"));
+        assert!(code.contains("        for e in entidades.iter_mut() {\n            e.update();\n            e.draw(&mut canvas)?;\n        }\n"));
+        assert!(generated.warnings.is_empty());
+
+        let mut config = Config::default();
+        config.set("classes.inheritance", "enum").unwrap();
+        let code = generate_rust(&state, &config, "juego.py").code;
+        assert!(code.contains("    pub fn draw(&self, renderer: &mut Canvas<Window>) -> Result<(), String> {\n        match self {\n"));
+        assert!(code.contains("    let mut entidades: Vec<SpriteKind> =\n        vec![SpriteKind::Sprite(jugador), SpriteKind::Enemy(enemigo)];\n"));
+        assert!(code.contains("        for e in entidades.iter_mut() {\n            e.update();\n            e.draw(&mut canvas)?;\n        }\n"));
+
+        // Un objeto que también se usa fuera de la lista: ni la lista ni el bucle se traducen
+        let state = extract_state(tokenize(&format!("{}    jugador.x = 0\n", script)));
+        let generated = generate_rust(&state, &Config::default(), "juego.py");
+        assert!(generated.code.contains("// sin traducción (línea 22)"));
+        let warnings: Vec<(Option<usize>, &str)> = generated.warnings.iter().map(|warning| (warning.python_line, warning.message.as_str())).collect();
+        assert!(warnings.contains(&(Some(20), "la lista `entidades` no se traduce: `jugador` se usa también fuera de ella")));
+        assert!(warnings.contains(&(Some(22), "sentencia sin traducir: se omite")));
+
+        // El elemento de una lista es de la jerarquía; `desconocido` no se sabe qué es
+        let classes = script.split("jugador = ").next().unwrap_or_default();
+        let script = format!("{}enemigos = [Enemy(1), Enemy(2), Enemy(3), Sprite(4), Enemy(5)]\nwhile running:\n    if isinstance(enemigos[0], Enemy):\n        running = False\n    if isinstance(desconocido, Enemy):\n        running = False\n", classes);
+        let generated = generate_rust(&extract_state(tokenize(&script)), &Config::default(), "juego.py");
+        let code = generated.code;
+        assert!(code.contains("    let mut enemigos: Vec<Box<dyn SpriteTrait>> = vec![\n        Box::new(Enemy::new(1)),\n        Box::new(Enemy::new(2)),\n"));
+        assert!(code.contains("        if enemigos[0 as usize].is_instance_of(\"Enemy\") {\n            running = false;\n        }\n        // sin traducción (línea 22)\n"));
+        let warnings: Vec<(Option<usize>, &str)> = generated.warnings.iter().map(|warning| (warning.python_line, warning.message.as_str())).collect();
+        assert_eq!(warnings, vec![(Some(22), "no se pudo traducir la llamada a `isinstance`: se omite su sentencia")]);
+    }
+}
@@ -0,0 +1,305 @@
+// Rectángulos y puntos de SDL (`sdl2.SDL_Rect`, `sdl2.SDL_Point`...) como
+// valores de `sdl2::rect`, con sus campos (`rect.x += 1`), las funciones de
+// intersección y los recortes de líneas de `sdl2.ext`. sdl2 0.35 no tiene
+// `FRect`: `SDL_FRect` y `SDL_FPoint` usan `Rect` y `Point` con enteros.
+use std::collections::{HashMap, HashSet};
+
+use crate::types::{Expr, State, Stmt, StmtKind};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Shape {
+    Rect,
+    Point,
+}
+
+// Constructores de SDL, la forma que crean y si en SDL es de `float`
+const CONSTRUCTORS: [(&str, Shape, bool); 4] = [
+    ("SDL_Rect", Shape::Rect, false),
+    ("SDL_Point", Shape::Point, false),
+    ("SDL_FRect", Shape::Rect, true),
+    ("SDL_FPoint", Shape::Point, true),
+];
+
+// Campos de PySDL2, su método de lectura y de escritura en sdl2 y su tipo
+const RECT_FIELDS: [(&str, &str, &str, &str); 4] = [
+    ("x", "x", "set_x", "i32"),
+    ("y", "y", "set_y", "i32"),
+    ("w", "width", "set_width", "u32"),
+    ("h", "height", "set_height", "u32"),
+];
+
+// Funciones de `sdl2.ext` que recortan la línea (x1, y1)-(x2, y2) al rectángulo
+// (left, top, right, bottom): `Rect::intersect_line`, que devuelve un `Option`
+const CLIP_FUNCTIONS: [&str; 3] = ["cohensutherland", "liangbarsky", "clipline"];
+
+impl Shape {
+    fn rust_type(self) -> &'static str {
+        match self {
+            Shape::Rect => "Rect",
+            Shape::Point => "Point",
+        }
+    }
+
+    fn fields(self) -> &'static [(&'static str, &'static str, &'static str, &'static str)] {
+        match self {
+            Shape::Rect => &RECT_FIELDS,
+            Shape::Point => &RECT_FIELDS[..2],
+        }
+    }
+}
+
+fn constructor(function: &str) -> Option<(Shape, bool)> {
+    let name = function_name(function);
+    CONSTRUCTORS.iter().find(|(n, _, _)| *n == name).map(|(_, shape, float)| (*shape, *float))
+}
+
+fn function_name(function: &str) -> &str {
+    function.rsplit('.').next().unwrap_or(function)
+}
+
+fn is_clip(function: &str) -> bool {
+    function.starts_with("sdl2.ext.") && CLIP_FUNCTIONS.contains(&function_name(function))
+}
+
+// Forma que crea `value`, si es un constructor de SDL.
+fn value_shape(value: &Expr) -> Option<Shape> {
+    match value {
+        Expr::Call { function, .. } => constructor(function).map(|(shape, _)| shape),
+        _ => None,
+    }
+}
+
+// Variables con un rectángulo o un punto.
+pub fn shapes(state: &State) -> HashMap<String, Shape> {
+    state.statements().into_iter()
+        .filter_map(|stmt| match &stmt.kind {
+            StmtKind::Assign { target, value } => value_shape(value).map(|shape| (target.clone(), shape)),
+            _ => None,
+        })
+        .collect()
+}
+
+// Variables asignadas con un rectángulo o un recorte en el nivel superior del script.
+pub fn setup_targets(state: &State) -> HashSet<String> {
+    state.setup.iter()
+        .filter_map(|stmt| match &stmt.kind {
+            StmtKind::Assign { target, value } if is_value(value) => Some(target.clone()),
+            _ => None,
+        })
+        .collect()
+}
+
+fn is_value(value: &Expr) -> bool {
+    matches!(value, Expr::Call { function, .. } if constructor(function).is_some() || is_clip(function))
+}
+
+// Rectángulos y puntos que cambian: se escribe en sus campos o reciben el
+// resultado de `SDL_IntersectRect`/`SDL_UnionRect`.
+pub fn mutated(state: &State) -> HashSet<String> {
+    state.statements().into_iter()
+        .filter_map(|stmt| match &stmt.kind {
+            StmtKind::Assign { target, .. } | StmtKind::AugAssign { target, .. } => {
+                target.split_once('.').map(|(object, _)| object.to_string())
+            }
+            StmtKind::Expr(Expr::Call { function, args }) if ["SDL_IntersectRect", "SDL_UnionRect"].contains(&function_name(function)) => {
+                match args.get(2) {
+                    Some(Expr::Name(result)) => Some(result.clone()),
+                    _ => None,
+                }
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+// Tipos de `sdl2::rect` que crean las sentencias del script.
+pub fn types_used(state: &State) -> Vec<&'static str> {
+    let functions = state.called_functions();
+    let mut types = Vec::new();
+    for (shape, name) in [(Shape::Point, "Point"), (Shape::Rect, "Rect")] {
+        let created = functions.iter().any(|f| constructor(f).is_some_and(|(s, _)| s == shape) || is_clip(f));
+        if created {
+            types.push(name);
+        }
+    }
+    types
+}
+
+// Líneas con un `SDL_FRect`/`SDL_FPoint`, que pierden los decimales.
+pub fn float_shapes(state: &State) -> Vec<(usize, String)> {
+    let mut found = Vec::new();
+    for stmt in state.statements() {
+        for function in stmt.called_functions() {
+            if constructor(function).is_some_and(|(_, float)| float) {
+                found.push((stmt.line, function_name(function).to_string()));
+            }
+        }
+    }
+    found
+}
+
+// `rect.w` sobre un rectángulo del script: `rect.width()`, con el tipo de los
+// enteros de Python si el de sdl2 es otro.
+pub fn field_read(path: &str, shapes: &HashMap<String, Shape>, int_type: &str) -> Option<Expr> {
+    let (object, attribute) = path.split_once('.')?;
+    let shape = shapes.get(object)?;
+    let (_, getter, _, ty) = shape.fields().iter().find(|(field, _, _, _)| *field == attribute)?;
+    let read = Expr::MethodCall { receiver: Box::new(Expr::Name(object.to_string())), method: getter.to_string(), args: Vec::new() };
+    Some(if *ty == int_type { read } else { Expr::Cast { value: Box::new(read), ty: int_type.to_string() } })
+}
+
+// Constructores, funciones de intersección y recortes dentro de una expresión.
+// `expr` traduce los argumentos y `expr_as` los convierte al tipo indicado.
+pub fn call_code(
+    function: &str,
+    args: &[Expr],
+    expr: &dyn Fn(&Expr) -> String,
+    expr_as: &dyn Fn(&Expr, &str) -> String,
+) -> Option<String> {
+    if let Some((shape, _)) = constructor(function) {
+        let fields = shape.fields();
+        let components: Vec<String> = match args.len() {
+            0 => fields.iter().map(|_| "0".to_string()).collect(),
+            n if n == fields.len() => args.iter().zip(fields).map(|(arg, (_, _, _, ty))| expr_as(arg, ty)).collect(),
+            _ => return None,
+        };
+        return Some(format!("{}::new({})", shape.rust_type(), components.join(", ")));
+    }
+    if is_clip(function) {
+        let [left, top, right, bottom, x1, y1, x2, y2] = args else {
+            return None;
+        };
+        let span = |end: &Expr, start: &Expr| match start {
+            Expr::Int(0) => end.clone(),
+            _ => Expr::BinOp { op: "-".to_string(), left: Box::new(end.clone()), right: Box::new(start.clone()) },
+        };
+        let rect = format!(
+            "Rect::new({}, {}, {}, {})",
+            expr_as(left, "i32"),
+            expr_as(top, "i32"),
+            expr_as(&span(right, left), "u32"),
+            expr_as(&span(bottom, top), "u32")
+        );
+        let point = |x: &Expr, y: &Expr| format!("Point::new({}, {})", expr_as(x, "i32"), expr_as(y, "i32"));
+        return Some(format!("{}.intersect_line({}, {})", rect, point(x1, y1), point(x2, y2)));
+    }
+    match (function_name(function), args) {
+        ("SDL_HasIntersection", [a, b]) => Some(format!("{}.has_intersection({})", expr(a), expr(b))),
+        ("SDL_PointInRect", [point, rect]) => Some(format!("{}.contains_point({})", expr(rect), expr(point))),
+        ("SDL_RectEquals", [a, b]) => Some(format!("{} == {}", expr(a), expr(b))),
+        // En una condición: si hay intersección y, de paso, la guarda en `result`
+        ("SDL_IntersectRect", [a, b, result]) => {
+            Some(format!("{}.intersection({}).map(|r| {} = r).is_some()", expr(a), expr(b), expr(result)))
+        }
+        _ => None,
+    }
+}
+
+// Código de una sentencia con rectángulos o puntos: su creación, la escritura
+// de un campo y `SDL_IntersectRect`/`SDL_UnionRect` sueltas. Las variables de
+// `declared` se reasignan y las de `mutable` se declaran `let mut`.
+pub fn stmt_code(
+    stmt: &Stmt,
+    shapes: &HashMap<String, Shape>,
+    declared: &HashSet<String>,
+    mutable: &HashSet<String>,
+    expr: &dyn Fn(&Expr) -> String,
+    expr_as: &dyn Fn(&Expr, &str) -> String,
+) -> Option<String> {
+    match &stmt.kind {
+        StmtKind::Assign { target, value: Expr::Call { function, args } } if constructor(function).is_some() || is_clip(function) => {
+            let value = call_code(function, args, expr, expr_as)?;
+            let keyword = match () {
+                _ if declared.contains(target) => "",
+                _ if mutable.contains(target) => "let mut ",
+                _ => "let ",
+            };
+            Some(format!("{}{} = {};", keyword, expr(&Expr::Name(target.clone())), value))
+        }
+        StmtKind::Assign { target, value } => field_write(target, value.clone(), shapes, expr, expr_as),
+        StmtKind::AugAssign { target, op, value } => {
+            let current = Expr::Attr(target.clone());
+            let value = Expr::BinOp { op: op.clone(), left: Box::new(current), right: Box::new(value.clone()) };
+            field_write(target, value, shapes, expr, expr_as)
+        }
+        StmtKind::Expr(Expr::Call { function, args }) => match (function_name(function), args.as_slice()) {
+            ("SDL_UnionRect", [a, b, result]) => Some(format!("{} = {}.union({});", expr(result), expr(a), expr(b))),
+            ("SDL_IntersectRect", [a, b, result]) => Some(format!(
+                "if let Some(r) = {}.intersection({}) {{\n    {} = r;\n}}",
+                expr(a),
+                expr(b),
+                expr(result)
+            )),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+// `rect.x = valor` -> `rect.set_x(valor)`. `Point` no tiene setters: se crea
+// otro con el campo cambiado.
+fn field_write(
+    target: &str,
+    value: Expr,
+    shapes: &HashMap<String, Shape>,
+    expr: &dyn Fn(&Expr) -> String,
+    expr_as: &dyn Fn(&Expr, &str) -> String,
+) -> Option<String> {
+    let (object, attribute) = target.split_once('.')?;
+    let shape = shapes.get(object)?;
+    let (_, _, setter, ty) = shape.fields().iter().find(|(field, _, _, _)| *field == attribute)?;
+    let name = expr(&Expr::Name(object.to_string()));
+    match shape {
+        Shape::Rect => Some(format!("{}.{}({});", name, setter, expr_as(&value, ty))),
+        Shape::Point => {
+            let components: Vec<String> = shape.fields().iter()
+                .map(|(field, getter, _, ty)| match *field == attribute {
+                    true => expr_as(&value, ty),
+                    false => format!("{}.{}()", name, getter),
+                })
+                .collect();
+            Some(format!("{} = Point::new({});", name, components.join(", ")))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::extract_state;
+    use crate::tokenizer::tokenize;
+
+    #[test]
+    fn test_rect_statements() {
+        let code = "jugador = sdl2.SDL_Rect(10, 20, 30, 40)\nmuro = sdl2.SDL_Rect()\nwhile running:\n    jugador.x += 5\n    jugador.w = 8\n    sdl2.SDL_UnionRect(jugador, muro, muro)\n";
+        let state = extract_state(tokenize(code));
+        let shapes = shapes(&state);
+        let expr = |e: &Expr| test_expr(e, &shapes);
+        let expr_as = |e: &Expr, _: &str| test_expr(e, &shapes);
+        let code: Vec<String> = state.statements().into_iter()
+            .filter_map(|stmt| stmt_code(stmt, &shapes, &HashSet::new(), &mutated(&state), &expr, &expr_as))
+            .collect();
+
+        assert_eq!(code, vec![
+            "let mut jugador = Rect::new(10, 20, 30, 40);",
+            "let mut muro = Rect::new(0, 0, 0, 0);",
+            "jugador.set_x(jugador.x() + 5);",
+            "jugador.set_width(8);",
+            "muro = jugador.union(muro);",
+        ]);
+        assert_eq!(types_used(&state), vec!["Rect"]);
+    }
+
+    fn test_expr(expr: &Expr, shapes: &HashMap<String, Shape>) -> String {
+        match expr {
+            Expr::Int(n) => n.to_string(),
+            Expr::Name(name) => name.clone(),
+            Expr::Attr(path) => match field_read(path, shapes, "i32") {
+                Some(Expr::MethodCall { receiver, method, .. }) => format!("{}.{}()", test_expr(&receiver, shapes), method),
+                _ => path.clone(),
+            },
+            Expr::BinOp { op, left, right } => format!("{} {} {}", test_expr(left, shapes), op, test_expr(right, shapes)),
+            _ => String::new(),
+        }
+    }
+}
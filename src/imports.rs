@@ -0,0 +1,106 @@
+// Nombres que importa el script y la ruta completa a la que se refieren:
+// `import sdl2.ext as ext` hace que `ext.Window` sea `sdl2.ext.Window`, y
+// `from sdl2.ext import Renderer` que `Renderer` sea `sdl2.ext.Renderer`. Con
+// `from módulo import *` se resuelven los nombres que el módulo exporta.
+use std::collections::HashMap;
+
+#[derive(Default)]
+pub(crate) struct ImportTable {
+    // Nombre local y ruta completa
+    aliases: HashMap<String, String>,
+    // Módulos de `from módulo import *`, en orden
+    star_modules: Vec<String>,
+}
+
+impl ImportTable {
+    // `import a.b` enlaza `a`; `import a.b as c` y `from a import b as c` enlazan `c`.
+    pub(crate) fn bind(&mut self, name: &str, path: &str) {
+        self.aliases.insert(name.to_string(), path.to_string());
+    }
+
+    pub(crate) fn bind_star(&mut self, module: &str) {
+        if !self.star_modules.iter().any(|m| m == module) {
+            self.star_modules.push(module.to_string());
+        }
+    }
+
+    // Una asignación o un `def` del script vuelve a enlazar el nombre.
+    pub(crate) fn shadow(&mut self, name: &str) {
+        self.aliases.insert(name.to_string(), name.to_string());
+    }
+
+    // Ruta completa de `path` si empieza por un nombre importado; None si ya lo
+    // es o no viene de un import. `exports` dice si un módulo importado con `*`
+    // tiene el nombre.
+    pub(crate) fn resolve(&self, path: &str, exports: impl Fn(&str, &str) -> bool) -> Option<String> {
+        let (first, rest) = match path.split_once('.') {
+            Some((first, rest)) => (first, Some(rest)),
+            None => (path, None),
+        };
+        let origin = match self.aliases.get(first) {
+            Some(origin) => origin.clone(),
+            // El último `import *` tapa a los anteriores
+            None => {
+                let module = self.star_modules.iter().rev().find(|module| exports(module, first))?;
+                format!("{}.{}", module, first)
+            }
+        };
+        let resolved = match rest {
+            Some(rest) => format!("{}.{}", origin, rest),
+            None => origin,
+        };
+        if resolved == path { None } else { Some(resolved) }
+    }
+}
+
+// Ruta absoluta de un import relativo desde el paquete `package`: `.nivel2` en
+// `levels` es `levels.nivel2` y cada punto de más sube un nivel.
+pub(crate) fn absolute(module: &str, package: &str) -> String {
+    let name = module.trim_start_matches('.');
+    let dots = module.len() - name.len();
+    if dots == 0 {
+        return module.to_string();
+    }
+    let mut base: Vec<&str> = package.split('.').filter(|segment| !segment.is_empty()).collect();
+    base.truncate(base.len().saturating_sub(dots - 1));
+    base.extend(name.split('.').filter(|segment| !segment.is_empty()));
+    base.join(".")
+}
+
+// `módulo.nombre`, sin punto si el módulo es la raíz del proyecto (`from . import x`).
+pub(crate) fn member(module: &str, name: &str) -> String {
+    if module.is_empty() { name.to_string() } else { format!("{}.{}", module, name) }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_aliases_and_star_imports() {
+        let mut imports = ImportTable::default();
+        imports.bind("sdl2", "sdl2");
+        imports.bind("ext", "sdl2.ext");
+        imports.bind("Renderer", "sdl2.ext.Renderer");
+        imports.bind_star("sdl2");
+        let exports = |module: &str, name: &str| module == "sdl2" && name.starts_with("SDL_");
+
+        assert_eq!(imports.resolve("ext.Window", exports), Some("sdl2.ext.Window".to_string()));
+        assert_eq!(imports.resolve("Renderer", exports), Some("sdl2.ext.Renderer".to_string()));
+        assert_eq!(imports.resolve("SDL_QUIT", exports), Some("sdl2.SDL_QUIT".to_string()));
+        assert_eq!(imports.resolve("sdl2.SDL_QUIT", exports), None);
+        assert_eq!(imports.resolve("event.type", exports), None);
+
+        imports.shadow("ext");
+        assert_eq!(imports.resolve("ext.Window", exports), None);
+    }
+
+    #[test]
+    fn test_relative_imports() {
+        assert_eq!(absolute(".nivel2", "levels"), "levels.nivel2");
+        assert_eq!(absolute(".", "levels"), "levels");
+        assert_eq!(absolute("..comun", "levels.bosque"), "levels.comun");
+        assert_eq!(absolute("player", "levels"), "player");
+        assert_eq!(member(&absolute(".", ""), "player"), "player");
+    }
+}
@@ -0,0 +1,546 @@
+// Constantes de teclado de PySDL2 (las mismas que en C) y su equivalente en el
+// crate sdl2 0.35: `SDLK_*` -> `Keycode`, `SDL_SCANCODE_*` -> `Scancode` y
+// `KMOD_*` -> `Mod`. Las tablas siguen el orden de `keycode.rs` y `scancode.rs`.
+
+const KEYCODES: [(&str, &str); 235] = [
+    ("SDLK_BACKSPACE", "Backspace"),
+    ("SDLK_TAB", "Tab"),
+    ("SDLK_RETURN", "Return"),
+    ("SDLK_ESCAPE", "Escape"),
+    ("SDLK_SPACE", "Space"),
+    ("SDLK_EXCLAIM", "Exclaim"),
+    ("SDLK_QUOTEDBL", "Quotedbl"),
+    ("SDLK_HASH", "Hash"),
+    ("SDLK_DOLLAR", "Dollar"),
+    ("SDLK_PERCENT", "Percent"),
+    ("SDLK_AMPERSAND", "Ampersand"),
+    ("SDLK_QUOTE", "Quote"),
+    ("SDLK_LEFTPAREN", "LeftParen"),
+    ("SDLK_RIGHTPAREN", "RightParen"),
+    ("SDLK_ASTERISK", "Asterisk"),
+    ("SDLK_PLUS", "Plus"),
+    ("SDLK_COMMA", "Comma"),
+    ("SDLK_MINUS", "Minus"),
+    ("SDLK_PERIOD", "Period"),
+    ("SDLK_SLASH", "Slash"),
+    ("SDLK_0", "Num0"),
+    ("SDLK_1", "Num1"),
+    ("SDLK_2", "Num2"),
+    ("SDLK_3", "Num3"),
+    ("SDLK_4", "Num4"),
+    ("SDLK_5", "Num5"),
+    ("SDLK_6", "Num6"),
+    ("SDLK_7", "Num7"),
+    ("SDLK_8", "Num8"),
+    ("SDLK_9", "Num9"),
+    ("SDLK_COLON", "Colon"),
+    ("SDLK_SEMICOLON", "Semicolon"),
+    ("SDLK_LESS", "Less"),
+    ("SDLK_EQUALS", "Equals"),
+    ("SDLK_GREATER", "Greater"),
+    ("SDLK_QUESTION", "Question"),
+    ("SDLK_AT", "At"),
+    ("SDLK_LEFTBRACKET", "LeftBracket"),
+    ("SDLK_BACKSLASH", "Backslash"),
+    ("SDLK_RIGHTBRACKET", "RightBracket"),
+    ("SDLK_CARET", "Caret"),
+    ("SDLK_UNDERSCORE", "Underscore"),
+    ("SDLK_BACKQUOTE", "Backquote"),
+    ("SDLK_a", "A"),
+    ("SDLK_b", "B"),
+    ("SDLK_c", "C"),
+    ("SDLK_d", "D"),
+    ("SDLK_e", "E"),
+    ("SDLK_f", "F"),
+    ("SDLK_g", "G"),
+    ("SDLK_h", "H"),
+    ("SDLK_i", "I"),
+    ("SDLK_j", "J"),
+    ("SDLK_k", "K"),
+    ("SDLK_l", "L"),
+    ("SDLK_m", "M"),
+    ("SDLK_n", "N"),
+    ("SDLK_o", "O"),
+    ("SDLK_p", "P"),
+    ("SDLK_q", "Q"),
+    ("SDLK_r", "R"),
+    ("SDLK_s", "S"),
+    ("SDLK_t", "T"),
+    ("SDLK_u", "U"),
+    ("SDLK_v", "V"),
+    ("SDLK_w", "W"),
+    ("SDLK_x", "X"),
+    ("SDLK_y", "Y"),
+    ("SDLK_z", "Z"),
+    ("SDLK_DELETE", "Delete"),
+    ("SDLK_CAPSLOCK", "CapsLock"),
+    ("SDLK_F1", "F1"),
+    ("SDLK_F2", "F2"),
+    ("SDLK_F3", "F3"),
+    ("SDLK_F4", "F4"),
+    ("SDLK_F5", "F5"),
+    ("SDLK_F6", "F6"),
+    ("SDLK_F7", "F7"),
+    ("SDLK_F8", "F8"),
+    ("SDLK_F9", "F9"),
+    ("SDLK_F10", "F10"),
+    ("SDLK_F11", "F11"),
+    ("SDLK_F12", "F12"),
+    ("SDLK_PRINTSCREEN", "PrintScreen"),
+    ("SDLK_SCROLLLOCK", "ScrollLock"),
+    ("SDLK_PAUSE", "Pause"),
+    ("SDLK_INSERT", "Insert"),
+    ("SDLK_HOME", "Home"),
+    ("SDLK_PAGEUP", "PageUp"),
+    ("SDLK_END", "End"),
+    ("SDLK_PAGEDOWN", "PageDown"),
+    ("SDLK_RIGHT", "Right"),
+    ("SDLK_LEFT", "Left"),
+    ("SDLK_DOWN", "Down"),
+    ("SDLK_UP", "Up"),
+    ("SDLK_NUMLOCKCLEAR", "NumLockClear"),
+    ("SDLK_KP_DIVIDE", "KpDivide"),
+    ("SDLK_KP_MULTIPLY", "KpMultiply"),
+    ("SDLK_KP_MINUS", "KpMinus"),
+    ("SDLK_KP_PLUS", "KpPlus"),
+    ("SDLK_KP_ENTER", "KpEnter"),
+    ("SDLK_KP_1", "Kp1"),
+    ("SDLK_KP_2", "Kp2"),
+    ("SDLK_KP_3", "Kp3"),
+    ("SDLK_KP_4", "Kp4"),
+    ("SDLK_KP_5", "Kp5"),
+    ("SDLK_KP_6", "Kp6"),
+    ("SDLK_KP_7", "Kp7"),
+    ("SDLK_KP_8", "Kp8"),
+    ("SDLK_KP_9", "Kp9"),
+    ("SDLK_KP_0", "Kp0"),
+    ("SDLK_KP_PERIOD", "KpPeriod"),
+    ("SDLK_APPLICATION", "Application"),
+    ("SDLK_POWER", "Power"),
+    ("SDLK_KP_EQUALS", "KpEquals"),
+    ("SDLK_F13", "F13"),
+    ("SDLK_F14", "F14"),
+    ("SDLK_F15", "F15"),
+    ("SDLK_F16", "F16"),
+    ("SDLK_F17", "F17"),
+    ("SDLK_F18", "F18"),
+    ("SDLK_F19", "F19"),
+    ("SDLK_F20", "F20"),
+    ("SDLK_F21", "F21"),
+    ("SDLK_F22", "F22"),
+    ("SDLK_F23", "F23"),
+    ("SDLK_F24", "F24"),
+    ("SDLK_EXECUTE", "Execute"),
+    ("SDLK_HELP", "Help"),
+    ("SDLK_MENU", "Menu"),
+    ("SDLK_SELECT", "Select"),
+    ("SDLK_STOP", "Stop"),
+    ("SDLK_AGAIN", "Again"),
+    ("SDLK_UNDO", "Undo"),
+    ("SDLK_CUT", "Cut"),
+    ("SDLK_COPY", "Copy"),
+    ("SDLK_PASTE", "Paste"),
+    ("SDLK_FIND", "Find"),
+    ("SDLK_MUTE", "Mute"),
+    ("SDLK_VOLUMEUP", "VolumeUp"),
+    ("SDLK_VOLUMEDOWN", "VolumeDown"),
+    ("SDLK_KP_COMMA", "KpComma"),
+    ("SDLK_KP_EQUALSAS400", "KpEqualsAS400"),
+    ("SDLK_ALTERASE", "AltErase"),
+    ("SDLK_SYSREQ", "Sysreq"),
+    ("SDLK_CANCEL", "Cancel"),
+    ("SDLK_CLEAR", "Clear"),
+    ("SDLK_PRIOR", "Prior"),
+    ("SDLK_RETURN2", "Return2"),
+    ("SDLK_SEPARATOR", "Separator"),
+    ("SDLK_OUT", "Out"),
+    ("SDLK_OPER", "Oper"),
+    ("SDLK_CLEARAGAIN", "ClearAgain"),
+    ("SDLK_CRSEL", "CrSel"),
+    ("SDLK_EXSEL", "ExSel"),
+    ("SDLK_KP_00", "Kp00"),
+    ("SDLK_KP_000", "Kp000"),
+    ("SDLK_THOUSANDSSEPARATOR", "ThousandsSeparator"),
+    ("SDLK_DECIMALSEPARATOR", "DecimalSeparator"),
+    ("SDLK_CURRENCYUNIT", "CurrencyUnit"),
+    ("SDLK_CURRENCYSUBUNIT", "CurrencySubUnit"),
+    ("SDLK_KP_LEFTPAREN", "KpLeftParen"),
+    ("SDLK_KP_RIGHTPAREN", "KpRightParen"),
+    ("SDLK_KP_LEFTBRACE", "KpLeftBrace"),
+    ("SDLK_KP_RIGHTBRACE", "KpRightBrace"),
+    ("SDLK_KP_TAB", "KpTab"),
+    ("SDLK_KP_BACKSPACE", "KpBackspace"),
+    ("SDLK_KP_A", "KpA"),
+    ("SDLK_KP_B", "KpB"),
+    ("SDLK_KP_C", "KpC"),
+    ("SDLK_KP_D", "KpD"),
+    ("SDLK_KP_E", "KpE"),
+    ("SDLK_KP_F", "KpF"),
+    ("SDLK_KP_XOR", "KpXor"),
+    ("SDLK_KP_POWER", "KpPower"),
+    ("SDLK_KP_PERCENT", "KpPercent"),
+    ("SDLK_KP_LESS", "KpLess"),
+    ("SDLK_KP_GREATER", "KpGreater"),
+    ("SDLK_KP_AMPERSAND", "KpAmpersand"),
+    ("SDLK_KP_DBLAMPERSAND", "KpDblAmpersand"),
+    ("SDLK_KP_VERTICALBAR", "KpVerticalBar"),
+    ("SDLK_KP_DBLVERTICALBAR", "KpDblVerticalBar"),
+    ("SDLK_KP_COLON", "KpColon"),
+    ("SDLK_KP_HASH", "KpHash"),
+    ("SDLK_KP_SPACE", "KpSpace"),
+    ("SDLK_KP_AT", "KpAt"),
+    ("SDLK_KP_EXCLAM", "KpExclam"),
+    ("SDLK_KP_MEMSTORE", "KpMemStore"),
+    ("SDLK_KP_MEMRECALL", "KpMemRecall"),
+    ("SDLK_KP_MEMCLEAR", "KpMemClear"),
+    ("SDLK_KP_MEMADD", "KpMemAdd"),
+    ("SDLK_KP_MEMSUBTRACT", "KpMemSubtract"),
+    ("SDLK_KP_MEMMULTIPLY", "KpMemMultiply"),
+    ("SDLK_KP_MEMDIVIDE", "KpMemDivide"),
+    ("SDLK_KP_PLUSMINUS", "KpPlusMinus"),
+    ("SDLK_KP_CLEAR", "KpClear"),
+    ("SDLK_KP_CLEARENTRY", "KpClearEntry"),
+    ("SDLK_KP_BINARY", "KpBinary"),
+    ("SDLK_KP_OCTAL", "KpOctal"),
+    ("SDLK_KP_DECIMAL", "KpDecimal"),
+    ("SDLK_KP_HEXADECIMAL", "KpHexadecimal"),
+    ("SDLK_LCTRL", "LCtrl"),
+    ("SDLK_LSHIFT", "LShift"),
+    ("SDLK_LALT", "LAlt"),
+    ("SDLK_LGUI", "LGui"),
+    ("SDLK_RCTRL", "RCtrl"),
+    ("SDLK_RSHIFT", "RShift"),
+    ("SDLK_RALT", "RAlt"),
+    ("SDLK_RGUI", "RGui"),
+    ("SDLK_MODE", "Mode"),
+    ("SDLK_AUDIONEXT", "AudioNext"),
+    ("SDLK_AUDIOPREV", "AudioPrev"),
+    ("SDLK_AUDIOSTOP", "AudioStop"),
+    ("SDLK_AUDIOPLAY", "AudioPlay"),
+    ("SDLK_AUDIOMUTE", "AudioMute"),
+    ("SDLK_MEDIASELECT", "MediaSelect"),
+    ("SDLK_WWW", "Www"),
+    ("SDLK_MAIL", "Mail"),
+    ("SDLK_CALCULATOR", "Calculator"),
+    ("SDLK_COMPUTER", "Computer"),
+    ("SDLK_AC_SEARCH", "AcSearch"),
+    ("SDLK_AC_HOME", "AcHome"),
+    ("SDLK_AC_BACK", "AcBack"),
+    ("SDLK_AC_FORWARD", "AcForward"),
+    ("SDLK_AC_STOP", "AcStop"),
+    ("SDLK_AC_REFRESH", "AcRefresh"),
+    ("SDLK_AC_BOOKMARKS", "AcBookmarks"),
+    ("SDLK_BRIGHTNESSDOWN", "BrightnessDown"),
+    ("SDLK_BRIGHTNESSUP", "BrightnessUp"),
+    ("SDLK_DISPLAYSWITCH", "DisplaySwitch"),
+    ("SDLK_KBDILLUMTOGGLE", "KbdIllumToggle"),
+    ("SDLK_KBDILLUMDOWN", "KbdIllumDown"),
+    ("SDLK_KBDILLUMUP", "KbdIllumUp"),
+    ("SDLK_EJECT", "Eject"),
+    ("SDLK_SLEEP", "Sleep"),
+];
+
+const SCANCODES: [(&str, &str); 240] = [
+    ("SDL_SCANCODE_A", "A"),
+    ("SDL_SCANCODE_B", "B"),
+    ("SDL_SCANCODE_C", "C"),
+    ("SDL_SCANCODE_D", "D"),
+    ("SDL_SCANCODE_E", "E"),
+    ("SDL_SCANCODE_F", "F"),
+    ("SDL_SCANCODE_G", "G"),
+    ("SDL_SCANCODE_H", "H"),
+    ("SDL_SCANCODE_I", "I"),
+    ("SDL_SCANCODE_J", "J"),
+    ("SDL_SCANCODE_K", "K"),
+    ("SDL_SCANCODE_L", "L"),
+    ("SDL_SCANCODE_M", "M"),
+    ("SDL_SCANCODE_N", "N"),
+    ("SDL_SCANCODE_O", "O"),
+    ("SDL_SCANCODE_P", "P"),
+    ("SDL_SCANCODE_Q", "Q"),
+    ("SDL_SCANCODE_R", "R"),
+    ("SDL_SCANCODE_S", "S"),
+    ("SDL_SCANCODE_T", "T"),
+    ("SDL_SCANCODE_U", "U"),
+    ("SDL_SCANCODE_V", "V"),
+    ("SDL_SCANCODE_W", "W"),
+    ("SDL_SCANCODE_X", "X"),
+    ("SDL_SCANCODE_Y", "Y"),
+    ("SDL_SCANCODE_Z", "Z"),
+    ("SDL_SCANCODE_1", "Num1"),
+    ("SDL_SCANCODE_2", "Num2"),
+    ("SDL_SCANCODE_3", "Num3"),
+    ("SDL_SCANCODE_4", "Num4"),
+    ("SDL_SCANCODE_5", "Num5"),
+    ("SDL_SCANCODE_6", "Num6"),
+    ("SDL_SCANCODE_7", "Num7"),
+    ("SDL_SCANCODE_8", "Num8"),
+    ("SDL_SCANCODE_9", "Num9"),
+    ("SDL_SCANCODE_0", "Num0"),
+    ("SDL_SCANCODE_RETURN", "Return"),
+    ("SDL_SCANCODE_ESCAPE", "Escape"),
+    ("SDL_SCANCODE_BACKSPACE", "Backspace"),
+    ("SDL_SCANCODE_TAB", "Tab"),
+    ("SDL_SCANCODE_SPACE", "Space"),
+    ("SDL_SCANCODE_MINUS", "Minus"),
+    ("SDL_SCANCODE_EQUALS", "Equals"),
+    ("SDL_SCANCODE_LEFTBRACKET", "LeftBracket"),
+    ("SDL_SCANCODE_RIGHTBRACKET", "RightBracket"),
+    ("SDL_SCANCODE_BACKSLASH", "Backslash"),
+    ("SDL_SCANCODE_NONUSHASH", "NonUsHash"),
+    ("SDL_SCANCODE_SEMICOLON", "Semicolon"),
+    ("SDL_SCANCODE_APOSTROPHE", "Apostrophe"),
+    ("SDL_SCANCODE_GRAVE", "Grave"),
+    ("SDL_SCANCODE_COMMA", "Comma"),
+    ("SDL_SCANCODE_PERIOD", "Period"),
+    ("SDL_SCANCODE_SLASH", "Slash"),
+    ("SDL_SCANCODE_CAPSLOCK", "CapsLock"),
+    ("SDL_SCANCODE_F1", "F1"),
+    ("SDL_SCANCODE_F2", "F2"),
+    ("SDL_SCANCODE_F3", "F3"),
+    ("SDL_SCANCODE_F4", "F4"),
+    ("SDL_SCANCODE_F5", "F5"),
+    ("SDL_SCANCODE_F6", "F6"),
+    ("SDL_SCANCODE_F7", "F7"),
+    ("SDL_SCANCODE_F8", "F8"),
+    ("SDL_SCANCODE_F9", "F9"),
+    ("SDL_SCANCODE_F10", "F10"),
+    ("SDL_SCANCODE_F11", "F11"),
+    ("SDL_SCANCODE_F12", "F12"),
+    ("SDL_SCANCODE_PRINTSCREEN", "PrintScreen"),
+    ("SDL_SCANCODE_SCROLLLOCK", "ScrollLock"),
+    ("SDL_SCANCODE_PAUSE", "Pause"),
+    ("SDL_SCANCODE_INSERT", "Insert"),
+    ("SDL_SCANCODE_HOME", "Home"),
+    ("SDL_SCANCODE_PAGEUP", "PageUp"),
+    ("SDL_SCANCODE_DELETE", "Delete"),
+    ("SDL_SCANCODE_END", "End"),
+    ("SDL_SCANCODE_PAGEDOWN", "PageDown"),
+    ("SDL_SCANCODE_RIGHT", "Right"),
+    ("SDL_SCANCODE_LEFT", "Left"),
+    ("SDL_SCANCODE_DOWN", "Down"),
+    ("SDL_SCANCODE_UP", "Up"),
+    ("SDL_SCANCODE_NUMLOCKCLEAR", "NumLockClear"),
+    ("SDL_SCANCODE_KP_DIVIDE", "KpDivide"),
+    ("SDL_SCANCODE_KP_MULTIPLY", "KpMultiply"),
+    ("SDL_SCANCODE_KP_MINUS", "KpMinus"),
+    ("SDL_SCANCODE_KP_PLUS", "KpPlus"),
+    ("SDL_SCANCODE_KP_ENTER", "KpEnter"),
+    ("SDL_SCANCODE_KP_1", "Kp1"),
+    ("SDL_SCANCODE_KP_2", "Kp2"),
+    ("SDL_SCANCODE_KP_3", "Kp3"),
+    ("SDL_SCANCODE_KP_4", "Kp4"),
+    ("SDL_SCANCODE_KP_5", "Kp5"),
+    ("SDL_SCANCODE_KP_6", "Kp6"),
+    ("SDL_SCANCODE_KP_7", "Kp7"),
+    ("SDL_SCANCODE_KP_8", "Kp8"),
+    ("SDL_SCANCODE_KP_9", "Kp9"),
+    ("SDL_SCANCODE_KP_0", "Kp0"),
+    ("SDL_SCANCODE_KP_PERIOD", "KpPeriod"),
+    ("SDL_SCANCODE_NONUSBACKSLASH", "NonUsBackslash"),
+    ("SDL_SCANCODE_APPLICATION", "Application"),
+    ("SDL_SCANCODE_POWER", "Power"),
+    ("SDL_SCANCODE_KP_EQUALS", "KpEquals"),
+    ("SDL_SCANCODE_F13", "F13"),
+    ("SDL_SCANCODE_F14", "F14"),
+    ("SDL_SCANCODE_F15", "F15"),
+    ("SDL_SCANCODE_F16", "F16"),
+    ("SDL_SCANCODE_F17", "F17"),
+    ("SDL_SCANCODE_F18", "F18"),
+    ("SDL_SCANCODE_F19", "F19"),
+    ("SDL_SCANCODE_F20", "F20"),
+    ("SDL_SCANCODE_F21", "F21"),
+    ("SDL_SCANCODE_F22", "F22"),
+    ("SDL_SCANCODE_F23", "F23"),
+    ("SDL_SCANCODE_F24", "F24"),
+    ("SDL_SCANCODE_EXECUTE", "Execute"),
+    ("SDL_SCANCODE_HELP", "Help"),
+    ("SDL_SCANCODE_MENU", "Menu"),
+    ("SDL_SCANCODE_SELECT", "Select"),
+    ("SDL_SCANCODE_STOP", "Stop"),
+    ("SDL_SCANCODE_AGAIN", "Again"),
+    ("SDL_SCANCODE_UNDO", "Undo"),
+    ("SDL_SCANCODE_CUT", "Cut"),
+    ("SDL_SCANCODE_COPY", "Copy"),
+    ("SDL_SCANCODE_PASTE", "Paste"),
+    ("SDL_SCANCODE_FIND", "Find"),
+    ("SDL_SCANCODE_MUTE", "Mute"),
+    ("SDL_SCANCODE_VOLUMEUP", "VolumeUp"),
+    ("SDL_SCANCODE_VOLUMEDOWN", "VolumeDown"),
+    ("SDL_SCANCODE_KP_COMMA", "KpComma"),
+    ("SDL_SCANCODE_KP_EQUALSAS400", "KpEqualsAS400"),
+    ("SDL_SCANCODE_INTERNATIONAL1", "International1"),
+    ("SDL_SCANCODE_INTERNATIONAL2", "International2"),
+    ("SDL_SCANCODE_INTERNATIONAL3", "International3"),
+    ("SDL_SCANCODE_INTERNATIONAL4", "International4"),
+    ("SDL_SCANCODE_INTERNATIONAL5", "International5"),
+    ("SDL_SCANCODE_INTERNATIONAL6", "International6"),
+    ("SDL_SCANCODE_INTERNATIONAL7", "International7"),
+    ("SDL_SCANCODE_INTERNATIONAL8", "International8"),
+    ("SDL_SCANCODE_INTERNATIONAL9", "International9"),
+    ("SDL_SCANCODE_LANG1", "Lang1"),
+    ("SDL_SCANCODE_LANG2", "Lang2"),
+    ("SDL_SCANCODE_LANG3", "Lang3"),
+    ("SDL_SCANCODE_LANG4", "Lang4"),
+    ("SDL_SCANCODE_LANG5", "Lang5"),
+    ("SDL_SCANCODE_LANG6", "Lang6"),
+    ("SDL_SCANCODE_LANG7", "Lang7"),
+    ("SDL_SCANCODE_LANG8", "Lang8"),
+    ("SDL_SCANCODE_LANG9", "Lang9"),
+    ("SDL_SCANCODE_ALTERASE", "AltErase"),
+    ("SDL_SCANCODE_SYSREQ", "SysReq"),
+    ("SDL_SCANCODE_CANCEL", "Cancel"),
+    ("SDL_SCANCODE_CLEAR", "Clear"),
+    ("SDL_SCANCODE_PRIOR", "Prior"),
+    ("SDL_SCANCODE_RETURN2", "Return2"),
+    ("SDL_SCANCODE_SEPARATOR", "Separator"),
+    ("SDL_SCANCODE_OUT", "Out"),
+    ("SDL_SCANCODE_OPER", "Oper"),
+    ("SDL_SCANCODE_CLEARAGAIN", "ClearAgain"),
+    ("SDL_SCANCODE_CRSEL", "CrSel"),
+    ("SDL_SCANCODE_EXSEL", "ExSel"),
+    ("SDL_SCANCODE_KP_00", "Kp00"),
+    ("SDL_SCANCODE_KP_000", "Kp000"),
+    ("SDL_SCANCODE_THOUSANDSSEPARATOR", "ThousandsSeparator"),
+    ("SDL_SCANCODE_DECIMALSEPARATOR", "DecimalSeparator"),
+    ("SDL_SCANCODE_CURRENCYUNIT", "CurrencyUnit"),
+    ("SDL_SCANCODE_CURRENCYSUBUNIT", "CurrencySubUnit"),
+    ("SDL_SCANCODE_KP_LEFTPAREN", "KpLeftParen"),
+    ("SDL_SCANCODE_KP_RIGHTPAREN", "KpRightParen"),
+    ("SDL_SCANCODE_KP_LEFTBRACE", "KpLeftBrace"),
+    ("SDL_SCANCODE_KP_RIGHTBRACE", "KpRightBrace"),
+    ("SDL_SCANCODE_KP_TAB", "KpTab"),
+    ("SDL_SCANCODE_KP_BACKSPACE", "KpBackspace"),
+    ("SDL_SCANCODE_KP_A", "KpA"),
+    ("SDL_SCANCODE_KP_B", "KpB"),
+    ("SDL_SCANCODE_KP_C", "KpC"),
+    ("SDL_SCANCODE_KP_D", "KpD"),
+    ("SDL_SCANCODE_KP_E", "KpE"),
+    ("SDL_SCANCODE_KP_F", "KpF"),
+    ("SDL_SCANCODE_KP_XOR", "KpXor"),
+    ("SDL_SCANCODE_KP_POWER", "KpPower"),
+    ("SDL_SCANCODE_KP_PERCENT", "KpPercent"),
+    ("SDL_SCANCODE_KP_LESS", "KpLess"),
+    ("SDL_SCANCODE_KP_GREATER", "KpGreater"),
+    ("SDL_SCANCODE_KP_AMPERSAND", "KpAmpersand"),
+    ("SDL_SCANCODE_KP_DBLAMPERSAND", "KpDblAmpersand"),
+    ("SDL_SCANCODE_KP_VERTICALBAR", "KpVerticalBar"),
+    ("SDL_SCANCODE_KP_DBLVERTICALBAR", "KpDblVerticalBar"),
+    ("SDL_SCANCODE_KP_COLON", "KpColon"),
+    ("SDL_SCANCODE_KP_HASH", "KpHash"),
+    ("SDL_SCANCODE_KP_SPACE", "KpSpace"),
+    ("SDL_SCANCODE_KP_AT", "KpAt"),
+    ("SDL_SCANCODE_KP_EXCLAM", "KpExclam"),
+    ("SDL_SCANCODE_KP_MEMSTORE", "KpMemStore"),
+    ("SDL_SCANCODE_KP_MEMRECALL", "KpMemRecall"),
+    ("SDL_SCANCODE_KP_MEMCLEAR", "KpMemClear"),
+    ("SDL_SCANCODE_KP_MEMADD", "KpMemAdd"),
+    ("SDL_SCANCODE_KP_MEMSUBTRACT", "KpMemSubtract"),
+    ("SDL_SCANCODE_KP_MEMMULTIPLY", "KpMemMultiply"),
+    ("SDL_SCANCODE_KP_MEMDIVIDE", "KpMemDivide"),
+    ("SDL_SCANCODE_KP_PLUSMINUS", "KpPlusMinus"),
+    ("SDL_SCANCODE_KP_CLEAR", "KpClear"),
+    ("SDL_SCANCODE_KP_CLEARENTRY", "KpClearEntry"),
+    ("SDL_SCANCODE_KP_BINARY", "KpBinary"),
+    ("SDL_SCANCODE_KP_OCTAL", "KpOctal"),
+    ("SDL_SCANCODE_KP_DECIMAL", "KpDecimal"),
+    ("SDL_SCANCODE_KP_HEXADECIMAL", "KpHexadecimal"),
+    ("SDL_SCANCODE_LCTRL", "LCtrl"),
+    ("SDL_SCANCODE_LSHIFT", "LShift"),
+    ("SDL_SCANCODE_LALT", "LAlt"),
+    ("SDL_SCANCODE_LGUI", "LGui"),
+    ("SDL_SCANCODE_RCTRL", "RCtrl"),
+    ("SDL_SCANCODE_RSHIFT", "RShift"),
+    ("SDL_SCANCODE_RALT", "RAlt"),
+    ("SDL_SCANCODE_RGUI", "RGui"),
+    ("SDL_SCANCODE_MODE", "Mode"),
+    ("SDL_SCANCODE_AUDIONEXT", "AudioNext"),
+    ("SDL_SCANCODE_AUDIOPREV", "AudioPrev"),
+    ("SDL_SCANCODE_AUDIOSTOP", "AudioStop"),
+    ("SDL_SCANCODE_AUDIOPLAY", "AudioPlay"),
+    ("SDL_SCANCODE_AUDIOMUTE", "AudioMute"),
+    ("SDL_SCANCODE_MEDIASELECT", "MediaSelect"),
+    ("SDL_SCANCODE_WWW", "Www"),
+    ("SDL_SCANCODE_MAIL", "Mail"),
+    ("SDL_SCANCODE_CALCULATOR", "Calculator"),
+    ("SDL_SCANCODE_COMPUTER", "Computer"),
+    ("SDL_SCANCODE_AC_SEARCH", "AcSearch"),
+    ("SDL_SCANCODE_AC_HOME", "AcHome"),
+    ("SDL_SCANCODE_AC_BACK", "AcBack"),
+    ("SDL_SCANCODE_AC_FORWARD", "AcForward"),
+    ("SDL_SCANCODE_AC_STOP", "AcStop"),
+    ("SDL_SCANCODE_AC_REFRESH", "AcRefresh"),
+    ("SDL_SCANCODE_AC_BOOKMARKS", "AcBookmarks"),
+    ("SDL_SCANCODE_BRIGHTNESSDOWN", "BrightnessDown"),
+    ("SDL_SCANCODE_BRIGHTNESSUP", "BrightnessUp"),
+    ("SDL_SCANCODE_DISPLAYSWITCH", "DisplaySwitch"),
+    ("SDL_SCANCODE_KBDILLUMTOGGLE", "KbdIllumToggle"),
+    ("SDL_SCANCODE_KBDILLUMDOWN", "KbdIllumDown"),
+    ("SDL_SCANCODE_KBDILLUMUP", "KbdIllumUp"),
+    ("SDL_SCANCODE_EJECT", "Eject"),
+    ("SDL_SCANCODE_SLEEP", "Sleep"),
+    ("SDL_SCANCODE_APP1", "App1"),
+    ("SDL_SCANCODE_APP2", "App2"),
+];
+
+// Los modificadores sin lado (`KMOD_SHIFT`...) son la unión de los dos lados
+const MODIFIERS: [(&str, &[&str]); 17] = [
+    ("KMOD_NONE", &["NOMOD"]),
+    ("KMOD_LSHIFT", &["LSHIFTMOD"]),
+    ("KMOD_RSHIFT", &["RSHIFTMOD"]),
+    ("KMOD_LCTRL", &["LCTRLMOD"]),
+    ("KMOD_RCTRL", &["RCTRLMOD"]),
+    ("KMOD_LALT", &["LALTMOD"]),
+    ("KMOD_RALT", &["RALTMOD"]),
+    ("KMOD_LGUI", &["LGUIMOD"]),
+    ("KMOD_RGUI", &["RGUIMOD"]),
+    ("KMOD_NUM", &["NUMMOD"]),
+    ("KMOD_CAPS", &["CAPSMOD"]),
+    ("KMOD_MODE", &["MODEMOD"]),
+    ("KMOD_RESERVED", &["RESERVEDMOD"]),
+    ("KMOD_SHIFT", &["LSHIFTMOD", "RSHIFTMOD"]),
+    ("KMOD_CTRL", &["LCTRLMOD", "RCTRLMOD"]),
+    ("KMOD_ALT", &["LALTMOD", "RALTMOD"]),
+    ("KMOD_GUI", &["LGUIMOD", "RGUIMOD"]),
+];
+
+// Si `name` es una constante de teclado de PySDL2, tenga o no equivalente.
+pub fn is_keyboard_constant(name: &str) -> bool {
+    name.starts_with("SDLK_") || name.starts_with("SDL_SCANCODE_") || name.starts_with("KMOD_")
+}
+
+// `SDLK_LEFT` -> `Keycode::Left`, `SDL_SCANCODE_A` -> `Scancode::A`,
+// `KMOD_SHIFT` -> `Mod::LSHIFTMOD | Mod::RSHIFTMOD`; None si no hay equivalente.
+pub fn rust_constant(name: &str) -> Option<String> {
+    if name.starts_with("SDLK_") {
+        KEYCODES.iter().find(|(c, _)| *c == name).map(|(_, key)| format!("Keycode::{}", key))
+    } else if name.starts_with("SDL_SCANCODE_") {
+        SCANCODES.iter().find(|(c, _)| *c == name).map(|(_, scancode)| format!("Scancode::{}", scancode))
+    } else {
+        let (_, flags) = MODIFIERS.iter().find(|(c, _)| *c == name)?;
+        Some(flags.iter().map(|flag| format!("Mod::{}", flag)).collect::<Vec<_>>().join(" | "))
+    }
+}
+
+// Scancode de la misma tecla que un keycode: `SDLK_a` -> `SDL_SCANCODE_A`.
+pub fn scancode_of(keycode: &str) -> Option<&'static str> {
+    let (_, key) = KEYCODES.iter().find(|(c, _)| *c == keycode)?;
+    SCANCODES.iter().find(|(_, scancode)| scancode == key).map(|(c, _)| *c)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_keyboard_constants() {
+        assert_eq!(rust_constant("SDLK_a"), Some("Keycode::A".to_string()));
+        assert_eq!(rust_constant("SDLK_KP_ENTER"), Some("Keycode::KpEnter".to_string()));
+        assert_eq!(rust_constant("SDL_SCANCODE_LEFT"), Some("Scancode::Left".to_string()));
+        assert_eq!(rust_constant("KMOD_CTRL"), Some("Mod::LCTRLMOD | Mod::RCTRLMOD".to_string()));
+        assert!(is_keyboard_constant("SDLK_NOEXISTE"));
+        assert_eq!(rust_constant("SDLK_NOEXISTE"), None);
+        assert_eq!(scancode_of("SDLK_a"), Some("SDL_SCANCODE_A"));
+        assert_eq!(scancode_of("SDLK_LEFT"), Some("SDL_SCANCODE_LEFT"));
+    }
+}
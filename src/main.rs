@@ -2,55 +2,157 @@
 pub mod types;
 pub mod parser;
 pub mod generator;
+pub mod config;
+pub mod sourcemap;
+pub mod diagnostics;
+pub mod writer;
+mod audio;
+mod capi;
+mod classes;
+mod cli;
+mod geometry;
+mod imports;
+mod keys;
+mod mappings;
+mod project;
+mod pygame;
+mod textures;
+mod timing;
 mod tokenizer;
 
 use std::env;
 use std::fs;
 use std::io::Write;
 use std::path::Path;
-use std::process::Command;
+use std::process::{Command, Stdio};
+
+use cli::Cli;
+use config::Config;
+use project::Project;
 
 fn main() {
     let args: Vec<String> = env::args().collect();
-    if args.len() != 2 {
-        eprintln!("Uso: compiler.exe <archivo.py>");
-        return;
+    let cli = match cli::parse_args(&args) {
+        Ok(cli) => cli,
+        Err(e) => {
+            eprintln!("{}", e);
+            return;
+        }
+    };
+    let config = match load_config(&cli) {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            return;
+        }
+    };
+
+    match &cli.command {
+        cli::Command::ShowConfig(_) => print!("{}", config.to_toml()),
+        cli::Command::Compile(input_path) => match compile_python(input_path, &config) {
+            Ok(output_path) => println!("{} {}", config.msg("Éxito! El ejecutable está en:", "Success! The executable is at:"), output_path),
+            Err(e) => eprintln!("Error: {}", e),
+        },
+    }
+}
+
+// Configuración efectiva: valores por defecto, luego py2rust.toml y por último las opciones de la línea de comandos.
+fn load_config(cli: &Cli) -> Result<Config, String> {
+    let file = match &cli.config_path {
+        Some(path) => Some(Path::new(path).to_path_buf()),
+        None => {
+            let start = match &cli.command {
+                cli::Command::Compile(input) | cli::Command::ShowConfig(Some(input)) => Path::new(input).to_path_buf(),
+                cli::Command::ShowConfig(None) => env::current_dir()
+                    .map_err(|e| format!("Error al obtener el directorio actual: {}", e))?,
+            };
+            Config::discover(&start)
+        }
+    };
+
+    let mut config = match file {
+        Some(path) => Config::load(&path)?,
+        None => Config::default(),
+    };
+    for (key, value) in &cli.overrides {
+        config.set(key, value)?;
     }
-    let input_path = &args[1];
-    match compile_python(input_path) {
-        Ok(output_path) => println!("Éxito! El ejecutable está en: {}", output_path),
-        Err(e) => eprintln!("Error: {}", e),
+    Ok(config)
+}
+
+// El proyecto se genera en otro directorio, así que el vendor_dir debe ser absoluto.
+fn absolute_paths(config: &Config) -> Result<Config, String> {
+    let mut config = config.clone();
+    if let Some(vendor_dir) = &config.vendor_dir {
+        let absolute = fs::canonicalize(vendor_dir)
+            .map_err(|e| format!("Directorio de vendor no válido {}: {}", vendor_dir.display(), e))?;
+        config.vendor_dir = Some(absolute);
     }
+    Ok(config)
 }
 
-fn compile_python(input_path: &str) -> Result<String, String> {
-    let python_code = fs::read_to_string(input_path)
-        .map_err(|e| format!("Error al leer el archivo Python: {}", e))?;
+fn compile_python(input_path: &str, config: &Config) -> Result<String, String> {
+    let project = Project::load(Path::new(input_path))?;
+    for cycle in &project.cycles {
+        let Some(module) = project.modules.iter().find(|m| m.name == cycle.closing_module()) else {
+            continue;
+        };
+        let message = config.msg("importación circular: ", "circular import: ");
+        let warning = diagnostics::Diagnostic::warning(format!("{}{}", message, cycle.cycle.join(" -> ")), cycle.line);
+        eprint!("{}", diagnostics::render(&warning, &module.python_file(), &module.source, config));
+    }
+
+    // Cada módulo del proyecto es un archivo Rust; el script de entrada es main.rs
+    let mut generated_files = Vec::new();
+    for (index, module) in project.modules.iter().enumerate() {
+        let python_file = module.python_file();
+        let mut generated = if index == 0 {
+            generator::generate_rust(&module.state, config, &python_file)
+        } else {
+            generator::generate_module(&module.state, config, &python_file, &project.crate_uses(index))
+        };
+        for warning in &generated.warnings {
+            eprint!("{}", diagnostics::render(warning, &python_file, &module.source, config));
+        }
 
-    let tokens = tokenizer::tokenize(&python_code);
-    let state = parser::extract_state(tokens);
-    let rust_code = generator::generate_rust(&state);
+        // El generador ya emite estilo rustfmt; con format.rustfmt se pasa además por rustfmt
+        if config.rustfmt {
+            match writer::run_rustfmt(&generated.code) {
+                Some(formatted) if formatted != generated.code => {
+                    eprintln!("{}", config.msg(
+                        "Aviso: rustfmt modificó el código generado; el mapa de fuentes puede no coincidir",
+                        "Warning: rustfmt changed the generated code; the source map may not match",
+                    ));
+                    generated.code = formatted;
+                }
+                Some(_) => {}
+                None => eprintln!("{}", config.msg(
+                    "Aviso: no se pudo ejecutar rustfmt; se usa el código sin formatear",
+                    "Warning: could not run rustfmt; using the unformatted code",
+                )),
+            }
+        }
+        generated_files.push((module.rust_path(index == 0), generated));
+    }
+    let state = &project.modules[0].state;
 
-    // Obtener el nombre base del archivo
-    let output_path = Path::new(input_path)
+    // Obtener el nombre base del archivo, o del directorio del proyecto (también `.`)
+    let input = Path::new(input_path);
+    let named = if input.is_dir() { input.canonicalize().unwrap_or(input.to_path_buf()) } else { input.to_path_buf() };
+    let package_name = named
         .file_stem()
         .ok_or("Error al extraer el nombre del archivo base")?
         .to_string_lossy()
         .into_owned();
+    // Un directorio no puede ser a la vez el proyecto Python y el de Rust
+    let output_path = if input.is_dir() { format!("{}_rust", package_name) } else { package_name.clone() };
 
     // Crear el directorio del proyecto
     fs::create_dir_all(&output_path)
         .map_err(|e| format!("Error al crear el directorio del proyecto: {}", e))?;
 
     // Crear el Cargo.toml
-    let cargo_toml = format!(r#"[package]
-name = "{}"
-version = "0.1.0"
-edition = "2021"
-
-[dependencies]
-sdl2 = {{ version = "0.35", features = ["bundled"] }}
-"#, output_path);
+    let cargo_toml = generator::generate_cargo_toml(&package_name, state, config);
 
     let cargo_path = Path::new(&output_path).join("Cargo.toml");
     let mut cargo_file = fs::File::create(&cargo_path)
@@ -58,42 +160,111 @@ sdl2 = {{ version = "0.35", features = ["bundled"] }}
     cargo_file.write_all(cargo_toml.as_bytes())
         .map_err(|e| format!("Error al escribir Cargo.toml: {}", e))?;
 
-    // Crear el directorio src y el archivo main.rs
+    // Fuentes de dependencias para compilaciones sin red
+    if let Some(cargo_config) = generator::generate_cargo_config(&absolute_paths(config)?) {
+        let dot_cargo = Path::new(&output_path).join(".cargo");
+        fs::create_dir_all(&dot_cargo)
+            .map_err(|e| format!("Error al crear el directorio .cargo: {}", e))?;
+        fs::write(dot_cargo.join("config.toml"), cargo_config)
+            .map_err(|e| format!("Error al escribir .cargo/config.toml: {}", e))?;
+    }
+
+    // Crear el directorio src con main.rs y un archivo por módulo, cada uno con su mapa de fuentes
     let src_dir = Path::new(&output_path).join("src");
-    fs::create_dir_all(&src_dir)
-        .map_err(|e| format!("Error al crear el directorio src: {}", e))?;
-
-    let rust_path = src_dir.join("main.rs");
-    let mut rust_file = fs::File::create(&rust_path)
-        .map_err(|e| format!("Error al crear main.rs: {}", e))?;
-    rust_file.write_all(rust_code.as_bytes())
-        .map_err(|e| format!("Error al escribir código Rust: {}", e))?;
-
-    // Compilar el proyecto usando cargo
-    let status = if cfg!(target_os = "windows") {
-        Command::new("cargo")
-            .current_dir(&output_path)
-            .args(["build", "--release"])
-            .status()
-            .map_err(|e| format!("Error al ejecutar cargo: {}", e))?
-    } else {
-        Command::new("cargo")
-            .current_dir(&output_path)
-            .args(["build", "--release"])
-            .status()
-            .map_err(|e| format!("Error al ejecutar cargo: {}", e))?
-    };
+    for ((rust_path, generated), module) in generated_files.iter().zip(&project.modules) {
+        let rust_path = src_dir.join(rust_path);
+        if let Some(parent) = rust_path.parent() {
+            fs::create_dir_all(parent)
+                .map_err(|e| format!("Error al crear el directorio {}: {}", parent.display(), e))?;
+        }
+        let mut rust_file = fs::File::create(&rust_path)
+            .map_err(|e| format!("Error al crear {}: {}", rust_path.display(), e))?;
+        rust_file.write_all(generated.code.as_bytes())
+            .map_err(|e| format!("Error al escribir código Rust: {}", e))?;
+
+        if config.source_map {
+            let file_name = rust_path.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default();
+            fs::write(rust_path.with_extension("rs.map"), generated.source_map.to_v3_json(&file_name, &generated.code, &module.source))
+                .map_err(|e| format!("Error al escribir {}.map: {}", file_name, e))?;
+        }
+    }
 
-    if !status.success() {
-        return Err("Error durante la compilación con cargo".to_string());
+    // Imágenes que cargan los scripts, con la misma ruta relativa dentro del proyecto
+    let mut copied = Vec::new();
+    for module in &project.modules {
+        let script_dir = project.root.join(module.file.as_deref().and_then(Path::parent).unwrap_or(Path::new("")));
+        for (line, asset) in textures::assets(&module.state) {
+            let source = script_dir.join(&asset);
+            if !source.is_file() {
+                let message = config.msg("no se encontró la imagen ", "image not found: ");
+                let warning = diagnostics::Diagnostic::warning(format!("{}{}", message, source.display()), line);
+                eprint!("{}", diagnostics::render(&warning, &module.python_file(), &module.source, config));
+                continue;
+            }
+            let target = Path::new(&output_path).join(&asset);
+            if let Some(parent) = target.parent() {
+                fs::create_dir_all(parent)
+                    .map_err(|e| format!("Error al crear el directorio {}: {}", parent.display(), e))?;
+            }
+            fs::copy(&source, &target)
+                .map_err(|e| format!("Error al copiar {}: {}", source.display(), e))?;
+            copied.push(asset);
+        }
+    }
+
+    // Compilar el proyecto usando cargo; los diagnósticos salen en JSON por stdout
+    let mut cargo_args = vec!["build", "--release", "--message-format=json"];
+    if config.offline {
+        cargo_args.push("--offline");
+    }
+    if config.instrument {
+        cargo_args.extend(["--features", "instrument"]);
+    }
+    let output = Command::new("cargo")
+        .current_dir(&output_path)
+        .args(&cargo_args)
+        .stderr(Stdio::inherit())
+        .output()
+        .map_err(|e| format!("Error al ejecutar cargo: {}", e))?;
+
+    if !output.status.success() {
+        let cargo_messages = String::from_utf8_lossy(&output.stdout);
+        let (_, entry) = &generated_files[0];
+        let errors = diagnostics::parse_cargo_messages(&cargo_messages, &entry.source_map);
+        for mut error in errors.iter().cloned() {
+            // Los errores de los otros módulos se llevan a su archivo Python
+            let in_module = generated_files.iter().zip(&project.modules).skip(1)
+                .find(|((rust_path, _), _)| Path::new(&error.rust_file) == Path::new("src").join(rust_path));
+            match in_module {
+                Some(((_, generated), module)) => {
+                    error.python_line = generated.source_map.python_line(error.rust_line);
+                    eprintln!("{}", diagnostics::render(&error, &module.python_file(), &module.source, config));
+                }
+                None => eprintln!("{}", diagnostics::render(&error, &project.modules[0].python_file(), &project.modules[0].source, config)),
+            }
+        }
+        let message = config.msg("Error durante la compilación con cargo", "cargo build failed");
+        if errors.is_empty() {
+            return Err(message.to_string());
+        }
+        return Err(format!("{} ({} {})", message, errors.len(), config.msg("errores", "errors")));
     }
 
     // La ubicación del ejecutable compilado
     let exe_extension = if cfg!(target_os = "windows") { ".exe" } else { "" };
-    let exe_path = Path::new(&output_path)
-        .join("target")
-        .join("release")
-        .join(format!("{}{}", output_path, exe_extension));
+    let release_dir = Path::new(&output_path).join("target").join("release");
+    let exe_path = release_dir.join(format!("{}{}", package_name, exe_extension));
+
+    // Los archivos también van junto al ejecutable, que los busca allí primero
+    for asset in &copied {
+        let target = release_dir.join(asset);
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent)
+                .map_err(|e| format!("Error al crear el directorio {}: {}", parent.display(), e))?;
+        }
+        fs::copy(Path::new(&output_path).join(asset), &target)
+            .map_err(|e| format!("Error al copiar {}: {}", target.display(), e))?;
+    }
 
     Ok(exe_path.to_string_lossy().into_owned())
 }
\ No newline at end of file
@@ -0,0 +1,372 @@
+// Mapeos de APIs de bibliotecas Python. Cada biblioteca es un `ApiMapping`
+// que reconoce las rutas de sus llamadas (`random.randint`, `sdl2.SDL_Quit`):
+// los frontends (la API C de SDL2, pygame) las reescriben en el parser como
+// llamadas de `sdl2.ext`, y las bibliotecas (`math`, `random` y las reglas de
+// `[modules]` en py2rust.toml) las traducen en el generador a código Rust, con
+// sus `use` y su dependencia de Cargo.
+use std::collections::BTreeMap;
+use std::sync::OnceLock;
+
+use crate::capi::CApi;
+use crate::config::{Config, ModuleMapping};
+use crate::parser::ASTNode;
+use crate::pygame::Pygame;
+use crate::types::State;
+
+pub(crate) trait ApiMapping {
+    // Módulo Python que traduce
+    fn module(&self) -> &str;
+
+    // Si `import module` (o `from module import ...`) activa el mapeo.
+    fn imported_by(&self, module: &str) -> bool {
+        module == self.module() || module.strip_prefix(self.module()).is_some_and(|rest| rest.starts_with('.'))
+    }
+
+    // Si `from module import *` trae el nombre `name`.
+    fn exports(&self, _module: &str, _name: &str) -> bool {
+        false
+    }
+
+    // En el parser: llamada reescrita como otra que ya sabe traducirse.
+    fn lower_call(&self, _function: &str, _args: &[ASTNode], _kwargs: &[(String, ASTNode)], _line: usize, _column: usize) -> Option<ASTNode> {
+        None
+    }
+
+    // Constante o atributo sin llamada (`pygame.K_LEFT`).
+    fn lower_constant(&self, _path: &str) -> Option<ASTNode> {
+        None
+    }
+
+    // Índice de un subscript (`teclas[pygame.K_LEFT]`).
+    fn lower_index(&self, _index: &ASTNode) -> Option<ASTNode> {
+        None
+    }
+
+    // Asignación (o expresión suelta si no hay `target`) que pasa a ser otra
+    // cosa; el mapeo puede recordar la variable para sus métodos.
+    fn lower_assignment(&mut self, _target: Option<&str>, _value: &ASTNode, _line: usize, _column: usize) -> Option<ASTNode> {
+        None
+    }
+
+    // Cuerpo del bucle de eventos, para los atributos propios de sus eventos.
+    fn lower_event_body(&self, _variable: &str, body: Vec<ASTNode>) -> Vec<ASTNode> {
+        body
+    }
+
+    // Métodos encadenados que no hacen falta en la traducción (`.convert()`).
+    fn discards_method(&self, _method: &str) -> bool {
+        false
+    }
+
+    // Si la llamada se queda como `SDL2Call` para el resto del parser.
+    fn owns_call(&self, _function: &str) -> bool {
+        false
+    }
+
+    // En el generador: si tiene traducción para la función.
+    fn translates(&self, _function: &str) -> bool {
+        false
+    }
+
+    // Expresión Rust de una llamada con sus argumentos ya traducidos.
+    fn call_code(&self, _function: &str, _args: &[String]) -> Option<String> {
+        None
+    }
+
+    fn constant_code(&self, _path: &str) -> Option<String> {
+        None
+    }
+
+    // Si la llamada devuelve un float (`math.sin`).
+    fn returns_float(&self, _function: &str) -> bool {
+        false
+    }
+
+    // `use` que necesita el código generado.
+    fn uses(&self) -> Vec<String> {
+        Vec::new()
+    }
+
+    // Línea de `[dependencies]` del Cargo.toml generado.
+    fn dependency(&self) -> Option<String> {
+        None
+    }
+}
+
+// Frontends que se activan al importar su módulo.
+const FRONTENDS: [fn() -> Box<dyn ApiMapping>; 1] = [pygame_frontend];
+
+fn pygame_frontend() -> Box<dyn ApiMapping> {
+    Box::new(Pygame::default())
+}
+
+// Mapeos con los que empieza el parser: la API C de SDL2 está siempre activa.
+pub(crate) fn base_mappings() -> Vec<Box<dyn ApiMapping>> {
+    vec![Box::new(CApi)]
+}
+
+// Frontend que activa `import module`, si lo hay.
+pub(crate) fn frontend_for(module: &str) -> Option<Box<dyn ApiMapping>> {
+    FRONTENDS.iter().map(|frontend| frontend()).find(|mapping| mapping.imported_by(module))
+}
+
+// `math`: las funciones trabajan en f64, como en Python
+const MATH_CALLS: [(&str, &str); 16] = [
+    ("sqrt", "({0} as f64).sqrt()"),
+    ("sin", "({0} as f64).sin()"),
+    ("cos", "({0} as f64).cos()"),
+    ("tan", "({0} as f64).tan()"),
+    ("asin", "({0} as f64).asin()"),
+    ("acos", "({0} as f64).acos()"),
+    ("atan", "({0} as f64).atan()"),
+    ("atan2", "({0} as f64).atan2({1} as f64)"),
+    ("hypot", "({0} as f64).hypot({1} as f64)"),
+    ("pow", "({0} as f64).powf({1} as f64)"),
+    ("exp", "({0} as f64).exp()"),
+    ("fabs", "({0} as f64).abs()"),
+    ("radians", "({0} as f64).to_radians()"),
+    ("degrees", "({0} as f64).to_degrees()"),
+    // Python devuelve un entero
+    ("floor", "({0} as f64).floor() as {int}"),
+    ("ceil", "({0} as f64).ceil() as {int}"),
+];
+
+const MATH_FLOATS: [&str; 14] = [
+    "sqrt", "sin", "cos", "tan", "asin", "acos", "atan", "atan2", "hypot", "pow", "exp", "fabs", "radians", "degrees",
+];
+
+const MATH_CONSTANTS: [(&str, &str); 4] = [
+    ("pi", "std::f64::consts::PI"),
+    ("tau", "std::f64::consts::TAU"),
+    ("e", "std::f64::consts::E"),
+    ("inf", "f64::INFINITY"),
+];
+
+// `random` con el crate rand
+const RANDOM_CALLS: [(&str, &str); 5] = [
+    ("randint", "rand::thread_rng().gen_range({0}..={1})"),
+    ("randrange", "rand::thread_rng().gen_range({0}..{1})"),
+    ("random", "rand::thread_rng().gen::<f64>()"),
+    ("uniform", "rand::thread_rng().gen_range({0} as f64..{1} as f64)"),
+    ("choice", "{0}[rand::thread_rng().gen_range(0..{0}.len())]"),
+];
+
+fn rules(table: &[(&str, &str)]) -> BTreeMap<String, String> {
+    table.iter().map(|(name, template)| (name.to_string(), template.to_string())).collect()
+}
+
+// Bibliotecas que py2rust conoce sin configuración.
+fn builtin_libraries() -> &'static BTreeMap<String, ModuleMapping> {
+    static LIBRARIES: OnceLock<BTreeMap<String, ModuleMapping>> = OnceLock::new();
+    LIBRARIES.get_or_init(|| {
+        let math = ModuleMapping {
+            calls: rules(&MATH_CALLS),
+            constants: rules(&MATH_CONSTANTS),
+            floats: MATH_FLOATS.map(String::from).to_vec(),
+            ..Default::default()
+        };
+        let random = ModuleMapping {
+            crate_name: Some("rand".to_string()),
+            version: Some("0.8".to_string()),
+            uses: vec!["rand::Rng".to_string()],
+            calls: rules(&RANDOM_CALLS),
+            floats: vec!["random".to_string(), "uniform".to_string()],
+            ..Default::default()
+        };
+        BTreeMap::from([("math".to_string(), math), ("random".to_string(), random)])
+    })
+}
+
+// Si `from module import *` trae `name` de una biblioteca incorporada
+// (`from math import *` y luego `sqrt(x)`).
+pub(crate) fn exports(module: &str, name: &str) -> bool {
+    builtin_libraries()
+        .get(module)
+        .is_some_and(|mapping| mapping.calls.contains_key(name) || mapping.constants.contains_key(name))
+}
+
+// Biblioteca con reglas declarativas: las de `[modules.<módulo>]` van por
+// delante de las incorporadas, que completan lo que falte.
+struct Library<'a> {
+    module: &'a str,
+    configured: Option<&'a ModuleMapping>,
+    builtin: Option<&'a ModuleMapping>,
+    int_type: &'a str,
+}
+
+impl Library<'_> {
+    fn mappings(&self) -> impl Iterator<Item = &ModuleMapping> {
+        self.configured.into_iter().chain(self.builtin)
+    }
+
+    fn rule<'b>(&'b self, path: &str, table: fn(&ModuleMapping) -> &BTreeMap<String, String>) -> Option<&'b str> {
+        let name = member(self.module, path)?;
+        self.mappings().find_map(|mapping| table(mapping).get(name)).map(String::as_str)
+    }
+}
+
+impl ApiMapping for Library<'_> {
+    fn module(&self) -> &str {
+        self.module
+    }
+
+    fn translates(&self, function: &str) -> bool {
+        self.rule(function, |m| &m.calls).is_some()
+    }
+
+    fn call_code(&self, function: &str, args: &[String]) -> Option<String> {
+        expand(self.rule(function, |m| &m.calls)?, args, self.int_type)
+    }
+
+    fn constant_code(&self, path: &str) -> Option<String> {
+        expand(self.rule(path, |m| &m.constants)?, &[], self.int_type)
+    }
+
+    fn returns_float(&self, function: &str) -> bool {
+        let name = member(self.module, function);
+        self.mappings().any(|mapping| mapping.floats.iter().any(|f| Some(f.as_str()) == name))
+    }
+
+    fn uses(&self) -> Vec<String> {
+        let mut uses: Vec<String> = self.mappings().flat_map(|mapping| mapping.uses.iter().cloned()).collect();
+        uses.sort();
+        uses.dedup();
+        uses
+    }
+
+    fn dependency(&self) -> Option<String> {
+        let crate_name = self.mappings().find_map(|m| m.crate_name.as_ref())?;
+        let version = self.mappings().find_map(|m| m.version.as_deref()).unwrap_or("*");
+        let features = self.mappings().map(|m| &m.features).find(|f| !f.is_empty());
+        let features: Vec<String> = features.into_iter().flatten().map(|f| format!("\"{}\"", f)).collect();
+        Some(format!("{} = {{ version = \"{}\", features = [{}] }}", crate_name, version, features.join(", ")))
+    }
+}
+
+// Bibliotecas para el generador: las incorporadas y las de `[modules]`.
+pub(crate) fn libraries(config: &Config) -> Vec<Box<dyn ApiMapping + '_>> {
+    let builtin = builtin_libraries();
+    let mut modules: Vec<&str> = builtin.keys().chain(config.modules.keys()).map(String::as_str).collect();
+    modules.sort();
+    modules.dedup();
+    modules
+        .into_iter()
+        .map(|module| -> Box<dyn ApiMapping + '_> {
+            Box::new(Library { module, configured: config.modules.get(module), builtin: builtin.get(module), int_type: &config.int_type })
+        })
+        .collect()
+}
+
+pub(crate) fn call_code(function: &str, args: &[String], config: &Config) -> Option<String> {
+    libraries(config).iter().find_map(|library| library.call_code(function, args))
+}
+
+pub(crate) fn constant_code(path: &str, config: &Config) -> Option<String> {
+    libraries(config).iter().find_map(|library| library.constant_code(path))
+}
+
+pub(crate) fn translates(function: &str, config: &Config) -> bool {
+    libraries(config).iter().any(|library| library.translates(function))
+}
+
+pub(crate) fn returns_float(function: &str, config: &Config) -> bool {
+    libraries(config).iter().any(|library| library.returns_float(function))
+}
+
+// `use` de las bibliotecas cuyas funciones llama el script.
+pub(crate) fn uses(state: &State, config: &Config) -> Vec<String> {
+    let called = state.called_functions();
+    let mut uses: Vec<String> = libraries(config)
+        .iter()
+        .filter(|library| called.iter().any(|function| member(library.module(), function).is_some()))
+        .flat_map(|library| library.uses())
+        .collect();
+    uses.sort();
+    uses.dedup();
+    uses
+}
+
+// Nombre dentro del módulo: `randint` en `random.randint`.
+fn member<'a>(module: &str, path: &'a str) -> Option<&'a str> {
+    path.strip_prefix(module)?.strip_prefix('.')
+}
+
+// Dependencias de Cargo de las bibliotecas que importa el script.
+pub(crate) fn dependencies(state: &State, config: &Config) -> Vec<String> {
+    libraries(config)
+        .iter()
+        .filter(|library| state.imports.iter().any(|import| library.imported_by(&import.module)))
+        .filter_map(|library| library.dependency())
+        .collect()
+}
+
+// Sustituye `{0}`, `{1}`... por los argumentos, `{args}` por todos ellos y
+// `{int}` por `integers.type`. Un argumento compuesto va entre paréntesis salvo
+// que ya esté delimitado (`f({0}, {1})`). None si faltan argumentos.
+fn expand(template: &str, args: &[String], int_type: &str) -> Option<String> {
+    let mut code = String::new();
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        let end = start + rest[start..].find('}')?;
+        let placeholder = &rest[start + 1..end];
+        code.push_str(&rest[..start]);
+        let value = match placeholder {
+            "args" => args.join(", "),
+            "int" => int_type.to_string(),
+            index => {
+                let arg = args.get(index.parse::<usize>().ok()?)?;
+                let delimited = code.trim_end().ends_with(['(', ',', '['])
+                    && rest[end + 1..].trim_start().starts_with([')', ',', ']']);
+                if delimited || is_atomic(arg) { arg.clone() } else { format!("({})", arg) }
+            }
+        };
+        code.push_str(&value);
+        rest = &rest[end + 1..];
+    }
+    code.push_str(rest);
+    Some(code)
+}
+
+// Sin espacios ni operadores fuera de paréntesis: `x`, `rect.x()`, `f(a, b)`.
+fn is_atomic(code: &str) -> bool {
+    let mut depth = 0;
+    for c in code.chars() {
+        match c {
+            '(' | '[' => depth += 1,
+            ')' | ']' => depth -= 1,
+            ' ' if depth == 0 => return false,
+            _ => {}
+        }
+    }
+    !code.starts_with(['-', '!', '&', '*'])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builtin_and_configured_rules() {
+        let mut config = Config::default();
+        assert_eq!(call_code("math.sqrt", &["dx * dx".to_string()], &config), Some("((dx * dx) as f64).sqrt()".to_string()));
+        assert_eq!(call_code("random.randint", &["0".to_string(), "ANCHO".to_string()], &config), Some("rand::thread_rng().gen_range(0..=ANCHO)".to_string()));
+        assert_eq!(call_code("random.randint", &["0".to_string()], &config), None);
+        assert_eq!(constant_code("math.pi", &config), Some("std::f64::consts::PI".to_string()));
+
+        let random = ModuleMapping { version: Some("0.9".to_string()), ..Default::default() };
+        let vector = ModuleMapping {
+            crate_name: Some("glam".to_string()),
+            calls: BTreeMap::from([("length".to_string(), "glam::Vec2::new({args}).length()".to_string())]),
+            ..Default::default()
+        };
+        config.modules = BTreeMap::from([("random".to_string(), random), ("vector".to_string(), vector)]);
+        let libraries = libraries(&config);
+        let dependencies: Vec<String> = libraries.iter().filter_map(|library| library.dependency()).collect();
+        assert_eq!(dependencies, vec![
+            "rand = { version = \"0.9\", features = [] }".to_string(),
+            "glam = { version = \"*\", features = [] }".to_string(),
+        ]);
+        let args = ["x".to_string(), "y".to_string()];
+        assert_eq!(call_code("vector.length", &args, &config), Some("glam::Vec2::new(x, y).length()".to_string()));
+    }
+}
@@ -1,30 +1,90 @@
-use crate::types::{Token, TokenType, State, Variable, DrawCall, DrawType, ControlFlow};
+use crate::classes;
+use crate::imports::{self, ImportTable};
+use crate::mappings::{self, ApiMapping};
+use crate::timing;
+use crate::types::{Token, TokenType, State, Variable, DrawCall, ControlFlow, Comment, Import, FunctionDef, Param, ClassDef, ClassAttr, Method, MethodKind, SdlCall, Expr, WindowSpec, WindowOp, RendererSpec, RendererSetting, Stmt, StmtKind, EventLoop, EventHandler};
 
 #[derive(Debug, PartialEq, Clone)]
-enum ASTNode {
+pub(crate) enum ASTNode {
     Program(Vec<ASTNode>),
-    Assignment { name: String, value: Box<ASTNode> },
+    Assignment { name: String, value: Box<ASTNode>, line: usize, column: usize },
     Number(i32),
+    Float(f64),
     Boolean(bool),
     Identifier(String),
     StringLiteral(String),
+    Attribute(String),
+    Tuple(Vec<ASTNode>),
+    List(Vec<ASTNode>),
     BinaryOp { op: String, left: Box<ASTNode>, right: Box<ASTNode> },
-    SDL2Call { function: String, args: Vec<ASTNode> },
-    DrawCall(DrawCall),
-    ControlFlow { control_type: String, condition: Option<Box<ASTNode>>, body: Vec<ASTNode> },
+    Subscript { value: Box<ASTNode>, index: Box<ASTNode> },
+    SDL2Call { function: String, args: Vec<ASTNode>, kwargs: Vec<(String, ASTNode)>, line: usize, column: usize },
+    // Llamada a una función del script o de Python: `draw_circle(...)`, `range(...)`
+    Call { function: String, args: Vec<ASTNode>, kwargs: Vec<(String, ASTNode)>, line: usize, column: usize },
+    UnaryOp { op: String, operand: Box<ASTNode> },
+    AugAssignment { name: String, op: String, value: Box<ASTNode>, line: usize, column: usize },
+    ControlFlow { control_type: String, condition: Option<Box<ASTNode>>, body: Vec<ASTNode>, orelse: Vec<ASTNode>, line: usize, column: usize },
+    For { target: String, iter: Box<ASTNode>, body: Vec<ASTNode>, line: usize, column: usize },
+    Break { line: usize, column: usize },
+    Continue { line: usize, column: usize },
+    Import { module: String, names: Vec<String>, line: usize },
+    FunctionDef(FunctionDef),
+    ClassDef(ClassDef),
+    Return { value: Option<Box<ASTNode>>, line: usize, column: usize },
     Ignore,
 }
 
+// Estado de un script suelto, con sus clases ya enlazadas.
 pub fn extract_state(tokens: Vec<Token>) -> State {
-    let mut parser = Parser { tokens, current: 0 };
+    let mut state = extract_module_state(tokens, "");
+    classes::link(&mut [("", &mut state)]);
+    state
+}
+
+// Estado de un módulo del proyecto; `package` es el paquete desde el que se
+// resuelven sus imports relativos (`from . import x`). Las clases se enlazan
+// después, con las de todos los módulos.
+pub fn extract_module_state(tokens: Vec<Token>, package: &str) -> State {
+    let mut parser = Parser {
+        tokens,
+        current: 0,
+        comments: Vec::new(),
+        pending_comments: Vec::new(),
+        mappings: mappings::base_mappings(),
+        imports: ImportTable::default(),
+        package: package.to_string(),
+    };
     let ast = parser.parse_program();
 
     let mut state = State {
         variables: Vec::new(),
         draw_calls: Vec::new(),
         control_flow: Vec::new(),
+        imports: Vec::new(),
+        functions: Vec::new(),
+        classes: Vec::new(),
+        sdl_calls: Vec::new(),
+        comments: parser.comments,
+        module_docstring: None,
+        window: None,
+        renderer: None,
+        events: None,
+        loop_body: Vec::new(),
+        setup: Vec::new(),
+        modules: Vec::new(),
+        hierarchies: Vec::new(),
     };
+
+    // Un string suelto como primera sentencia es el docstring del módulo
+    if let ASTNode::Program(statements) = &ast {
+        if let Some(ASTNode::StringLiteral(value)) = statements.first() {
+            state.module_docstring = Some(clean_docstring(value));
+        }
+    }
     extract_state_from_ast(&ast, &mut state);
+    if let ASTNode::Program(statements) = &ast {
+        state.setup = to_stmts(statements);
+    }
     
     state
 }
@@ -36,33 +96,386 @@ fn extract_state_from_ast(node: &ASTNode, state: &mut State) {
                 extract_state_from_ast(statement, state);
             }
         }
-        ASTNode::Assignment { name, value } => {
-            let value_str = format!("{:?}", value);
-            let tipo = if value_str == "Boolean(true)" || value_str == "Boolean(false)" {
-                "bool".to_string()
-            } else if value_str.contains(',') {
-                "tuple".to_string()
-            } else {
-                "i32".to_string()
+        ASTNode::Assignment { name, value, line, column } => {
+            if let ASTNode::SDL2Call { function, args, kwargs, line, column } = value.as_ref() {
+                if function.ends_with(".Window") {
+                    state.window = Some(window_spec(name, args, kwargs, *line, *column));
+                } else if function.ends_with(".Renderer") {
+                    state.renderer = Some(renderer_spec(name, args, kwargs, *line, *column));
+                }
+            }
+            extract_state_from_ast(value, state);
+            // `buttons, x, y = ...`: cada destino lo trata el generador
+            if name.contains(", ") {
+                return;
+            }
+            // Asignación a un atributo: solo interesan las de la ventana y el renderer
+            if let Some((object, attribute)) = name.split_once('.') {
+                if let (Some(window), "title") = (state.window.as_mut(), attribute) {
+                    if window.variable == object {
+                        window.title = to_expr(value);
+                        return;
+                    }
+                }
+                if let (Some(renderer), Some(value)) = (state.renderer.as_mut(), to_expr(value)) {
+                    if renderer.variable == object && attribute == "color" {
+                        state.draw_calls.push(DrawCall {
+                            method: "set_draw_color".to_string(),
+                            args: vec![value],
+                            kwargs: Vec::new(),
+                            line: *line,
+                            column: *column,
+                        });
+                    } else if renderer.variable == object {
+                        renderer.settings.push(RendererSetting {
+                            attribute: attribute.to_string(),
+                            value,
+                            line: *line,
+                            column: *column,
+                        });
+                    }
+                }
+                return;
+            }
+            let value = to_expr(value);
+            let tipo = match &value {
+                Some(Expr::Bool(_)) => "bool",
+                Some(Expr::Str(_)) => "str",
+                Some(Expr::Tuple(_)) => "tuple",
+                Some(value) if timing::is_float(value) => "float",
+                Some(Expr::Attr(_) | Expr::List(_) | Expr::Call { .. }) | None => "unknown",
+                Some(_) => "int",
             };
             state.variables.push(Variable {
                 name: name.clone(),
-                value: value_str.clone(),
-                tipo,
+                value,
+                tipo: tipo.to_string(),
+                line: *line,
+                column: *column,
             });
         }
-        ASTNode::DrawCall(draw_call) => {
-            state.draw_calls.push(draw_call.clone());
+        ASTNode::Import { module, names, line } => match state.imports.iter_mut().find(|i| &i.module == module) {
+            Some(import) => {
+                for name in names.iter().filter(|name| !import.names.contains(name)).collect::<Vec<_>>() {
+                    import.names.push(name.clone());
+                }
+            }
+            None => state.imports.push(Import { module: module.clone(), names: names.clone(), line: *line }),
+        },
+        ASTNode::FunctionDef(function) => {
+            state.functions.push(Method { function: function.clone(), kind: MethodKind::Static, return_type: None, mutates: false, inherited: None });
+        }
+        ASTNode::ClassDef(class) => {
+            state.classes.push(class.clone());
+        }
+        ASTNode::SDL2Call { function, args, kwargs, line, column } => {
+            if let Some(window) = state.window.as_mut() {
+                if let Some(method) = function.strip_prefix(&format!("{}.", window.variable)) {
+                    window.operations.push(WindowOp { method: method.to_string(), line: *line, column: *column });
+                }
+            }
+            if let Some(renderer) = &state.renderer {
+                if let Some(method) = function.strip_prefix(&format!("{}.", renderer.variable)) {
+                    if RENDERER_METHODS.contains(&method) {
+                        state.draw_calls.extend(draw_call(method, args, kwargs, *line, *column));
+                    }
+                }
+            }
+            state.sdl_calls.push(SdlCall { function: function.clone(), line: *line, column: *column });
         }
-        ASTNode::ControlFlow { control_type, condition, body } => {
+        // Funciones del script que dibujan con el renderer que reciben
+        ASTNode::Call { function, args, kwargs, line, column } => {
+            let renderer_first = match (&state.renderer, args.first()) {
+                (Some(renderer), Some(ASTNode::Identifier(name))) => &renderer.variable == name,
+                _ => false,
+            };
+            // `draw_circle` es también la que genera `pygame.draw.circle`
+            let drawing_function = function == "draw_circle" || state.functions.iter().any(|f| &f.function.name == function);
+            if renderer_first && drawing_function {
+                state.draw_calls.extend(draw_call(function, &args[1..], kwargs, *line, *column));
+            }
+        }
+        ASTNode::ControlFlow { control_type, condition, body, orelse, line, column } => {
             state.control_flow.push(ControlFlow {
                 control_type: control_type.clone(),
                 condition: condition.as_ref().map(|c| format!("{:?}", c)),
+                line: *line,
+                column: *column,
             });
+            if let Some(condition) = condition {
+                extract_state_from_ast(condition, state);
+                // `while sdl2.SDL_PollEvent(ctypes.byref(event)):`; el primer
+                // `while` que no es de eventos es el bucle principal
+                if control_type == "while" {
+                    if let Some(variable) = event_source(condition) {
+                        state.events = Some(event_loop(&variable, body, *line, *column));
+                    } else if state.loop_body.is_empty() {
+                        state.loop_body = to_stmts(body);
+                    }
+                }
+            }
+            for statement in body.iter().chain(orelse) {
+                extract_state_from_ast(statement, state);
+            }
+        }
+        ASTNode::For { target, iter, body, line, column } => {
+            state.control_flow.push(ControlFlow {
+                control_type: "for".to_string(),
+                condition: None,
+                line: *line,
+                column: *column,
+            });
+            extract_state_from_ast(iter, state);
+            if event_source(iter).is_some() {
+                state.events = Some(event_loop(target, body, *line, *column));
+            }
             for statement in body {
                 extract_state_from_ast(statement, state);
             }
         }
+        ASTNode::BinaryOp { left, right, .. } => {
+            extract_state_from_ast(left, state);
+            extract_state_from_ast(right, state);
+        }
+        _ => {}
+    }
+}
+
+// Si la expresión es (o compara) una llamada a `get_events`, la variable del
+// evento: la de `SDL_PollEvent(ctypes.byref(event))` o `event` por defecto.
+fn event_source(node: &ASTNode) -> Option<String> {
+    match node {
+        ASTNode::SDL2Call { function, args, .. } if function.ends_with("get_events") => match args.first() {
+            Some(ASTNode::Identifier(name)) => Some(name.clone()),
+            _ => Some("event".to_string()),
+        },
+        ASTNode::BinaryOp { left, right, .. } => event_source(left).or_else(|| event_source(right)),
+        _ => None,
+    }
+}
+
+// Manejadores del cuerpo del bucle de eventos: cada rama `if`/`elif` que
+// compara `event.type` con una constante.
+fn event_loop(variable: &str, body: &[ASTNode], line: usize, column: usize) -> EventLoop {
+    let mut handlers = Vec::new();
+    for node in body {
+        let mut chained = false;
+        let mut current = node;
+        while let ASTNode::ControlFlow { control_type, condition: Some(condition), body, orelse, line, column } = current {
+            if control_type != "if" {
+                break;
+            }
+            if let Some((event_type, guard)) = to_expr(condition).and_then(|c| split_event_condition(&c, variable)) {
+                handlers.push(EventHandler { event_type, guard, body: to_stmts(body), chained, line: *line, column: *column });
+                chained = true;
+            }
+            match orelse.as_slice() {
+                [next] => current = next,
+                _ => break,
+            }
+        }
+    }
+    EventLoop { variable: variable.to_string(), handlers, line, column }
+}
+
+// `event.type == sdl2.SDL_KEYDOWN and resto` -> ("SDL_KEYDOWN", Some(resto))
+fn split_event_condition(condition: &Expr, variable: &str) -> Option<(String, Option<Expr>)> {
+    let type_attr = format!("{}.type", variable);
+    match condition {
+        Expr::BinOp { op, left, right } if op == "==" => match (left.as_ref(), right.as_ref()) {
+            (Expr::Attr(attr), Expr::Attr(constant)) | (Expr::Attr(constant), Expr::Attr(attr)) if *attr == type_attr => {
+                Some((constant.rsplit('.').next().unwrap_or(constant).to_string(), None))
+            }
+            _ => None,
+        },
+        Expr::BinOp { op, left, right } if op == "and" => {
+            let and = |a: Option<Expr>, b: &Expr| match a {
+                Some(a) => Expr::BinOp { op: "and".to_string(), left: Box::new(a), right: Box::new(b.clone()) },
+                None => b.clone(),
+            };
+            if let Some((event_type, guard)) = split_event_condition(left, variable) {
+                Some((event_type, Some(and(guard, right))))
+            } else {
+                let (event_type, guard) = split_event_condition(right, variable)?;
+                Some((event_type, Some(match guard {
+                    Some(guard) => and(Some(left.as_ref().clone()), &guard),
+                    None => left.as_ref().clone(),
+                })))
+            }
+        }
+        _ => None,
+    }
+}
+
+fn to_stmts(nodes: &[ASTNode]) -> Vec<Stmt> {
+    nodes.iter()
+        .flat_map(|node| match node {
+            // `a, b = 1, 2` se reparte en varias asignaciones
+            ASTNode::Program(statements) => to_stmts(statements),
+            node => to_stmt(node).into_iter().collect(),
+        })
+        .collect()
+}
+
+fn to_stmt(node: &ASTNode) -> Option<Stmt> {
+    let (kind, line, column) = match node {
+        ASTNode::Assignment { name, value, line, column } => {
+            (StmtKind::Assign { target: name.clone(), value: to_expr(value)? }, *line, *column)
+        }
+        ASTNode::AugAssignment { name, op, value, line, column } => {
+            (StmtKind::AugAssign { target: name.clone(), op: op.clone(), value: to_expr(value)? }, *line, *column)
+        }
+        ASTNode::ControlFlow { control_type, condition: Some(condition), body, orelse, line, column } if control_type == "if" => {
+            (StmtKind::If { condition: to_expr(condition)?, body: to_stmts(body), orelse: to_stmts(orelse) }, *line, *column)
+        }
+        // El bucle de eventos se extrae aparte
+        ASTNode::For { target, iter, body, line, column } if event_source(iter).is_none() => {
+            (StmtKind::For { target: target.clone(), iter: to_expr(iter)?, body: to_stmts(body) }, *line, *column)
+        }
+        ASTNode::SDL2Call { line, column, .. } | ASTNode::Call { line, column, .. } => (StmtKind::Expr(to_expr(node)?), *line, *column),
+        ASTNode::Break { line, column } => (StmtKind::Break, *line, *column),
+        ASTNode::Continue { line, column } => (StmtKind::Continue, *line, *column),
+        ASTNode::Return { value, line, column } => {
+            let value = match value {
+                Some(value) => Some(to_expr(value)?),
+                None => None,
+            };
+            (StmtKind::Return(value), *line, *column)
+        }
+        _ => return None,
+    };
+    Some(Stmt { kind, line, column })
+}
+
+fn to_expr(node: &ASTNode) -> Option<Expr> {
+    match node {
+        ASTNode::Number(n) => Some(Expr::Int(*n as i64)),
+        ASTNode::Float(x) => Some(Expr::Float(*x)),
+        ASTNode::Boolean(b) => Some(Expr::Bool(*b)),
+        ASTNode::Identifier(name) if name == "True" || name == "False" => Some(Expr::Bool(name == "True")),
+        ASTNode::Identifier(name) if name != "undefined" => Some(Expr::Name(name.clone())),
+        ASTNode::StringLiteral(value) => Some(Expr::Str(value.clone())),
+        // `x.value` de una variable de ctypes es la propia variable
+        ASTNode::Attribute(path) if path.ends_with(".value") && !path[..path.len() - 6].contains('.') => {
+            Some(Expr::Name(path[..path.len() - 6].to_string()))
+        }
+        ASTNode::Attribute(path) => Some(Expr::Attr(path.clone())),
+        ASTNode::Tuple(items) => Some(Expr::Tuple(items.iter().map(to_expr).collect::<Option<_>>()?)),
+        ASTNode::List(items) => Some(Expr::List(items.iter().map(to_expr).collect::<Option<_>>()?)),
+        ASTNode::SDL2Call { function, args, kwargs, .. } | ASTNode::Call { function, args, kwargs, .. } if kwargs.is_empty() => {
+            Some(Expr::Call { function: function.clone(), args: args.iter().map(to_expr).collect::<Option<_>>()? })
+        }
+        ASTNode::Subscript { value, index } => Some(Expr::Index { value: Box::new(to_expr(value)?), index: Box::new(to_expr(index)?) }),
+        ASTNode::UnaryOp { op, operand } => Some(Expr::UnaryOp { op: op.clone(), operand: Box::new(to_expr(operand)?) }),
+        ASTNode::BinaryOp { op, left, right } => Some(Expr::BinOp {
+            op: op.clone(),
+            left: Box::new(to_expr(left)?),
+            right: Box::new(to_expr(right)?),
+        }),
+        _ => None,
+    }
+}
+
+// Niveles de precedencia de los operadores binarios de Python, de menor a mayor
+const BINARY_PRECEDENCE: [&[&str]; 7] = [
+    &["or"],
+    &["and"],
+    &["==", "!=", "<", "<=", ">", ">="],
+    &["|"],
+    &["&"],
+    &["+", "-"],
+    &["*", "/", "//", "%"],
+];
+
+// Nivel en el que se reconoce `not`: justo por encima de las comparaciones
+const NOT_LEVEL: usize = 2;
+
+const AUGMENTED_ASSIGNMENTS: [&str; 6] = ["+=", "-=", "*=", "/=", "//=", "%="];
+
+// Métodos de `sdl2.ext.Renderer` que se traducen a llamadas sobre el canvas
+pub const RENDERER_METHODS: [&str; 10] = ["clear", "present", "set_draw_color", "draw_point", "draw_line", "draw_rect", "fill", "copy", "rcopy", "blit"];
+
+// None si algún argumento no es una expresión reconocida.
+fn draw_call(method: &str, args: &[ASTNode], kwargs: &[(String, ASTNode)], line: usize, column: usize) -> Option<DrawCall> {
+    Some(DrawCall {
+        method: method.to_string(),
+        args: args.iter().map(to_expr).collect::<Option<_>>()?,
+        kwargs: kwargs.iter().map(|(k, v)| Some((k.clone(), to_expr(v)?))).collect::<Option<_>>()?,
+        line,
+        column,
+    })
+}
+
+// Argumentos de `sdl2.ext.Window(title, size, position=None, flags=None)`,
+// posicionales o por nombre.
+fn window_spec(variable: &str, args: &[ASTNode], kwargs: &[(String, ASTNode)], line: usize, column: usize) -> WindowSpec {
+    let argument = |index: usize, name: &str| {
+        kwargs.iter()
+            .find(|(k, _)| k == name)
+            .map(|(_, v)| v)
+            .or(args.get(index))
+            .and_then(to_expr)
+    };
+    let pair = |expr: Option<Expr>| match expr {
+        Some(Expr::Tuple(items)) if items.len() == 2 => Some((items[0].clone(), items[1].clone())),
+        _ => None,
+    };
+
+    WindowSpec {
+        variable: variable.to_string(),
+        title: argument(0, "title"),
+        size: pair(argument(1, "size")),
+        position: pair(argument(2, "position")),
+        flags: argument(3, "flags").map(|flags| {
+            let mut names = Vec::new();
+            collect_flags(&flags, &mut names);
+            names
+        }),
+        operations: Vec::new(),
+        line,
+        column,
+    }
+}
+
+// Argumentos de `sdl2.ext.Renderer(target, backend=-1, logical_size=None, flags=...)`;
+// versiones antiguas de PySDL2 llaman `index` a `backend`.
+fn renderer_spec(variable: &str, args: &[ASTNode], kwargs: &[(String, ASTNode)], line: usize, column: usize) -> RendererSpec {
+    let argument = |index: usize, names: &[&str]| {
+        kwargs.iter()
+            .find(|(k, _)| names.contains(&k.as_str()))
+            .map(|(_, v)| v)
+            .or(args.get(index))
+            .and_then(to_expr)
+    };
+
+    let mut settings = Vec::new();
+    if let Some(value) = argument(2, &["logical_size"]) {
+        settings.push(RendererSetting { attribute: "logical_size".to_string(), value, line, column });
+    }
+    RendererSpec {
+        variable: variable.to_string(),
+        index: argument(1, &["backend", "index"]),
+        flags: argument(3, &["flags"]).map(|flags| {
+            let mut names = Vec::new();
+            collect_flags(&flags, &mut names);
+            names
+        }),
+        settings,
+        line,
+        column,
+    }
+}
+
+// Flags combinados con `|`: `sdl2.SDL_WINDOW_RESIZABLE | sdl2.SDL_WINDOW_BORDERLESS`
+fn collect_flags(expr: &Expr, names: &mut Vec<String>) {
+    match expr {
+        Expr::BinOp { op, left, right } if op == "|" => {
+            collect_flags(left, names);
+            collect_flags(right, names);
+        }
+        Expr::Attr(path) | Expr::Name(path) => {
+            names.push(path.rsplit('.').next().unwrap_or(path).to_string());
+        }
         _ => {}
     }
 }
@@ -70,6 +483,43 @@ fn extract_state_from_ast(node: &ASTNode, state: &mut State) {
 struct Parser {
     tokens: Vec<Token>,
     current: usize,
+    comments: Vec<Comment>,
+    // Comentarios en líneas propias que esperan a la siguiente sentencia
+    pending_comments: Vec<String>,
+    // Mapeos de APIs activos, en el orden en que se consultan: los frontends
+    // que activan los imports del script van delante de PySDL2
+    mappings: Vec<Box<dyn ApiMapping>>,
+    // Nombres que enlazan los imports del script
+    imports: ImportTable,
+    // Paquete del módulo, para los imports relativos
+    package: String,
+}
+
+// Quita las comillas y la indentación común, como `inspect.cleandoc`.
+pub fn clean_docstring(raw: &str) -> String {
+    let quote_len = if raw.starts_with("\"\"\"") || raw.starts_with("'''") { 3 } else { 1 };
+    let inner = if raw.len() >= quote_len * 2 { &raw[quote_len..raw.len() - quote_len] } else { raw };
+    let lines: Vec<&str> = inner.lines().collect();
+    let indent = lines
+        .iter()
+        .skip(1)
+        .filter(|l| !l.trim().is_empty())
+        .map(|l| l.len() - l.trim_start().len())
+        .min()
+        .unwrap_or(0);
+
+    let mut cleaned: Vec<String> = lines
+        .iter()
+        .enumerate()
+        .map(|(i, l)| if i == 0 { l.trim().to_string() } else { l.get(indent..).unwrap_or("").trim_end().to_string() })
+        .collect();
+    while cleaned.first().is_some_and(|l| l.is_empty()) {
+        cleaned.remove(0);
+    }
+    while cleaned.last().is_some_and(|l| l.is_empty()) {
+        cleaned.pop();
+    }
+    cleaned.join("\n")
 }
 
 impl Parser {
@@ -89,47 +539,518 @@ impl Parser {
             return None;
         }
 
+        if self.check(TokenType::Comment, "") {
+            self.parse_comment();
+            return None;
+        }
+
+        let line = self.peek().line;
+        for text in self.pending_comments.drain(..) {
+            self.comments.push(Comment { text, line, trailing: false });
+        }
         Some(self.parse_statement())
     }
 
+    // Un comentario tras código en la misma línea pertenece a esa sentencia;
+    // si está solo en su línea, a la siguiente sentencia.
+    fn parse_comment(&mut self) {
+        let token = self.advance();
+        let text = token.value.trim_start_matches('#').trim().to_string();
+        let previous = if self.current >= 2 { Some(&self.tokens[self.current - 2]) } else { None };
+        match previous {
+            Some(prev) if prev.line == token.line && prev.token_type != TokenType::Eol => {
+                self.comments.push(Comment { text, line: token.line, trailing: true });
+            }
+            _ => self.pending_comments.push(text),
+        }
+    }
+
     fn parse_statement(&mut self) -> ASTNode {
         let token = self.peek();
         match &token.token_type {
+            TokenType::Identifier if token.value == "import" || token.value == "from" => {
+                self.parse_import()
+            },
+            TokenType::Identifier if token.value == "def" => {
+                self.parse_function_def(Vec::new())
+            },
+            TokenType::Identifier if token.value == "class" => {
+                self.parse_class_def()
+            },
+            TokenType::Symbol if token.value == "@" => {
+                self.parse_decorated()
+            },
+            TokenType::Identifier if token.value == "return" => {
+                self.advance();
+                let value = if self.check(TokenType::Eol, "") || self.check(TokenType::Comment, "") || self.is_at_end() {
+                    None
+                } else {
+                    Some(Box::new(self.parse_expression_list()))
+                };
+                ASTNode::Return { value, line: token.line, column: token.column }
+            },
+            TokenType::Identifier if token.value == "for" => {
+                self.parse_for()
+            },
+            TokenType::Identifier if token.value == "break" || token.value == "continue" || token.value == "pass" => {
+                self.advance();
+                match token.value.as_str() {
+                    "break" => ASTNode::Break { line: token.line, column: token.column },
+                    "continue" => ASTNode::Continue { line: token.line, column: token.column },
+                    _ => ASTNode::Ignore,
+                }
+            },
             TokenType::Identifier => {
-                if self.peek_next().token_type == TokenType::Symbol && self.peek_next().value == "=" {
+                if self.is_assignment() {
                     self.parse_assignment()
+                } else if self.is_aug_assignment() {
+                    self.parse_aug_assignment()
                 } else {
-                    self.parse_expression()
+                    self.parse_expression_statement()
                 }
             },
             TokenType::Keyword => {
                 if self.match_keyword("while") || self.match_keyword("if") {
                     self.parse_control_flow()
                 } else {
-                    self.parse_expression()
+                    self.parse_expression_statement()
+                }
+            }
+            _ => self.parse_expression_statement()
+        }
+    }
+
+    // `import a.b, c as d` y `from a.b import c as d, e` (o `*`, o entre
+    // paréntesis): se registran los módulos y los nombres que enlazan.
+    fn parse_import(&mut self) -> ASTNode {
+        let keyword = self.advance();
+        let line = keyword.line;
+        // Módulos importados y los nombres que se traen de cada uno
+        let mut modules = Vec::new();
+        if keyword.value == "from" {
+            let module = imports::absolute(&self.parse_dotted_name(), &self.package);
+            if self.check(TokenType::Identifier, "import") {
+                self.advance();
+            }
+            let parenthesized = self.check(TokenType::Symbol, "(");
+            if parenthesized {
+                self.advance();
+            }
+            let mut names = Vec::new();
+            loop {
+                if parenthesized {
+                    self.skip_newlines();
+                }
+                if self.check(TokenType::Symbol, "*") {
+                    self.advance();
+                    self.imports.bind_star(&module);
+                } else if self.check(TokenType::Identifier, "") {
+                    let name = self.advance().value;
+                    let local = self.parse_alias().unwrap_or_else(|| name.clone());
+                    self.imports.bind(&local, &imports::member(&module, &name));
+                    names.push(name);
+                }
+                if parenthesized {
+                    self.skip_newlines();
+                }
+                if !self.check(TokenType::Symbol, ",") {
+                    break;
+                }
+                self.advance();
+            }
+            if parenthesized && self.check(TokenType::Symbol, ")") {
+                self.advance();
+            }
+            modules.push((module, names));
+        } else {
+            loop {
+                let module = self.parse_dotted_name();
+                match self.parse_alias() {
+                    Some(alias) => self.imports.bind(&alias, &module),
+                    // `import a.b` enlaza `a`
+                    None => {
+                        let first = module.split('.').next().unwrap_or(&module);
+                        self.imports.bind(first, first);
+                    }
+                }
+                modules.push((module, Vec::new()));
+                if !self.check(TokenType::Symbol, ",") {
+                    break;
+                }
+                self.advance();
+            }
+        }
+        while !self.is_at_end() && !self.check(TokenType::Eol, "") {
+            self.advance();
+        }
+
+        let mut nodes = Vec::new();
+        // `from . import x` en la raíz del proyecto no tiene módulo, solo nombres
+        for (module, names) in modules.into_iter().filter(|(module, names)| !module.is_empty() || !names.is_empty()) {
+            if !self.mappings.iter().any(|mapping| mapping.imported_by(&module)) {
+                if let Some(frontend) = mappings::frontend_for(&module) {
+                    self.mappings.insert(0, frontend);
                 }
             }
-            _ => self.parse_expression()
+            nodes.push(ASTNode::Import { module, names, line });
+        }
+        match nodes.len() {
+            1 => nodes.remove(0),
+            _ => ASTNode::Program(nodes),
         }
     }
 
+    // `a.b.c`, o relativo (`.modulo`, `..`); la keyword `sdl2.` ya trae su punto.
+    fn parse_dotted_name(&mut self) -> String {
+        let mut name = String::new();
+        while self.check(TokenType::Symbol, ".") {
+            name.push_str(&self.advance().value);
+        }
+        let mut expect_name = true;
+        loop {
+            let is_name = self.check(TokenType::Keyword, "")
+                || (self.check(TokenType::Identifier, "") && !["as", "import"].contains(&self.peek().value.as_str()));
+            if expect_name && is_name {
+                name.push_str(&self.advance().value);
+                expect_name = name.ends_with('.');
+            } else if !expect_name && self.check(TokenType::Symbol, ".") {
+                name.push_str(&self.advance().value);
+                expect_name = true;
+            } else {
+                break;
+            }
+        }
+        name
+    }
+
+    // `as nombre` tras un import
+    fn parse_alias(&mut self) -> Option<String> {
+        if !self.check(TokenType::Identifier, "as") {
+            return None;
+        }
+        self.advance();
+        Some(self.consume(TokenType::Identifier, "Expected alias").value)
+    }
+
+    // Ruta completa de un nombre que viene de un import: `ext.Window` tras
+    // `import sdl2.ext as ext` es `sdl2.ext.Window`.
+    fn resolve(&self, path: &str) -> Option<String> {
+        self.imports.resolve(path, |module, name| {
+            self.mappings.iter().any(|mapping| mapping.exports(module, name)) || mappings::exports(module, name)
+        })
+    }
+
+    // `def nombre(params) -> tipo:` con su docstring y su cuerpo. Las funciones
+    // del nivel superior se traducen a mano en el generador; el cuerpo se usa
+    // en los métodos de las clases.
+    fn parse_function_def(&mut self, decorators: Vec<String>) -> ASTNode {
+        let def_token = self.advance();
+        let name = self.consume(TokenType::Identifier, "Expected function name").value;
+        self.imports.shadow(&name);
+        let params = self.parse_params();
+        let mut returns = None;
+        if self.check(TokenType::Symbol, "-") && self.peek_next().value == ">" {
+            self.advance();
+            self.advance();
+            returns = Some(self.parse_annotation());
+        }
+        while !self.is_at_end() && !self.check(TokenType::Eol, "") {
+            self.advance();
+        }
+
+        let docstring = self.parse_docstring();
+        let body = to_stmts(&self.parse_block(def_token.column));
+
+        ASTNode::FunctionDef(FunctionDef {
+            name,
+            params,
+            returns,
+            decorators,
+            docstring,
+            body,
+            line: def_token.line,
+            column: def_token.column,
+        })
+    }
+
+    // `(self, x: int, y=0, *args)`: nombre, anotación y valor por defecto de cada parámetro
+    fn parse_params(&mut self) -> Vec<Param> {
+        let mut params = Vec::new();
+        if !self.check(TokenType::Symbol, "(") {
+            return params;
+        }
+        self.advance();
+        loop {
+            self.skip_newlines();
+            if self.is_at_end() || self.check(TokenType::Symbol, ")") {
+                break;
+            }
+            while self.check(TokenType::Symbol, "*") {
+                self.advance();
+            }
+            if self.check(TokenType::Identifier, "") {
+                let name = self.advance().value;
+                let mut annotation = None;
+                if self.check(TokenType::Symbol, ":") {
+                    self.advance();
+                    annotation = Some(self.parse_annotation());
+                }
+                let mut default = None;
+                if self.check(TokenType::Symbol, "=") {
+                    self.advance();
+                    default = to_expr(&self.parse_expression());
+                }
+                params.push(Param { name, annotation, default, tipo: "unknown".to_string(), mutated: false });
+            }
+            self.skip_newlines();
+            if !self.check(TokenType::Symbol, ",") {
+                break;
+            }
+            self.advance();
+        }
+        if self.check(TokenType::Symbol, ")") {
+            self.advance();
+        }
+        params
+    }
+
+    // Anotación de tipo (`int`, `Player`, `"Player"`, `list[int]`): solo el nombre
+    fn parse_annotation(&mut self) -> String {
+        let name = if self.check(TokenType::String, "") {
+            self.advance().value.trim_matches(['"', '\'']).to_string()
+        } else {
+            self.parse_dotted_name()
+        };
+        if self.check(TokenType::Symbol, "[") {
+            self.parse_call_arguments("]");
+        }
+        self.resolve(&name).unwrap_or(name)
+    }
+
+    // Un string suelto al principio de un bloque
+    fn parse_docstring(&mut self) -> Option<String> {
+        while self.check(TokenType::Eol, "") {
+            self.advance();
+        }
+        if self.check(TokenType::String, "") && self.peek_next().token_type == TokenType::Eol {
+            return Some(clean_docstring(&self.advance().value));
+        }
+        None
+    }
+
+    // `@staticmethod`, `@classmethod`... delante de un `def`; los de otras
+    // sentencias se descartan.
+    fn parse_decorated(&mut self) -> ASTNode {
+        let mut decorators = Vec::new();
+        while self.check(TokenType::Symbol, "@") {
+            self.advance();
+            decorators.push(self.parse_dotted_name());
+            while !self.is_at_end() && !self.check(TokenType::Eol, "") {
+                self.advance();
+            }
+            self.skip_newlines();
+        }
+        if self.check(TokenType::Identifier, "def") {
+            self.parse_function_def(decorators)
+        } else {
+            self.parse_statement()
+        }
+    }
+
+    // `class Nombre(Base):` con su docstring, sus atributos de clase y sus métodos
+    fn parse_class_def(&mut self) -> ASTNode {
+        let class_token = self.advance();
+        let name = self.consume(TokenType::Identifier, "Expected class name").value;
+        self.imports.shadow(&name);
+        let mut bases = Vec::new();
+        let mut metaclass = None;
+        if self.check(TokenType::Symbol, "(") {
+            let (args, kwargs) = self.parse_call_arguments(")");
+            bases = args.iter()
+                .filter_map(|arg| match arg {
+                    ASTNode::Identifier(base) | ASTNode::Attribute(base) if base != "object" => Some(base.clone()),
+                    _ => None,
+                })
+                .collect();
+            metaclass = kwargs.into_iter().find_map(|(name, value)| match value {
+                ASTNode::Identifier(meta) | ASTNode::Attribute(meta) if name == "metaclass" => Some(meta),
+                _ => None,
+            });
+        }
+        while !self.is_at_end() && !self.check(TokenType::Eol, "") {
+            self.advance();
+        }
+
+        let docstring = self.parse_docstring();
+        let mut attributes = Vec::new();
+        let mut methods = Vec::new();
+        for node in self.parse_block(class_token.column) {
+            let assignments = match node {
+                ASTNode::Program(statements) => statements,
+                node => vec![node],
+            };
+            for node in assignments {
+                match node {
+                    ASTNode::FunctionDef(function) => {
+                        let kind = if function.decorators.iter().any(|d| d == "staticmethod") {
+                            MethodKind::Static
+                        } else if function.decorators.iter().any(|d| d == "classmethod") {
+                            MethodKind::Class
+                        } else {
+                            MethodKind::Instance
+                        };
+                        methods.push(Method { function, kind, return_type: None, mutates: false, inherited: None });
+                    }
+                    ASTNode::Assignment { name, value, line, column } if !name.contains(['.', ',']) => {
+                        if let Some(value) = to_expr(&value) {
+                            attributes.push(ClassAttr { name, value, tipo: "unknown".to_string(), line, column });
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        ASTNode::ClassDef(ClassDef {
+            name,
+            bases,
+            docstring,
+            attributes,
+            methods,
+            fields: Vec::new(),
+            metaclass,
+            hierarchy: None,
+            line: class_token.line,
+            column: class_token.column,
+        })
+    }
+
+    // `nombre = ...` o `objeto.atributo = ...`
+    fn is_assignment(&self) -> bool {
+        let mut index = self.current + 1;
+        while index + 1 < self.tokens.len() && (self.tokens[index].value == "." || self.tokens[index].value == ",")
+            && self.tokens[index + 1].token_type == TokenType::Identifier {
+            index += 2;
+        }
+        index < self.tokens.len() && self.tokens[index].token_type == TokenType::Symbol && self.tokens[index].value == "="
+    }
+
+    // `x += 1` o, sobre un atributo, `rect.x += 1`
+    fn is_aug_assignment(&self) -> bool {
+        let mut index = self.current + 1;
+        while index + 1 < self.tokens.len() && self.tokens[index].value == "."
+            && self.tokens[index + 1].token_type == TokenType::Identifier {
+            index += 2;
+        }
+        index < self.tokens.len() && AUGMENTED_ASSIGNMENTS.contains(&self.tokens[index].value.as_str())
+    }
+
     fn parse_assignment(&mut self) -> ASTNode {
-        let name = self.consume(TokenType::Identifier, "Expected identifier").value;
+        let name_token = self.consume(TokenType::Identifier, "Expected identifier");
+        let mut name = name_token.value;
+        let mut targets = Vec::new();
+        loop {
+            if self.check(TokenType::Symbol, ".") {
+                self.advance();
+                name.push('.');
+                name.push_str(&self.consume(TokenType::Identifier, "Expected attribute name").value);
+            } else if self.check(TokenType::Symbol, ",") {
+                self.advance();
+                targets.push(std::mem::replace(&mut name, self.consume(TokenType::Identifier, "Expected identifier").value));
+            } else {
+                break;
+            }
+        }
+        targets.push(name);
         self.consume(TokenType::Symbol, "Expected '='");
+        let value = self.parse_expression_list();
+        // Asignar a un nombre importado lo vuelve a enlazar; el valor aún usa el import
+        for target in targets.iter().filter(|target| !target.contains('.')) {
+            self.imports.shadow(target);
+        }
+        let (line, column) = (name_token.line, name_token.column);
+
+        // `x, y = ctypes.c_int(0), ctypes.c_int(0)`: una asignación por destino
+        if let ASTNode::Tuple(values) = &value {
+            if targets.len() > 1 && values.len() == targets.len() {
+                return ASTNode::Program(targets.into_iter().zip(values.iter().cloned())
+                    .map(|(name, value)| ASTNode::Assignment { name, value: Box::new(value), line, column })
+                    .collect());
+            }
+        }
+        let name = targets.join(", ");
+        self.lower_assignment(Some(&name), &value, line, column)
+            .unwrap_or(ASTNode::Assignment { name, value: Box::new(value), line, column })
+    }
+
+    // Expresión como sentencia; las funciones con parámetros de salida
+    // (`SDL_GetMouseState(ctypes.byref(x), ...)`) pasan a ser asignaciones
+    fn parse_expression_statement(&mut self) -> ASTNode {
+        let start = self.peek();
+        let expression = self.parse_expression();
+        self.lower_assignment(None, &expression, start.line, start.column).unwrap_or(expression)
+    }
+
+    fn lower_assignment(&mut self, target: Option<&str>, value: &ASTNode, line: usize, column: usize) -> Option<ASTNode> {
+        self.mappings.iter_mut().find_map(|mapping| mapping.lower_assignment(target, value, line, column))
+    }
+
+    fn lower_call(&self, function: &str, args: &[ASTNode], kwargs: &[(String, ASTNode)], line: usize, column: usize) -> Option<ASTNode> {
+        self.mappings.iter().find_map(|mapping| mapping.lower_call(function, args, kwargs, line, column))
+    }
+
+    fn lower_constant(&self, path: &str) -> Option<ASTNode> {
+        self.mappings.iter().find_map(|mapping| mapping.lower_constant(path))
+    }
+
+    // `x += 1`, `x //= 2`, `rect.x -= 5`...
+    fn parse_aug_assignment(&mut self) -> ASTNode {
+        let name_token = self.advance();
+        let mut name = name_token.value;
+        while self.check(TokenType::Symbol, ".") {
+            self.advance();
+            name.push('.');
+            name.push_str(&self.advance().value);
+        }
+        let op = self.advance().value.trim_end_matches('=').to_string();
         let value = self.parse_expression();
-        ASTNode::Assignment { name, value: Box::new(value) }
+        ASTNode::AugAssignment { name, op, value: Box::new(value), line: name_token.line, column: name_token.column }
     }
 
     fn parse_expression(&mut self) -> ASTNode {
-        self.parse_binary_expression()
+        self.parse_binary_expression(0)
     }
 
-    fn parse_binary_expression(&mut self) -> ASTNode {
-        let mut left = self.parse_primary();
+    // Tupla sin paréntesis: `x, y = 0, 0`, `return x, y`
+    fn parse_expression_list(&mut self) -> ASTNode {
+        let value = self.parse_expression();
+        if !self.check(TokenType::Symbol, ",") {
+            return value;
+        }
+        let mut items = vec![value];
+        while self.check(TokenType::Symbol, ",") {
+            self.advance();
+            items.push(self.parse_expression());
+        }
+        ASTNode::Tuple(items)
+    }
 
-        while self.is_binary_operator() {
+    // Operadores binarios por niveles de precedencia, de menor a mayor
+    fn parse_binary_expression(&mut self, level: usize) -> ASTNode {
+        if level == BINARY_PRECEDENCE.len() {
+            return self.parse_unary();
+        }
+        // `not` se aplica a una comparación entera
+        if level == NOT_LEVEL && self.check(TokenType::Identifier, "not") {
+            self.advance();
+            let operand = self.parse_binary_expression(level);
+            return ASTNode::UnaryOp { op: "not".to_string(), operand: Box::new(operand) };
+        }
+
+        let mut left = self.parse_binary_expression(level + 1);
+        while self.is_binary_operator(BINARY_PRECEDENCE[level]) {
             let operator = self.advance().value;
-            let right = self.parse_primary();
+            let right = self.parse_binary_expression(level + 1);
             left = ASTNode::BinaryOp {
                 op: operator,
                 left: Box::new(left),
@@ -140,13 +1061,25 @@ impl Parser {
         left
     }
 
-    fn is_binary_operator(&self) -> bool {
+    fn is_binary_operator(&self, operators: &[&str]) -> bool {
         if self.is_at_end() {
             return false;
         }
         let token = self.peek();
-        token.token_type == TokenType::Symbol && 
-        ["+", "-", "*", "/", "//"].contains(&token.value.as_str())
+        matches!(token.token_type, TokenType::Symbol | TokenType::Identifier) &&
+        operators.contains(&token.value.as_str())
+    }
+
+    fn parse_unary(&mut self) -> ASTNode {
+        if self.check(TokenType::Symbol, "-") {
+            self.advance();
+            return match self.parse_unary() {
+                ASTNode::Number(value) => ASTNode::Number(-value),
+                ASTNode::Float(value) => ASTNode::Float(-value),
+                operand => ASTNode::UnaryOp { op: "-".to_string(), operand: Box::new(operand) },
+            };
+        }
+        self.parse_primary()
     }
 
     fn parse_primary(&mut self) -> ASTNode {
@@ -156,6 +1089,10 @@ impl Parser {
 
         let token = self.peek();
         match &token.token_type {
+            TokenType::Number if token.value.contains('.') => {
+                let value = self.advance().value.parse::<f64>().unwrap_or(0.0);
+                ASTNode::Float(value)
+            },
             TokenType::Number => {
                 let value = self.advance().value.parse::<i32>().unwrap_or(0);
                 ASTNode::Number(value)
@@ -165,14 +1102,49 @@ impl Parser {
                 if !self.is_at_end() && self.check(TokenType::Symbol, ".") {
                     self.current -= 1; // Retroceder para procesar la llamada completa
                     self.parse_sdl2_call()
+                } else if value == "super" && self.check(TokenType::Symbol, "(") {
+                    // `super().__init__(x)` es la llamada `super.__init__(x)`
+                    self.parse_call_arguments(")");
+                    if !self.check(TokenType::Symbol, ".") || self.peek_next().token_type != TokenType::Identifier {
+                        return ASTNode::Identifier(value);
+                    }
+                    self.advance();
+                    let method = self.advance().value;
+                    if !self.check(TokenType::Symbol, "(") {
+                        return ASTNode::Attribute(format!("super.{}", method));
+                    }
+                    let (args, kwargs) = self.parse_call_arguments(")");
+                    ASTNode::Call { function: format!("super.{}", method), args, kwargs, line: token.line, column: token.column }
+                } else if self.check(TokenType::Symbol, "(") {
+                    let (args, kwargs) = self.parse_call_arguments(")");
+                    self.skip_text_conversion();
+                    // `Renderer(window)` tras `from sdl2.ext import Renderer`
+                    let function = self.resolve(&value).unwrap_or(value);
+                    self.finish_call(function, args, kwargs, token.line, token.column)
+                } else if self.check(TokenType::Symbol, "[") {
+                    self.advance();
+                    let mut index = self.parse_expression();
+                    if let Some(lowered) = self.mappings.iter().find_map(|mapping| mapping.lower_index(&index)) {
+                        index = lowered;
+                    }
+                    if self.check(TokenType::Symbol, "]") {
+                        self.advance();
+                    }
+                    ASTNode::Subscript { value: Box::new(ASTNode::Identifier(value)), index: Box::new(index) }
+                } else if let Some(path) = self.resolve(&value) {
+                    // `SDL_QUIT` tras `from sdl2 import *`, `K_LEFT` tras `from pygame.locals import *`
+                    self.lower_constant(&path).unwrap_or(ASTNode::Attribute(path))
                 } else {
                     ASTNode::Identifier(value)
                 }
             },
             TokenType::String => {
                 let value = self.advance().value.clone();
+                self.skip_text_conversion();
                 ASTNode::StringLiteral(value)
             },
+            TokenType::Symbol if token.value == "(" => self.parse_parenthesized(),
+            TokenType::Symbol if token.value == "[" => ASTNode::List(self.parse_call_arguments("]").0),
             TokenType::Keyword => {
                 if self.match_keyword("true") {
                     ASTNode::Boolean(true)
@@ -192,123 +1164,237 @@ impl Parser {
         }
     }
 
+    // `.encode()`/`.decode()` detrás de un texto: en Rust el texto ya es UTF-8
+    fn skip_text_conversion(&mut self) {
+        while self.check(TokenType::Symbol, ".")
+            && ["encode", "decode"].contains(&self.peek_next().value.as_str()) {
+            self.advance();
+            self.advance();
+            if self.check(TokenType::Symbol, "(") {
+                self.parse_call_arguments(")");
+            }
+        }
+    }
+
     fn parse_sdl2_call(&mut self) -> ASTNode {
+        let call_start = self.peek();
         let mut function_path = String::new();
         
-        // Consumir todos los identificadores y puntos hasta llegar a la función
-        while !self.is_at_end() && (self.check(TokenType::Identifier, "") || self.check(TokenType::Symbol, ".") || 
-              self.check(TokenType::Keyword, "")) {
+        // Consumir `nombre.nombre...` hasta llegar a la función; dos nombres
+        // seguidos (`sdl2.SDLK_f and ...`) ya no son parte de la ruta
+        let mut expect_name = true;
+        while !self.is_at_end() {
+            let is_name = self.check(TokenType::Identifier, "") || self.check(TokenType::Keyword, "");
+            if is_name != expect_name || !(is_name || self.check(TokenType::Symbol, ".")) {
+                break;
+            }
             let token = self.advance();
             function_path.push_str(&token.value);
+            // La keyword `sdl2.` ya trae su punto
+            expect_name = function_path.ends_with('.');
         }
 
-        // Si no hay una función SDL2 válida, retornar Ignore
-        if !function_path.contains("sdl2") {
-            return ASTNode::Ignore;
+        // `ext.Window` tras `import sdl2.ext as ext` es `sdl2.ext.Window`
+        if let Some(resolved) = self.resolve(&function_path) {
+            function_path = resolved;
         }
 
-        // Manejar los diferentes tipos de llamadas SDL2
-        let last_part = function_path.split('.').last().unwrap_or("");
-        match last_part {
-            "rect" | "fill" => self.parse_sdl2_rect_or_fill_call(function_path),
-            "draw_point" => self.parse_sdl2_draw_point_call(),
-            "Window" | "Renderer" | "init" | "quit" | "get_events" | "show" | "clear" | "present" =>
-                ASTNode::SDL2Call { function: function_path, args: Vec::new() },
-            _ => ASTNode::SDL2Call { function: function_path, args: Vec::new() }
+        let (line, column) = (call_start.line, call_start.column);
+        if self.check(TokenType::Symbol, "(") {
+            let (args, kwargs) = self.parse_call_arguments(")");
+            while self.check(TokenType::Symbol, ".") && self.mappings.iter().any(|m| m.discards_method(&self.peek_next().value)) {
+                self.advance();
+                self.advance();
+                self.parse_call_arguments(")");
+            }
+            self.finish_call(function_path, args, kwargs, line, column)
+        } else {
+            // Sin paréntesis es una constante o atributo: `sdl2.SDL_QUIT`
+            self.lower_constant(&function_path).unwrap_or(ASTNode::Attribute(function_path))
         }
     }
 
-    fn parse_sdl2_rect_or_fill_call(&mut self, function: String) -> ASTNode {
-        if !self.check(TokenType::Symbol, "(") {
-            return ASTNode::SDL2Call { function, args: Vec::new() };
+    // Llamada con su ruta completa: la reescribe un mapeo, se queda como
+    // `SDL2Call` si es de un mapeo o es una llamada normal.
+    fn finish_call(&self, function: String, args: Vec<ASTNode>, kwargs: Vec<(String, ASTNode)>, line: usize, column: usize) -> ASTNode {
+        if let Some(lowered) = self.lower_call(&function, &args, &kwargs, line, column) {
+            lowered
+        } else if self.mappings.iter().any(|mapping| mapping.owns_call(&function)) {
+            ASTNode::SDL2Call { function, args, kwargs, line, column }
+        } else {
+            ASTNode::Call { function, args, kwargs, line, column }
         }
+    }
 
-        self.consume(TokenType::Symbol, "Expected '('");
+    // `(a, b=c)`: argumentos posicionales y por nombre, que pueden ocupar varias
+    // líneas. También sirve para los elementos de una lista `[a, b]`.
+    fn parse_call_arguments(&mut self, close: &str) -> (Vec<ASTNode>, Vec<(String, ASTNode)>) {
+        self.advance();
         let mut args = Vec::new();
+        let mut kwargs = Vec::new();
 
-        // Parsear argumentos hasta encontrar el paréntesis de cierre
-        while !self.is_at_end() && !self.check(TokenType::Symbol, ")") {
-            args.push(self.parse_expression());
+        loop {
+            self.skip_newlines();
+            if self.is_at_end() || self.check(TokenType::Symbol, close) {
+                break;
+            }
+            if self.check(TokenType::Identifier, "") && self.peek_next().token_type == TokenType::Symbol && self.peek_next().value == "=" {
+                let name = self.advance().value;
+                self.advance();
+                kwargs.push((name, self.parse_expression()));
+            } else {
+                args.push(self.parse_expression());
+            }
+            self.skip_newlines();
             if self.check(TokenType::Symbol, ",") {
                 self.advance();
+            } else {
+                break;
             }
         }
 
-        self.consume(TokenType::Symbol, "Expected ')'");
-
-        // Crear DrawCall si tenemos suficientes argumentos
-        if args.len() >= 4 {
-            let draw_call = DrawCall {
-                draw_type: DrawType::Rect,
-                x: format!("{:?}", args[1]),
-                y: format!("{:?}", args[2]),
-                w: Some(format!("{:?}", args[3])),
-                h: if args.len() > 4 { Some(format!("{:?}", args[4])) } else { Some(String::from("50")) },
-                radius: None,
-                color: format!("{:?}", args[0]),
-            };
-            ASTNode::DrawCall(draw_call)
-        } else {
-            ASTNode::SDL2Call { function, args }
+        if self.check(TokenType::Symbol, close) {
+            self.advance();
         }
+        (args, kwargs)
     }
 
-    fn parse_sdl2_draw_point_call(&mut self) -> ASTNode {
-        self.consume(TokenType::Symbol, "Expected '('");
-        let mut args = Vec::new();
+    // Expresión entre paréntesis o tupla: `(ANCHO, ALTO)`
+    fn parse_parenthesized(&mut self) -> ASTNode {
+        self.advance();
+        let mut items = Vec::new();
+        let mut is_tuple = false;
 
-        while !self.is_at_end() && !self.check(TokenType::Symbol, ")") {
-            args.push(self.parse_expression());
+        loop {
+            self.skip_newlines();
+            if self.is_at_end() || self.check(TokenType::Symbol, ")") {
+                break;
+            }
+            items.push(self.parse_expression());
+            self.skip_newlines();
             if self.check(TokenType::Symbol, ",") {
                 self.advance();
+                is_tuple = true;
+            } else {
+                break;
             }
         }
 
-        self.consume(TokenType::Symbol, "Expected ')'");
-
-        if args.len() >= 3 {
-            let draw_call = DrawCall {
-                draw_type: DrawType::Circle,
-                x: format!("{:?}", args[1]),
-                y: format!("{:?}", args[2]),
-                w: None,
-                h: None,
-                radius: Some(String::from("50")),
-                color: format!("{:?}", args[0]),
-            };
-            ASTNode::DrawCall(draw_call)
+        if self.check(TokenType::Symbol, ")") {
+            self.advance();
+        }
+        if items.len() == 1 && !is_tuple {
+            items.remove(0)
         } else {
-            ASTNode::SDL2Call { 
-                function: String::from("draw_point"), 
-                args
+            ASTNode::Tuple(items)
+        }
+    }
+
+    // Dentro de paréntesis los saltos de línea (y comentarios) no terminan la sentencia
+    fn skip_newlines(&mut self) {
+        while self.check(TokenType::Eol, "") || self.check(TokenType::Comment, "") {
+            if self.check(TokenType::Comment, "") {
+                self.parse_comment();
+            } else {
+                self.advance();
             }
         }
     }
 
     fn parse_control_flow(&mut self) -> ASTNode {
-        let control_type = self.peek_previous().value.clone();
-        let mut condition = None;
+        let header = self.peek_previous();
+        self.parse_control_flow_from(header.value, header.line, header.column)
+    }
 
-        if self.check(TokenType::Symbol, "(") {
-            self.advance();
+    // `while cond:` o `if cond:` con su bloque; un `if` recoge además sus
+    // `elif` (como un `if` anidado en `orelse`) y su `else`.
+    fn parse_control_flow_from(&mut self, control_type: String, line: usize, column: usize) -> ASTNode {
+        let mut condition = None;
+        if !self.check(TokenType::Symbol, ":") {
             condition = Some(Box::new(self.parse_expression()));
-            self.consume(TokenType::Symbol, "Expected ')'");
+        }
+        if self.check(TokenType::Symbol, ":") {
+            self.advance();
         }
 
-        let mut body = Vec::new();
-        while !self.is_at_end() && !self.check(TokenType::Keyword, "end") {
-            if let Some(stmt) = self.try_parse_statement() {
-                body.push(stmt);
+        let body = self.parse_block(column);
+
+        let mut orelse = Vec::new();
+        if control_type == "if" && self.peek().column == column {
+            if self.check(TokenType::Identifier, "elif") {
+                let elif = self.advance();
+                orelse.push(self.parse_control_flow_from("if".to_string(), elif.line, elif.column));
+            } else if self.check(TokenType::Identifier, "else") {
+                self.advance();
+                if self.check(TokenType::Symbol, ":") {
+                    self.advance();
+                }
+                orelse = self.parse_block(column);
             }
         }
 
         ASTNode::ControlFlow { 
             control_type, 
             condition, 
-            body 
+            body,
+            orelse,
+            line,
+            column,
         }
     }
 
+    // `for objetivo in iterable:` con su bloque
+    fn parse_for(&mut self) -> ASTNode {
+        let for_token = self.advance();
+        let mut targets = Vec::new();
+        while !self.is_at_end() && !self.check(TokenType::Identifier, "in") && !self.check(TokenType::Eol, "") {
+            let token = self.advance();
+            if token.token_type == TokenType::Identifier {
+                targets.push(token.value);
+            }
+        }
+        if self.check(TokenType::Identifier, "in") {
+            self.advance();
+        }
+        let iter = self.parse_expression();
+        if self.check(TokenType::Symbol, ":") {
+            self.advance();
+        }
+        let mut body = self.parse_block(for_token.column);
+        // `for event in pygame.event.get():` con los atributos de sus eventos
+        if event_source(&iter).is_some() {
+            for mapping in &self.mappings {
+                body = mapping.lower_event_body(&targets.join(", "), body);
+            }
+        }
+        ASTNode::For { target: targets.join(", "), iter: Box::new(iter), body, line: for_token.line, column: for_token.column }
+    }
+
+    // Sentencias indentadas más allá de `header_column`: el bloque de un
+    // `def`, `while` o `if` termina en la primera línea que vuelve a su nivel.
+    fn parse_block(&mut self, header_column: usize) -> Vec<ASTNode> {
+        let mut body = Vec::new();
+        loop {
+            while self.check(TokenType::Eol, "") {
+                self.advance();
+            }
+            if self.is_at_end() {
+                break;
+            }
+            if self.check(TokenType::Comment, "") {
+                self.parse_comment();
+                continue;
+            }
+            if self.peek().column <= header_column {
+                break;
+            }
+            if let Some(stmt) = self.try_parse_statement() {
+                body.push(stmt);
+            }
+        }
+        body
+    }
+
     fn consume(&mut self, expected_type: TokenType, message: &str) -> Token {
         if self.check(expected_type.clone(), "") {
             self.advance()
@@ -353,7 +1439,9 @@ impl Parser {
         if self.is_at_end() {
             Token { 
                 token_type: TokenType::Eol, 
-                value: "\n".to_string() 
+                value: "\n".to_string(),
+                line: 0,
+                column: 0,
             }
         } else {
             self.tokens[self.current].clone()
@@ -364,7 +1452,9 @@ impl Parser {
         if self.current + 1 >= self.tokens.len() {
             Token { 
                 token_type: TokenType::Eol, 
-                value: "\n".to_string() 
+                value: "\n".to_string(),
+                line: 0,
+                column: 0,
             }
         } else {
             self.tokens[self.current + 1].clone()
@@ -378,4 +1468,118 @@ impl Parser {
             self.tokens[self.current - 1].clone()
         }
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tokenizer::tokenize;
+
+    #[test]
+    fn test_comments_and_docstrings() {
+        let code = "\"\"\"Juego de ejemplo.\"\"\"\n# Posición inicial\nx = 100  # en píxeles\n\ndef mover(dx):\n    \"\"\"Mueve el rectángulo.\n\n    Usa dx.\n    \"\"\"\n";
+        let state = extract_state(tokenize(code));
+
+        assert_eq!(state.module_docstring.as_deref(), Some("Juego de ejemplo."));
+        assert_eq!(state.comments, vec![
+            Comment { text: "Posición inicial".to_string(), line: 3, trailing: false },
+            Comment { text: "en píxeles".to_string(), line: 3, trailing: true },
+        ]);
+        assert_eq!(state.functions[0].function.docstring.as_deref(), Some("Mueve el rectángulo.\n\nUsa dx."));
+    }
+
+    #[test]
+    fn test_window_arguments() {
+        let code = "window = sdl2.ext.Window(\"Ejemplo\", size=(ANCHO, ALTO), flags=sdl2.SDL_WINDOW_FULLSCREEN)\nwindow.show()\n";
+        let window = extract_state(tokenize(code)).window.unwrap();
+
+        assert_eq!(window.title, Some(Expr::Str("\"Ejemplo\"".to_string())));
+        assert_eq!(window.size, Some((Expr::Name("ANCHO".to_string()), Expr::Name("ALTO".to_string()))));
+        assert_eq!(window.position, None);
+        assert_eq!(window.flags, Some(vec!["SDL_WINDOW_FULLSCREEN".to_string()]));
+        assert_eq!(window.operations[0].method, "show");
+    }
+
+    #[test]
+    fn test_c_api_is_lowered() {
+        let code = "window = sdl2.SDL_CreateWindow(b\"Hola\", 10, 20, 640, 480, sdl2.SDL_WINDOW_SHOWN)\nrenderer = sdl2.SDL_CreateRenderer(window, -1, 0)\nsdl2.SDL_ShowWindow(window)\nsdl2.SDL_RenderFillRect(renderer, ctypes.byref(rect))\n";
+        let state = extract_state(tokenize(code));
+
+        let window = state.window.unwrap();
+        assert_eq!(window.title, Some(Expr::Str("\"Hola\"".to_string())));
+        assert_eq!(window.position, Some((Expr::Int(10), Expr::Int(20))));
+        assert_eq!(window.flags, Some(vec!["SDL_WINDOW_SHOWN".to_string()]));
+        assert_eq!(window.operations[0].method, "show");
+        assert_eq!(state.renderer.unwrap().index, Some(Expr::Int(-1)));
+        assert_eq!(state.draw_calls[0].method, "fill");
+        assert_eq!(state.draw_calls[0].args, vec![Expr::Name("rect".to_string())]);
+    }
+
+    #[test]
+    fn test_imports_resolve_to_qualified_names() {
+        let code = "import sdl2.ext as ext, math\nfrom sdl2.ext import (Renderer,\n    get_events as eventos)\nfrom sdl2 import *\nwindow = ext.Window(\"Hola\", size=(640, 480))\nrenderer = Renderer(window)\nfor event in eventos():\n    if event.type == SDL_QUIT:\n        running = False\n";
+        let state = extract_state(tokenize(code));
+
+        let modules: Vec<&str> = state.imports.iter().map(|i| i.module.as_str()).collect();
+        assert_eq!(modules, vec!["sdl2.ext", "math", "sdl2"]);
+        assert_eq!(state.window.unwrap().title, Some(Expr::Str("\"Hola\"".to_string())));
+        assert_eq!(state.renderer.unwrap().variable, "renderer");
+        let events = state.events.expect("bucle de eventos");
+        assert_eq!(events.handlers[0].event_type, "SDL_QUIT");
+    }
+
+    #[test]
+    fn test_blocks_end_with_indentation() {
+        let code = "renderer = sdl2.ext.Renderer(window)\ndef punto(renderer, x):\n    renderer.draw_point((x, 0))\nwhile running:\n    if x > 1:\n        x = 0\n    renderer.present()\nsdl2.ext.quit()\n";
+        let state = extract_state(tokenize(code));
+
+        let methods: Vec<&str> = state.draw_calls.iter().map(|c| c.method.as_str()).collect();
+        assert_eq!(methods, vec!["present"]);
+        assert_eq!(state.control_flow.len(), 2);
+    }
+
+    #[test]
+    fn test_event_handlers() {
+        let code = "for event in sdl2.ext.get_events():\n    if event.type == sdl2.SDL_QUIT:\n        running = False\n    elif event.type == sdl2.SDL_KEYDOWN and event.key.keysym.sym == sdl2.SDLK_UP:\n        y -= 1\n";
+        let state = extract_state(tokenize(code));
+        let events = state.events.expect("bucle de eventos");
+
+        assert_eq!(events.variable, "event");
+        let types: Vec<&str> = events.handlers.iter().map(|h| h.event_type.as_str()).collect();
+        assert_eq!(types, vec!["SDL_QUIT", "SDL_KEYDOWN"]);
+        assert!(events.handlers[1].chained);
+        assert_eq!(events.handlers[1].guard, Some(Expr::BinOp {
+            op: "==".to_string(),
+            left: Box::new(Expr::Attr("event.key.keysym.sym".to_string())),
+            right: Box::new(Expr::Attr("sdl2.SDLK_UP".to_string())),
+        }));
+        assert!(matches!(&events.handlers[1].body[0].kind, StmtKind::AugAssign { op, .. } if op == "-"));
+    }
+
+    #[test]
+    fn test_class_definitions() {
+        let code = "class Player(object):\n    \"\"\"Jugador.\"\"\"\n    VIDAS = 3\n    def __init__(self, x: int, y=0.5):\n        self.x = x\n    @staticmethod\n    def limite() -> int:\n        return 10\n    @classmethod\n    def crear(cls):\n        return cls(1)\n";
+        let state = extract_module_state(tokenize(code), "");
+
+        let class = &state.classes[0];
+        assert_eq!(class.name, "Player");
+        assert!(class.bases.is_empty());
+        assert_eq!(class.docstring.as_deref(), Some("Jugador."));
+        assert_eq!(class.attributes[0].name, "VIDAS");
+        let kinds: Vec<MethodKind> = class.methods.iter().map(|m| m.kind).collect();
+        assert_eq!(kinds, vec![MethodKind::Instance, MethodKind::Static, MethodKind::Class]);
+        let init = &class.methods[0].function;
+        assert_eq!(init.params[1].annotation.as_deref(), Some("int"));
+        assert_eq!(init.params[2].default, Some(Expr::Float(0.5)));
+        assert_eq!(class.methods[1].function.returns.as_deref(), Some("int"));
+        assert!(matches!(&class.methods[1].function.body[0].kind, StmtKind::Return(Some(Expr::Int(10)))));
+
+        let code = "class Enemy(Sprite, metaclass=Meta):\n    def __init__(self, x):\n        super().__init__(x)\n";
+        let state = extract_module_state(tokenize(code), "");
+        let class = &state.classes[0];
+        assert_eq!(class.bases, vec!["Sprite".to_string()]);
+        assert_eq!(class.metaclass.as_deref(), Some("Meta"));
+        assert!(matches!(&class.methods[0].function.body[0].kind,
+            StmtKind::Expr(Expr::Call { function, args }) if function == "super.__init__" && args.len() == 1));
+    }
+}
@@ -0,0 +1,341 @@
+// Proyectos de varios archivos: desde el script de entrada se siguen los imports
+// de módulos locales (`import player`, `from levels import nivel1`,
+// `from . import comun`) y cada uno pasa a ser un módulo Rust: `player.py` es
+// `src/player.rs` y el paquete `levels/` es `src/levels/mod.rs`.
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::classes;
+use crate::imports;
+use crate::parser;
+use crate::tokenizer;
+use crate::types::{Expr, State};
+
+// Scripts de entrada que se buscan si se indica un directorio
+const ENTRY_SCRIPTS: [&str; 2] = ["main.py", "__main__.py"];
+
+pub struct Module {
+    // Nombre Python (`levels.nivel1`); el del script de entrada es su nombre de archivo
+    pub name: String,
+    // Archivo relativo a la raíz; None en un paquete sin `__init__.py`
+    pub file: Option<PathBuf>,
+    pub source: String,
+    pub state: State,
+    // Un paquete es un directorio con `mod.rs`
+    pub package: bool,
+}
+
+// Ciclo de imports (`player`, `enemigo`, `player`) y la línea del import que
+// lo cierra, en el penúltimo módulo.
+pub struct ImportCycle {
+    pub cycle: Vec<String>,
+    pub line: usize,
+}
+
+impl ImportCycle {
+    // Módulo con el import que cierra el ciclo
+    pub fn closing_module(&self) -> &str {
+        &self.cycle[self.cycle.len() - 2]
+    }
+}
+
+pub struct Project {
+    // Directorio desde el que se buscan los módulos
+    pub root: PathBuf,
+    // El script de entrada va primero
+    pub modules: Vec<Module>,
+    pub cycles: Vec<ImportCycle>,
+}
+
+impl Module {
+    // Archivo Rust dentro de `src/`
+    pub fn rust_path(&self, entry: bool) -> PathBuf {
+        if entry {
+            return PathBuf::from("main.rs");
+        }
+        let path: PathBuf = self.name.split('.').collect();
+        if self.package { path.join("mod.rs") } else { path.with_extension("rs") }
+    }
+
+    // Nombre del archivo Python para los diagnósticos
+    pub fn python_file(&self) -> String {
+        match &self.file {
+            Some(file) => file.to_string_lossy().into_owned(),
+            None => format!("{}/", self.name.replace('.', "/")),
+        }
+    }
+}
+
+impl Project {
+    // `input` es un script o un directorio con `main.py` o `__main__.py`.
+    pub fn load(input: &Path) -> Result<Project, String> {
+        let (root, entry) = if input.is_dir() {
+            let entry = ENTRY_SCRIPTS
+                .iter()
+                .find(|name| input.join(name).is_file())
+                .ok_or_else(|| format!("No se encontró {} en {}", ENTRY_SCRIPTS.join(" ni "), input.display()))?;
+            (input.to_path_buf(), PathBuf::from(entry))
+        } else {
+            let root = input.parent().unwrap_or(Path::new("")).to_path_buf();
+            let entry = input.file_name().map(PathBuf::from).ok_or("Error al extraer el nombre del archivo base")?;
+            (root, entry)
+        };
+        let source = fs::read_to_string(root.join(&entry))
+            .map_err(|e| format!("Error al leer el archivo Python: {}", e))?;
+        let read = |path: &Path| fs::read_to_string(root.join(path)).ok();
+        Ok(Project::build(root.clone(), entry, source, &read))
+    }
+
+    fn build(root: PathBuf, entry: PathBuf, source: String, read: &dyn Fn(&Path) -> Option<String>) -> Project {
+        let name = entry.file_stem().map(|stem| stem.to_string_lossy().into_owned()).unwrap_or_default();
+        let state = parser::extract_module_state(tokenizer::tokenize(&source), "");
+        let entry_module = Module { name, file: Some(entry), source, state, package: false };
+        let mut project = Project { root, modules: vec![entry_module], cycles: Vec::new() };
+
+        // Imports locales de cada módulo: (módulo importado, línea)
+        let mut edges: Vec<Vec<(usize, usize)>> = Vec::new();
+        let mut next = 0;
+        while next < project.modules.len() {
+            let imports: Vec<(String, Vec<String>, usize)> = project.modules[next].state.imports
+                .iter()
+                .map(|import| (import.module.clone(), import.names.clone(), import.line))
+                .collect();
+            let mut targets = Vec::new();
+            for (module, names, line) in imports {
+                // `from levels import nivel1` puede traer el submódulo `levels.nivel1`
+                let candidates = std::iter::once(module.clone()).chain(names.iter().map(|name| imports::member(&module, name)));
+                for candidate in candidates.filter(|candidate| !candidate.is_empty()) {
+                    if let Some(index) = project.find_or_load(&candidate, read) {
+                        if !targets.contains(&(index, line)) {
+                            targets.push((index, line));
+                        }
+                    }
+                }
+            }
+            edges.push(targets);
+            next += 1;
+        }
+
+        project.cycles = find_cycles(&edges)
+            .into_iter()
+            .map(|(cycle, line)| ImportCycle { cycle: cycle.iter().map(|&i| project.modules[i].name.clone()).collect(), line })
+            .collect();
+        project.link_types();
+        project.link_classes();
+        project.declare_modules();
+        project
+    }
+
+    // Índice del módulo local `name`, cargándolo (con sus paquetes padre) si
+    // hace falta; None si no es un módulo del proyecto.
+    fn find_or_load(&mut self, name: &str, read: &dyn Fn(&Path) -> Option<String>) -> Option<usize> {
+        if let Some(index) = self.modules.iter().skip(1).position(|module| module.name == name) {
+            return Some(index + 1);
+        }
+        // El script de entrada no es un módulo de la raíz: `import main` no se sigue
+        if self.modules[0].file.as_deref() == Some(Path::new(&format!("{}.py", name))) {
+            return None;
+        }
+        let path: PathBuf = name.split('.').collect();
+        let (file, source, package) = [(path.with_extension("py"), false), (path.join("__init__.py"), true)]
+            .into_iter()
+            .find_map(|(file, package)| read(&file).map(|source| (file, source, package)))?;
+        // Los paquetes padre, aunque no tengan `__init__.py`
+        if let Some((parent, _)) = name.rsplit_once('.') {
+            if self.find_or_load(parent, read).is_none() {
+                self.add(Module { name: parent.to_string(), file: None, source: String::new(), state: empty_state(), package: true });
+            }
+        }
+        // Los imports relativos se resuelven desde el paquete del módulo
+        let package_name = if package { name } else { name.rsplit_once('.').map_or("", |(parent, _)| parent) };
+        let state = parser::extract_module_state(tokenizer::tokenize(&source), package_name);
+        Some(self.add(Module { name: name.to_string(), file: Some(file), source, state, package }))
+    }
+
+    fn add(&mut self, module: Module) -> usize {
+        self.modules.push(module);
+        self.modules.len() - 1
+    }
+
+    // Si `name` es un módulo del proyecto.
+    pub fn is_local(&self, name: &str) -> bool {
+        self.modules.iter().skip(1).any(|module| module.name == name)
+    }
+
+    // `x = player.VELOCIDAD`: la variable tiene el tipo de la constante del otro módulo.
+    fn link_types(&mut self) {
+        let mut types = HashMap::new();
+        for module in self.modules.iter().skip(1) {
+            for var in module.state.variables.iter().filter(|var| var.tipo != "unknown") {
+                types.entry(format!("{}.{}", module.name, var.name)).or_insert_with(|| var.tipo.clone());
+            }
+        }
+        for module in &mut self.modules {
+            for var in module.state.variables.iter_mut().filter(|var| var.tipo == "unknown") {
+                if let Some(Expr::Attr(path)) = &var.value {
+                    if let Some(tipo) = types.get(path) {
+                        var.tipo = tipo.clone();
+                    }
+                }
+            }
+        }
+    }
+
+    // Las clases se enlazan con las de todos los módulos: `player.Player(1)` en
+    // el script de entrada da el tipo de los parámetros de `Player`.
+    fn link_classes(&mut self) {
+        let mut states: Vec<(&str, &mut State)> = self.modules
+            .iter_mut()
+            .enumerate()
+            .map(|(index, module)| (if index == 0 { "" } else { module.name.as_str() }, &mut module.state))
+            .collect();
+        classes::link(&mut states);
+    }
+
+    // Cada módulo declara sus hijos: el script de entrada los de la raíz y un
+    // paquete los de su directorio.
+    fn declare_modules(&mut self) {
+        let names: Vec<String> = self.modules.iter().skip(1).map(|module| module.name.clone()).collect();
+        for name in names {
+            let (parent, child) = match name.rsplit_once('.') {
+                Some((parent, child)) => (self.modules.iter().skip(1).position(|m| m.name == parent).map(|i| i + 1), child),
+                None => (Some(0), name.as_str()),
+            };
+            if let Some(parent) = parent {
+                self.modules[parent].state.modules.push(child.to_string());
+            }
+        }
+        for module in &mut self.modules {
+            module.state.modules.sort();
+        }
+    }
+
+    // Módulos de la raíz que usa un módulo que no es el de entrada: en Rust se
+    // traen con `use crate::player;`.
+    pub fn crate_uses(&self, index: usize) -> Vec<String> {
+        let mut uses: Vec<String> = self.modules[index].state.imports
+            .iter()
+            .flat_map(|import| std::iter::once(import.module.clone()).chain(import.names.iter().map(|name| imports::member(&import.module, name))))
+            .filter(|name| self.is_local(name))
+            .filter_map(|name| name.split('.').next().map(String::from))
+            .collect();
+        uses.sort();
+        uses.dedup();
+        uses
+    }
+}
+
+fn empty_state() -> State {
+    parser::extract_state(Vec::new())
+}
+
+// Ciclos del grafo de imports, cada uno una sola vez, con la línea del import
+// que lo cierra.
+fn find_cycles(edges: &[Vec<(usize, usize)>]) -> Vec<(Vec<usize>, usize)> {
+    fn visit(node: usize, edges: &[Vec<(usize, usize)>], stack: &mut Vec<usize>, done: &mut Vec<bool>, cycles: &mut Vec<(Vec<usize>, usize)>) {
+        stack.push(node);
+        for &(target, line) in &edges[node] {
+            if let Some(start) = stack.iter().position(|&n| n == target) {
+                let mut cycle = stack[start..].to_vec();
+                cycle.push(target);
+                let mut members = cycle[1..].to_vec();
+                members.sort();
+                let seen = cycles.iter().any(|(other, _)| {
+                    let mut other = other[1..].to_vec();
+                    other.sort();
+                    other == members
+                });
+                if !seen {
+                    cycles.push((cycle, line));
+                }
+            } else if !done[target] {
+                visit(target, edges, stack, done, cycles);
+            }
+        }
+        stack.pop();
+        done[node] = true;
+    }
+
+    let mut cycles = Vec::new();
+    let mut done = vec![false; edges.len()];
+    for node in 0..edges.len() {
+        if !done[node] {
+            visit(node, edges, &mut Vec::new(), &mut done, &mut cycles);
+        }
+    }
+    cycles
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use crate::generator;
+
+    #[test]
+    fn test_module_tree_and_cycles() {
+        let files = HashMap::from([
+            ("player.py", "import enemigo\nVELOCIDAD = 5\n"),
+            ("enemigo.py", "from player import VELOCIDAD\n"),
+            ("levels/nivel1.py", "from . import comun\nANCHO = 20\n"),
+            ("levels/comun.py", "\"\"\"Comun.\"\"\"\n"),
+        ]);
+        let read = |path: &Path| files.get(path.to_str()?).map(|source| source.to_string());
+        let source = "import player\nfrom levels import nivel1\nx = player.VELOCIDAD\n".to_string();
+        let project = Project::build(PathBuf::new(), PathBuf::from("main.py"), source, &read);
+
+        let names: Vec<&str> = project.modules.iter().map(|m| m.name.as_str()).collect();
+        assert_eq!(names, vec!["main", "player", "levels", "levels.nivel1", "enemigo", "levels.comun"]);
+        assert_eq!(project.modules[0].state.modules, vec!["enemigo", "levels", "player"]);
+        assert_eq!(project.modules[2].state.modules, vec!["comun", "nivel1"]);
+        assert_eq!(project.modules[2].rust_path(false), PathBuf::from("levels/mod.rs"));
+        assert_eq!(project.modules[3].rust_path(false), PathBuf::from("levels/nivel1.rs"));
+        assert_eq!(project.modules[0].state.variables[0].tipo, "int");
+        assert_eq!(project.crate_uses(4), vec!["player"]);
+
+        assert_eq!(project.cycles.len(), 1);
+        assert_eq!(project.cycles[0].cycle, vec!["player", "enemigo", "player"]);
+        assert_eq!(project.cycles[0].line, 1);
+    }
+
+    #[test]
+    fn test_module_functions_and_constants() {
+        let files = HashMap::from([
+            ("niveles/__init__.py", ""),
+            ("niveles/nivel1.py", "VELOCIDAD = 3\nfondo = [0, 0]\ndef doble(x):\n    \"\"\"Devuelve el doble.\"\"\"\n    return x * 2\ndef triple(x):\n    return doble(x) + x\ndef sumar(x):\n    return fondo[0] + x\ndef mitad(x):\n    return x\n"),
+            ("player.py", "from niveles import nivel1\nclass Player:\n    def __init__(self, x):\n        self.x = x\n    def mover(self, dx):\n        self.x += dx\n    def saltar(self):\n        self.x = nivel1.doble(self.x)\n"),
+        ]);
+        let read = |path: &Path| files.get(path.to_str()?).map(|source| source.to_string());
+        let source = "from niveles import nivel1\nfrom niveles.nivel1 import VELOCIDAD, triple\nfrom player import Player\np = Player(1)\nn = triple(3)\nwhile running:\n    p.mover(VELOCIDAD)\n    nivel1.sumar(2)\n".to_string();
+        let mut project = Project::build(PathBuf::new(), PathBuf::from("main.py"), source, &read);
+        let config = Config::default();
+        let generate = |project: &Project, name: &str| {
+            let index = project.modules.iter().position(|m| m.name == name).unwrap();
+            generator::generate_module(&project.modules[index].state, &config, "m.py", &project.crate_uses(index))
+        };
+
+        let nivel1 = generate(&project, "niveles.nivel1");
+        assert!(nivel1.code.contains("/// Devuelve el doble.\npub fn doble(x: i32) -> i32 {\n    x * 2\n}\n"));
+        assert!(nivel1.code.contains("pub fn triple(x: i32) -> i32 {\n    doble(x) + x\n}\n"));
+        assert!(nivel1.code.contains("// sin traducción: def sumar(x)"));
+        assert!(nivel1.code.contains("// sin traducción: def mitad(x)"));
+        let messages: Vec<&str> = nivel1.warnings.iter().map(|warning| warning.message.as_str()).collect();
+        assert_eq!(messages, vec![
+            "sumar no se traduce: usa `fondo`, una variable del módulo que no es constante",
+            "no se pudo deducir el tipo de `x` en mitad",
+        ]);
+
+        let player = generate(&project, "player");
+        assert!(player.code.contains("    pub fn mover(&mut self, dx: i32) {\n"));
+        assert!(player.code.contains("        self.x = niveles::nivel1::doble(self.x);\n"));
+
+        project.modules[0].state.modules = vec!["niveles".to_string(), "player".to_string()];
+        let main = generator::generate_rust(&project.modules[0].state, &config, "main.py");
+        assert!(main.code.contains("let mut n: i32 = niveles::nivel1::triple(3);"));
+        assert!(main.code.contains("p.mover(niveles::nivel1::VELOCIDAD);"));
+        assert!(main.code.contains("// sin traducción (línea 8)"));
+        let messages: Vec<(&str, Option<usize>)> = main.warnings.iter().map(|warning| (warning.message.as_str(), warning.python_line)).collect();
+        assert!(messages.contains(&("no se pudo traducir la llamada a `niveles.nivel1.sumar`: se omite su sentencia", Some(8))));
+    }
+}
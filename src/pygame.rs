@@ -0,0 +1,602 @@
+// Frontend de pygame: cuando el script importa `pygame`, sus llamadas se
+// reescriben como las de PySDL2 que el resto del parser ya sabe traducir, igual
+// que la API C en `capi.rs`. La superficie de `pygame.display.set_mode` pasa a
+// ser el renderer y la ventana se crea aparte.
+use crate::keys;
+use crate::mappings::ApiMapping;
+use crate::parser::ASTNode;
+
+// Funciones de pygame con una equivalente de PySDL2 que recibe los mismos argumentos
+const RENAMED_FUNCTIONS: [(&str, &str); 13] = [
+    ("pygame.init", "sdl2.ext.init"),
+    ("pygame.quit", "sdl2.ext.quit"),
+    ("pygame.event.get", "sdl2.ext.get_events"),
+    ("pygame.time.get_ticks", "sdl2.SDL_GetTicks"),
+    ("pygame.time.delay", "sdl2.SDL_Delay"),
+    ("pygame.time.wait", "sdl2.SDL_Delay"),
+    ("pygame.font.Font", "sdl2.sdlttf.TTF_OpenFont"),
+    ("pygame.mixer.Sound", "sdl2.sdlmixer.Mix_LoadWAV"),
+    ("pygame.mixer.quit", "sdl2.sdlmixer.Mix_CloseAudio"),
+    ("pygame.mixer.music.stop", "sdl2.sdlmixer.Mix_HaltMusic"),
+    ("pygame.mixer.music.pause", "sdl2.sdlmixer.Mix_PauseMusic"),
+    ("pygame.mixer.music.unpause", "sdl2.sdlmixer.Mix_ResumeMusic"),
+    ("pygame.mixer.music.rewind", "sdl2.sdlmixer.Mix_RewindMusic"),
+];
+
+// Inicializaciones que en la traducción ya hace `main`
+const IGNORED_FUNCTIONS: [&str; 3] = ["pygame.display.init", "pygame.font.init", "pygame.mixer.pre_init"];
+
+// Constantes de pygame (o de `pygame.locals`) y su nombre en PySDL2; las
+// teclas `K_*` pasan a `SDLK_*` y los modificadores `KMOD_*` se llaman igual
+const CONSTANTS: [(&str, &str); 19] = [
+    ("QUIT", "SDL_QUIT"),
+    ("KEYDOWN", "SDL_KEYDOWN"),
+    ("KEYUP", "SDL_KEYUP"),
+    ("MOUSEMOTION", "SDL_MOUSEMOTION"),
+    ("MOUSEBUTTONDOWN", "SDL_MOUSEBUTTONDOWN"),
+    ("MOUSEBUTTONUP", "SDL_MOUSEBUTTONUP"),
+    ("MOUSEWHEEL", "SDL_MOUSEWHEEL"),
+    ("TEXTINPUT", "SDL_TEXTINPUT"),
+    ("TEXTEDITING", "SDL_TEXTEDITING"),
+    ("BUTTON_LEFT", "SDL_BUTTON_LEFT"),
+    ("BUTTON_MIDDLE", "SDL_BUTTON_MIDDLE"),
+    ("BUTTON_RIGHT", "SDL_BUTTON_RIGHT"),
+    ("FULLSCREEN", "SDL_WINDOW_FULLSCREEN"),
+    ("RESIZABLE", "SDL_WINDOW_RESIZABLE"),
+    ("NOFRAME", "SDL_WINDOW_BORDERLESS"),
+    ("HIDDEN", "SDL_WINDOW_HIDDEN"),
+    ("SHOWN", "SDL_WINDOW_SHOWN"),
+    ("OPENGL", "SDL_WINDOW_OPENGL"),
+    ("SCALED", "SDL_WINDOW_ALLOW_HIGHDPI"),
+];
+
+// Submódulos y clases que trae `from pygame import *`
+const NAMES: [&str; 12] = ["display", "draw", "event", "time", "font", "mixer", "image", "key", "Surface", "Rect", "init", "quit"];
+
+// Atributos de los eventos de pygame y su ruta en PySDL2 según el tipo de
+// evento (por prefijo); `pos` y `rel` son tuplas de dos campos
+const EVENT_FIELDS: [(&str, &str, &[&str]); 10] = [
+    ("SDL_KEY", "key", &["key.keysym.sym"]),
+    ("SDL_KEY", "mod", &["key.keysym.mod"]),
+    ("SDL_KEY", "scancode", &["key.keysym.scancode"]),
+    ("SDL_MOUSEBUTTON", "button", &["button.button"]),
+    ("SDL_MOUSEBUTTON", "pos", &["button.x", "button.y"]),
+    ("SDL_MOUSEMOTION", "pos", &["motion.x", "motion.y"]),
+    ("SDL_MOUSEMOTION", "rel", &["motion.xrel", "motion.yrel"]),
+    ("SDL_MOUSEWHEEL", "x", &["wheel.x"]),
+    ("SDL_MOUSEWHEEL", "y", &["wheel.y"]),
+    ("SDL_TEXTINPUT", "text", &["text.text"]),
+];
+
+// Números de `event.button` en pygame
+const MOUSE_BUTTONS: [(i32, &str); 3] = [(1, "SDL_BUTTON_LEFT"), (2, "SDL_BUTTON_MIDDLE"), (3, "SDL_BUTTON_RIGHT")];
+
+// Variable de `pygame.mixer.music.load`: pygame tiene una sola música cargada
+const MUSIC_VARIABLE: &str = "music";
+
+// Variables del script que pygame trata como objetos: se conocen al asignarse
+// y sus métodos se traducen después.
+#[derive(Default)]
+pub(crate) struct Pygame {
+    // Superficie de `pygame.display.set_mode`, que hace de renderer
+    screen: Option<String>,
+    window: Option<String>,
+    clocks: Vec<String>,
+    sounds: Vec<String>,
+    fonts: Vec<String>,
+    // Superficies que hay que convertir en textura para dibujarlas
+    surfaces: Vec<String>,
+}
+
+// Argumento que marca una llamada de dibujo que no dibuja lo mismo que en
+// pygame; el generador avisa con su valor (`polygon`, `circle`, `line`)
+pub(crate) const APPROXIMATION: &str = "pygame_approximation";
+
+fn approximate(node: Option<ASTNode>, what: &str) -> Option<ASTNode> {
+    let mut node = node?;
+    if let ASTNode::SDL2Call { kwargs, .. } | ASTNode::Call { kwargs, .. } = &mut node {
+        kwargs.push((APPROXIMATION.to_string(), ASTNode::StringLiteral(what.to_string())));
+    }
+    Some(node)
+}
+
+fn call(function: &str, args: Vec<ASTNode>, line: usize, column: usize) -> Option<ASTNode> {
+    Some(ASTNode::SDL2Call { function: function.to_string(), args, kwargs: Vec::new(), line, column })
+}
+
+fn constant(name: &str) -> ASTNode {
+    ASTNode::Attribute(format!("sdl2.{}", name))
+}
+
+// Argumento `index` o `name=` de una llamada.
+fn argument<'a>(args: &'a [ASTNode], kwargs: &'a [(String, ASTNode)], index: usize, name: &str) -> Option<&'a ASTNode> {
+    kwargs.iter().find(|(k, _)| k == name).map(|(_, v)| v).or(args.get(index))
+}
+
+// Volumen de pygame (0.0 a 1.0) en la escala de SDL_mixer (0 a 128).
+fn volume(value: &ASTNode) -> ASTNode {
+    match value {
+        ASTNode::Float(x) => ASTNode::Number((x * 128.0).round() as i32),
+        ASTNode::Number(n) => ASTNode::Number(n * 128),
+        value => ASTNode::BinaryOp { op: "*".to_string(), left: Box::new(value.clone()), right: Box::new(ASTNode::Number(128)) },
+    }
+}
+
+// Puntos de un polígono cerrado: el primero se repite al final.
+fn closed(points: &ASTNode) -> ASTNode {
+    match points {
+        ASTNode::List(items) | ASTNode::Tuple(items) if !items.is_empty() => {
+            ASTNode::List(items.iter().chain(items.first()).cloned().collect())
+        }
+        points => points.clone(),
+    }
+}
+
+impl ApiMapping for Pygame {
+    fn module(&self) -> &str {
+        "pygame"
+    }
+
+    // `from pygame.locals import *` trae las constantes; `from pygame import *`
+    // también los submódulos.
+    fn exports(&self, module: &str, name: &str) -> bool {
+        let constant = name.starts_with("K_") || name.starts_with("KMOD_") || CONSTANTS.iter().any(|(c, _)| *c == name);
+        match module {
+            "pygame.locals" => constant,
+            "pygame" => constant || NAMES.contains(&name),
+            _ => false,
+        }
+    }
+
+    // Constante de pygame con su ruta: `pygame.K_LEFT` o `pygame.locals.K_LEFT`.
+    fn lower_constant(&self, path: &str) -> Option<ASTNode> {
+        let name = path.strip_prefix("pygame.locals.").or_else(|| path.strip_prefix("pygame."))?;
+        if let Some(key) = name.strip_prefix("K_") {
+            return Some(constant(&format!("SDLK_{}", key)));
+        }
+        if name.starts_with("KMOD_") {
+            return Some(constant(name));
+        }
+        CONSTANTS.iter().find(|(c, _)| *c == name).map(|(_, sdl)| constant(sdl))
+    }
+
+    // `teclas[pygame.K_LEFT]` sobre `pygame.key.get_pressed()`: el estado del
+    // teclado se consulta por scancode.
+    fn lower_index(&self, index: &ASTNode) -> Option<ASTNode> {
+        let ASTNode::Attribute(path) = index else {
+            return None;
+        };
+        path.strip_prefix("sdl2.").and_then(keys::scancode_of).map(constant)
+    }
+
+    // Asignación de un objeto de pygame: registra la variable y devuelve lo que
+    // sustituye a la asignación, o None si se queda como está.
+    fn lower_assignment(&mut self, target: Option<&str>, value: &ASTNode, line: usize, column: usize) -> Option<ASTNode> {
+        let name = target?;
+        let (ASTNode::Call { function, args, kwargs, .. } | ASTNode::SDL2Call { function, args, kwargs, .. }) = value else {
+            return None;
+        };
+        let function_name = function.rsplit('.').next().unwrap_or(function);
+        match function.as_str() {
+            // set_mode(size=(0, 0), flags=0, depth=0, display=0, vsync=0)
+            "pygame.display.set_mode" => {
+                let window = if name == "window" { "display" } else { "window" };
+                self.screen = Some(name.to_string());
+                self.window = Some(window.to_string());
+                // pygame muestra la ventana al crearla
+                let mut flags = constant("SDL_WINDOW_SHOWN");
+                if let Some(extra) = argument(args, kwargs, 1, "flags").filter(|f| **f != ASTNode::Number(0)) {
+                    flags = ASTNode::BinaryOp { op: "|".to_string(), left: Box::new(flags), right: Box::new(extra.clone()) };
+                }
+                let mut window_kwargs = vec![("flags".to_string(), flags)];
+                if let Some(size) = argument(args, kwargs, 0, "size") {
+                    window_kwargs.insert(0, ("size".to_string(), size.clone()));
+                }
+                let mut renderer_kwargs = Vec::new();
+                if matches!(argument(args, kwargs, 4, "vsync"), Some(ASTNode::Number(1) | ASTNode::Boolean(true))) {
+                    let vsync = ASTNode::BinaryOp {
+                        op: "|".to_string(),
+                        left: Box::new(constant("SDL_RENDERER_ACCELERATED")),
+                        right: Box::new(constant("SDL_RENDERER_PRESENTVSYNC")),
+                    };
+                    renderer_kwargs.push(("flags".to_string(), vsync));
+                }
+                let window_value = ASTNode::SDL2Call {
+                    function: "sdl2.ext.Window".to_string(),
+                    args: vec![ASTNode::StringLiteral("\"pygame window\"".to_string())],
+                    kwargs: window_kwargs,
+                    line,
+                    column,
+                };
+                let renderer_value = ASTNode::SDL2Call {
+                    function: "sdl2.ext.Renderer".to_string(),
+                    args: vec![ASTNode::Identifier(window.to_string())],
+                    kwargs: renderer_kwargs,
+                    line,
+                    column,
+                };
+                Some(ASTNode::Program(vec![
+                    ASTNode::Assignment { name: window.to_string(), value: Box::new(window_value), line, column },
+                    ASTNode::Assignment { name: name.to_string(), value: Box::new(renderer_value), line, column },
+                ]))
+            }
+            // El reloj solo sirve para `tick`
+            "pygame.time.Clock" => {
+                self.clocks.push(name.to_string());
+                Some(ASTNode::Ignore)
+            }
+            _ => {
+                match function_name {
+                    "Mix_LoadWAV" => self.sounds.push(name.to_string()),
+                    "TTF_OpenFont" => self.fonts.push(name.to_string()),
+                    "SDL_CreateRGBSurface" => self.surfaces.push(name.to_string()),
+                    _ if function_name.starts_with("TTF_Render") => self.surfaces.push(name.to_string()),
+                    _ => {}
+                }
+                None
+            }
+        }
+    }
+
+    // Llamada de pygame como su equivalente de PySDL2; None si no lo tiene.
+    fn lower_call(&self, function: &str, args: &[ASTNode], kwargs: &[(String, ASTNode)], line: usize, column: usize) -> Option<ASTNode> {
+        // La fuente por defecto de pygame (`Font(None, size)`) no existe fuera de pygame
+        if function == "pygame.font.Font" && matches!(args.first(), Some(ASTNode::Identifier(n)) if n == "None") {
+            return None;
+        }
+        if let Some((_, renamed)) = RENAMED_FUNCTIONS.iter().find(|(f, _)| *f == function) {
+            return call(renamed, args.to_vec(), line, column);
+        }
+        if IGNORED_FUNCTIONS.contains(&function) {
+            return Some(ASTNode::Ignore);
+        }
+        let arg = |index: usize, name: &str| argument(args, kwargs, index, name).cloned();
+        let screen = self.screen.as_deref().unwrap_or("renderer");
+        // Las funciones de `pygame.draw` solo se traducen sobre la pantalla
+        let on_screen = matches!(args.first(), Some(ASTNode::Identifier(surface)) if Some(surface.as_str()) == self.screen.as_deref());
+        let method = |name: &str| format!("{}.{}", screen, name);
+
+        match function {
+            "pygame.key.get_pressed" => call("sdl2.SDL_GetKeyboardState", vec![ASTNode::Identifier("None".to_string())], line, column),
+            "pygame.display.set_caption" => {
+                let window = self.window.as_ref()?;
+                Some(ASTNode::Assignment { name: format!("{}.title", window), value: Box::new(arg(0, "title")?), line, column })
+            }
+            "pygame.display.flip" | "pygame.display.update" => call(&method("present"), Vec::new(), line, column),
+            // rect(surface, color, rect, width=0): sin grosor es un relleno
+            "pygame.draw.rect" if on_screen => {
+                let outline = arg(3, "width").is_some_and(|width| width != ASTNode::Number(0));
+                let name = if outline { "draw_rect" } else { "fill" };
+                call(&method(name), vec![arg(2, "rect")?, arg(1, "color")?], line, column)
+            }
+            // line(surface, color, start_pos, end_pos, width=1): sdl2 solo tiene líneas de un píxel
+            "pygame.draw.line" if on_screen => {
+                let points = ASTNode::List(vec![arg(2, "start_pos")?, arg(3, "end_pos")?]);
+                let lowered = call(&method("draw_line"), vec![points, arg(1, "color")?], line, column);
+                let wide = arg(4, "width").is_some_and(|width| width != ASTNode::Number(1));
+                if wide { approximate(lowered, "line") } else { lowered }
+            }
+            // lines(surface, color, closed, points, width=1)
+            "pygame.draw.lines" if on_screen => {
+                let points = arg(3, "points")?;
+                let points = if matches!(arg(2, "closed")?, ASTNode::Boolean(true)) { closed(&points) } else { points };
+                let lowered = call(&method("draw_line"), vec![points, arg(1, "color")?], line, column);
+                let wide = arg(4, "width").is_some_and(|width| width != ASTNode::Number(1));
+                if wide { approximate(lowered, "line") } else { lowered }
+            }
+            // Sin primitiva de relleno en sdl2, el polígono se dibuja con su contorno
+            "pygame.draw.polygon" if on_screen => {
+                approximate(call(&method("draw_line"), vec![closed(&arg(2, "points")?), arg(1, "color")?], line, column), "polygon")
+            }
+            // circle(surface, color, center, radius, width=0): `draw_circle` siempre rellena
+            "pygame.draw.circle" if on_screen => {
+                let args = vec![ASTNode::Identifier(screen.to_string()), arg(1, "color")?, arg(2, "center")?, arg(3, "radius")?];
+                let lowered = Some(ASTNode::Call { function: "draw_circle".to_string(), args, kwargs: Vec::new(), line, column });
+                let outline = arg(4, "width").is_some_and(|width| width != ASTNode::Number(0));
+                if outline { approximate(lowered, "circle") } else { lowered }
+            }
+            // Las imágenes se cargan directamente como texturas del renderer
+            "pygame.image.load" => {
+                call("sdl2.sdlimage.IMG_LoadTexture", vec![ASTNode::Identifier(screen.to_string()), arg(0, "file")?], line, column)
+            }
+            "pygame.Surface" => {
+                let ASTNode::Tuple(size) = arg(0, "size")? else {
+                    return None;
+                };
+                let [width, height] = size.as_slice() else {
+                    return None;
+                };
+                let mut args = vec![ASTNode::Number(0), width.clone(), height.clone(), ASTNode::Number(32)];
+                args.extend([0, 0, 0, 0].map(ASTNode::Number));
+                call("sdl2.SDL_CreateRGBSurface", args, line, column)
+            }
+            // init(frequency=44100, size=-16, channels=2, buffer=512)
+            "pygame.mixer.init" => {
+                let args = vec![
+                    arg(0, "frequency").unwrap_or(ASTNode::Number(44100)),
+                    ASTNode::Attribute("sdl2.sdlmixer.MIX_DEFAULT_FORMAT".to_string()),
+                    arg(2, "channels").unwrap_or(ASTNode::Number(2)),
+                    arg(3, "buffer").unwrap_or(ASTNode::Number(512)),
+                ];
+                call("sdl2.sdlmixer.Mix_OpenAudio", args, line, column)
+            }
+            "pygame.mixer.stop" => call("sdl2.sdlmixer.Mix_HaltChannel", vec![ASTNode::Number(-1)], line, column),
+            "pygame.mixer.music.load" => {
+                let value = call("sdl2.sdlmixer.Mix_LoadMUS", vec![arg(0, "filename")?], line, column)?;
+                Some(ASTNode::Assignment { name: MUSIC_VARIABLE.to_string(), value: Box::new(value), line, column })
+            }
+            "pygame.mixer.music.play" => {
+                let args = vec![ASTNode::Identifier(MUSIC_VARIABLE.to_string()), arg(0, "loops").unwrap_or(ASTNode::Number(0))];
+                call("sdl2.sdlmixer.Mix_PlayMusic", args, line, column)
+            }
+            "pygame.mixer.music.set_volume" => call("sdl2.sdlmixer.Mix_VolumeMusic", vec![volume(&arg(0, "volume")?)], line, column),
+            _ => self.lower_method(function, args, kwargs, line, column),
+        }
+    }
+
+    // Atributos de los eventos de pygame (`event.key`, `event.pos`...) en las
+    // ramas de cada tipo de evento del bucle `for event in pygame.event.get()`.
+    fn lower_event_body(&self, variable: &str, body: Vec<ASTNode>) -> Vec<ASTNode> {
+        body.into_iter().map(|node| branch_fields(variable, node, None)).collect()
+    }
+
+    // `pygame.image.load(..).convert()`: la textura ya tiene el formato del renderer
+    fn discards_method(&self, method: &str) -> bool {
+        ["convert", "convert_alpha"].contains(&method)
+    }
+}
+
+impl Pygame {
+
+    // Métodos de la pantalla, los relojes, los sonidos, las fuentes y las superficies.
+    fn lower_method(&self, function: &str, args: &[ASTNode], kwargs: &[(String, ASTNode)], line: usize, column: usize) -> Option<ASTNode> {
+        let (object, method) = function.rsplit_once('.')?;
+        let arg = |index: usize, name: &str| argument(args, kwargs, index, name).cloned();
+        let is = |objects: &[String]| objects.iter().any(|o| o == object);
+        let variable = || ASTNode::Identifier(object.to_string());
+
+        match method {
+            // fill(color, rect=None): sin rectángulo borra toda la pantalla
+            "fill" if self.screen.as_deref() == Some(object) => match arg(1, "rect") {
+                Some(rect) => call(&format!("{}.fill", object), vec![rect, arg(0, "color")?], line, column),
+                None => call(&format!("{}.clear", object), vec![arg(0, "color")?], line, column),
+            },
+            // blit(source, dest): las superficies pasan antes a una textura
+            "blit" if self.screen.as_deref() == Some(object) => {
+                let source = arg(0, "source")?;
+                let dest = arg(1, "dest")?;
+                let none = ASTNode::Identifier("None".to_string());
+                match &source {
+                    ASTNode::Identifier(surface) if self.surfaces.contains(surface) => {
+                        let texture = format!("{}_texture", surface);
+                        let value = call("sdl2.ext.Texture", vec![variable(), source.clone()], line, column)?;
+                        Some(ASTNode::Program(vec![
+                            ASTNode::Assignment { name: texture.clone(), value: Box::new(value), line, column },
+                            call(&format!("{}.copy", object), vec![ASTNode::Identifier(texture), none, dest], line, column)?,
+                        ]))
+                    }
+                    _ => call(&format!("{}.copy", object), vec![source, none, dest], line, column),
+                }
+            }
+            // tick(framerate=0): una pausa de un fotograma
+            "tick" if is(&self.clocks) => match arg(0, "framerate") {
+                Some(ASTNode::Number(fps)) if fps > 0 => call("sdl2.SDL_Delay", vec![ASTNode::Number(1000 / fps)], line, column),
+                Some(fps) => {
+                    let delay = ASTNode::BinaryOp { op: "//".to_string(), left: Box::new(ASTNode::Number(1000)), right: Box::new(fps) };
+                    call("sdl2.SDL_Delay", vec![delay], line, column)
+                }
+                None => Some(ASTNode::Ignore),
+            },
+            // play(loops=0)
+            "play" if is(&self.sounds) => {
+                let args = vec![ASTNode::Number(-1), variable(), arg(0, "loops").unwrap_or(ASTNode::Number(0))];
+                call("sdl2.sdlmixer.Mix_PlayChannel", args, line, column)
+            }
+            "stop" if is(&self.sounds) => call("sdl2.sdlmixer.Mix_HaltChannel", vec![ASTNode::Number(-1)], line, column),
+            "set_volume" if is(&self.sounds) => {
+                call("sdl2.sdlmixer.Mix_VolumeChunk", vec![variable(), volume(&arg(0, "value")?)], line, column)
+            }
+            // render(text, antialias, color, background=None)
+            "render" if is(&self.fonts) => {
+                let text = arg(0, "text")?;
+                let color = arg(2, "color")?;
+                let background = arg(3, "background").filter(|b| !matches!(b, ASTNode::Identifier(n) if n == "None"));
+                let (style, colors) = match (arg(1, "antialias")?, background) {
+                    (_, Some(background)) => ("Shaded", vec![color, background]),
+                    (ASTNode::Boolean(false), None) => ("Solid", vec![color]),
+                    _ => ("Blended", vec![color]),
+                };
+                let mut args = vec![variable(), text];
+                args.extend(colors);
+                call(&format!("sdl2.sdlttf.TTF_RenderUTF8_{}", style), args, line, column)
+            }
+            // fill(color, rect=None) sobre una superficie propia
+            "fill" if is(&self.surfaces) => {
+                let mut args = vec![variable(), arg(0, "color")?];
+                args.extend(arg(1, "rect"));
+                call("sdl2.ext.fill", args, line, column)
+            }
+            _ => None,
+        }
+    }
+}
+
+fn branch_fields(variable: &str, node: ASTNode, event_type: Option<&str>) -> ASTNode {
+    let rewrite = |node: &ASTNode, event_type: Option<&str>| map_attributes(node, &|node| field(variable, node, event_type?));
+    match node {
+        ASTNode::ControlFlow { control_type, condition: Some(condition), body, orelse, line, column } if control_type == "if" => {
+            let branch_type = compared_type(variable, &condition);
+            let inner = branch_type.as_deref().or(event_type);
+            ASTNode::ControlFlow {
+                control_type,
+                condition: Some(Box::new(rewrite(&condition, inner))),
+                body: body.into_iter().map(|n| branch_fields(variable, n, inner)).collect(),
+                orelse: orelse.into_iter().map(|n| branch_fields(variable, n, event_type)).collect(),
+                line,
+                column,
+            }
+        }
+        // `x, y = event.pos`: una asignación por componente
+        ASTNode::Assignment { name, value, line, column } => match rewrite(&value, event_type) {
+            ASTNode::Tuple(values) if name.split(", ").count() == values.len() && values.len() > 1 => {
+                ASTNode::Program(name.split(", ").zip(values)
+                    .map(|(name, value)| ASTNode::Assignment { name: name.to_string(), value: Box::new(value), line, column })
+                    .collect())
+            }
+            value => ASTNode::Assignment { name, value: Box::new(value), line, column },
+        },
+        node => rewrite(&node, event_type),
+    }
+}
+
+// Tipo de evento que compara la condición: `event.type == pygame.KEYDOWN`.
+fn compared_type(variable: &str, condition: &ASTNode) -> Option<String> {
+    match condition {
+        ASTNode::BinaryOp { op, left, right } if op == "==" => match (left.as_ref(), right.as_ref()) {
+            (ASTNode::Attribute(attr), ASTNode::Attribute(constant)) if *attr == format!("{}.type", variable) => {
+                constant.rsplit('.').next().map(String::from)
+            }
+            _ => None,
+        },
+        ASTNode::BinaryOp { op, left, right } if op == "and" => {
+            compared_type(variable, left).or_else(|| compared_type(variable, right))
+        }
+        _ => None,
+    }
+}
+
+fn field(variable: &str, node: &ASTNode, event_type: &str) -> Option<ASTNode> {
+    let paths = |name: &str| {
+        EVENT_FIELDS.iter()
+            .find(|(prefix, field, _)| event_type.starts_with(prefix) && *field == name)
+            .map(|(_, _, paths)| *paths)
+    };
+    match node {
+        ASTNode::Attribute(path) => {
+            let attribute = path.strip_prefix(variable)?.strip_prefix('.')?;
+            let fields: Vec<ASTNode> = paths(attribute)?.iter()
+                .map(|field| ASTNode::Attribute(format!("{}.{}", variable, field)))
+                .collect();
+            Some(if fields.len() == 1 { fields[0].clone() } else { ASTNode::Tuple(fields) })
+        }
+        // `event.button == 1`
+        ASTNode::BinaryOp { op, left, right } if op == "==" || op == "!=" => {
+            let ASTNode::Number(number) = right.as_ref() else {
+                return None;
+            };
+            let button = format!("{}.button", variable);
+            if !matches!(left.as_ref(), ASTNode::Attribute(path) if *path == button) || !event_type.starts_with("SDL_MOUSEBUTTON") {
+                return None;
+            }
+            let (_, name) = MOUSE_BUTTONS.iter().find(|(n, _)| n == number)?;
+            Some(ASTNode::BinaryOp { op: op.clone(), left: Box::new(field(variable, left, event_type)?), right: Box::new(constant(name)) })
+        }
+        _ => None,
+    }
+}
+
+// Copia de `node` con los nodos que `replace` sustituye, de fuera hacia dentro.
+fn map_attributes(node: &ASTNode, replace: &dyn Fn(&ASTNode) -> Option<ASTNode>) -> ASTNode {
+    if let Some(replaced) = replace(node) {
+        return replaced;
+    }
+    let map = |node: &ASTNode| map_attributes(node, replace);
+    let map_all = |nodes: &[ASTNode]| nodes.iter().map(map).collect::<Vec<_>>();
+    let map_kwargs = |kwargs: &[(String, ASTNode)]| kwargs.iter().map(|(k, v)| (k.clone(), map(v))).collect::<Vec<_>>();
+    match node {
+        ASTNode::Program(nodes) => ASTNode::Program(map_all(nodes)),
+        ASTNode::Assignment { name, value, line, column } => {
+            ASTNode::Assignment { name: name.clone(), value: Box::new(map(value)), line: *line, column: *column }
+        }
+        ASTNode::AugAssignment { name, op, value, line, column } => {
+            ASTNode::AugAssignment { name: name.clone(), op: op.clone(), value: Box::new(map(value)), line: *line, column: *column }
+        }
+        ASTNode::Tuple(items) => ASTNode::Tuple(map_all(items)),
+        ASTNode::List(items) => ASTNode::List(map_all(items)),
+        ASTNode::BinaryOp { op, left, right } => ASTNode::BinaryOp { op: op.clone(), left: Box::new(map(left)), right: Box::new(map(right)) },
+        ASTNode::UnaryOp { op, operand } => ASTNode::UnaryOp { op: op.clone(), operand: Box::new(map(operand)) },
+        ASTNode::Subscript { value, index } => ASTNode::Subscript { value: Box::new(map(value)), index: Box::new(map(index)) },
+        ASTNode::SDL2Call { function, args, kwargs, line, column } => ASTNode::SDL2Call {
+            function: function.clone(),
+            args: map_all(args),
+            kwargs: map_kwargs(kwargs),
+            line: *line,
+            column: *column,
+        },
+        ASTNode::Call { function, args, kwargs, line, column } => ASTNode::Call {
+            function: function.clone(),
+            args: map_all(args),
+            kwargs: map_kwargs(kwargs),
+            line: *line,
+            column: *column,
+        },
+        ASTNode::ControlFlow { control_type, condition, body, orelse, line, column } => ASTNode::ControlFlow {
+            control_type: control_type.clone(),
+            condition: condition.as_ref().map(|c| Box::new(map(c))),
+            body: map_all(body),
+            orelse: map_all(orelse),
+            line: *line,
+            column: *column,
+        },
+        ASTNode::For { target, iter, body, line, column } => ASTNode::For {
+            target: target.clone(),
+            iter: Box::new(map(iter)),
+            body: map_all(body),
+            line: *line,
+            column: *column,
+        },
+        node => node.clone(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lower_pygame_calls() {
+        let mut pygame = Pygame::default();
+        let screen = ASTNode::Identifier("screen".to_string());
+        let mode = ASTNode::Call {
+            function: "pygame.display.set_mode".to_string(),
+            args: vec![ASTNode::Tuple(vec![ASTNode::Number(640), ASTNode::Number(480)])],
+            kwargs: Vec::new(),
+            line: 2,
+            column: 1,
+        };
+        let Some(ASTNode::Program(assignments)) = pygame.lower_assignment(Some("screen"), &mode, 2, 1) else {
+            panic!("set_mode sin traducir");
+        };
+        assert!(matches!(&assignments[1], ASTNode::Assignment { name, value, .. }
+            if name == "screen" && matches!(value.as_ref(), ASTNode::SDL2Call { function, .. } if function == "sdl2.ext.Renderer")));
+
+        let red = ASTNode::Tuple(vec![ASTNode::Number(255), ASTNode::Number(0), ASTNode::Number(0)]);
+        let rect = ASTNode::Tuple(vec![ASTNode::Number(0), ASTNode::Number(0), ASTNode::Number(10), ASTNode::Number(10)]);
+        let fill = pygame.lower_call("pygame.draw.rect", &[screen.clone(), red.clone(), rect.clone()], &[], 5, 5);
+        assert_eq!(fill, call("screen.fill", vec![rect, red.clone()], 5, 5));
+        assert_eq!(pygame.lower_call("screen.fill", std::slice::from_ref(&red), &[], 6, 5), call("screen.clear", vec![red], 6, 5));
+        assert_eq!(pygame.lower_constant("pygame.K_LEFT"), Some(constant("SDLK_LEFT")));
+        assert_eq!(pygame.lower_index(&constant("SDLK_a")), Some(constant("SDL_SCANCODE_A")));
+    }
+
+    #[test]
+    fn test_event_fields() {
+        let attribute = |path: &str| ASTNode::Attribute(path.to_string());
+        let equals = |left: ASTNode, right: ASTNode| ASTNode::BinaryOp { op: "==".to_string(), left: Box::new(left), right: Box::new(right) };
+        let branch = ASTNode::ControlFlow {
+            control_type: "if".to_string(),
+            condition: Some(Box::new(equals(attribute("event.type"), attribute("sdl2.SDL_MOUSEBUTTONDOWN")))),
+            body: vec![ASTNode::Assignment { name: "x, y".to_string(), value: Box::new(attribute("event.pos")), line: 3, column: 9 }],
+            orelse: Vec::new(),
+            line: 2,
+            column: 5,
+        };
+        let Some(ASTNode::ControlFlow { body, .. }) = Pygame::default().lower_event_body("event", vec![branch]).pop() else {
+            panic!("rama sin traducir");
+        };
+        assert_eq!(body, vec![ASTNode::Program(vec![
+            ASTNode::Assignment { name: "x".to_string(), value: Box::new(attribute("event.button.x")), line: 3, column: 9 },
+            ASTNode::Assignment { name: "y".to_string(), value: Box::new(attribute("event.button.y")), line: 3, column: 9 },
+        ])]);
+    }
+}
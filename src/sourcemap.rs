@@ -0,0 +1,143 @@
+// Correspondencia entre líneas del main.rs generado y posiciones del script Python.
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct LineMapping {
+    pub rust_start: usize,
+    pub rust_end: usize,
+    pub python_line: usize,
+    pub python_column: usize,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SourceMap {
+    pub python_file: String,
+    pub mappings: Vec<LineMapping>,
+}
+
+impl SourceMap {
+    pub fn new(python_file: &str) -> Self {
+        SourceMap { python_file: python_file.to_string(), mappings: Vec::new() }
+    }
+
+    pub fn add(&mut self, rust_start: usize, rust_end: usize, python_line: usize, python_column: usize) {
+        if python_line == 0 || rust_end < rust_start {
+            return;
+        }
+        self.mappings.push(LineMapping { rust_start, rust_end, python_line, python_column });
+    }
+
+    // Mapeo más interno que contiene `rust_line`.
+    pub fn lookup(&self, rust_line: usize) -> Option<&LineMapping> {
+        self.mappings
+            .iter()
+            .filter(|m| m.rust_start <= rust_line && rust_line <= m.rust_end)
+            .min_by_key(|m| m.rust_end - m.rust_start)
+    }
+
+    pub fn python_line(&self, rust_line: usize) -> Option<usize> {
+        self.lookup(rust_line).map(|m| m.python_line)
+    }
+
+    // Formato Source Map v3: un segmento por línea generada con correspondencia,
+    // en la columna del primer carácter no blanco. Las columnas se cuentan en
+    // unidades UTF-16, como esperan los consumidores del formato.
+    pub fn to_v3_json(&self, generated_file: &str, generated_code: &str, python_code: &str) -> String {
+        let mut mappings = String::new();
+        let (mut prev_line, mut prev_column) = (0i64, 0i64);
+        let python_lines: Vec<&str> = python_code.lines().collect();
+
+        for (index, line) in generated_code.lines().enumerate() {
+            if index > 0 {
+                mappings.push(';');
+            }
+            let Some(mapping) = self.lookup(index + 1) else {
+                continue;
+            };
+            let generated_column = utf16_len(&line[..line.len() - line.trim_start().len()]) as i64;
+            let python_line = mapping.python_line as i64 - 1;
+            // Las columnas del tokenizador cuentan caracteres
+            let python_text = python_lines.get(mapping.python_line - 1).copied().unwrap_or("");
+            let prefix: String = python_text.chars().take(mapping.python_column.saturating_sub(1)).collect();
+            let python_column = utf16_len(&prefix) as i64;
+
+            encode_vlq(&mut mappings, generated_column);
+            encode_vlq(&mut mappings, 0);
+            encode_vlq(&mut mappings, python_line - prev_line);
+            encode_vlq(&mut mappings, python_column - prev_column);
+            prev_line = python_line;
+            prev_column = python_column;
+        }
+
+        format!(
+            "{{\"version\":3,\"file\":{},\"sources\":[{}],\"names\":[],\"mappings\":\"{}\"}}\n",
+            json_string(generated_file),
+            json_string(&self.python_file),
+            mappings
+        )
+    }
+}
+
+// Número (1-based) de la línea en la que caerá el próximo texto añadido a `code`.
+pub fn next_line(code: &str) -> usize {
+    code.matches('\n').count() + 1
+}
+
+fn utf16_len(text: &str) -> usize {
+    text.encode_utf16().count()
+}
+
+fn encode_vlq(out: &mut String, value: i64) {
+    const BASE64: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut vlq = if value < 0 { ((-value) << 1) | 1 } else { value << 1 };
+    loop {
+        let mut digit = vlq & 0b11111;
+        vlq >>= 5;
+        if vlq > 0 {
+            digit |= 0b100000;
+        }
+        out.push(BASE64[digit as usize] as char);
+        if vlq == 0 {
+            break;
+        }
+    }
+}
+
+fn json_string(value: &str) -> String {
+    serde_json::Value::String(value.to_string()).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_innermost_range_wins() {
+        let mut map = SourceMap::new("juego.py");
+        map.add(10, 40, 31, 1);
+        map.add(20, 22, 47, 5);
+        assert_eq!(map.python_line(21), Some(47));
+        assert_eq!(map.python_line(35), Some(31));
+        assert_eq!(map.python_line(5), None);
+    }
+
+    #[test]
+    fn test_v3_mappings() {
+        let mut map = SourceMap::new("juego.py");
+        map.add(2, 2, 12, 1);
+        map.add(3, 3, 11, 5);
+        let python = "x = 0\n".repeat(9) + "if x:\n    y = 1\n";
+        let json = map.to_v3_json("main.rs", "fn main() {\n    let x = 100;\n    let y = 200;\n}\n", &python);
+        // Línea 2: col 4 -> juego.py 11:0; línea 3: col 4 -> 10:4 (deltas -1 y +4)
+        assert!(json.contains("\"mappings\":\";IAWA;IADI;\""), "{}", json);
+        assert!(json.contains("\"sources\":[\"juego.py\"]"));
+    }
+
+    #[test]
+    fn test_v3_columns_are_utf16() {
+        let mut map = SourceMap::new("juego.py");
+        // `año` empieza en el carácter 8; 🎮 ocupa dos unidades UTF-16 y cuatro bytes
+        map.add(1, 1, 1, 8);
+        let json = map.to_v3_json("main.rs", "let año = 2;\n", "🎮 = 1; año = 2\n");
+        assert!(json.contains("\"mappings\":\"AAAQ\""), "{}", json);
+    }
+}
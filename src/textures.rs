@@ -0,0 +1,259 @@
+// Texturas, superficies y fuentes de PySDL2 (`SpriteFactory.from_image`,
+// `sdl2.ext.load_image`, `sdl2.ext.Texture`, `sdl2.ext.FontTTF`...) y los
+// archivos que cargan. Las rutas relativas son relativas al script y se copian
+// al proyecto generado con la misma ruta.
+use std::collections::HashSet;
+
+use crate::audio;
+use crate::types::{Expr, State, Stmt, StmtKind};
+
+// Funciones que cargan una textura de un archivo y la posición de la ruta
+const TEXTURE_LOADERS: [(&str, usize); 2] = [
+    ("from_image", 0),
+    ("IMG_LoadTexture", 1),
+];
+
+// Funciones que cargan una superficie de un archivo y la posición de la ruta
+const SURFACE_LOADERS: [(&str, usize); 5] = [
+    ("load_image", 0),
+    ("load_img", 0),
+    ("load_bmp", 0),
+    ("IMG_Load", 0),
+    ("SDL_LoadBMP", 0),
+];
+
+// Funciones que crean una textura a partir de una superficie y su posición
+const SURFACE_TEXTURES: [(&str, usize); 3] = [
+    ("from_surface", 0),
+    ("Texture", 1),
+    ("SDL_CreateTextureFromSurface", 1),
+];
+
+// Funciones que abren una fuente y la posición de la ruta y del tamaño
+const FONT_LOADERS: [(&str, usize, usize); 2] = [
+    ("FontTTF", 0, 1),
+    ("TTF_OpenFont", 0, 1),
+];
+
+// Estilos de `TTF_RenderUTF8_*`/`TTF_RenderText_*` y su método en `PartialRendering`
+const TEXT_STYLES: [(&str, &str); 4] = [
+    ("Solid", "solid"),
+    ("Shaded", "shaded"),
+    ("Blended", "blended"),
+    ("Blended_Wrapped", "blended_wrapped"),
+];
+
+// `sdl2.ext.fill(superficie, color, area=None)` rellena una superficie
+const FILL_FUNCTION: &str = "ext.fill";
+
+// Funciones que liberan una superficie, textura o fuente: en Rust, `drop`
+const RELEASE_FUNCTIONS: [&str; 3] = ["SDL_FreeSurface", "SDL_DestroyTexture", "TTF_CloseFont"];
+
+pub enum ImageValue<'a> {
+    // Textura cargada de un archivo: la ruta
+    Texture(&'a Expr),
+    // Superficie cargada de un archivo: la ruta
+    Surface(&'a Expr),
+    // Superficie vacía de `SDL_CreateRGBSurface(flags, ancho, alto, ...)`
+    Blank { width: &'a Expr, height: &'a Expr },
+    // Textura creada a partir de una superficie
+    FromSurface(&'a Expr),
+    // `TextureSprite(textura)`: la misma textura
+    Sprite(&'a Expr),
+    // Fuente cargada de un archivo
+    Font { path: &'a Expr, size: Option<&'a Expr> },
+    // Superficie con un texto: `fuente.render_text(texto)` usa el color de la
+    // fuente; `TTF_Render*` lleva los colores detrás del texto
+    Text { font: &'a str, text: &'a Expr, style: &'static str, colors: &'a [Expr] },
+}
+
+// Qué imagen crea `value`, o None si no es una llamada de texturas.
+pub fn image_value(value: &Expr) -> Option<ImageValue<'_>> {
+    let Expr::Call { function, args } = value else {
+        return None;
+    };
+    let name = function.rsplit('.').next().unwrap_or(function);
+    let arg = |table: &[(&str, usize)]| table.iter().find(|(n, _)| *n == name).and_then(|(_, i)| args.get(*i));
+    if let Some(path) = arg(&TEXTURE_LOADERS) {
+        Some(ImageValue::Texture(path))
+    } else if let Some(path) = arg(&SURFACE_LOADERS) {
+        Some(ImageValue::Surface(path))
+    } else if let Some(surface) = arg(&SURFACE_TEXTURES) {
+        Some(ImageValue::FromSurface(surface))
+    } else if name == "SDL_CreateRGBSurface" && args.len() >= 3 {
+        Some(ImageValue::Blank { width: &args[1], height: &args[2] })
+    } else if name == "TextureSprite" {
+        args.first().map(ImageValue::Sprite)
+    } else if let Some((_, path, size)) = FONT_LOADERS.iter().find(|(n, _, _)| *n == name) {
+        args.get(*path).map(|path| ImageValue::Font { path, size: args.get(*size) })
+    } else if name == "render_text" {
+        // La fuente es la variable sobre la que se llama
+        let font = function.strip_suffix(".render_text")?;
+        args.first().map(|text| ImageValue::Text { font, text, style: "blended", colors: &[] })
+    } else {
+        let style = name.strip_prefix("TTF_RenderUTF8_").or_else(|| name.strip_prefix("TTF_RenderText_"))?;
+        let (_, style) = TEXT_STYLES.iter().find(|(s, _)| *s == style)?;
+        match args.as_slice() {
+            [Expr::Name(font), text, colors @ ..] => Some(ImageValue::Text { font, text, style, colors }),
+            _ => None,
+        }
+    }
+}
+
+// `SDL_FreeSurface(x)` y similares: el valor se libera con `drop`.
+pub fn is_release(function: &str) -> bool {
+    RELEASE_FUNCTIONS.contains(&function.rsplit('.').next().unwrap_or(function))
+}
+
+// `sdl2.ext.fill(superficie, color, area)`: la superficie, el color y el
+// rectángulo, si lo hay.
+pub fn fill_args<'a>(function: &str, args: &'a [Expr]) -> Option<(&'a str, &'a Expr, Option<&'a Expr>)> {
+    if !function.ends_with(FILL_FUNCTION) {
+        return None;
+    }
+    match args {
+        [Expr::Name(surface), color, area @ ..] => Some((surface, color, area.first())),
+        _ => None,
+    }
+}
+
+// Superficies que se rellenan con `sdl2.ext.fill`: se declaran `let mut`.
+pub fn filled_surfaces(state: &State) -> HashSet<String> {
+    state.statements().into_iter()
+        .filter_map(|stmt| match &stmt.kind {
+            StmtKind::Expr(Expr::Call { function, args }) => fill_args(function, args).map(|(surface, _, _)| surface.to_string()),
+            _ => None,
+        })
+        .collect()
+}
+
+// `pygame.font.Font(None, tamaño)` usa la fuente que trae pygame, que sdl2 no
+// tiene. Variables con esa fuente o con lo que se hace con ella (`texto =
+// fuente.render(...)`), con la línea de su asignación: no se traducen.
+pub fn default_font_bindings(state: &State) -> Vec<(usize, String)> {
+    let mut bindings: Vec<(usize, String)> = Vec::new();
+    loop {
+        let found = bindings.len();
+        for stmt in state.statements() {
+            let StmtKind::Assign { target, value } = &stmt.kind else {
+                continue;
+            };
+            if !bindings.iter().any(|(_, name)| name == target) && (is_default_font(value) || uses_binding(value, &bindings)) {
+                bindings.push((stmt.line, target.clone()));
+            }
+        }
+        if bindings.len() == found {
+            return bindings;
+        }
+    }
+}
+
+// `pygame.font.Font(None, tamaño)`
+pub fn is_default_font(value: &Expr) -> bool {
+    matches!(value, Expr::Call { function, args }
+        if function == "pygame.font.Font" && matches!(args.first(), Some(Expr::Name(name)) if name == "None"))
+}
+
+// Si la expresión usa alguna de las variables de `bindings`.
+pub fn uses_binding(expr: &Expr, bindings: &[(usize, String)]) -> bool {
+    let bound = |path: &str| bindings.iter().any(|(_, name)| name == path.split('.').next().unwrap_or(path));
+    match expr {
+        Expr::Name(path) | Expr::Attr(path) => bound(path),
+        Expr::Call { function, args } => bound(function) || args.iter().any(|arg| uses_binding(arg, bindings)),
+        Expr::Tuple(items) | Expr::List(items) => items.iter().any(|item| uses_binding(item, bindings)),
+        Expr::Index { value, index } => uses_binding(value, bindings) || uses_binding(index, bindings),
+        Expr::MethodCall { receiver, args, .. } => uses_binding(receiver, bindings) || args.iter().any(|arg| uses_binding(arg, bindings)),
+        Expr::UnaryOp { operand, .. } | Expr::Cast { value: operand, .. } | Expr::Upcast { value: operand, .. } => {
+            uses_binding(operand, bindings)
+        }
+        Expr::BinOp { left, right, .. } => uses_binding(left, bindings) || uses_binding(right, bindings),
+        _ => false,
+    }
+}
+
+// Si el script usa fuentes, lo que necesita la feature `ttf` de sdl2.
+pub fn uses_fonts(state: &State) -> bool {
+    images(state).any(|(_, _, value)| matches!(value, ImageValue::Font { .. }))
+}
+
+// Asignaciones que crean texturas, superficies o fuentes: las del nivel
+// superior y las del bucle principal y sus eventos.
+pub fn images(state: &State) -> impl Iterator<Item = (&Stmt, &str, ImageValue<'_>)> {
+    state.statements().into_iter().filter_map(|stmt| match &stmt.kind {
+        StmtKind::Assign { target, value } => image_value(value).map(|image| (stmt, target.as_str(), image)),
+        _ => None,
+    })
+}
+
+// Si el script carga imágenes, lo que necesita la feature `image` de sdl2.
+pub fn uses_image(state: &State) -> bool {
+    images(state).any(|(_, _, value)| matches!(value, ImageValue::Texture(_) | ImageValue::Surface(_)))
+}
+
+// Ruta relativa al script de un recurso: `"img/a.png"` o, con
+// `RESOURCES = sdl2.ext.Resources(__file__, "img")`, `RESOURCES.get_path("a.png")`.
+// Las rutas absolutas o que salen del directorio del script no se copian.
+pub fn asset_path(expr: &Expr, state: &State) -> Option<String> {
+    let path = match expr {
+        Expr::Str(raw) => raw.trim_matches(|c| c == '"' || c == '\'').to_string(),
+        Expr::Call { function, args } if function.ends_with(".get_path") => {
+            let Some(Expr::Str(raw)) = args.first() else {
+                return None;
+            };
+            let file = raw.trim_matches(|c| c == '"' || c == '\'');
+            let resources = function.trim_end_matches(".get_path");
+            let directory = state.variables.iter()
+                .find(|v| v.name == resources)
+                .and_then(|v| match &v.value {
+                    Some(Expr::Call { function, args }) if function.ends_with(".Resources") => Some(args.get(1)),
+                    _ => None,
+                })?;
+            match directory {
+                Some(Expr::Str(dir)) => format!("{}/{}", dir.trim_matches(|c| c == '"' || c == '\''), file),
+                _ => file.to_string(),
+            }
+        }
+        _ => return None,
+    };
+    if path.starts_with('/') || path.split('/').any(|part| part == "..") {
+        return None;
+    }
+    Some(path)
+}
+
+// Archivos de imagen, fuentes y sonidos que el script carga, sin repetir, con su línea.
+pub fn assets(state: &State) -> Vec<(usize, String)> {
+    let mut assets: Vec<(usize, String)> = Vec::new();
+    for stmt in state.statements() {
+        let StmtKind::Assign { value, .. } = &stmt.kind else {
+            continue;
+        };
+        let path = match image_value(value) {
+            Some(ImageValue::Texture(path) | ImageValue::Surface(path) | ImageValue::Font { path, .. }) => Some(path),
+            _ => audio::loaded_file(value),
+        };
+        let path = path.and_then(|path| asset_path(path, state));
+        if let Some(path) = path.filter(|p| !assets.iter().any(|(_, a)| a == p)) {
+            assets.push((stmt.line, path));
+        }
+    }
+    assets
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::extract_state;
+    use crate::tokenizer::tokenize;
+
+    #[test]
+    fn test_image_assets() {
+        let code = "RES = sdl2.ext.Resources(__file__, \"img\")\nfactory = sdl2.ext.SpriteFactory(sdl2.ext.TEXTURE, renderer=renderer)\na = factory.from_image(\"sprites/a.png\")\nb = sdl2.ext.load_image(RES.get_path(\"b.bmp\"))\nc = sdl2.ext.Texture(renderer, b)\nd = factory.from_image(\"/tmp/d.png\")\n";
+        let state = extract_state(tokenize(code));
+
+        let kinds: Vec<&str> = images(&state).map(|(_, target, _)| target).collect();
+        assert_eq!(kinds, vec!["a", "b", "c", "d"]);
+        assert!(uses_image(&state));
+        assert_eq!(assets(&state), vec![(3, "sprites/a.png".to_string()), (4, "img/b.bmp".to_string())]);
+    }
+}
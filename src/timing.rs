@@ -0,0 +1,194 @@
+// Tiempo del script: `sdl2.SDL_GetTicks`, `sdl2.SDL_Delay`, `time.time`,
+// `time.sleep`... sobre `TimerSubsystem::ticks`, `std::time::Instant` y
+// `thread::sleep`. Los segundos de `time.time()` se cuentan desde el inicio de
+// `main` (`start_time`), que basta para medir intervalos.
+use std::collections::HashSet;
+
+use crate::types::{Expr, State, Stmt, StmtKind};
+
+// Funciones que devuelven los milisegundos desde que se inició SDL
+const TICKS_FUNCTIONS: [&str; 2] = ["SDL_GetTicks", "SDL_GetTicks64"];
+
+// Funciones de `time` que devuelven segundos como `float`
+const CLOCK_FUNCTIONS: [&str; 3] = ["time.time", "time.perf_counter", "time.monotonic"];
+
+// Pausas y la unidad de su argumento
+const SLEEP_FUNCTIONS: [(&str, &str); 2] = [
+    ("SDL_Delay", "millis"),
+    ("time.sleep", "secs"),
+];
+
+fn is_ticks(function: &str) -> bool {
+    TICKS_FUNCTIONS.contains(&function.rsplit('.').next().unwrap_or(function))
+}
+
+fn is_clock(function: &str) -> bool {
+    CLOCK_FUNCTIONS.contains(&function)
+}
+
+// Unidad de la pausa de `function`, o None si no es una pausa.
+fn sleep_unit(function: &str) -> Option<&'static str> {
+    SLEEP_FUNCTIONS.iter()
+        .find(|(name, _)| *name == function || function.strip_prefix("sdl2.") == Some(name))
+        .map(|(_, unit)| *unit)
+}
+
+// Si `expr` es un número con decimales: un literal o una medida de `time`.
+pub fn is_float(expr: &Expr) -> bool {
+    match expr {
+        Expr::Float(_) => true,
+        Expr::Call { function, args } => args.is_empty() && is_clock(function),
+        Expr::BinOp { op, left, right } if ["+", "-", "*", "/"].contains(&op.as_str()) => is_float(left) || is_float(right),
+        Expr::UnaryOp { operand, .. } => is_float(operand),
+        _ => false,
+    }
+}
+
+fn uses(state: &State, matches: fn(&str) -> bool) -> bool {
+    state.called_functions().into_iter().any(matches)
+}
+
+// Si el script lee `SDL_GetTicks`: necesita el subsistema `timer`.
+pub fn uses_timer(state: &State) -> bool {
+    uses(state, is_ticks)
+}
+
+// Si el script mide segundos con `time`: necesita `start_time`.
+pub fn uses_clock(state: &State) -> bool {
+    uses(state, is_clock)
+}
+
+// Si el script hace pausas, lo que necesita `Duration`.
+pub fn uses_sleep(state: &State) -> bool {
+    uses(state, |function| sleep_unit(function).is_some())
+}
+
+// Si el bucle principal ya limita los fotogramas: hace una pausa o el renderer
+// espera al refresco vertical.
+pub fn has_limiter(state: &State) -> bool {
+    let sleeps = state.loop_statements().into_iter().any(|stmt| match &stmt.kind {
+        StmtKind::Expr(Expr::Call { function, .. }) => sleep_unit(function).is_some(),
+        _ => false,
+    });
+    let vsync = state.renderer.as_ref()
+        .and_then(|renderer| renderer.flags.as_ref())
+        .is_some_and(|flags| flags.iter().any(|flag| flag == "SDL_RENDERER_PRESENTVSYNC"));
+    sleeps || vsync
+}
+
+// Valor de una medida de tiempo, sin paréntesis.
+fn reading(function: &str, int_type: &str) -> Option<String> {
+    if is_ticks(function) {
+        Some(format!("timer.ticks() as {}", int_type))
+    } else if is_clock(function) {
+        Some("start_time.elapsed().as_secs_f64()".to_string())
+    } else {
+        None
+    }
+}
+
+// `sdl2.SDL_GetTicks()` o `time.time()` dentro de una expresión.
+pub fn value_code(function: &str, args: &[Expr], int_type: &str) -> Option<String> {
+    let code = reading(function, int_type).filter(|_| args.is_empty())?;
+    Some(if code.contains(" as ") { format!("({})", code) } else { code })
+}
+
+// Variables asignadas con una medida de tiempo en el nivel superior del script.
+pub fn setup_targets(state: &State) -> HashSet<String> {
+    state.setup.iter()
+        .filter_map(|stmt| match &stmt.kind {
+            StmtKind::Assign { target, value } if has_reading(value) => Some(target.clone()),
+            _ => None,
+        })
+        .collect()
+}
+
+fn has_reading(expr: &Expr) -> bool {
+    match expr {
+        Expr::Call { function, args } => (args.is_empty() && (is_ticks(function) || is_clock(function)))
+            || args.iter().any(has_reading),
+        Expr::BinOp { left, right, .. } => has_reading(left) || has_reading(right),
+        Expr::UnaryOp { operand, .. } => has_reading(operand),
+        _ => false,
+    }
+}
+
+// Código de una pausa o de una asignación con una medida de tiempo. Las
+// variables de `declared` se reasignan y las de `mutable` se declaran `let mut`.
+// `expr` traduce las expresiones.
+pub fn stmt_code(
+    stmt: &Stmt,
+    declared: &HashSet<String>,
+    mutable: &HashSet<String>,
+    expr: &dyn Fn(&Expr) -> String,
+    int_type: &str,
+) -> Option<String> {
+    match &stmt.kind {
+        StmtKind::Expr(Expr::Call { function, args }) => {
+            let [duration] = args.as_slice() else {
+                return None;
+            };
+            let duration = match (sleep_unit(function)?, duration) {
+                ("millis", Expr::Int(ms)) => format!("Duration::from_millis({})", ms),
+                ("millis", ms) => format!("Duration::from_millis({})", expr_as(ms, "u64", expr)),
+                (_, Expr::Int(secs)) => format!("Duration::from_secs({})", secs),
+                (_, Expr::Float(secs)) => format!("Duration::from_secs_f64({:?})", secs),
+                (_, secs) if is_float(secs) => format!("Duration::from_secs_f64({})", expr(secs)),
+                (_, secs) => format!("Duration::from_secs_f64({})", expr_as(secs, "f64", expr)),
+            };
+            Some(format!("::std::thread::sleep({});", duration))
+        }
+        StmtKind::Assign { target, value } if has_reading(value) => {
+            let value = match value {
+                Expr::Call { function, args } if args.is_empty() => reading(function, int_type)?,
+                _ if declared.contains(target) => return None,
+                _ => expr(value),
+            };
+            let keyword = match () {
+                _ if declared.contains(target) => "",
+                _ if mutable.contains(target) => "let mut ",
+                _ => "let ",
+            };
+            Some(format!("{}{} = {};", keyword, expr(&Expr::Name(target.clone())), value))
+        }
+        _ => None,
+    }
+}
+
+fn expr_as(value: &Expr, ty: &str, expr: &dyn Fn(&Expr) -> String) -> String {
+    match value {
+        Expr::Name(_) => format!("{} as {}", expr(value), ty),
+        _ => format!("({}) as {}", expr(value), ty),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::extract_state;
+    use crate::tokenizer::tokenize;
+
+    #[test]
+    fn test_timing_statements() {
+        let code = "antes = sdl2.SDL_GetTicks()\ninicio = time.time()\nwhile running:\n    sdl2.SDL_Delay(1000 // FPS)\n    time.sleep(0.5)\n    antes = sdl2.SDL_GetTicks()\n";
+        let state = extract_state(tokenize(code));
+        let expr = |e: &Expr| match e {
+            Expr::Int(n) => n.to_string(),
+            Expr::Name(name) => name.clone(),
+            _ => String::new(),
+        };
+        let mutable = state.loop_targets();
+        let setup: Vec<String> = state.setup.iter()
+            .filter_map(|stmt| stmt_code(stmt, &HashSet::new(), &mutable, &expr, "i32"))
+            .collect();
+
+        assert!(uses_timer(&state) && uses_clock(&state) && uses_sleep(&state));
+        assert!(has_limiter(&state));
+        assert_eq!(setup, vec!["let mut antes = timer.ticks() as i32;", "let inicio = start_time.elapsed().as_secs_f64();"]);
+        assert_eq!(setup_targets(&state), HashSet::from(["antes".to_string(), "inicio".to_string()]));
+        let sleep = stmt_code(&state.loop_body[1], &HashSet::new(), &mutable, &expr, "i32");
+        assert_eq!(sleep.as_deref(), Some("::std::thread::sleep(Duration::from_secs_f64(0.5));"));
+        let reassigned = stmt_code(&state.loop_body[2], &setup_targets(&state), &mutable, &expr, "i32");
+        assert_eq!(reassigned.as_deref(), Some("antes = timer.ticks() as i32;"));
+    }
+}
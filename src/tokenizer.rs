@@ -4,27 +4,72 @@ use crate::types::{Token, TokenType};
 pub fn tokenize(code: &str) -> Vec<Token> {
     let mut tokens = Vec::new();
     
-    let re_number = Regex::new(r"^[0-9]+").unwrap();
+    let re_number = Regex::new(r"^[0-9]+(\.[0-9]+)?").unwrap();
     let re_identifier = Regex::new(r"^[[:alpha:]_][[:alpha:][:digit:]_]*").unwrap();
     let re_string = Regex::new(r#"^"([^"\\]*(\\.[^"\\]*)*)""#).unwrap();
-    let re_symbol = Regex::new(r"^(\(|\)|,|:|#|\+|\-|\*|\/\/|\/|=|\.|>|<)").unwrap();
+    let re_single_string = Regex::new(r"^'([^'\\]*(\\.[^'\\]*)*)'").unwrap();
+    let re_string_prefix = Regex::new(r#"^[bBuUfF]["']"#).unwrap();
+    let re_symbol = Regex::new(r"^(\/\/=|\+=|-=|\*=|\/=|%=|\(|\)|\[|\]|,|:|#|\+|\-|\*|\/\/|\/|%|==|!=|<=|>=|=|\.|>|<|\||&|@)").unwrap();
 
-    let mut line_number = 1;
     let mut column = 1;
-    
-    for line in code.lines() {
+    let mut format_string = false;
+    let lines: Vec<&str> = code.lines().collect();
+    let mut line_index = 0;
+
+    while line_index < lines.len() {
+        let mut line_number = line_index + 1;
         let mut pos = 0;
-        let line_chars: Vec<char> = line.chars().collect();
+        let mut line_chars: Vec<char> = lines[line_index].chars().collect();
 
         while pos < line_chars.len() {
             let rest: String = line_chars[pos..].iter().collect();
 
+            // Strings con triple comilla (docstrings), que pueden ocupar varias líneas
+            if rest.starts_with("\"\"\"") || rest.starts_with("'''") {
+                let delimiter = &rest[..3];
+                // Un prefijo `f` solo vale para el string que lo sigue
+                format_string = false;
+                let start_line = line_number;
+                let start_column = column;
+                let mut value = String::new();
+                let mut tail = rest[3..].to_string();
+                let mut consumed = pos + 3;
+                loop {
+                    if let Some(end) = tail.find(delimiter) {
+                        value.push_str(&tail[..end]);
+                        consumed += tail[..end].chars().count() + 3;
+                        break;
+                    }
+                    value.push_str(&tail);
+                    if line_index + 1 >= lines.len() {
+                        consumed = line_chars.len();
+                        break;
+                    }
+                    value.push('\n');
+                    line_index += 1;
+                    line_number = line_index + 1;
+                    line_chars = lines[line_index].chars().collect();
+                    tail = lines[line_index].to_string();
+                    consumed = 0;
+                }
+                tokens.push(Token {
+                    token_type: TokenType::String,
+                    value: format!("{}{}{}", delimiter, value, delimiter),
+                    line: start_line,
+                    column: start_column,
+                });
+                column = if line_number == start_line { column + consumed - pos } else { consumed + 1 };
+                pos = consumed;
+            }
+
             // Manejar comentarios
-            if rest.starts_with("#") {
+            else if rest.starts_with("#") {
                 let comment = rest;
                 tokens.push(Token {
                     token_type: TokenType::Comment,
                     value: comment,
+                    line: line_number,
+                    column,
                 });
                 break;
             }
@@ -34,33 +79,45 @@ pub fn tokenize(code: &str) -> Vec<Token> {
                  tokens.push(Token {
                     token_type: TokenType::Keyword,
                     value: "sdl2.".to_string(),
+                    line: line_number,
+                    column,
                 });
                 pos += "sdl2.".len();
                 column += "sdl2.".len();
              }
             // Otros keywords (while, if, etc.)
              else if is_keyword(&rest, "while", pos, &line_chars) {
-                add_keyword_token(&mut tokens, "while", &mut pos, &mut column);
+                add_keyword_token(&mut tokens, "while", line_number, &mut pos, &mut column);
             }
             else if is_keyword(&rest, "if", pos, &line_chars) {
-                add_keyword_token(&mut tokens, "if", &mut pos, &mut column);
+                add_keyword_token(&mut tokens, "if", line_number, &mut pos, &mut column);
             }
             else if is_keyword(&rest, "true", pos, &line_chars) {
-                add_keyword_token(&mut tokens, "true", &mut pos, &mut column);
+                add_keyword_token(&mut tokens, "true", line_number, &mut pos, &mut column);
             }
             else if is_keyword(&rest, "false", pos, &line_chars) {
-                 add_keyword_token(&mut tokens, "false", &mut pos, &mut column);
+                 add_keyword_token(&mut tokens, "false", line_number, &mut pos, &mut column);
              }
             
+            // Prefijos `b"..."` y `u"..."`: el texto del string es el mismo. Los
+            // f-strings conservan la `f` para traducirse a `format!`
+            else if re_string_prefix.is_match(&rest) {
+                format_string = rest.starts_with(['f', 'F']);
+                pos += 1;
+                column += 1;
+            }
+
             // Identificadores
             else if let Some(mat) = re_identifier.find(&rest) {
                 let identifier = mat.as_str().to_string();
                 tokens.push(Token {
                     token_type: TokenType::Identifier,
                     value: identifier,
+                    line: line_number,
+                    column,
                 });
-                pos += mat.end();
-                column += mat.end();
+                pos += mat.as_str().chars().count();
+                column += mat.as_str().chars().count();
             }
             
             // Números
@@ -68,19 +125,25 @@ pub fn tokenize(code: &str) -> Vec<Token> {
                 tokens.push(Token {
                     token_type: TokenType::Number,
                     value: mat.as_str().to_string(),
+                    line: line_number,
+                    column,
                 });
-                pos += mat.end();
-                column += mat.end();
+                pos += mat.as_str().chars().count();
+                column += mat.as_str().chars().count();
             }
             
             // Strings
-            else if let Some(mat) = re_string.find(&rest) {
+            else if let Some(mat) = re_string.find(&rest).or_else(|| re_single_string.find(&rest)) {
+                let prefix = if format_string { "f" } else { "" };
+                format_string = false;
                 tokens.push(Token {
                     token_type: TokenType::String,
-                    value: mat.as_str().to_string(),
+                    value: format!("{}{}", prefix, mat.as_str()),
+                    line: line_number,
+                    column: column - prefix.len(),
                 });
-                pos += mat.end();
-                column += mat.end();
+                pos += mat.as_str().chars().count();
+                column += mat.as_str().chars().count();
             }
             
             // Símbolos
@@ -88,9 +151,11 @@ pub fn tokenize(code: &str) -> Vec<Token> {
                 tokens.push(Token {
                     token_type: TokenType::Symbol,
                     value: mat.as_str().to_string(),
+                    line: line_number,
+                    column,
                 });
-                pos += mat.end();
-                column += mat.end();
+                pos += mat.as_str().chars().count();
+                column += mat.as_str().chars().count();
             }
             
             // Espacios en blanco
@@ -111,10 +176,12 @@ pub fn tokenize(code: &str) -> Vec<Token> {
         tokens.push(Token {
             token_type: TokenType::Eol,
             value: "\n".to_string(),
+            line: line_number,
+            column,
         });
         
-        line_number += 1;
         column = 1;
+        line_index += 1;
     }
     
     tokens
@@ -124,13 +191,15 @@ fn is_keyword(rest: &str, keyword: &str, pos: usize, line_chars: &[char]) -> boo
     rest.starts_with(keyword) && 
         (pos + keyword.len() >= line_chars.len() || 
          line_chars[pos + keyword.len()].is_whitespace() || 
-         !line_chars[pos + keyword.len()].is_alphanumeric())
+         !(line_chars[pos + keyword.len()].is_alphanumeric() || line_chars[pos + keyword.len()] == '_'))
 }
 
-fn add_keyword_token(tokens: &mut Vec<Token>, keyword: &str, pos: &mut usize, column: &mut usize) {
+fn add_keyword_token(tokens: &mut Vec<Token>, keyword: &str, line: usize, pos: &mut usize, column: &mut usize) {
     tokens.push(Token {
         token_type: TokenType::Keyword,
         value: keyword.to_string(),
+        line,
+        column: *column,
     });
     *pos += keyword.len();
     *column += keyword.len();
@@ -150,6 +219,37 @@ mod tests {
         assert_eq!(tokens[2].value, "10");
     }
 
+    #[test]
+    fn test_multiline_docstring() {
+        let code = "def f():\n    \"\"\"Dibuja.\n\n    Más texto.\n    \"\"\"\n    x = 1\n";
+        let tokens = tokenize(code);
+        let doc = tokens.iter().find(|t| t.token_type == TokenType::String).unwrap();
+        assert_eq!(doc.value, "\"\"\"Dibuja.\n\n    Más texto.\n    \"\"\"");
+        assert_eq!(doc.line, 2);
+        let x = tokens.iter().find(|t| t.value == "x").unwrap();
+        assert_eq!(x.line, 6);
+    }
+
+    #[test]
+    fn test_format_string() {
+        let tokens = tokenize("texto = f\"Puntos: {puntos}\"\n");
+        assert_eq!(tokens[2].value, "f\"Puntos: {puntos}\"");
+        assert_eq!(tokens[2].column, 9);
+    }
+
+    #[test]
+    fn test_format_flag_ends_with_its_string() {
+        let tokens = tokenize("texto = f\"\"\"Hola\"\"\"\ndef g():\n    \"\"\"Doc.\"\"\"\n    return \"x\"\n");
+        let strings: Vec<&str> = tokens.iter().filter(|t| t.token_type == TokenType::String).map(|t| t.value.as_str()).collect();
+        assert_eq!(strings, vec!["\"\"\"Hola\"\"\"", "\"\"\"Doc.\"\"\"", "\"x\""]);
+    }
+
+    #[test]
+    fn test_float_number() {
+        let tokens = tokenize("time.sleep(0.016)");
+        assert!(tokens.iter().any(|t| t.token_type == TokenType::Number && t.value == "0.016"));
+    }
+
     #[test]
     fn test_sdl2_keyword() {
          let code = "sdl2.init()\n";
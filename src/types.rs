@@ -1,3 +1,5 @@
+use std::collections::HashSet;
+
 #[derive(PartialEq, Debug, Clone)]
 pub enum TokenType {
     Keyword,
@@ -13,36 +15,288 @@ pub enum TokenType {
 pub struct Token {
     pub token_type: TokenType,
     pub value: String,
+    pub line: usize,
+    pub column: usize,
+}
+
+// Expresión Python ya reconocida, lista para traducirse a Rust.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expr {
+    Int(i64),
+    Float(f64),
+    Bool(bool),
+    Str(String),
+    Name(String),
+    // Ruta con puntos, como `sdl2.SDL_WINDOW_RESIZABLE`
+    Attr(String),
+    Tuple(Vec<Expr>),
+    List(Vec<Expr>),
+    Call { function: String, args: Vec<Expr> },
+    // `keys[sdl2.SDL_SCANCODE_LEFT]`
+    Index { value: Box<Expr>, index: Box<Expr> },
+    // Solo la genera el traductor: `buttons.left()`
+    MethodCall { receiver: Box<Expr>, method: String, args: Vec<Expr> },
+    UnaryOp { op: String, operand: Box<Expr> },
+    // Solo la genera el traductor: `rect.width() as i32`
+    Cast { value: Box<Expr>, ty: String },
+    // Solo la genera el traductor: un objeto de la clase `class` guardado como
+    // cualquier clase de la jerarquía de `root` (`Box<dyn ..>` o una variante);
+    // sin `class` el valor ya lo es y solo se presta (`&mut *e` con un trait)
+    Upcast { value: Box<Expr>, root: String, class: Option<String> },
+    // Solo la genera el traductor: llamada a una función o método del proyecto
+    // que no se pudo traducir (`nivel1.doble(x)` sin el tipo de `x`); la
+    // sentencia que la contiene se omite
+    Unlinked(String),
+    // Solo la genera el traductor: llamada a un método que dibuja, que puede
+    // fallar como las llamadas de dibujo (`e.draw(&mut canvas)?`)
+    Try(Box<Expr>),
+    BinOp { op: String, left: Box<Expr>, right: Box<Expr> },
 }
 
 #[derive(Debug)]
 pub struct Variable {
     pub name: String,
-    pub value: String,
+    pub value: Option<Expr>,
     pub tipo: String,
+    pub line: usize,
+    pub column: usize,
 }
 
+// Llamada a un método de dibujo del renderer (`renderer.fill(rect, ROJO)`) o a
+// una función del script que recibe el renderer (`draw_circle(renderer, ...)`).
 #[derive(Debug, Clone, PartialEq)]
 pub struct DrawCall {
-    pub draw_type: DrawType,
-    pub x: String,
-    pub y: String,
-    pub w: Option<String>,
-    pub h: Option<String>,
-    pub radius: Option<String>,
-    pub color: String,
-}
-
-#[derive(Debug, Clone, PartialEq)]
-pub enum DrawType {
-    Rect,
-    Circle,
+    pub method: String,
+    pub args: Vec<Expr>,
+    pub kwargs: Vec<(String, Expr)>,
+    pub line: usize,
+    pub column: usize,
 }
 
 #[derive(Debug)]
 pub struct ControlFlow {
     pub control_type: String,
     pub condition: Option<String>,
+    pub line: usize,
+    pub column: usize,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Comment {
+    pub text: String,
+    // Línea de la sentencia a la que pertenece el comentario
+    pub line: usize,
+    pub trailing: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Import {
+    pub module: String,
+    // Nombres de `from módulo import a, b`; vacío con `import módulo` y `*`
+    pub names: Vec<String>,
+    pub line: usize,
+}
+
+// Parámetro de una función: `x`, `x: int` o `x=0`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Param {
+    pub name: String,
+    pub annotation: Option<String>,
+    pub default: Option<Expr>,
+    // Tipo deducido por `classes`; "unknown" si no se sabe
+    pub tipo: String,
+    // Un objeto que el método modifica se pasa como `&mut`
+    pub mutated: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct FunctionDef {
+    pub name: String,
+    pub params: Vec<Param>,
+    // `-> int`
+    pub returns: Option<String>,
+    // `@staticmethod`, `@classmethod`...
+    pub decorators: Vec<String>,
+    pub docstring: Option<String>,
+    pub body: Vec<Stmt>,
+    pub line: usize,
+    pub column: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MethodKind {
+    Instance,
+    // `@staticmethod`: sin `self`
+    Static,
+    // `@classmethod`: `cls` es `Self`
+    Class,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Method {
+    pub function: FunctionDef,
+    pub kind: MethodKind,
+    // Tipo deducido de sus `return`: None si no devuelve nada, "unknown" si no se sabe
+    pub return_type: Option<String>,
+    // Asigna campos de `self` o llama a métodos que lo hacen: `&mut self`
+    pub mutates: bool,
+    // Copia de un método heredado: la clase donde está escrito
+    pub inherited: Option<String>,
+}
+
+// Campo de una clase, de sus asignaciones `self.x = ...`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Field {
+    pub name: String,
+    pub tipo: String,
+    pub line: usize,
+    pub column: usize,
+}
+
+// Atributo de clase (`VELOCIDAD = 5`): una constante asociada.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ClassAttr {
+    pub name: String,
+    pub value: Expr,
+    pub tipo: String,
+    pub line: usize,
+    pub column: usize,
+}
+
+// `class Player:` con sus métodos; los campos y los tipos los deduce `classes`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ClassDef {
+    pub name: String,
+    pub bases: Vec<String>,
+    pub docstring: Option<String>,
+    pub attributes: Vec<ClassAttr>,
+    pub methods: Vec<Method>,
+    pub fields: Vec<Field>,
+    // `metaclass=...` en la cabecera
+    pub metaclass: Option<String>,
+    // Si hereda de otra clase del proyecto o alguna hereda de ella
+    pub hierarchy: Option<Hierarchy>,
+    pub line: usize,
+    pub column: usize,
+}
+
+// Jerarquía de herencia simple de una clase. Los métodos de instancia de la
+// raíz se despachan según la clase del objeto, con un trait o un enum.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Hierarchy {
+    // Ruta de la raíz desde el módulo de la clase: `Sprite`, `player::Sprite`
+    pub root: String,
+    // Ruta del padre; None en la raíz
+    pub parent: Option<String>,
+    // La clase y sus antecesoras hasta la raíz, por nombre
+    pub ancestors: Vec<String>,
+    pub methods: Vec<String>,
+    // Solo en la raíz: todas las clases de la jerarquía
+    pub members: Vec<HierarchyMember>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct HierarchyMember {
+    // Ruta desde el módulo de la raíz: `Enemy`, `crate::enemigo::Enemy`
+    pub path: String,
+    pub ancestors: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SdlCall {
+    pub function: String,
+    pub line: usize,
+    pub column: usize,
+}
+
+// Ventana creada con `sdl2.ext.Window(title, size, position, flags)`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WindowSpec {
+    pub variable: String,
+    pub title: Option<Expr>,
+    pub size: Option<(Expr, Expr)>,
+    pub position: Option<(Expr, Expr)>,
+    // Nombres SDL_WINDOW_*; None si se usan los de PySDL2 (ventana oculta)
+    pub flags: Option<Vec<String>>,
+    pub operations: Vec<WindowOp>,
+    pub line: usize,
+    pub column: usize,
+}
+
+// Llamada a un método de la ventana: `window.show()`, `window.maximize()`...
+#[derive(Debug, Clone, PartialEq)]
+pub struct WindowOp {
+    pub method: String,
+    pub line: usize,
+    pub column: usize,
+}
+
+// Renderer creado con `sdl2.ext.Renderer(target, backend, logical_size, flags)`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RendererSpec {
+    pub variable: String,
+    pub index: Option<Expr>,
+    // Nombres SDL_RENDERER_*; None si se usan los de PySDL2 (acelerado)
+    pub flags: Option<Vec<String>>,
+    pub settings: Vec<RendererSetting>,
+    pub line: usize,
+    pub column: usize,
+}
+
+// `logical_size` o `blendmode`, en el constructor o asignado después
+// (`renderer.blendmode = sdl2.SDL_BLENDMODE_BLEND`).
+#[derive(Debug, Clone, PartialEq)]
+pub struct RendererSetting {
+    pub attribute: String,
+    pub value: Expr,
+    pub line: usize,
+    pub column: usize,
+}
+
+// Sentencia Python dentro de un bloque que se traduce tal cual (por ahora, el
+// cuerpo de los manejadores de eventos).
+#[derive(Debug, Clone, PartialEq)]
+pub struct Stmt {
+    pub kind: StmtKind,
+    pub line: usize,
+    pub column: usize,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum StmtKind {
+    Assign { target: String, value: Expr },
+    // `x += 1`: `op` sin el `=`
+    AugAssign { target: String, op: String, value: Expr },
+    If { condition: Expr, body: Vec<Stmt>, orelse: Vec<Stmt> },
+    // `for e in entidades:`; el bucle de eventos va aparte
+    For { target: String, iter: Expr, body: Vec<Stmt> },
+    Expr(Expr),
+    Break,
+    Continue,
+    Return(Option<Expr>),
+}
+
+// Bucle `for event in sdl2.ext.get_events():` (o `while SDL_PollEvent(...)`).
+#[derive(Debug, Clone, PartialEq)]
+pub struct EventLoop {
+    pub variable: String,
+    pub handlers: Vec<EventHandler>,
+    pub line: usize,
+    pub column: usize,
+}
+
+// Rama `if event.type == sdl2.SDL_KEYDOWN and ...:` del bucle de eventos.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EventHandler {
+    // Constante SDL_* del tipo de evento
+    pub event_type: String,
+    // El resto de la condición, si la hay
+    pub guard: Option<Expr>,
+    pub body: Vec<Stmt>,
+    // `elif` del manejador anterior del mismo tipo
+    pub chained: bool,
+    pub line: usize,
+    pub column: usize,
 }
 
 #[derive(Debug)]
@@ -50,4 +304,115 @@ pub struct State {
     pub variables: Vec<Variable>,
     pub draw_calls: Vec<DrawCall>,
     pub control_flow: Vec<ControlFlow>,
+    pub imports: Vec<Import>,
+    // Funciones del nivel superior, como métodos estáticos
+    pub functions: Vec<Method>,
+    pub classes: Vec<ClassDef>,
+    pub sdl_calls: Vec<SdlCall>,
+    pub comments: Vec<Comment>,
+    pub module_docstring: Option<String>,
+    pub window: Option<WindowSpec>,
+    pub renderer: Option<RendererSpec>,
+    pub events: Option<EventLoop>,
+    // Sentencias del bucle principal (`while running:`), en orden
+    pub loop_body: Vec<Stmt>,
+    // Sentencias del nivel superior del script, fuera de bucles y funciones
+    pub setup: Vec<Stmt>,
+    // Módulos locales hijos (`mod player;`); los rellena `project`
+    pub modules: Vec<String>,
+    // Raíces de jerarquías de otros módulos (`player.Sprite`) cuyos métodos se
+    // usan aquí; con `classes.inheritance = "trait"` hay que importar su trait
+    pub hierarchies: Vec<String>,
+}
+
+impl State {
+    // Todas las sentencias traducibles: las del nivel superior, las del bucle
+    // principal y las de los manejadores de eventos, con las de sus `if`.
+    pub fn statements(&self) -> Vec<&Stmt> {
+        let mut stmts = Vec::new();
+        collect_stmts(&self.setup, &mut stmts);
+        stmts.extend(self.loop_statements());
+        stmts
+    }
+
+    // Solo las del bucle principal y sus eventos.
+    pub fn loop_statements(&self) -> Vec<&Stmt> {
+        let mut stmts = Vec::new();
+        collect_stmts(&self.loop_body, &mut stmts);
+        for handler in self.events.iter().flat_map(|events| &events.handlers) {
+            collect_stmts(&handler.body, &mut stmts);
+        }
+        stmts
+    }
+
+    // Funciones que llama el script en cualquier sentencia, también dentro de
+    // condiciones y argumentos, con su ruta completa (`sdl2.SDL_GetTicks`).
+    pub fn called_functions(&self) -> Vec<&str> {
+        self.statements().into_iter().flat_map(Stmt::called_functions).collect()
+    }
+
+    // Variables que el bucle principal vuelve a asignar.
+    pub fn loop_targets(&self) -> HashSet<String> {
+        self.loop_statements().into_iter()
+            .filter_map(|stmt| match &stmt.kind {
+                StmtKind::Assign { target, .. } | StmtKind::AugAssign { target, .. } => Some(target.clone()),
+                _ => None,
+            })
+            .collect()
+    }
+}
+
+impl Stmt {
+    // Funciones que llama la sentencia, sin contar las de sus bloques.
+    pub fn called_functions(&self) -> Vec<&str> {
+        let mut names = Vec::new();
+        match &self.kind {
+            StmtKind::Assign { value, .. } | StmtKind::AugAssign { value, .. } | StmtKind::Expr(value) => {
+                collect_calls(value, &mut names);
+            }
+            StmtKind::If { condition, .. } | StmtKind::For { iter: condition, .. } | StmtKind::Return(Some(condition)) => {
+                collect_calls(condition, &mut names);
+            }
+            StmtKind::Break | StmtKind::Continue | StmtKind::Return(None) => {}
+        }
+        names
+    }
+}
+
+fn collect_calls<'a>(expr: &'a Expr, names: &mut Vec<&'a str>) {
+    match expr {
+        Expr::Call { function, args } => {
+            names.push(function);
+            args.iter().for_each(|arg| collect_calls(arg, names));
+        }
+        Expr::Tuple(items) | Expr::List(items) => items.iter().for_each(|item| collect_calls(item, names)),
+        Expr::Index { value, index } => {
+            collect_calls(value, names);
+            collect_calls(index, names);
+        }
+        Expr::MethodCall { receiver, args, .. } => {
+            collect_calls(receiver, names);
+            args.iter().for_each(|arg| collect_calls(arg, names));
+        }
+        Expr::BinOp { left, right, .. } => {
+            collect_calls(left, names);
+            collect_calls(right, names);
+        }
+        Expr::UnaryOp { operand, .. } | Expr::Cast { value: operand, .. } | Expr::Try(operand) => collect_calls(operand, names),
+        _ => {}
+    }
+}
+
+fn collect_stmts<'a>(stmts: &'a [Stmt], out: &mut Vec<&'a Stmt>) {
+    for stmt in stmts {
+        out.push(stmt);
+        match &stmt.kind {
+            StmtKind::If { body, orelse, .. } => {
+                collect_stmts(body, out);
+                collect_stmts(orelse, out);
+            }
+            StmtKind::For { body, .. } => collect_stmts(body, out),
+            _ => {}
+        }
+    }
 }
@@ -0,0 +1,372 @@
+// Constructor de código Rust con indentación al estilo de rustfmt. También
+// lleva las regiones ligadas a sentencias Python: mapa de fuentes y comentarios.
+use std::collections::HashSet;
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+use crate::sourcemap::{next_line, SourceMap};
+use crate::types::Comment;
+
+const INDENT: &str = "    ";
+
+pub struct CodeWriter<'a> {
+    code: String,
+    indent: usize,
+    comments: &'a [Comment],
+    line_comments: bool,
+    source_map: SourceMap,
+    leading_done: HashSet<usize>,
+    trailing_done: HashSet<usize>,
+    // `// py: juego.py:42` que espera a la primera línea de su sentencia; otra
+    // sentencia que empieza antes lo sustituye
+    marker: Option<String>,
+}
+
+pub struct Region {
+    start: usize,
+    python_line: usize,
+    python_column: usize,
+    // Si lleva los comentarios de la sentencia
+    comments: bool,
+}
+
+impl<'a> CodeWriter<'a> {
+    pub fn new(python_file: &str, comments: &'a [Comment], line_comments: bool) -> Self {
+        CodeWriter {
+            code: String::new(),
+            indent: 0,
+            comments,
+            line_comments,
+            source_map: SourceMap::new(python_file),
+            leading_done: HashSet::new(),
+            trailing_done: HashSet::new(),
+            marker: None,
+        }
+    }
+
+    // Una línea con la indentación actual; el texto puede traer varias líneas
+    // ya indentadas de forma relativa.
+    pub fn line(&mut self, text: &str) {
+        if let Some(marker) = self.marker.take() {
+            self.line(&marker);
+        }
+        for line in text.lines() {
+            if line.trim().is_empty() {
+                self.code.push('\n');
+            } else {
+                self.code.push_str(&INDENT.repeat(self.indent));
+                self.code.push_str(line);
+                self.code.push('\n');
+            }
+        }
+    }
+
+    // Línea en blanco, salvo al principio de un bloque o tras otra en blanco.
+    pub fn blank(&mut self) {
+        let last_line = self.code.trim_end_matches('\n').lines().last().unwrap_or("");
+        if self.code.is_empty() || self.code.ends_with("\n\n") || last_line.ends_with('{') {
+            return;
+        }
+        self.code.push('\n');
+    }
+
+    // `texto {` y entra en el bloque.
+    pub fn open(&mut self, text: &str) {
+        self.line(text);
+        self.indent += 1;
+    }
+
+    // Columnas que ocupa la sangría actual
+    pub fn indent_width(&self) -> usize {
+        self.indent * INDENT.len()
+    }
+
+    // Sale del bloque y cierra con `texto` (normalmente `}`).
+    pub fn close(&mut self, text: &str) {
+        while self.code.ends_with("\n\n") {
+            self.code.pop();
+        }
+        self.indent = self.indent.saturating_sub(1);
+        self.line(text);
+    }
+
+    // Cierra un bloque y abre el siguiente en la misma línea (`} else {`).
+    pub fn reopen(&mut self, text: &str) {
+        self.close(text);
+        self.indent += 1;
+    }
+
+    pub fn doc_comment(&mut self, marker: &str, text: &str) {
+        for line in text.lines() {
+            if line.is_empty() {
+                self.line(marker);
+            } else {
+                self.line(&format!("{} {}", marker, line));
+            }
+        }
+    }
+
+    // Empieza el código de una sentencia Python: emite sus comentarios previos
+    // (y `// py: juego.py:42` con `output.line_comments`).
+    pub fn begin(&mut self, python_line: usize, python_column: usize) -> Region {
+        // Una sentencia que aún no escribió nada, como el `if` que se vuelve
+        // una rama del `match`, no deja su marca
+        self.marker = None;
+        if python_line > 0 && self.leading_done.insert(python_line) {
+            let comments = self.comments;
+            for comment in comments.iter().filter(|c| c.line == python_line && !c.trailing) {
+                self.line(&line_comment_text(&comment.text));
+            }
+        }
+        let mut region = self.begin_hoisted(python_line, python_column);
+        region.comments = true;
+        region
+    }
+
+    // Como `begin`, para una declaración adelantada a su sentencia (`let mut x`
+    // antes del bucle): los comentarios se quedan con la sentencia.
+    pub fn begin_hoisted(&mut self, python_line: usize, python_column: usize) -> Region {
+        if python_line > 0 && self.line_comments {
+            self.marker = Some(format!("// py: {}:{}", self.source_map.python_file, python_line));
+        }
+        Region { start: self.code.len(), python_line, python_column, comments: false }
+    }
+
+    // Registra la región en el mapa y pone el comentario final de la sentencia
+    // al final de su primera línea de código.
+    pub fn end(&mut self, mut region: Region) {
+        if self.code.len() == region.start {
+            self.marker = None;
+        }
+        if region.python_line == 0 || self.code.len() == region.start {
+            return;
+        }
+        // La marca no es parte del código de la sentencia
+        if self.code[region.start..].trim_start().starts_with("// py: ") {
+            region.start += self.code[region.start..].find('\n').map_or(0, |end| end + 1);
+        }
+        if region.comments && self.trailing_done.insert(region.python_line) {
+            let trailing = self.comments.iter().find(|c| c.line == region.python_line && c.trailing);
+            if let Some(comment) = trailing {
+                let mut line_start = region.start;
+                for line in self.code[region.start..].split_inclusive('\n') {
+                    if !line.trim().is_empty() {
+                        let line_end = line_start + line.trim_end_matches('\n').len();
+                        self.code.insert_str(line_end, &format!(" {}", line_comment_text(&comment.text)));
+                        break;
+                    }
+                    line_start += line.len();
+                }
+            }
+        }
+        let first_line = next_line(&self.code[..region.start]);
+        let last_line = next_line(&self.code) - 1;
+        self.source_map.add(first_line, last_line, region.python_line, region.python_column);
+    }
+
+    pub fn finish(mut self) -> (String, SourceMap) {
+        while self.code.ends_with("\n\n") {
+            self.code.pop();
+        }
+        (self.code, self.source_map)
+    }
+}
+
+// Cadena de métodos como la formatea rustfmt: en una línea si cabe en
+// `chain_width` (60), si no una llamada por línea. `calls` empiezan por `.`.
+pub fn method_chain(receiver: &str, calls: &[String], terminator: &str) -> String {
+    let single_line = format!("{}{}", receiver, calls.concat());
+    if single_line.len() <= 60 {
+        return format!("{}{}", single_line, terminator);
+    }
+    let mut chain = receiver.to_string();
+    for call in calls {
+        chain.push_str(&format!("\n{}{}", INDENT, call));
+    }
+    chain.push_str(terminator);
+    chain
+}
+
+// Llamada como la formatea rustfmt: los argumentos en una línea si caben en
+// `fn_call_width` (60) y ninguno ocupa varias líneas, si no uno por línea con
+// coma final.
+pub fn function_call(function: &str, args: &[String], terminator: &str) -> String {
+    let single_line = args.join(", ");
+    if single_line.len() <= 60 && !single_line.contains('\n') {
+        return format!("{}({}){}", function, single_line, terminator);
+    }
+    // Un único argumento que es otra llamada se desborda: `f(Rect::new(\n    x,\n ...))`
+    if let [arg] = args {
+        if let Some((inner, inner_args)) = split_call(arg) {
+            let inner_args: String = inner_args.iter().map(|arg| format!("{}{},\n", INDENT, arg)).collect();
+            return format!("{}({}(\n{})){}", function, inner, inner_args, terminator);
+        }
+    }
+    let nested = format!("\n{}", INDENT);
+    let args: String = args.iter().map(|arg| format!("{}{},\n", INDENT, arg.replace('\n', &nested))).collect();
+    format!("{}(\n{}){}", function, args, terminator)
+}
+
+// Firma de función como la formatea rustfmt: en una línea si no pasa de
+// `max_width` (100) con la sangría `indent`, si no un parámetro por línea.
+pub fn fn_signature(head: &str, params: &[String], tail: &str, indent: usize) -> String {
+    let single_line = format!("{}({}){}", head, params.join(", "), tail);
+    if indent + single_line.len() <= 100 {
+        return single_line;
+    }
+    let params: String = params.iter().map(|param| format!("{}{},\n", INDENT, param)).collect();
+    format!("{}(\n{}){}", head, params, tail)
+}
+
+// `nombre(a, b)` en una línea: el nombre y sus argumentos.
+fn split_call(code: &str) -> Option<(&str, Vec<String>)> {
+    let open = code.find('(')?;
+    let function = &code[..open];
+    if code.contains('\n') || !code.ends_with(')') || function.is_empty() || function.contains(' ') {
+        return None;
+    }
+    let mut args = Vec::new();
+    let mut depth = 0;
+    let mut in_string = false;
+    let mut start = open + 1;
+    for (i, c) in code[open + 1..].char_indices().map(|(i, c)| (i + open + 1, c)) {
+        match c {
+            '"' => in_string = !in_string,
+            _ if in_string => {}
+            '(' | '[' => depth += 1,
+            ')' | ']' if depth > 0 => depth -= 1,
+            // El paréntesis que cierra la llamada tiene que ser el último
+            ')' if i + 1 < code.len() => return None,
+            ',' if depth == 0 => {
+                args.push(code[start..i].trim().to_string());
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    args.push(code[start..code.len() - 1].trim().to_string());
+    Some((function, args))
+}
+
+// Array literal como lo formatea rustfmt: en una línea si los elementos caben
+// en `array_width` (60), si no uno por línea con coma final.
+pub fn array(items: &[String]) -> String {
+    let single_line = items.join(", ");
+    if single_line.len() <= 60 {
+        return format!("[{}]", single_line);
+    }
+    let items: String = items.iter().map(|item| format!("{}{},\n", INDENT, item)).collect();
+    format!("[\n{}]", items)
+}
+
+// `let` con un `vec![...]` como lo formatea rustfmt: en una línea si no pasa
+// de `max_width` (100) con la sangría `indent`; si no, el `vec!` en la línea
+// siguiente si sus elementos caben en `array_width` (60), o uno por línea.
+pub fn vec_declaration(head: &str, items: &[String], indent: usize) -> String {
+    let single_line = items.join(", ");
+    let declaration = format!("{} = vec![{}];", head, single_line);
+    if indent + declaration.len() <= 100 {
+        return declaration;
+    }
+    if single_line.len() <= 60 {
+        return format!("{} =\n{}vec![{}];", head, INDENT, single_line);
+    }
+    let items: String = items.iter().map(|item| format!("{}{},\n", INDENT, item)).collect();
+    format!("{} = vec![\n{}];", head, items)
+}
+
+fn line_comment_text(text: &str) -> String {
+    if text.is_empty() { "//".to_string() } else { format!("// {}", text) }
+}
+
+// Pasa el código por rustfmt si está instalado. Devuelve None si no se pudo
+// ejecutar o si rustfmt rechazó el código.
+pub fn run_rustfmt(code: &str) -> Option<String> {
+    let mut child = Command::new("rustfmt")
+        .args(["--edition", "2021", "--emit", "stdout"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .ok()?;
+    child.stdin.take()?.write_all(code.as_bytes()).ok()?;
+    let output = child.wait_with_output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    String::from_utf8(output.stdout).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_blocks_and_blank_lines() {
+        let comments = vec![
+            Comment { text: "Posición".to_string(), line: 3, trailing: false },
+            Comment { text: "px".to_string(), line: 3, trailing: true },
+        ];
+        let mut writer = CodeWriter::new("juego.py", &comments, false);
+        writer.open("fn main() {");
+        writer.blank();
+        let region = writer.begin(3, 1);
+        writer.line("let x = 100;");
+        writer.end(region);
+        writer.blank();
+        writer.blank();
+        writer.line("let y = 200;");
+        writer.blank();
+        writer.close("}");
+        let (code, map) = writer.finish();
+
+        assert_eq!(code, "fn main() {\n    // Posición\n    let x = 100; // px\n\n    let y = 200;\n}\n");
+        assert_eq!(map.python_line(3), Some(3));
+    }
+
+    #[test]
+    fn test_line_comments() {
+        let mut writer = CodeWriter::new("juego.py", &[], true);
+        writer.open("match event {");
+        writer.open("Event::Quit { .. } => {");
+        // El `if` de la línea 34 es la rama; su cuerpo es lo primero que se escribe
+        let branch = writer.begin(34, 9);
+        let body = writer.begin(35, 13);
+        writer.line("running = false;");
+        writer.end(body);
+        let empty = writer.begin(36, 13);
+        writer.end(empty);
+        writer.end(branch);
+        writer.close("}");
+        writer.close("}");
+        let (code, map) = writer.finish();
+
+        assert_eq!(code, "match event {\n    Event::Quit { .. } => {\n        // py: juego.py:35\n        running = false;\n    }\n}\n");
+        assert_eq!(map.python_line(4), Some(35));
+    }
+
+    #[test]
+    fn test_function_call_layout() {
+        let rect = "Rect::new(x, y, ajustes::TAMANO as u32, ajustes::TAMANO as u32)".to_string();
+        assert_eq!(
+            function_call("canvas.fill_rect", &[rect], "?;"),
+            "canvas.fill_rect(Rect::new(\n    x,\n    y,\n    ajustes::TAMANO as u32,\n    ajustes::TAMANO as u32,\n))?;"
+        );
+        assert_eq!(function_call("canvas.fill_rect", &["rect".to_string()], "?;"), "canvas.fill_rect(rect)?;");
+    }
+
+    #[test]
+    fn test_vec_declaration_layout() {
+        let head = "let mut enemigos: Vec<Box<dyn SpriteTrait>>";
+        let items = |n: usize| (0..n).map(|i| format!("Box::new(Enemy::new({}))", i)).collect::<Vec<_>>();
+        assert_eq!(vec_declaration(head, &items(1), 4), "let mut enemigos: Vec<Box<dyn SpriteTrait>> = vec![Box::new(Enemy::new(0))];");
+        assert_eq!(
+            vec_declaration(head, &items(2), 4),
+            "let mut enemigos: Vec<Box<dyn SpriteTrait>> =\n    vec![Box::new(Enemy::new(0)), Box::new(Enemy::new(1))];"
+        );
+        assert_eq!(
+            vec_declaration(head, &items(3), 4),
+            "let mut enemigos: Vec<Box<dyn SpriteTrait>> = vec![\n    Box::new(Enemy::new(0)),\n    Box::new(Enemy::new(1)),\n    Box::new(Enemy::new(2)),\n];"
+        );
+    }
+}